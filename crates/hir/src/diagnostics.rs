@@ -7,10 +7,12 @@ use std::any::Any;
 
 use cfg::{CfgExpr, CfgOptions, DnfExpr};
 use hir_def::path::ModPath;
-use hir_expand::{HirFileId, InFile};
+use hir_expand::{name::Name, HirFileId, InFile};
 use stdx::format_to;
 use syntax::{ast, AstPtr, SyntaxNodePtr, TextRange};
 
+use crate::Type;
+
 pub use hir_ty::{
     diagnostics::{
         IncorrectCase, MismatchedArgCount, MissingFields, MissingMatchArms,
@@ -206,6 +208,9 @@ impl Diagnostic for UnresolvedProcMacro {
 pub struct MacroError {
     pub file: HirFileId,
     pub node: SyntaxNodePtr,
+    /// If the error points at a token of the macro input, this is the range of that token, to use
+    /// instead of `node`.
+    pub precise_location: Option<TextRange>,
     pub message: String,
 }
 
@@ -251,3 +256,106 @@ impl Diagnostic for UnimplementedBuiltinMacro {
         self
     }
 }
+
+// Diagnostic: type-mismatch
+//
+// This diagnostic is triggered when the type of an expression does not match
+// the expected type.
+//
+// Mismatches involving types rust-analyzer could not infer are not reported.
+#[derive(Debug)]
+pub struct TypeMismatch {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::Expr>,
+    pub expected: Type,
+    pub actual: Type,
+    // `message` can't access the database, so the types are rendered upfront.
+    pub(crate) expected_display: String,
+    pub(crate) actual_display: String,
+}
+
+impl Diagnostic for TypeMismatch {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("type-mismatch")
+    }
+    fn message(&self) -> String {
+        format!("expected {}, found {}", self.expected_display, self.actual_display)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.expr.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+    fn is_experimental(&self) -> bool {
+        // Newly added and not very well-tested, might contain false positives.
+        true
+    }
+}
+
+// Diagnostic: unresolved-method
+//
+// This diagnostic is triggered if a method call can't be resolved on the
+// type of its receiver.
+//
+// It is only reported when the receiver type is fully known.
+#[derive(Debug)]
+pub struct UnresolvedMethodCall {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::MethodCallExpr>,
+    pub receiver: Type,
+    pub name: Name,
+    // `message` can't access the database, so the type is rendered upfront.
+    pub(crate) receiver_display: String,
+}
+
+impl Diagnostic for UnresolvedMethodCall {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unresolved-method")
+    }
+    fn message(&self) -> String {
+        format!("no method `{}` on type `{}`", self.name, self.receiver_display)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.expr.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+    fn is_experimental(&self) -> bool {
+        true
+    }
+}
+
+// Diagnostic: unresolved-field
+//
+// This diagnostic is triggered if a field access can't be resolved on the
+// type of its receiver.
+//
+// It is only reported when the receiver type is fully known.
+#[derive(Debug)]
+pub struct UnresolvedField {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::FieldExpr>,
+    pub receiver: Type,
+    pub name: Name,
+    pub(crate) receiver_display: String,
+}
+
+impl Diagnostic for UnresolvedField {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unresolved-field")
+    }
+    fn message(&self) -> String {
+        format!("no field `{}` on type `{}`", self.name, self.receiver_display)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.expr.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+    fn is_experimental(&self) -> bool {
+        true
+    }
+}
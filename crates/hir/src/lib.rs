@@ -36,14 +36,15 @@ use std::{iter, sync::Arc};
 use arrayvec::ArrayVec;
 use base_db::{CrateDisplayName, CrateId, Edition, FileId};
 use diagnostics::{
-    InactiveCode, MacroError, UnimplementedBuiltinMacro, UnresolvedExternCrate, UnresolvedImport,
-    UnresolvedMacroCall, UnresolvedModule, UnresolvedProcMacro,
+    InactiveCode, MacroError, TypeMismatch, UnimplementedBuiltinMacro, UnresolvedExternCrate,
+    UnresolvedField, UnresolvedImport, UnresolvedMacroCall, UnresolvedMethodCall, UnresolvedModule,
+    UnresolvedProcMacro,
 };
 use either::Either;
 use hir_def::{
-    adt::{ReprKind, VariantData},
+    adt::{ReprData, VariantData},
     body::BodyDiagnostic,
-    expr::{BindingAnnotation, LabelId, Pat, PatId},
+    expr::{BindingAnnotation, Expr, ExprId, LabelId, Pat, PatId},
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
     nameres,
@@ -52,9 +53,9 @@ use hir_def::{
     src::HasSource as _,
     type_ref::TraitRef,
     AdtId, AssocContainerId, AssocItemId, AssocItemLoc, AttrDefId, ConstId, ConstParamId,
-    DefWithBodyId, EnumId, FunctionId, GenericDefId, HasModule, ImplId, LifetimeParamId,
-    LocalEnumVariantId, LocalFieldId, Lookup, ModuleId, StaticId, StructId, TraitId, TypeAliasId,
-    TypeParamId, UnionId,
+    DefWithBodyId, EnumId, EnumVariantId, FunctionId, GenericDefId, HasModule, ImplId,
+    LifetimeParamId, LocalEnumVariantId, LocalFieldId, Lookup, ModuleId, StaticId, StructId,
+    TraitId, TypeAliasId, TypeParamId, UnionId,
 };
 use hir_expand::{name::name, MacroCallKind, MacroDefKind};
 use hir_ty::{
@@ -77,7 +78,7 @@ use rustc_hash::FxHashSet;
 use stdx::{format_to, impl_from};
 use syntax::{
     ast::{self, AttrsOwner, NameOwner},
-    AstNode, AstPtr, SmolStr, SyntaxKind, SyntaxNodePtr,
+    AstNode, AstPtr, SmolStr, SyntaxKind, SyntaxNodePtr, TextRange,
 };
 use tt::{Ident, Leaf, Literal, TokenTree};
 
@@ -114,10 +115,15 @@ pub use {
     },
     hir_expand::{
         name::{known, Name},
-        ExpandResult, HirFileId, InFile, MacroCallId, MacroCallLoc, /* FIXME */ MacroDefId,
-        MacroFile, Origin,
+        ExpandResult, ExpansionTrace, HirFileId, InFile, MacroCallId, MacroCallLoc,
+        /* FIXME */ MacroDefId, MacroFile, MetaVarBinding, Origin, RuleTrace,
+    },
+    hir_ty::{
+        capture::CaptureKind,
+        consteval::{ComputedExpr, ConstEvalError},
+        display::HirDisplay,
+        layout::Layout,
     },
-    hir_ty::display::HirDisplay,
 };
 
 // These are negative re-exports: pub using these names is forbidden, they
@@ -564,10 +570,13 @@ impl Module {
                     });
                 }
 
-                DefDiagnosticKind::MacroError { ast, message } => {
+                DefDiagnosticKind::MacroError { ast, message, token_range } => {
+                    let mut precise_location = None;
                     let (file, ast) = match ast {
                         MacroCallKind::FnLike { ast_id, .. } => {
                             let node = ast_id.to_node(db.upcast());
+                            precise_location =
+                                macro_input_location(db, ast_id.file_id, &node, *token_range);
                             (ast_id.file_id, SyntaxNodePtr::from(AstPtr::new(&node)))
                         }
                         MacroCallKind::Derive { ast_id, .. }
@@ -577,7 +586,12 @@ impl Module {
                             (ast_id.file_id, SyntaxNodePtr::from(AstPtr::new(&node)))
                         }
                     };
-                    sink.push(MacroError { file, node: ast, message: message.clone() });
+                    sink.push(MacroError {
+                        file,
+                        node: ast,
+                        precise_location,
+                        message: message.clone(),
+                    });
                 }
 
                 DefDiagnosticKind::UnimplementedBuiltinMacro { ast } => {
@@ -641,6 +655,20 @@ impl Module {
     }
 }
 
+/// Computes the range of the input token a macro error points at from `token_range`, which is
+/// relative to the token tree of `call`. Returns `None` for calls inside macro expansions.
+fn macro_input_location(
+    db: &dyn HirDatabase,
+    file: HirFileId,
+    call: &ast::MacroCall,
+    token_range: Option<TextRange>,
+) -> Option<TextRange> {
+    if file.call_node(db.upcast()).is_some() {
+        return None;
+    }
+    Some(token_range? + call.token_tree()?.syntax().text_range().start())
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Field {
     pub(crate) parent: VariantDef,
@@ -676,6 +704,22 @@ impl Field {
     pub fn parent_def(&self, _db: &dyn HirDatabase) -> VariantDef {
         self.parent
     }
+
+    pub fn layout(&self, db: &dyn HirDatabase) -> Option<Layout> {
+        self.ty(db).layout(db)
+    }
+
+    /// Returns the offset of this field within its struct or union. Fields of
+    /// enum variants don't have a fixed offset, so this returns `None` for them.
+    pub fn offset(&self, db: &dyn HirDatabase) -> Option<u64> {
+        let adt = match self.parent {
+            VariantDef::Struct(it) => Adt::Struct(it),
+            VariantDef::Union(it) => Adt::Union(it),
+            VariantDef::Variant(_) => return None,
+        };
+        let idx = u32::from(self.id.into_raw()) as usize;
+        adt.layout(db)?.field_offsets.get(idx).copied()
+    }
 }
 
 impl HasVisibility for Field {
@@ -714,8 +758,8 @@ impl Struct {
         Type::from_def(db, self.id.lookup(db.upcast()).container.krate(), self.id)
     }
 
-    pub fn repr(self, db: &dyn HirDatabase) -> Option<ReprKind> {
-        db.struct_data(self.id).repr.clone()
+    pub fn repr(self, db: &dyn HirDatabase) -> Option<ReprData> {
+        db.struct_data(self.id).repr
     }
 
     pub fn kind(self, db: &dyn HirDatabase) -> StructKind {
@@ -831,6 +875,12 @@ impl Variant {
         self.variant_data(db).kind()
     }
 
+    /// The discriminant of this variant, if it can be evaluated.
+    pub fn discriminant(self, db: &dyn HirDatabase) -> Option<i128> {
+        let id = EnumVariantId { parent: self.parent.id, local_id: self.id };
+        hir_ty::consteval::enum_variant_discriminant(db, id)
+    }
+
     pub(crate) fn variant_data(self, db: &dyn HirDatabase) -> Arc<VariantData> {
         db.enum_data(self.parent.id).variants[self.id].variant_data.clone()
     }
@@ -859,6 +909,15 @@ impl Adt {
         Type::from_def(db, id.module(db.upcast()).krate(), id)
     }
 
+    /// Computes the memory layout of this ADT, with placeholder types for type
+    /// parameters. Returns `None` if the layout depends on them.
+    pub fn layout(self, db: &dyn HirDatabase) -> Option<Layout> {
+        let id = AdtId::from(self);
+        let substs = TyBuilder::type_params_subst(db, id);
+        let ty = db.ty(id.into()).substitute(&Interner, &substs);
+        hir_ty::layout::layout_of_ty(db, &ty, id.module(db.upcast()).krate())
+    }
+
     pub fn module(self, db: &dyn HirDatabase) -> Module {
         match self {
             Adt::Struct(s) => s.module(db),
@@ -1021,11 +1080,18 @@ impl Function {
                     cfg: cfg.clone(),
                     opts: opts.clone(),
                 }),
-                BodyDiagnostic::MacroError { node, message } => sink.push(MacroError {
-                    file: node.file_id,
-                    node: node.value.clone().into(),
-                    message: message.to_string(),
-                }),
+                BodyDiagnostic::MacroError { node, message, token_range } => {
+                    let precise_location = db.parse_or_expand(node.file_id).and_then(|root| {
+                        let call = node.value.to_node(&root);
+                        macro_input_location(db, node.file_id, &call, *token_range)
+                    });
+                    sink.push(MacroError {
+                        file: node.file_id,
+                        node: node.value.clone().into(),
+                        precise_location,
+                        message: message.to_string(),
+                    })
+                }
                 BodyDiagnostic::UnresolvedProcMacro { node } => sink.push(UnresolvedProcMacro {
                     file: node.file_id,
                     node: node.value.clone().into(),
@@ -1044,6 +1110,131 @@ impl Function {
 
         hir_ty::diagnostics::validate_module_item(db, krate, self.id.into(), sink);
         hir_ty::diagnostics::validate_body(db, self.id.into(), sink);
+        self.type_mismatch_diagnostics(db, krate, sink);
+        self.unresolved_access_diagnostics(db, krate, sink);
+    }
+
+    fn type_mismatch_diagnostics(
+        self,
+        db: &dyn HirDatabase,
+        krate: CrateId,
+        sink: &mut DiagnosticSink,
+    ) {
+        let (body, source_map) = db.body_with_source_map(self.id.into());
+        let infer = db.infer(self.id.into());
+        let tail_expr = match &body[body.body_expr] {
+            Expr::Block { tail, .. } => *tail,
+            _ => None,
+        };
+        for (expr, mismatch) in infer.expr_type_mismatches() {
+            // Return type mismatches are left to the more specific
+            // `MissingOkOrSomeInTailExpr` and `RemoveThisSemicolon`.
+            if expr == body.body_expr || Some(expr) == tail_expr {
+                continue;
+            }
+            // Don't report the same mismatch for a block and its tail.
+            if let Expr::Block { tail: Some(tail), .. } = &body[expr] {
+                if infer.type_mismatch_for_expr(*tail).is_some() {
+                    continue;
+                }
+            }
+            let source = match source_map.expr_syntax(expr) {
+                Ok(it) if it.file_id.call_node(db.upcast()).is_none() => it,
+                _ => continue,
+            };
+            let expected = Type::new(db, krate, self.id, mismatch.expected.clone());
+            let actual = Type::new(db, krate, self.id, mismatch.actual.clone());
+            if expected.contains_unknown() || actual.contains_unknown() {
+                continue;
+            }
+            sink.push(TypeMismatch {
+                file: source.file_id,
+                expr: source.value,
+                expected_display: expected.display(db).to_string(),
+                actual_display: actual.display(db).to_string(),
+                expected,
+                actual,
+            });
+        }
+    }
+
+    fn unresolved_access_diagnostics(
+        self,
+        db: &dyn HirDatabase,
+        krate: CrateId,
+        sink: &mut DiagnosticSink,
+    ) {
+        let (body, source_map) = db.body_with_source_map(self.id.into());
+        let infer = db.infer(self.id.into());
+        let mut accesses = infer.unresolved_accesses().collect::<Vec<_>>();
+        accesses.sort_by_key(|(expr, _)| expr.into_raw());
+        for (expr, receiver) in accesses {
+            let receiver = Type::new(db, krate, self.id, receiver.clone());
+            if receiver.contains_unknown() {
+                continue;
+            }
+            // Methods and fields could come from bounds or traits we don't
+            // fully understand, or from lang impls in a `core` we failed to
+            // load, so only report receivers we can see through.
+            let derefs = receiver.autoderef(db).collect::<Vec<_>>();
+            let is_confident = derefs.iter().all(|ty| {
+                matches!(
+                    ty.ty.kind(&Interner),
+                    TyKind::Adt(..) | TyKind::Tuple(..) | TyKind::Ref(..) | TyKind::Raw(..)
+                )
+            });
+            if !is_confident {
+                continue;
+            }
+            let source = match source_map.expr_syntax(expr) {
+                Ok(it) if it.file_id.call_node(db.upcast()).is_none() => it,
+                _ => continue,
+            };
+            let receiver_display = receiver.display(db).to_string();
+            match &body[expr] {
+                Expr::MethodCall { method_name, .. } => {
+                    let expr = match source.value.cast::<ast::MethodCallExpr>() {
+                        Some(it) => it,
+                        None => continue,
+                    };
+                    sink.push(UnresolvedMethodCall {
+                        file: source.file_id,
+                        expr,
+                        receiver,
+                        name: method_name.clone(),
+                        receiver_display,
+                    });
+                }
+                Expr::Field { name, .. } => {
+                    // Existing but inaccessible fields are a different error.
+                    let has_field = derefs.iter().any(|ty| {
+                        ty.as_adt().map_or(false, |adt| {
+                            let fields = match adt {
+                                Adt::Struct(it) => it.fields(db),
+                                Adt::Union(it) => it.fields(db),
+                                Adt::Enum(_) => Vec::new(),
+                            };
+                            fields.iter().any(|field| field.name(db) == *name)
+                        })
+                    });
+                    if has_field {
+                        continue;
+                    }
+                    let expr = match source.value.cast::<ast::FieldExpr>() {
+                        Some(it) => it,
+                        None => continue,
+                    };
+                    sink.push(UnresolvedField {
+                        file: source.file_id,
+                        expr,
+                        receiver,
+                        name: name.clone(),
+                        receiver_display,
+                    });
+                }
+                _ => {}
+            }
+        }
     }
 
     /// Whether this function declaration has a definition.
@@ -1181,6 +1372,10 @@ impl Const {
     pub fn type_ref(self, db: &dyn HirDatabase) -> TypeRef {
         db.const_data(self.id).type_ref.as_ref().clone()
     }
+
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        db.const_eval(self.id)
+    }
 }
 
 impl HasVisibility for Const {
@@ -1208,6 +1403,10 @@ impl Static {
     pub fn is_mut(self, db: &dyn HirDatabase) -> bool {
         db.static_data(self.id).mutable
     }
+
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        hir_ty::consteval::eval_static(db, self.id)
+    }
 }
 
 impl HasVisibility for Static {
@@ -1241,6 +1440,12 @@ impl Trait {
     pub fn is_unsafe(&self, db: &dyn HirDatabase) -> bool {
         db.trait_data(self.id).is_unsafe
     }
+
+    /// Returns the traits listed as bounds on `Self`, i.e. `A` and `B` in
+    /// `trait T: A where Self: B`.
+    pub fn direct_supertraits(self, db: &dyn HirDatabase) -> Vec<Trait> {
+        hir_ty::direct_super_traits(db.upcast(), self.id).into_iter().map(Trait::from).collect()
+    }
 }
 
 impl HasVisibility for Trait {
@@ -1802,6 +2007,12 @@ impl Impl {
         db.impl_data(self.id).target_trait.as_deref().cloned()
     }
 
+    /// Returns the resolved trait this impl implements, if any.
+    pub fn trait_def(self, db: &dyn HirDatabase) -> Option<Trait> {
+        let trait_ref = db.impl_trait(self.id)?;
+        Some(Trait::from(trait_ref.skip_binders().hir_trait_id()))
+    }
+
     pub fn self_ty(self, db: &dyn HirDatabase) -> Type {
         let impl_data = db.impl_data(self.id);
         let resolver = self.id.resolver(db.upcast());
@@ -1891,9 +2102,16 @@ impl Type {
         Type::new(db, krate, def, ty)
     }
 
+    pub fn layout(&self, db: &dyn HirDatabase) -> Option<Layout> {
+        hir_ty::layout::layout_of_ty(db, &self.ty, self.krate)
+    }
+
     pub fn is_unit(&self) -> bool {
         matches!(self.ty.kind(&Interner), TyKind::Tuple(0, ..))
     }
+    pub fn is_never(&self) -> bool {
+        matches!(self.ty.kind(&Interner), TyKind::Never)
+    }
     pub fn is_bool(&self) -> bool {
         matches!(self.ty.kind(&Interner), TyKind::Scalar(Scalar::Bool))
     }
@@ -1921,6 +2139,20 @@ impl Type {
         self.ty.is_unknown()
     }
 
+    pub fn as_slice(&self) -> Option<Type> {
+        match &self.ty.kind(&Interner) {
+            TyKind::Slice(ty) => Some(self.derived(ty.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<Type> {
+        match &self.ty.kind(&Interner) {
+            TyKind::Array(ty, _) => Some(self.derived(ty.clone())),
+            _ => None,
+        }
+    }
+
     /// Checks that particular type `ty` implements `std::future::Future`.
     /// This function is used in `.await` syntax completion.
     pub fn impls_future(&self, db: &dyn HirDatabase) -> bool {
@@ -1970,6 +2202,16 @@ impl Type {
     }
 
     pub fn impls_trait(&self, db: &dyn HirDatabase, trait_: Trait, args: &[Type]) -> bool {
+        self.solve_trait(db, trait_, args).is_some()
+    }
+
+    /// Like [`Type::impls_trait`], but ambiguous solutions, where the solver can't tell which
+    /// impl applies, don't count.
+    pub fn impls_trait_uniquely(&self, db: &dyn HirDatabase, trait_: Trait, args: &[Type]) -> bool {
+        matches!(self.solve_trait(db, trait_, args), Some(Solution::Unique(_)))
+    }
+
+    fn solve_trait(&self, db: &dyn HirDatabase, trait_: Trait, args: &[Type]) -> Option<Solution> {
         let trait_ref = TyBuilder::trait_ref(db, trait_.id)
             .push(self.ty.clone())
             .fill(args.iter().map(|t| t.ty.clone()))
@@ -1980,7 +2222,7 @@ impl Type {
             binders: CanonicalVarKinds::empty(&Interner),
         };
 
-        db.trait_solve(self.krate, goal).is_some()
+        db.trait_solve(self.krate, goal)
     }
 
     pub fn normalize_trait_assoc_type(
@@ -2017,6 +2259,14 @@ impl Type {
         }
     }
 
+    /// Whether both types are the same once their associated type projections
+    /// are normalized, each in its own environment.
+    pub fn equals_normalized(&self, db: &dyn HirDatabase, other: &Type) -> bool {
+        let normalize =
+            |it: &Type| hir_ty::traits::normalize_projections(db, &it.env, it.ty.clone());
+        normalize(self) == normalize(other)
+    }
+
     pub fn is_copy(&self, db: &dyn HirDatabase) -> bool {
         let lang_item = db.lang_item(self.krate, SmolStr::new("copy"));
         let copy_trait = match lang_item {
@@ -2037,6 +2287,16 @@ impl Type {
         matches!(&self.ty.kind(&Interner), TyKind::Closure { .. })
     }
 
+    pub fn as_closure(&self, db: &dyn HirDatabase) -> Option<Closure> {
+        match self.ty.kind(&Interner) {
+            TyKind::Closure(id, _) => {
+                let (owner, expr) = db.lookup_intern_closure((*id).into());
+                Some(Closure { owner, expr })
+            }
+            _ => None,
+        }
+    }
+
     pub fn is_fn(&self) -> bool {
         matches!(&self.ty.kind(&Interner), TyKind::FnDef(..) | TyKind::Function { .. })
     }
@@ -2049,7 +2309,7 @@ impl Type {
 
         let adt = adt_id.into();
         match adt {
-            Adt::Struct(s) => matches!(s.repr(db), Some(ReprKind::Packed)),
+            Adt::Struct(s) => s.repr(db).map_or(false, |repr| repr.packed.is_some()),
             _ => false,
         }
     }
@@ -2383,6 +2643,42 @@ impl Type {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Closure {
+    owner: DefWithBodyId,
+    expr: ExprId,
+}
+
+impl Closure {
+    /// Returns the locals the closure captures from its environment, in the
+    /// order of their first use.
+    pub fn captured_items(&self, db: &dyn HirDatabase) -> Vec<ClosureCapture> {
+        hir_ty::capture::closure_captures(db, self.owner, self.expr)
+            .into_iter()
+            .map(|it| ClosureCapture {
+                local: Local { parent: self.owner, pat_id: it.local },
+                kind: it.kind,
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClosureCapture {
+    local: Local,
+    kind: CaptureKind,
+}
+
+impl ClosureCapture {
+    pub fn local(&self) -> Local {
+        self.local
+    }
+
+    pub fn kind(&self) -> CaptureKind {
+        self.kind
+    }
+}
+
 // FIXME: closures
 #[derive(Debug)]
 pub struct Callable {
@@ -10,7 +10,7 @@ use hir_def::{
     resolver::{self, HasResolver, Resolver, TypeNs},
     AsMacroCall, FunctionId, TraitId, VariantId,
 };
-use hir_expand::{name::AsName, ExpansionInfo};
+use hir_expand::{name::AsName, ExpansionInfo, ExpansionTrace};
 use hir_ty::{associated_type_shorthand_candidates, Interner};
 use itertools::Itertools;
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -126,6 +126,12 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.speculative_expand(actual_macro_call, speculative_args, token_to_map)
     }
 
+    /// Expands the macro call like `expand`, recording how its input matched against each rule
+    /// of the macro. Only supported for declarative macros.
+    pub fn trace_expand(&self, macro_call: &ast::MacroCall) -> Option<ExpansionTrace> {
+        self.imp.trace_expand(macro_call)
+    }
+
     pub fn descend_into_macros(&self, token: SyntaxToken) -> SyntaxToken {
         self.imp.descend_into_macros(token)
     }
@@ -352,6 +358,16 @@ impl<'db> SemanticsImpl<'db> {
         )
     }
 
+    fn trace_expand(&self, macro_call: &ast::MacroCall) -> Option<ExpansionTrace> {
+        let sa = self.analyze(macro_call.syntax());
+        let macro_call = InFile::new(sa.file_id, macro_call);
+        let krate = sa.resolver.krate()?;
+        let macro_call_id = macro_call.as_call_id(self.db.upcast(), krate, |path| {
+            sa.resolver.resolve_path_as_macro(self.db.upcast(), &path)
+        })?;
+        hir_expand::db::trace_expansion(self.db.upcast(), macro_call_id)
+    }
+
     fn descend_into_macros(&self, token: SyntaxToken) -> SyntaxToken {
         let _p = profile::span("descend_into_macros");
         let parent = match token.parent() {
@@ -14,13 +14,14 @@ use tt::{Delimiter, DelimiterKind, Leaf, Subtree, TokenTree};
 
 use crate::{
     body::{CfgExpander, LowerCtx},
+    builtin_type::{BuiltinInt, BuiltinUint},
     db::DefDatabase,
     intern::Interned,
     item_tree::{AttrOwner, Field, Fields, ItemTree, ModItem, RawVisibilityId},
     src::HasChildSource,
     src::HasSource,
     trace::Trace,
-    type_ref::TypeRef,
+    type_ref::{ConstRef, TypeRef},
     visibility::RawVisibility,
     EnumId, LocalEnumVariantId, LocalFieldId, Lookup, ModuleId, StructId, UnionId, VariantId,
 };
@@ -31,7 +32,7 @@ use cfg::CfgOptions;
 pub struct StructData {
     pub name: Name,
     pub variant_data: Arc<VariantData>,
-    pub repr: Option<ReprKind>,
+    pub repr: Option<ReprData>,
     pub visibility: RawVisibility,
 }
 
@@ -39,6 +40,7 @@ pub struct StructData {
 pub struct EnumData {
     pub name: Name,
     pub variants: Arena<EnumVariantData>,
+    pub repr: Option<ReprData>,
     pub visibility: RawVisibility,
 }
 
@@ -46,6 +48,7 @@ pub struct EnumData {
 pub struct EnumVariantData {
     pub name: Name,
     pub variant_data: Arc<VariantData>,
+    pub discriminant: Option<ConstRef>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,10 +66,19 @@ pub struct FieldData {
     pub visibility: RawVisibility,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ReprKind {
-    Packed,
-    Other,
+/// The data of the `#[repr]` attributes of an ADT.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReprData {
+    /// `repr(C)`
+    pub c: bool,
+    /// `repr(transparent)`
+    pub transparent: bool,
+    /// The maximum alignment of fields set by `repr(packed)` or `repr(packed(N))`.
+    pub packed: Option<u64>,
+    /// The minimum alignment set by `repr(align(N))`.
+    pub align: Option<u64>,
+    /// The type of enum discriminants set by e.g. `repr(u8)`.
+    pub int: Option<Either<BuiltinInt, BuiltinUint>>,
 }
 
 fn repr_from_value(
@@ -74,20 +86,50 @@ fn repr_from_value(
     krate: CrateId,
     item_tree: &ItemTree,
     of: AttrOwner,
-) -> Option<ReprKind> {
-    item_tree.attrs(db, krate, of).by_key("repr").tt_values().find_map(parse_repr_tt)
+) -> Option<ReprData> {
+    let mut res = None;
+    for tt in item_tree.attrs(db, krate, of).by_key("repr").tt_values() {
+        parse_repr_tt(tt, res.get_or_insert_with(ReprData::default));
+    }
+    res
 }
 
-fn parse_repr_tt(tt: &Subtree) -> Option<ReprKind> {
+fn parse_repr_tt(tt: &Subtree, repr: &mut ReprData) {
     match tt.delimiter {
         Some(Delimiter { kind: DelimiterKind::Parenthesis, .. }) => {}
-        _ => return None,
+        _ => return,
     }
 
-    let mut it = tt.token_trees.iter();
-    match it.next()? {
-        TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "packed" => Some(ReprKind::Packed),
-        _ => Some(ReprKind::Other),
+    let mut tts = tt.token_trees.iter().peekable();
+    while let Some(tt) = tts.next() {
+        let ident = match tt {
+            TokenTree::Leaf(Leaf::Ident(ident)) => ident,
+            _ => continue,
+        };
+        // The argument of e.g. `packed(2)`.
+        let arg = match tts.peek() {
+            Some(TokenTree::Subtree(arg)) => {
+                tts.next();
+                match arg.token_trees.as_slice() {
+                    [TokenTree::Leaf(Leaf::Literal(lit))] => lit.text.parse::<u64>().ok(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match ident.text.as_str() {
+            "C" => repr.c = true,
+            "transparent" => repr.transparent = true,
+            "packed" => repr.packed = Some(arg.unwrap_or(1)),
+            "align" => repr.align = arg.or(repr.align),
+            int => {
+                if let Some(it) = BuiltinInt::from_suffix(int) {
+                    repr.int = Some(Either::Left(it));
+                } else if let Some(it) = BuiltinUint::from_suffix(int) {
+                    repr.int = Some(Either::Right(it));
+                }
+            }
+        }
     }
 }
 
@@ -132,6 +174,7 @@ impl EnumData {
         let loc = e.lookup(db);
         let krate = loc.container.krate;
         let item_tree = loc.id.item_tree(db);
+        let repr = repr_from_value(db, krate, &item_tree, ModItem::from(loc.id.value).into());
         let cfg_options = db.crate_graph()[krate].cfg_options.clone();
 
         let enum_ = &item_tree[loc.id.value];
@@ -151,6 +194,7 @@ impl EnumData {
                 variants.alloc(EnumVariantData {
                     name: var.name.clone(),
                     variant_data: Arc::new(var_data),
+                    discriminant: var.discriminant.clone(),
                 });
             }
         }
@@ -158,6 +202,7 @@ impl EnumData {
         Arc::new(EnumData {
             name: enum_.name.clone(),
             variants,
+            repr,
             visibility: item_tree[enum_.visibility].clone(),
         })
     }
@@ -188,6 +233,7 @@ fn lower_enum(
     module_id: ModuleId,
 ) {
     let expander = CfgExpander::new(db, ast.file_id, module_id.krate);
+    let ctx = LowerCtx::new(db, ast.file_id);
     let variants = ast
         .value
         .variant_list()
@@ -200,6 +246,7 @@ fn lower_enum(
             || EnumVariantData {
                 name: var.name().map_or_else(Name::missing, |it| it.as_name()),
                 variant_data: Arc::new(VariantData::new(db, ast.with_value(var.kind()), module_id)),
+                discriminant: var.expr().map(|it| ConstRef::from_expr(&ctx, it)),
             },
         );
     }
@@ -17,7 +17,7 @@ use hir_expand::{
 use la_arena::{Arena, ArenaMap};
 use profile::Count;
 use rustc_hash::FxHashMap;
-use syntax::{ast, AstNode, AstPtr, SyntaxNodePtr};
+use syntax::{ast, AstNode, AstPtr, SyntaxNodePtr, TextRange};
 
 use crate::{
     attr::{Attrs, RawAttrs},
@@ -279,10 +279,24 @@ pub struct SyntheticSyntax;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum BodyDiagnostic {
-    InactiveCode { node: InFile<SyntaxNodePtr>, cfg: CfgExpr, opts: CfgOptions },
-    MacroError { node: InFile<AstPtr<ast::MacroCall>>, message: String },
-    UnresolvedProcMacro { node: InFile<AstPtr<ast::MacroCall>> },
-    UnresolvedMacroCall { node: InFile<AstPtr<ast::MacroCall>>, path: ModPath },
+    InactiveCode {
+        node: InFile<SyntaxNodePtr>,
+        cfg: CfgExpr,
+        opts: CfgOptions,
+    },
+    /// `token_range` locates the offending input token within the token tree of the call.
+    MacroError {
+        node: InFile<AstPtr<ast::MacroCall>>,
+        message: String,
+        token_range: Option<TextRange>,
+    },
+    UnresolvedProcMacro {
+        node: InFile<AstPtr<ast::MacroCall>>,
+    },
+    UnresolvedMacroCall {
+        node: InFile<AstPtr<ast::MacroCall>>,
+        path: ModPath,
+    },
 }
 
 impl Body {
@@ -499,7 +499,11 @@ impl ExprCollector<'_> {
                     .and_then(|r| r.ty())
                     .map(|it| Interned::new(TypeRef::from_ast(&self.ctx(), it)));
                 let body = self.collect_expr_opt(e.body());
-                self.alloc_expr(Expr::Lambda { args, arg_types, ret_type, body }, syntax_ptr)
+                let is_move = e.move_token().is_some();
+                self.alloc_expr(
+                    Expr::Lambda { args, arg_types, ret_type, body, is_move },
+                    syntax_ptr,
+                )
             }
             ast::Expr::BinExpr(e) => {
                 let lhs = self.collect_expr_opt(e.lhs());
@@ -585,6 +589,7 @@ impl ExprCollector<'_> {
         let outer_file = self.expander.current_file_id;
 
         let macro_call = self.expander.to_source(AstPtr::new(&e));
+        let token_tree = e.token_tree();
         let res = self.expander.enter_expand(self.db, e);
 
         let res = match res {
@@ -606,9 +611,16 @@ impl ExprCollector<'_> {
                 });
             }
             Some(err) => {
+                let token_range = match (err, token_tree) {
+                    (ExpandError::NoRuleExpectedToken(failure), Some(token_tree)) => {
+                        failure.range(&mbe::syntax_node_to_token_tree(token_tree.syntax()).1)
+                    }
+                    _ => None,
+                };
                 self.source_map.diagnostics.push(BodyDiagnostic::MacroError {
                     node: InFile::new(outer_file, syntax_ptr),
                     message: err.to_string(),
+                    token_range,
                 });
             }
             None => {}
@@ -651,7 +663,16 @@ impl ExprCollector<'_> {
                 let type_ref =
                     stmt.ty().map(|it| Interned::new(TypeRef::from_ast(&self.ctx(), it)));
                 let initializer = stmt.initializer().map(|e| self.collect_expr(e));
-                self.statements_in_scope.push(Statement::Let { pat, type_ref, initializer });
+                let else_branch = stmt
+                    .let_else()
+                    .and_then(|it| it.block_expr())
+                    .map(|block| self.collect_expr(ast::Expr::BlockExpr(block)));
+                self.statements_in_scope.push(Statement::Let {
+                    pat,
+                    type_ref,
+                    initializer,
+                    else_branch,
+                });
             }
             ast::Stmt::ExprStmt(stmt) => {
                 if self.check_cfg(&stmt).is_none() {
@@ -149,11 +149,16 @@ fn compute_block_scopes(
 ) {
     for stmt in statements {
         match stmt {
-            Statement::Let { pat, initializer, .. } => {
+            Statement::Let { pat, initializer, else_branch, .. } => {
                 if let Some(expr) = initializer {
                     scopes.set_scope(*expr, scope);
                     compute_expr_scopes(*expr, body, scopes, scope);
                 }
+                // The bindings of the pattern aren't in scope in the `else` block.
+                if let Some(expr) = else_branch {
+                    scopes.set_scope(*expr, scope);
+                    compute_expr_scopes(*expr, body, scopes, scope);
+                }
                 scope = scopes.new_scope(scope);
                 scopes.add_bindings(body, scope, *pat);
             }
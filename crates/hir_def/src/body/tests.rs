@@ -176,7 +176,7 @@ fn f() {
     m!();
 
     m!(hi);
-  //^^^^^^ leftover tokens
+     //^^ no rules expected the token `hi`
 }
       "#,
     );
@@ -216,7 +216,7 @@ macro_rules! outer {
 
 fn f() {
     outer!();
-  //^^^^^^^^ leftover tokens
+  //^^^^^^^^ no rules expected the token `1`
 }
         "#,
     )
@@ -165,6 +165,7 @@ pub enum Expr {
         arg_types: Vec<Option<Interned<TypeRef>>>,
         ret_type: Option<Interned<TypeRef>>,
         body: ExprId,
+        is_move: bool,
     },
     Tuple {
         exprs: Vec<ExprId>,
@@ -219,6 +220,23 @@ pub enum ArithOp {
     BitAnd,
 }
 
+impl std::fmt::Display for ArithOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ArithOp::Add => "+",
+            ArithOp::Mul => "*",
+            ArithOp::Sub => "-",
+            ArithOp::Div => "/",
+            ArithOp::Rem => "%",
+            ArithOp::Shl => "<<",
+            ArithOp::Shr => ">>",
+            ArithOp::BitXor => "^",
+            ArithOp::BitOr => "|",
+            ArithOp::BitAnd => "&",
+        })
+    }
+}
+
 pub use syntax::ast::PrefixOp as UnaryOp;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Array {
@@ -241,8 +259,16 @@ pub struct RecordLitField {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Statement {
-    Let { pat: PatId, type_ref: Option<Interned<TypeRef>>, initializer: Option<ExprId> },
-    Expr { expr: ExprId, has_semi: bool },
+    Let {
+        pat: PatId,
+        type_ref: Option<Interned<TypeRef>>,
+        initializer: Option<ExprId>,
+        else_branch: Option<ExprId>,
+    },
+    Expr {
+        expr: ExprId,
+        has_semi: bool,
+    },
 }
 
 impl Expr {
@@ -260,10 +286,13 @@ impl Expr {
             Expr::Block { statements, tail, .. } => {
                 for stmt in statements {
                     match stmt {
-                        Statement::Let { initializer, .. } => {
+                        Statement::Let { initializer, else_branch, .. } => {
                             if let Some(expr) = initializer {
                                 f(*expr);
                             }
+                            if let Some(expr) = else_branch {
+                                f(*expr);
+                            }
                         }
                         Statement::Expr { expr: expression, .. } => f(*expression),
                     }
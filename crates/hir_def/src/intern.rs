@@ -217,6 +217,7 @@ impl_internable!(
     crate::type_ref::TypeRef,
     crate::type_ref::TraitRef,
     crate::type_ref::TypeBound,
+    crate::type_ref::UnevaluatedConst,
     crate::path::ModPath,
     crate::path::GenericArgs,
     crate::attr::AttrInput,
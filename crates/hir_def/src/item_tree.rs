@@ -65,7 +65,7 @@ use crate::{
     generics::GenericParams,
     intern::Interned,
     path::{path, AssociatedTypeBinding, GenericArgs, ImportAlias, ModPath, Path, PathKind},
-    type_ref::{Mutability, TraitRef, TypeBound, TypeRef},
+    type_ref::{ConstRef, Mutability, TraitRef, TypeBound, TypeRef},
     visibility::RawVisibility,
 };
 
@@ -909,6 +909,8 @@ impl From<AssocItem> for ModItem {
 pub struct Variant {
     pub name: Name,
     pub fields: Fields,
+    /// The explicit discriminant, e.g. the `1 << 3` in `A = 1 << 3`.
+    pub discriminant: Option<ConstRef>,
 }
 
 /// A range of densely allocated ItemTree IDs.
@@ -10,7 +10,7 @@ use syntax::{
 
 use crate::{
     generics::{GenericParams, TypeParamData, TypeParamProvenance},
-    type_ref::{LifetimeRef, TraitRef},
+    type_ref::{ConstRef, LifetimeRef, TraitRef},
 };
 
 use super::*;
@@ -301,7 +301,8 @@ impl<'a> Ctx<'a> {
     fn lower_variant(&mut self, variant: &ast::Variant) -> Option<Variant> {
         let name = variant.name()?.as_name();
         let fields = self.lower_fields(&variant.kind());
-        let res = Variant { name, fields };
+        let discriminant = variant.expr().map(|it| ConstRef::from_expr(&self.body_ctx, it));
+        let res = Variant { name, fields, discriminant };
         Some(res)
     }
 
@@ -303,10 +303,13 @@ impl<'a> Printer<'a> {
                 self.print_where_clause_and_opening_brace(generic_params);
                 self.indented(|this| {
                     for variant in variants.clone() {
-                        let Variant { name, fields } = &this.tree[variant];
+                        let Variant { name, fields, discriminant } = &this.tree[variant];
                         this.print_attrs_of(variant);
                         w!(this, "{}", name);
                         this.print_fields(fields);
+                        if let Some(discriminant) = discriminant {
+                            w!(this, " = {}", discriminant);
+                        }
                         wln!(this, ",");
                     }
                 });
@@ -1152,12 +1152,22 @@ impl DefCollector<'_> {
         let loc: MacroCallLoc = self.db.lookup_intern_macro(macro_call_id);
         let err = self.db.macro_expand_error(macro_call_id);
         if let Some(err) = err {
-            let diag = match err {
+            let diag = match &err {
                 hir_expand::ExpandError::UnresolvedProcMacro => {
                     // Missing proc macros are non-fatal, so they are handled specially.
                     DefDiagnostic::unresolved_proc_macro(module_id, loc.kind.clone())
                 }
-                _ => DefDiagnostic::macro_error(module_id, loc.kind.clone(), err.to_string()),
+                hir_expand::ExpandError::NoRuleExpectedToken(failure) => {
+                    let token_range =
+                        self.db.macro_arg(macro_call_id).and_then(|arg| failure.range(&arg.1));
+                    DefDiagnostic::macro_error(
+                        module_id,
+                        loc.kind.clone(),
+                        err.to_string(),
+                        token_range,
+                    )
+                }
+                _ => DefDiagnostic::macro_error(module_id, loc.kind.clone(), err.to_string(), None),
             };
 
             self.def_map.diagnostics.push(diag);
@@ -1912,6 +1922,7 @@ impl ModCollector<'_, '_> {
                     self.module_id,
                     MacroCallKind::FnLike { ast_id: ast_id.ast_id, fragment },
                     error.unwrap().to_string(),
+                    None,
                 ));
                 return;
             }
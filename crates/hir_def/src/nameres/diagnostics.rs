@@ -3,7 +3,7 @@
 use cfg::{CfgExpr, CfgOptions};
 use hir_expand::MacroCallKind;
 use la_arena::Idx;
-use syntax::ast;
+use syntax::{ast, TextRange};
 
 use crate::{
     item_tree::{self, ItemTreeId},
@@ -26,7 +26,7 @@ pub enum DefDiagnosticKind {
 
     UnresolvedMacroCall { ast: AstId<ast::MacroCall>, path: ModPath },
 
-    MacroError { ast: MacroCallKind, message: String },
+    MacroError { ast: MacroCallKind, message: String, token_range: Option<TextRange> },
 
     UnimplementedBuiltinMacro { ast: AstId<ast::Macro> },
 }
@@ -80,12 +80,18 @@ impl DefDiagnostic {
         Self { in_module: container, kind: DefDiagnosticKind::UnresolvedProcMacro { ast } }
     }
 
+    /// `token_range` is the range of the input token the error points at, relative to the start of
+    /// the macro call's token tree.
     pub(super) fn macro_error(
         container: LocalModuleId,
         ast: MacroCallKind,
         message: String,
+        token_range: Option<TextRange>,
     ) -> Self {
-        Self { in_module: container, kind: DefDiagnosticKind::MacroError { ast, message } }
+        Self {
+            in_module: container,
+            kind: DefDiagnosticKind::MacroError { ast, message, token_range },
+        }
     }
 
     pub(super) fn unresolved_macro_call(
@@ -194,6 +194,22 @@ fn include_macro_should_allow_empty_content() {
     );
 }
 
+#[test]
+fn macro_rules_no_rule_expected_token() {
+    check_diagnostics(
+        r#"
+        macro_rules! m {
+            ($i:ident, $e:expr) => { fn $i() { $e; } };
+        }
+
+        m!(f; 1);
+          //^ no rules expected the token `;`; expected `,`
+        m!(g,);
+           //^ unexpected end of macro invocation; expected expr
+        "#,
+    );
+}
+
 #[test]
 fn good_out_dir_diagnostic() {
     check_diagnostics(
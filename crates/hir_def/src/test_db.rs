@@ -270,12 +270,28 @@ impl TestDB {
             .collect()
     }
 
+    /// Returns the range of the input token a macro error points at, if the macro call is in a
+    /// real file.
+    fn macro_input_range(
+        &self,
+        call: InFile<&SyntaxNode>,
+        token_range: Option<TextRange>,
+    ) -> Option<FileRange> {
+        if call.file_id.call_node(self).is_some() {
+            return None;
+        }
+        let token_tree = ast::MacroCall::cast(call.value.clone())?.token_tree()?;
+        let range = token_range? + token_tree.syntax().text_range().start();
+        Some(FileRange { file_id: call.file_id.original_file(self), range })
+    }
+
     pub(crate) fn diagnostics(&self, cb: &mut dyn FnMut(FileRange, String)) {
         let crate_graph = self.crate_graph();
         for krate in crate_graph.iter() {
             let crate_def_map = self.crate_def_map(krate);
 
             for diag in crate_def_map.diagnostics() {
+                let mut token_range = None;
                 let (node, message): (InFile<SyntaxNode>, &str) = match &diag.kind {
                     DefDiagnosticKind::UnresolvedModule { ast, .. } => {
                         let node = ast.to_node(self.upcast());
@@ -302,7 +318,8 @@ impl TestDB {
                         let node = ast.to_node(self.upcast());
                         (InFile::new(ast.file_id, node.syntax().clone()), "UnresolvedMacroCall")
                     }
-                    DefDiagnosticKind::MacroError { ast, message } => {
+                    DefDiagnosticKind::MacroError { ast, message, token_range: range } => {
+                        token_range = *range;
                         (ast.to_node(self.upcast()), message.as_str())
                     }
                     DefDiagnosticKind::UnimplementedBuiltinMacro { ast } => {
@@ -314,7 +331,9 @@ impl TestDB {
                     }
                 };
 
-                let frange = node.as_ref().original_file_range(self);
+                let frange = self
+                    .macro_input_range(node.as_ref(), token_range)
+                    .unwrap_or_else(|| node.as_ref().original_file_range(self));
                 cb(frange, message.to_string())
             }
 
@@ -323,11 +342,17 @@ impl TestDB {
                     if let ModuleDefId::FunctionId(it) = decl {
                         let source_map = self.body_with_source_map(it.into()).1;
                         for diag in source_map.diagnostics() {
+                            let mut token_range = None;
                             let (ptr, message): (InFile<SyntaxNodePtr>, &str) = match diag {
                                 BodyDiagnostic::InactiveCode { node, .. } => {
                                     (node.clone().map(|it| it.into()), "InactiveCode")
                                 }
-                                BodyDiagnostic::MacroError { node, message } => {
+                                BodyDiagnostic::MacroError {
+                                    node,
+                                    message,
+                                    token_range: range,
+                                } => {
+                                    token_range = *range;
                                     (node.clone().map(|it| it.into()), message.as_str())
                                 }
                                 BodyDiagnostic::UnresolvedProcMacro { node } => {
@@ -340,7 +365,9 @@ impl TestDB {
 
                             let root = self.parse_or_expand(ptr.file_id).unwrap();
                             let node = ptr.map(|ptr| ptr.to_node(&root));
-                            let frange = node.as_ref().original_file_range(self);
+                            let frange = self
+                                .macro_input_range(node.as_ref(), token_range)
+                                .unwrap_or_else(|| node.as_ref().original_file_range(self));
                             cb(frange, message.to_string())
                         }
                     }
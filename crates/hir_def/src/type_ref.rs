@@ -5,7 +5,13 @@ use hir_expand::{name::Name, AstId, InFile};
 use std::convert::TryInto;
 use syntax::ast;
 
-use crate::{body::LowerCtx, intern::Interned, path::Path};
+use crate::{
+    body::LowerCtx,
+    expr::{ArithOp, BinaryOp},
+    intern::Interned,
+    path::Path,
+    ConstId,
+};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Mutability {
@@ -84,9 +90,7 @@ pub enum TypeRef {
     Path(Path),
     RawPtr(Box<TypeRef>, Mutability),
     Reference(Box<TypeRef>, Option<LifetimeRef>, Mutability),
-    // FIXME: for full const generics, the latter element (length) here is going to have to be an
-    // expression that is further lowered later in hir_ty.
-    Array(Box<TypeRef>, ConstScalar),
+    Array(Box<TypeRef>, ConstRef),
     Slice(Box<TypeRef>),
     /// A fn pointer. Last element of the vector is the return type.
     Fn(Vec<TypeRef>, bool /*varargs*/),
@@ -147,14 +151,10 @@ impl TypeRef {
                 TypeRef::RawPtr(Box::new(inner_ty), mutability)
             }
             ast::Type::ArrayType(inner) => {
-                // FIXME: This is a hack. We should probably reuse the machinery of
-                // `hir_def::body::lower` to lower this into an `Expr` and then evaluate it at the
-                // `hir_ty` level, which would allow knowing the type of:
-                // let v: [u8; 2 + 2] = [0u8; 4];
                 let len = inner
                     .expr()
-                    .map(ConstScalar::usize_from_literal_expr)
-                    .unwrap_or(ConstScalar::Unknown);
+                    .map(|it| ConstRef::from_expr(ctx, it))
+                    .unwrap_or(ConstRef::Scalar(ConstScalar::Unknown));
 
                 TypeRef::Array(Box::new(TypeRef::from_ast_opt(&ctx, inner.ty())), len)
             }
@@ -316,12 +316,17 @@ impl TypeBound {
 }
 
 /// A concrete constant value
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ConstScalar {
     // for now, we only support the trivial case of constant evaluating the length of an array
     // Note that this is u64 because the target usize may be bigger than our usize
     Usize(u64),
 
+    /// A value that refers to other constants, e.g. the `N * 2` in `[u8; N * 2]`. Evaluating `N`
+    /// needs type inference of its initializer, which may lower the very type this value is part
+    /// of, so it is only evaluated when the value is needed.
+    Unevaluated(Interned<UnevaluatedConst>),
+
     /// Case of an unknown value that rustc might know but we don't
     // FIXME: this is a hack to get around chalk not being able to represent unevaluatable
     // constants
@@ -334,7 +339,7 @@ impl std::fmt::Display for ConstScalar {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
             ConstScalar::Usize(us) => write!(fmt, "{}", us),
-            ConstScalar::Unknown => write!(fmt, "_"),
+            ConstScalar::Unevaluated(_) | ConstScalar::Unknown => write!(fmt, "_"),
         }
     }
 }
@@ -348,20 +353,12 @@ impl ConstScalar {
         }
     }
 
-    // FIXME: as per the comments on `TypeRef::Array`, this evaluation should not happen at this
-    // parse stage.
-    fn usize_from_literal_expr(expr: ast::Expr) -> ConstScalar {
-        match expr {
-            ast::Expr::Literal(lit) => {
-                let lkind = lit.kind();
-                match lkind {
-                    ast::LiteralKind::IntNumber(num)
-                        if num.suffix() == None || num.suffix() == Some("usize") =>
-                    {
-                        num.value().and_then(|v| v.try_into().ok())
-                    }
-                    _ => None,
-                }
+    fn usize_from_literal(lit: ast::Literal) -> ConstScalar {
+        match lit.kind() {
+            ast::LiteralKind::IntNumber(num)
+                if num.suffix() == None || num.suffix() == Some("usize") =>
+            {
+                num.value().and_then(|v| v.try_into().ok())
             }
             _ => None,
         }
@@ -369,3 +366,66 @@ impl ConstScalar {
         .unwrap_or(ConstScalar::Unknown)
     }
 }
+
+/// An arithmetic expression on constants whose paths have been resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UnevaluatedConst {
+    Usize(u64),
+    Const(ConstId),
+    Neg(Box<UnevaluatedConst>),
+    BinaryOp(Box<UnevaluatedConst>, ArithOp, Box<UnevaluatedConst>),
+}
+
+/// The length of an array type, e.g. the `N * 2` in `[u8; N * 2]`, or the
+/// discriminant of an enum variant.
+///
+/// Only literals, paths and arithmetic on them are represented, their paths
+/// are resolved when lowering the type in `hir_ty`. Any other expression is
+/// lowered to `ConstScalar::Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConstRef {
+    Scalar(ConstScalar),
+    Path(Path),
+    Neg(Box<ConstRef>),
+    BinaryOp(Box<ConstRef>, ArithOp, Box<ConstRef>),
+}
+
+impl ConstRef {
+    pub(crate) fn from_expr(ctx: &LowerCtx, expr: ast::Expr) -> ConstRef {
+        let res = match expr {
+            ast::Expr::Literal(lit) => Some(ConstRef::Scalar(ConstScalar::usize_from_literal(lit))),
+            ast::Expr::ParenExpr(it) => it.expr().map(|it| ConstRef::from_expr(ctx, it)),
+            ast::Expr::PathExpr(it) => {
+                it.path().and_then(|it| ctx.lower_path(it)).map(ConstRef::Path)
+            }
+            ast::Expr::PrefixExpr(it) => match it.op_kind() {
+                Some(ast::PrefixOp::Neg) => {
+                    it.expr().map(|it| ConstRef::Neg(Box::new(ConstRef::from_expr(ctx, it))))
+                }
+                _ => None,
+            },
+            ast::Expr::BinExpr(it) => match it.op_kind().map(BinaryOp::from) {
+                Some(BinaryOp::ArithOp(op)) => {
+                    let lhs = it.lhs().map(|it| ConstRef::from_expr(ctx, it));
+                    let rhs = it.rhs().map(|it| ConstRef::from_expr(ctx, it));
+                    lhs.zip(rhs)
+                        .map(|(lhs, rhs)| ConstRef::BinaryOp(Box::new(lhs), op, Box::new(rhs)))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        res.unwrap_or(ConstRef::Scalar(ConstScalar::Unknown))
+    }
+}
+
+impl std::fmt::Display for ConstRef {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ConstRef::Scalar(it) => write!(fmt, "{}", it),
+            ConstRef::Path(it) => write!(fmt, "{}", it.mod_path()),
+            ConstRef::Neg(it) => write!(fmt, "-{}", it),
+            ConstRef::BinaryOp(lhs, op, rhs) => write!(fmt, "{} {} {}", lhs, op, rhs),
+        }
+    }
+}
@@ -160,6 +160,21 @@ pub fn expand_speculative(
     Some((node.syntax_node(), token))
 }
 
+/// Expands the given `macro_rules!` or macro 2.0 call, recording how its input matched
+/// against each rule of the macro. Returns `None` for other kinds of macros.
+pub fn trace_expansion(db: &dyn AstDatabase, id: MacroCallId) -> Option<mbe::ExpansionTrace> {
+    let loc: MacroCallLoc = db.lookup_intern_macro(id);
+    let macro_def = db.macro_def(loc.def)?;
+    let macro_arg = db.macro_arg(id)?;
+    match &*macro_def {
+        TokenExpander::MacroRules { mac, .. } => Some(mac.trace_expand(&macro_arg.0)),
+        TokenExpander::MacroDef { mac, .. } => Some(mac.trace_expand(&macro_arg.0)),
+        TokenExpander::Builtin(..)
+        | TokenExpander::BuiltinDerive(..)
+        | TokenExpander::ProcMacro(..) => None,
+    }
+}
+
 fn ast_id_map(db: &dyn AstDatabase, file_id: HirFileId) -> Arc<AstIdMap> {
     let map = db.parse_or_expand(file_id).map(|it| AstIdMap::from_source(&it)).unwrap_or_default();
     Arc::new(map)
@@ -18,7 +18,7 @@ mod input;
 use base_db::ProcMacroKind;
 use either::Either;
 
-pub use mbe::{ExpandError, ExpandResult};
+pub use mbe::{ExpandError, ExpandResult, ExpansionTrace, MetaVarBinding, RuleTrace};
 pub use parser::FragmentKind;
 
 use std::hash::Hash;
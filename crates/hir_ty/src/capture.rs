@@ -0,0 +1,281 @@
+//! Computes which locals a closure captures from its environment, and how.
+//!
+//! Closures capture whole variables here, as they do before the 2021 edition:
+//! using a field of a local captures the local itself.
+
+use std::sync::Arc;
+
+use chalk_ir::{cast::Cast, Mutability};
+use hir_def::{
+    body::Body,
+    expr::{BinaryOp, Expr, ExprId, Pat, PatId, Statement, UnaryOp},
+    lang_item::LangItemTarget,
+    path::Path,
+    resolver::{resolver_for_expr, ValueNs},
+    DefWithBodyId, HasModule,
+};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    db::HirDatabase, CallableDefId, Canonical, CanonicalVarKinds, InEnvironment, InferenceResult,
+    Interner, Solution, TraitEnvironment, Ty, TyBuilder, TyExt, TyKind,
+};
+
+/// How a closure captures a local. Ordered from the weakest to the strongest
+/// capture, a local used in several ways is captured by the strongest one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CaptureKind {
+    ByRef,
+    ByMutRef,
+    ByValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedItem {
+    pub local: PatId,
+    pub kind: CaptureKind,
+}
+
+/// Returns the locals captured by the closure `closure` in `owner`'s body, in
+/// the order of their first use.
+pub fn closure_captures(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    closure: ExprId,
+) -> Vec<CapturedItem> {
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let (closure_body, is_move) = match &body[closure] {
+        Expr::Lambda { body, is_move, .. } => (*body, *is_move),
+        _ => return Vec::new(),
+    };
+
+    let mut ctx = CaptureCtx {
+        db,
+        owner,
+        body: &body,
+        infer: &infer,
+        inner_pats: FxHashSet::default(),
+        captures: Vec::new(),
+    };
+    ctx.collect_inner_pats(closure);
+    ctx.walk_expr(closure_body, Usage::Move);
+
+    let mut captures = ctx.captures;
+    if is_move {
+        captures.iter_mut().for_each(|it| it.kind = CaptureKind::ByValue);
+    }
+    captures
+}
+
+/// The way an expression is used by its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Usage {
+    /// The value is moved (or copied, if its type is `Copy`).
+    Move,
+    Ref,
+    MutRef,
+}
+
+struct CaptureCtx<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    /// Bindings introduced inside the closure, these aren't captures.
+    inner_pats: FxHashSet<PatId>,
+    captures: Vec<CapturedItem>,
+}
+
+impl CaptureCtx<'_> {
+    fn collect_inner_pats(&mut self, expr: ExprId) {
+        let body = self.body;
+        match &body[expr] {
+            Expr::Lambda { args, .. } => args.iter().for_each(|&pat| self.collect_pat(pat)),
+            Expr::Block { statements, .. } => {
+                for stmt in statements {
+                    if let Statement::Let { pat, .. } = stmt {
+                        self.collect_pat(*pat);
+                    }
+                }
+            }
+            Expr::For { pat, .. } => self.collect_pat(*pat),
+            Expr::Match { arms, .. } => arms.iter().for_each(|arm| self.collect_pat(arm.pat)),
+            _ => {}
+        }
+        body[expr].walk_child_exprs(|expr| self.collect_inner_pats(expr));
+    }
+
+    fn collect_pat(&mut self, pat: PatId) {
+        self.inner_pats.insert(pat);
+        self.body[pat].walk_child_pats(|pat| self.collect_pat(pat));
+    }
+
+    fn walk_expr(&mut self, expr: ExprId, usage: Usage) {
+        let body = self.body;
+        match &body[expr] {
+            Expr::Path(path) => {
+                if let Some(pat) = self.captured_local(expr, path) {
+                    let kind = match usage {
+                        Usage::Move if self.is_copy(&self.infer[pat]) => CaptureKind::ByRef,
+                        Usage::Move => CaptureKind::ByValue,
+                        Usage::Ref => CaptureKind::ByRef,
+                        Usage::MutRef => CaptureKind::ByMutRef,
+                    };
+                    self.add_capture(pat, kind);
+                }
+            }
+            Expr::Ref { expr, mutability, .. } => {
+                let usage = match mutability {
+                    hir_def::type_ref::Mutability::Shared => Usage::Ref,
+                    hir_def::type_ref::Mutability::Mut => Usage::MutRef,
+                };
+                self.walk_expr(*expr, usage);
+            }
+            Expr::Field { expr: base, .. } => {
+                let usage = match usage {
+                    Usage::Move if self.is_copy(&self.infer[expr]) => Usage::Ref,
+                    Usage::Move if self.infer[*base].as_reference().is_some() => Usage::Ref,
+                    usage => usage,
+                };
+                self.walk_expr(*base, usage);
+            }
+            Expr::Index { base, index } => {
+                let usage = if usage == Usage::MutRef { Usage::MutRef } else { Usage::Ref };
+                self.walk_expr(*base, usage);
+                self.walk_expr(*index, Usage::Move);
+            }
+            Expr::UnaryOp { expr, op: UnaryOp::Deref } => {
+                let usage = if usage == Usage::MutRef { Usage::MutRef } else { Usage::Ref };
+                self.walk_expr(*expr, usage);
+            }
+            Expr::MethodCall { receiver, args, .. } => {
+                let usage = self.receiver_usage(expr);
+                self.walk_expr(*receiver, usage);
+                args.iter().for_each(|&arg| self.walk_expr(arg, Usage::Move));
+            }
+            Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { .. }) } => {
+                self.walk_expr(*lhs, Usage::MutRef);
+                self.walk_expr(*rhs, Usage::Move);
+            }
+            Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::CmpOp(_)) } => {
+                self.walk_expr(*lhs, Usage::Ref);
+                self.walk_expr(*rhs, Usage::Ref);
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                let usage = self.scrutinee_usage(*scrutinee, arms.iter().map(|arm| arm.pat));
+                self.walk_expr(*scrutinee, usage);
+                for arm in arms {
+                    if let Some(guard) = arm.guard {
+                        self.walk_expr(guard, Usage::Move);
+                    }
+                    self.walk_expr(arm.expr, Usage::Move);
+                }
+            }
+            Expr::Block { statements, tail, .. } => {
+                for stmt in statements {
+                    match stmt {
+                        Statement::Let { pat, initializer: Some(init), else_branch, .. } => {
+                            let usage = self.scrutinee_usage(*init, std::iter::once(*pat));
+                            self.walk_expr(*init, usage);
+                            if let Some(else_branch) = else_branch {
+                                self.walk_expr(*else_branch, Usage::Move);
+                            }
+                        }
+                        Statement::Let { initializer: None, .. } => {}
+                        Statement::Expr { expr, .. } => self.walk_expr(*expr, Usage::Move),
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.walk_expr(*tail, Usage::Move);
+                }
+            }
+            Expr::Lambda { .. } => {
+                for item in closure_captures(self.db, self.owner, expr) {
+                    if !self.inner_pats.contains(&item.local) {
+                        self.add_capture(item.local, item.kind);
+                    }
+                }
+            }
+            it => it.walk_child_exprs(|expr| self.walk_expr(expr, Usage::Move)),
+        }
+    }
+
+    /// Returns the local `path` refers to, if it's bound outside the closure.
+    fn captured_local(&self, expr: ExprId, path: &Path) -> Option<PatId> {
+        let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+        match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())? {
+            ValueNs::LocalBinding(pat) if !self.inner_pats.contains(&pat) => Some(pat),
+            _ => None,
+        }
+    }
+
+    fn add_capture(&mut self, local: PatId, kind: CaptureKind) {
+        match self.captures.iter_mut().find(|it| it.local == local) {
+            Some(item) => item.kind = item.kind.max(kind),
+            None => self.captures.push(CapturedItem { local, kind }),
+        }
+    }
+
+    /// Method receivers are borrowed or moved depending on the `self` parameter.
+    fn receiver_usage(&self, call: ExprId) -> Usage {
+        let (func, _) = match self.infer.method_resolution(call) {
+            Some(it) => it,
+            None => return Usage::Ref,
+        };
+        let sig = self.db.callable_item_signature(CallableDefId::FunctionId(func));
+        match sig.skip_binders().params().first().map(|ty| ty.kind(&Interner)) {
+            Some(TyKind::Ref(Mutability::Mut, ..)) => Usage::MutRef,
+            Some(TyKind::Ref(Mutability::Not, ..)) => Usage::Ref,
+            _ => Usage::Move,
+        }
+    }
+
+    /// A matched place is moved if a pattern binds a non-`Copy` part of it by
+    /// value, and borrowed otherwise.
+    fn scrutinee_usage(&self, scrutinee: ExprId, pats: impl Iterator<Item = PatId>) -> Usage {
+        let mut usage = Usage::Ref;
+        let mut stack: Vec<PatId> = pats.collect();
+        while let Some(pat) = stack.pop() {
+            let pat_usage = match &self.body[pat] {
+                Pat::Bind { mode, .. } => match mode {
+                    hir_def::expr::BindingAnnotation::Ref => Usage::Ref,
+                    hir_def::expr::BindingAnnotation::RefMut => Usage::MutRef,
+                    _ if self.is_copy(&self.infer[pat]) => Usage::Ref,
+                    _ => Usage::Move,
+                },
+                _ => Usage::Ref,
+            };
+            usage = match (usage, pat_usage) {
+                (Usage::Move, _) | (_, Usage::Move) => Usage::Move,
+                (Usage::MutRef, _) | (_, Usage::MutRef) => Usage::MutRef,
+                _ => Usage::Ref,
+            };
+            self.body[pat].walk_child_pats(|pat| stack.push(pat));
+        }
+        // Bindings in a place behind a reference can't move out of it.
+        match self.infer[scrutinee].as_reference() {
+            Some((_, _, Mutability::Mut)) if usage == Usage::Move => Usage::MutRef,
+            Some((_, _, Mutability::Not)) => Usage::Ref,
+            _ => usage,
+        }
+    }
+
+    fn is_copy(&self, ty: &Ty) -> bool {
+        let krate = self.owner.module(self.db.upcast()).krate();
+        let copy_trait = match self.db.lang_item(krate, "copy".into()) {
+            Some(LangItemTarget::TraitId(it)) => it,
+            _ => return false,
+        };
+        let env = self.owner.as_generic_def_id().map_or_else(
+            || Arc::new(TraitEnvironment::empty(krate)),
+            |def| self.db.trait_environment(def),
+        );
+        let trait_ref = TyBuilder::trait_ref(self.db, copy_trait).push(ty.clone()).build();
+        let goal = Canonical {
+            value: InEnvironment::new(&env.env, trait_ref.cast(&Interner)),
+            binders: CanonicalVarKinds::empty(&Interner),
+        };
+        matches!(self.db.trait_solve(krate, goal), Some(Solution::Unique(_)))
+    }
+}
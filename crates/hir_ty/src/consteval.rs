@@ -1,14 +1,31 @@
 //! Constant evaluation details
 
-use std::convert::TryInto;
+use std::{convert::TryInto, fmt};
 
+use chalk_ir::{IntTy, Scalar, UintTy};
+use either::Either;
 use hir_def::{
-    builtin_type::BuiltinUint,
-    expr::{Expr, Literal},
-    type_ref::ConstScalar,
+    body::Body,
+    builtin_type::{BuiltinInt, BuiltinUint},
+    expr::{
+        ArithOp, BinaryOp, CmpOp, Expr, ExprId, Literal, LogicOp, Ordering, Pat, PatId, Statement,
+    },
+    intern::Interned,
+    path::ModPath,
+    resolver::HasResolver,
+    resolver::{resolver_for_expr, ResolveValueResult, Resolver, TypeNs, ValueNs},
+    type_ref::{ConstRef, ConstScalar, UnevaluatedConst},
+    AssocItemId, ConstId, DefWithBodyId, EnumVariantId, StaticId,
 };
+use rustc_hash::FxHashMap;
+use syntax::ast::PrefixOp;
 
-use crate::{Const, ConstData, ConstValue, Interner, TyKind};
+use crate::{
+    db::HirDatabase,
+    primitive::{int_ty_from_builtin, uint_ty_from_builtin},
+    CallableDefId, Const, ConstData, ConstValue, InferenceResult, Interner, Ty, TyExt, TyKind,
+    ValueTyDefId,
+};
 
 /// Extension trait for [`Const`]
 pub trait ConstExt {
@@ -35,22 +52,696 @@ impl ConstExt for Const {
     }
 }
 
-// FIXME: support more than just evaluating literals
-pub fn eval_usize(expr: &Expr) -> Option<u64> {
-    match expr {
-        Expr::Literal(Literal::Uint(v, None))
-        | Expr::Literal(Literal::Uint(v, Some(BuiltinUint::Usize))) => (*v).try_into().ok(),
+/// The result of evaluating a constant expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputedExpr {
+    Literal(Literal),
+    Tuple(Box<[ComputedExpr]>),
+}
+
+impl ComputedExpr {
+    /// The value as an integer, if it is one.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            ComputedExpr::Literal(Literal::Int(it, _)) => Some(*it),
+            ComputedExpr::Literal(Literal::Uint(it, _)) => (*it).try_into().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            ComputedExpr::Literal(Literal::Bool(it)) => Some(*it),
+            _ => None,
+        }
+    }
+
+    fn int_kind(&self) -> Option<IntKind> {
+        match self {
+            ComputedExpr::Literal(Literal::Int(_, Some(it))) => {
+                Some(IntKind::Signed(int_ty_from_builtin(*it)))
+            }
+            ComputedExpr::Literal(Literal::Uint(_, Some(it))) => {
+                Some(IntKind::Unsigned(uint_ty_from_builtin(*it)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ComputedExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputedExpr::Literal(lit) => match lit {
+                Literal::Int(it, _) => write!(f, "{}", it),
+                Literal::Uint(it, _) => write!(f, "{}", it),
+                Literal::Bool(it) => write!(f, "{}", it),
+                Literal::Char(it) => write!(f, "{:?}", it),
+                Literal::String(it) => write!(f, "{:?}", it),
+                Literal::ByteString(it) => {
+                    f.write_str("b\"")?;
+                    for &byte in it {
+                        write!(f, "{}", std::ascii::escape_default(byte))?;
+                    }
+                    f.write_str("\"")
+                }
+                Literal::Float(it, _) => write!(f, "{}", f64::from_bits(*it)),
+            },
+            ComputedExpr::Tuple(fields) => {
+                f.write_str("(")?;
+                for (idx, field) in fields.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", field)?;
+                }
+                if fields.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstEvalError {
+    /// The expression uses a language feature the evaluator does not handle.
+    NotSupported(&'static str),
+    /// The expression is ill-typed, e.g. `1 + true`.
+    TypeError,
+    /// The expression has syntax errors or missing parts.
+    IncompleteExpr,
+    /// Evaluation would panic at compile time, e.g. on division by zero.
+    Panic(String),
+    /// Arithmetic overflow.
+    Overflow,
+    /// The constant depends on itself, or evaluation exceeded the recursion
+    /// or step limit.
+    Loop,
+}
+
+impl fmt::Display for ConstEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstEvalError::NotSupported(what) => write!(f, "not supported: {}", what),
+            ConstEvalError::TypeError => f.write_str("type error"),
+            ConstEvalError::IncompleteExpr => f.write_str("incomplete expression"),
+            ConstEvalError::Panic(msg) => write!(f, "evaluation panicked: {}", msg),
+            ConstEvalError::Overflow => f.write_str("arithmetic overflow"),
+            ConstEvalError::Loop => f.write_str("cycle or recursion limit reached"),
+        }
+    }
+}
+
+const MAX_CALL_DEPTH: usize = 64;
+const MAX_STEPS: usize = 100_000;
+
+/// State for evaluating expressions of one body.
+pub struct ConstEvalCtx<'a> {
+    pub db: &'a dyn HirDatabase,
+    pub owner: DefWithBodyId,
+    pub body: &'a Body,
+    pub infer: &'a InferenceResult,
+    pub local_data: FxHashMap<PatId, ComputedExpr>,
+    depth: usize,
+    steps: usize,
+}
+
+impl<'a> ConstEvalCtx<'a> {
+    pub fn new(
+        db: &'a dyn HirDatabase,
+        owner: DefWithBodyId,
+        body: &'a Body,
+        infer: &'a InferenceResult,
+    ) -> ConstEvalCtx<'a> {
+        ConstEvalCtx {
+            db,
+            owner,
+            body,
+            infer,
+            local_data: FxHashMap::default(),
+            depth: 0,
+            steps: 0,
+        }
+    }
+
+    fn int_kind_of(&self, expr: ExprId) -> Option<IntKind> {
+        self.infer.type_of_expr.get(expr).and_then(IntKind::from_ty)
+    }
+
+    fn eval_path(&mut self, expr: ExprId, path: &ModPath) -> Result<ComputedExpr, ConstEvalError> {
+        if let Some(AssocItemId::ConstId(konst)) = self.infer.assoc_resolutions_for_expr(expr) {
+            return self.db.const_eval(konst);
+        }
+        let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+        match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path) {
+            Some(ValueNs::LocalBinding(pat)) => self
+                .local_data
+                .get(&pat)
+                .cloned()
+                .ok_or(ConstEvalError::NotSupported("non-const local")),
+            Some(ValueNs::ConstId(konst)) => self.db.const_eval(konst),
+            Some(ValueNs::StaticId(_)) => Err(ConstEvalError::NotSupported("static")),
+            _ => Err(ConstEvalError::NotSupported("path")),
+        }
+    }
+
+    fn eval_call(
+        &mut self,
+        callee: ExprId,
+        args: &[ExprId],
+    ) -> Result<ComputedExpr, ConstEvalError> {
+        let func = match self.infer.type_of_expr.get(callee).and_then(|it| it.callable_def(self.db))
+        {
+            Some(CallableDefId::FunctionId(it)) => it,
+            Some(_) => return Err(ConstEvalError::NotSupported("constructor call")),
+            None => return Err(ConstEvalError::NotSupported("call")),
+        };
+        if !self.db.function_data(func).is_const() {
+            return Err(ConstEvalError::NotSupported("call to non-const fn"));
+        }
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(ConstEvalError::Loop);
+        }
+        let args = args.iter().map(|&arg| eval_const(arg, self)).collect::<Result<Vec<_>, _>>()?;
+
+        let owner = func.into();
+        let body = self.db.body(owner);
+        let infer = self.db.infer(owner);
+        let mut ctx = ConstEvalCtx {
+            db: self.db,
+            owner,
+            body: &body,
+            infer: &infer,
+            local_data: FxHashMap::default(),
+            depth: self.depth + 1,
+            steps: self.steps,
+        };
+        if body.params.len() != args.len() {
+            return Err(ConstEvalError::IncompleteExpr);
+        }
+        for (&param, arg) in body.params.iter().zip(args) {
+            match &body[param] {
+                Pat::Bind { subpat: None, .. } => {
+                    ctx.local_data.insert(param, arg);
+                }
+                Pat::Wild => {}
+                _ => return Err(ConstEvalError::NotSupported("destructuring parameter")),
+            }
+        }
+        let result = eval_const(body.body_expr, &mut ctx);
+        self.steps = ctx.steps;
+        result
+    }
+}
+
+/// Evaluates `expr` in the body of `ctx`.
+pub fn eval_const(
+    expr: ExprId,
+    ctx: &mut ConstEvalCtx<'_>,
+) -> Result<ComputedExpr, ConstEvalError> {
+    ctx.steps += 1;
+    if ctx.steps > MAX_STEPS {
+        return Err(ConstEvalError::Loop);
+    }
+    let body = ctx.body;
+    match &body[expr] {
+        Expr::Missing => Err(ConstEvalError::IncompleteExpr),
+        Expr::Literal(lit) => match lit {
+            Literal::Int(..) | Literal::Uint(..) | Literal::Bool(_) | Literal::ByteString(_) => {
+                Ok(ComputedExpr::Literal(lit.clone()))
+            }
+            // Lowering doesn't record the values of these yet.
+            Literal::Char(_) | Literal::String(_) | Literal::Float(..) => {
+                Err(ConstEvalError::NotSupported("char, string or float literal"))
+            }
+        },
+        Expr::Path(path) => ctx.eval_path(expr, path.mod_path()),
+        Expr::Block { statements, tail, .. } => {
+            for statement in statements {
+                match *statement {
+                    Statement::Let { else_branch: Some(_), .. } => {
+                        return Err(ConstEvalError::NotSupported("let-else"));
+                    }
+                    Statement::Let { pat, initializer, .. } => {
+                        let initializer = initializer.ok_or(ConstEvalError::IncompleteExpr)?;
+                        let value = eval_const(initializer, ctx)?;
+                        match &body[pat] {
+                            Pat::Bind { subpat: None, .. } => {
+                                ctx.local_data.insert(pat, value);
+                            }
+                            Pat::Wild => {}
+                            _ => return Err(ConstEvalError::NotSupported("destructuring let")),
+                        }
+                    }
+                    Statement::Expr { expr, .. } => {
+                        eval_const(expr, ctx)?;
+                    }
+                }
+            }
+            match tail {
+                Some(tail) => eval_const(*tail, ctx),
+                None => Ok(ComputedExpr::Tuple(Box::new([]))),
+            }
+        }
+        Expr::Unsafe { body } => eval_const(*body, ctx),
+        Expr::Tuple { exprs } => {
+            let fields = exprs.iter().map(|&it| eval_const(it, ctx)).collect::<Result<_, _>>()?;
+            Ok(ComputedExpr::Tuple(fields))
+        }
+        &Expr::If { condition, then_branch, else_branch } => {
+            let condition =
+                eval_const(condition, ctx)?.as_bool().ok_or(ConstEvalError::TypeError)?;
+            if condition {
+                eval_const(then_branch, ctx)
+            } else {
+                match else_branch {
+                    Some(else_branch) => eval_const(else_branch, ctx),
+                    None => Ok(ComputedExpr::Tuple(Box::new([]))),
+                }
+            }
+        }
+        &Expr::UnaryOp { expr: operand, op } => {
+            let value = eval_const(operand, ctx)?;
+            match op {
+                PrefixOp::Not => {
+                    if let Some(it) = value.as_bool() {
+                        return Ok(ComputedExpr::Literal(Literal::Bool(!it)));
+                    }
+                    let v = value.as_i128().ok_or(ConstEvalError::TypeError)?;
+                    match ctx.int_kind_of(expr).or_else(|| value.int_kind()) {
+                        Some(kind) => Ok(kind.literal(kind.wrap(!v))),
+                        None => Err(ConstEvalError::NotSupported("`!` on integer of unknown type")),
+                    }
+                }
+                PrefixOp::Neg => {
+                    let v = value.as_i128().ok_or(ConstEvalError::TypeError)?;
+                    let kind = ctx.int_kind_of(expr).or_else(|| value.int_kind());
+                    if let Some(IntKind::Unsigned(_)) = kind {
+                        return Err(ConstEvalError::TypeError);
+                    }
+                    let v = v.checked_neg().ok_or(ConstEvalError::Overflow)?;
+                    int_literal(kind, v)
+                }
+                PrefixOp::Deref => Err(ConstEvalError::NotSupported("deref")),
+            }
+        }
+        &Expr::BinaryOp { lhs, rhs, op } => {
+            let op = op.ok_or(ConstEvalError::IncompleteExpr)?;
+            match op {
+                BinaryOp::LogicOp(op) => {
+                    let lhs = eval_const(lhs, ctx)?.as_bool().ok_or(ConstEvalError::TypeError)?;
+                    let short_circuit = match op {
+                        LogicOp::And => !lhs,
+                        LogicOp::Or => lhs,
+                    };
+                    if short_circuit {
+                        return Ok(ComputedExpr::Literal(Literal::Bool(lhs)));
+                    }
+                    let rhs = eval_const(rhs, ctx)?.as_bool().ok_or(ConstEvalError::TypeError)?;
+                    Ok(ComputedExpr::Literal(Literal::Bool(rhs)))
+                }
+                BinaryOp::ArithOp(op) => {
+                    let l = eval_const(lhs, ctx)?;
+                    let r = eval_const(rhs, ctx)?;
+                    if let (Some(l), Some(r)) = (l.as_bool(), r.as_bool()) {
+                        let value = match op {
+                            ArithOp::BitAnd => l & r,
+                            ArithOp::BitOr => l | r,
+                            ArithOp::BitXor => l ^ r,
+                            _ => return Err(ConstEvalError::TypeError),
+                        };
+                        return Ok(ComputedExpr::Literal(Literal::Bool(value)));
+                    }
+                    let kind =
+                        ctx.int_kind_of(expr).or_else(|| l.int_kind()).or_else(|| r.int_kind());
+                    let l = l.as_i128().ok_or(ConstEvalError::TypeError)?;
+                    let r = r.as_i128().ok_or(ConstEvalError::TypeError)?;
+                    int_literal(kind, eval_arith_op(kind, op, l, r)?)
+                }
+                BinaryOp::CmpOp(op) => {
+                    let l = eval_const(lhs, ctx)?;
+                    let r = eval_const(rhs, ctx)?;
+                    let ordering = match (l.as_i128(), r.as_i128()) {
+                        (Some(l), Some(r)) => l.cmp(&r),
+                        _ => match (l.as_bool(), r.as_bool()) {
+                            (Some(l), Some(r)) => l.cmp(&r),
+                            _ => return Err(ConstEvalError::TypeError),
+                        },
+                    };
+                    let value = match op {
+                        CmpOp::Eq { negated } => ordering.is_eq() != negated,
+                        CmpOp::Ord { ordering: Ordering::Less, strict: true } => ordering.is_lt(),
+                        CmpOp::Ord { ordering: Ordering::Less, strict: false } => ordering.is_le(),
+                        CmpOp::Ord { ordering: Ordering::Greater, strict: true } => {
+                            ordering.is_gt()
+                        }
+                        CmpOp::Ord { ordering: Ordering::Greater, strict: false } => {
+                            ordering.is_ge()
+                        }
+                    };
+                    Ok(ComputedExpr::Literal(Literal::Bool(value)))
+                }
+                BinaryOp::Assignment { .. } => Err(ConstEvalError::NotSupported("assignment")),
+            }
+        }
+        &Expr::Cast { expr: operand, .. } => {
+            let value = eval_const(operand, ctx)?;
+            let kind = ctx
+                .int_kind_of(expr)
+                .ok_or(ConstEvalError::NotSupported("cast to a non-integer type"))?;
+            let v = match value.as_bool() {
+                Some(it) => it as i128,
+                None => value.as_i128().ok_or(ConstEvalError::TypeError)?,
+            };
+            Ok(kind.literal(kind.wrap(v)))
+        }
+        Expr::Call { callee, args } => ctx.eval_call(*callee, args),
+        _ => Err(ConstEvalError::NotSupported("expression")),
+    }
+}
+
+fn eval_arith_op(
+    kind: Option<IntKind>,
+    op: ArithOp,
+    l: i128,
+    r: i128,
+) -> Result<i128, ConstEvalError> {
+    let value = match op {
+        ArithOp::Add => l.checked_add(r),
+        ArithOp::Sub => l.checked_sub(r),
+        ArithOp::Mul => l.checked_mul(r),
+        ArithOp::Div | ArithOp::Rem if r == 0 => {
+            let msg = match op {
+                ArithOp::Div => "attempt to divide by zero",
+                _ => "attempt to calculate the remainder with a divisor of zero",
+            };
+            return Err(ConstEvalError::Panic(msg.to_string()));
+        }
+        ArithOp::Div => l.checked_div(r),
+        ArithOp::Rem => l.checked_rem(r),
+        ArithOp::BitAnd => Some(l & r),
+        ArithOp::BitOr => Some(l | r),
+        ArithOp::BitXor => Some(l ^ r),
+        ArithOp::Shl | ArithOp::Shr => {
+            let bits = kind.map_or(128, IntKind::bits);
+            let shift: u32 = match r.try_into() {
+                Ok(it) if it < bits => it,
+                _ => return Err(ConstEvalError::Overflow),
+            };
+            match op {
+                ArithOp::Shl => Some(kind.map_or(l << shift, |kind| kind.wrap(l << shift))),
+                _ => Some(l >> shift),
+            }
+        }
+    };
+    value.ok_or(ConstEvalError::Overflow)
+}
+
+fn int_literal(kind: Option<IntKind>, value: i128) -> Result<ComputedExpr, ConstEvalError> {
+    match kind {
+        Some(kind) if !kind.contains(value) => Err(ConstEvalError::Overflow),
+        Some(kind) => Ok(kind.literal(value)),
+        None if value < 0 => Ok(ComputedExpr::Literal(Literal::Int(value, None))),
+        None => Ok(ComputedExpr::Literal(Literal::Uint(value as u128, None))),
+    }
+}
+
+/// An integer type. `usize` and `isize` are assumed to be 64 bits wide.
+#[derive(Debug, Clone, Copy)]
+enum IntKind {
+    Signed(IntTy),
+    Unsigned(UintTy),
+}
+
+impl IntKind {
+    fn from_ty(ty: &Ty) -> Option<IntKind> {
+        match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Int(it)) => Some(IntKind::Signed(*it)),
+            TyKind::Scalar(Scalar::Uint(it)) => Some(IntKind::Unsigned(*it)),
+            _ => None,
+        }
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            IntKind::Signed(IntTy::I8) | IntKind::Unsigned(UintTy::U8) => 8,
+            IntKind::Signed(IntTy::I16) | IntKind::Unsigned(UintTy::U16) => 16,
+            IntKind::Signed(IntTy::I32) | IntKind::Unsigned(UintTy::U32) => 32,
+            IntKind::Signed(IntTy::I64 | IntTy::Isize)
+            | IntKind::Unsigned(UintTy::U64 | UintTy::Usize) => 64,
+            IntKind::Signed(IntTy::I128) | IntKind::Unsigned(UintTy::U128) => 128,
+        }
+    }
+
+    fn contains(self, value: i128) -> bool {
+        let bits = self.bits();
+        match self {
+            IntKind::Signed(_) if bits == 128 => true,
+            IntKind::Signed(_) => (-(1 << (bits - 1))..(1 << (bits - 1))).contains(&value),
+            // `u128` values above `i128::MAX` are not representable here.
+            IntKind::Unsigned(_) if bits == 128 => value >= 0,
+            IntKind::Unsigned(_) => (0..(1 << bits)).contains(&value),
+        }
+    }
+
+    /// Truncates `value` to this type, like an `as` cast.
+    fn wrap(self, value: i128) -> i128 {
+        let bits = self.bits();
+        if bits == 128 {
+            return value;
+        }
+        let truncated = value & ((1 << bits) - 1);
+        match self {
+            IntKind::Signed(_) if truncated >= 1 << (bits - 1) => truncated - (1 << bits),
+            _ => truncated,
+        }
+    }
+
+    fn literal(self, value: i128) -> ComputedExpr {
+        let lit = match self {
+            IntKind::Signed(it) => Literal::Int(value, Some(builtin_int(it))),
+            IntKind::Unsigned(it) => Literal::Uint(value as u128, Some(builtin_uint(it))),
+        };
+        ComputedExpr::Literal(lit)
+    }
+}
+
+fn builtin_int(ty: IntTy) -> BuiltinInt {
+    match ty {
+        IntTy::Isize => BuiltinInt::Isize,
+        IntTy::I8 => BuiltinInt::I8,
+        IntTy::I16 => BuiltinInt::I16,
+        IntTy::I32 => BuiltinInt::I32,
+        IntTy::I64 => BuiltinInt::I64,
+        IntTy::I128 => BuiltinInt::I128,
+    }
+}
+
+fn builtin_uint(ty: UintTy) -> BuiltinUint {
+    match ty {
+        UintTy::Usize => BuiltinUint::Usize,
+        UintTy::U8 => BuiltinUint::U8,
+        UintTy::U16 => BuiltinUint::U16,
+        UintTy::U32 => BuiltinUint::U32,
+        UintTy::U64 => BuiltinUint::U64,
+        UintTy::U128 => BuiltinUint::U128,
+    }
+}
+
+pub(crate) fn const_eval_query(
+    db: &dyn HirDatabase,
+    konst: ConstId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    eval_item_body(db, konst.into(), konst.into())
+}
+
+pub(crate) fn const_eval_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _konst: &ConstId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    Err(ConstEvalError::Loop)
+}
+
+/// Evaluates the initializer of a `static`.
+pub fn eval_static(db: &dyn HirDatabase, id: StaticId) -> Result<ComputedExpr, ConstEvalError> {
+    eval_item_body(db, id.into(), id.into())
+}
+
+fn eval_item_body(
+    db: &dyn HirDatabase,
+    def: DefWithBodyId,
+    value_def: ValueTyDefId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let body = db.body(def);
+    let infer = db.infer(def);
+    let mut ctx = ConstEvalCtx::new(db, def, &body, &infer);
+    let value = eval_const(body.body_expr, &mut ctx)?;
+
+    // Give the value the declared type of the item, so that e.g.
+    // `const X: u8 = 256;` is reported as an overflow.
+    let ty = db.value_ty(value_def);
+    match (IntKind::from_ty(ty.skip_binders()), value.as_i128()) {
+        (Some(kind), Some(v)) => int_literal(Some(kind), v),
+        _ => Ok(value),
+    }
+}
+
+/// Computes the discriminant of an enum variant, counting up from the
+/// previous explicit discriminant (or zero). The discriminants have the type
+/// set by the enum's `repr`, `isize` by default.
+pub fn enum_variant_discriminant(db: &dyn HirDatabase, variant: EnumVariantId) -> Option<i128> {
+    let enum_data = db.enum_data(variant.parent);
+    let resolver = variant.parent.resolver(db.upcast());
+    let kind = match enum_data.repr.and_then(|it| it.int) {
+        Some(Either::Left(it)) => IntKind::Signed(int_ty_from_builtin(it)),
+        Some(Either::Right(it)) => IntKind::Unsigned(uint_ty_from_builtin(it)),
+        None => IntKind::Signed(IntTy::Isize),
+    };
+    let mut value = -1;
+    for (id, data) in enum_data.variants.iter() {
+        value = match &data.discriminant {
+            Some(discriminant) => eval_const_ref_as(db, &resolver, discriminant, kind)?,
+            None => value.checked_add(1).filter(|&it| kind.contains(it))?,
+        };
+        if id == variant.local_id {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Evaluates the length of an array repeat expression, e.g. the `N * 2` in
+/// `[0; N * 2]`.
+pub fn eval_usize(expr: ExprId, mut ctx: ConstEvalCtx<'_>) -> Option<u64> {
+    eval_const(expr, &mut ctx).ok()?.as_i128()?.try_into().ok()
+}
+
+/// Lowers the length of an array type, e.g. the `N * 2` in `[u8; N * 2]`.
+///
+/// Lengths that refer to other constants are only resolved here and evaluated by
+/// [`try_const_usize`], see [`ConstScalar::Unevaluated`].
+pub fn lower_array_len(db: &dyn HirDatabase, resolver: &Resolver, len: &ConstRef) -> Const {
+    let value = match resolve_const_ref(db, resolver, len) {
+        Some(UnevaluatedConst::Usize(it)) => ConstScalar::Usize(it),
+        Some(it) if !refers_to_consts(&it) => {
+            match eval_unevaluated(db, &it, IntKind::Unsigned(UintTy::Usize)) {
+                Some(it) => ConstScalar::Usize(it as u64),
+                None => ConstScalar::Unknown,
+            }
+        }
+        Some(it) => ConstScalar::Unevaluated(Interned::new(it)),
+        None => ConstScalar::Unknown,
+    };
+    usize_const_from_scalar(value)
+}
+
+/// The value of a `usize` constant, e.g. the length of an array type, evaluating
+/// it if necessary.
+pub fn try_const_usize(db: &dyn HirDatabase, c: &Const) -> Option<u64> {
+    match &c.data(&Interner).value {
+        ConstValue::Concrete(it) => match &it.interned {
+            ConstScalar::Usize(it) => Some(*it),
+            ConstScalar::Unevaluated(it) => {
+                eval_unevaluated(db, it, IntKind::Unsigned(UintTy::Usize))?.try_into().ok()
+            }
+            ConstScalar::Unknown => None,
+        },
         _ => None,
     }
 }
 
+fn eval_const_ref_as(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    value: &ConstRef,
+    kind: IntKind,
+) -> Option<i128> {
+    eval_unevaluated(db, &resolve_const_ref(db, resolver, value)?, kind)
+}
+
+fn resolve_const_ref(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    value: &ConstRef,
+) -> Option<UnevaluatedConst> {
+    match value {
+        ConstRef::Scalar(it) => it.as_usize().map(UnevaluatedConst::Usize),
+        ConstRef::Path(path) => {
+            let path = path.mod_path();
+            let konst = match resolver.resolve_path_in_value_ns(db.upcast(), path)? {
+                ResolveValueResult::ValueNs(ValueNs::ConstId(it)) => it,
+                // `Self::CONST` in an impl
+                ResolveValueResult::Partial(TypeNs::SelfType(impl_id), 1) => {
+                    let name = path.segments().last()?;
+                    db.impl_data(impl_id).items.iter().find_map(|&item| match item {
+                        AssocItemId::ConstId(it)
+                            if db.const_data(it).name.as_ref() == Some(name) =>
+                        {
+                            Some(it)
+                        }
+                        _ => None,
+                    })?
+                }
+                _ => return None,
+            };
+            Some(UnevaluatedConst::Const(konst))
+        }
+        ConstRef::Neg(it) => {
+            Some(UnevaluatedConst::Neg(Box::new(resolve_const_ref(db, resolver, it)?)))
+        }
+        ConstRef::BinaryOp(lhs, op, rhs) => {
+            let lhs = resolve_const_ref(db, resolver, lhs)?;
+            let rhs = resolve_const_ref(db, resolver, rhs)?;
+            Some(UnevaluatedConst::BinaryOp(Box::new(lhs), *op, Box::new(rhs)))
+        }
+    }
+}
+
+fn refers_to_consts(value: &UnevaluatedConst) -> bool {
+    match value {
+        UnevaluatedConst::Usize(_) => false,
+        UnevaluatedConst::Const(_) => true,
+        UnevaluatedConst::Neg(it) => refers_to_consts(it),
+        UnevaluatedConst::BinaryOp(lhs, _, rhs) => refers_to_consts(lhs) || refers_to_consts(rhs),
+    }
+}
+
+fn eval_unevaluated(db: &dyn HirDatabase, value: &UnevaluatedConst, kind: IntKind) -> Option<i128> {
+    match value {
+        UnevaluatedConst::Usize(it) => Some(i128::from(*it)),
+        UnevaluatedConst::Const(konst) => db.const_eval(*konst).ok()?.as_i128(),
+        UnevaluatedConst::Neg(it) => {
+            if let IntKind::Unsigned(_) = kind {
+                return None;
+            }
+            let value = match &**it {
+                // `-128` is a valid `i8`, even though `128` isn't.
+                UnevaluatedConst::Usize(it) => i128::from(*it),
+                it => eval_unevaluated(db, it, kind)?,
+            };
+            value.checked_neg()
+        }
+        UnevaluatedConst::BinaryOp(lhs, op, rhs) => {
+            let lhs = eval_unevaluated(db, lhs, kind)?;
+            let rhs = eval_unevaluated(db, rhs, kind)?;
+            eval_arith_op(Some(kind), *op, lhs, rhs).ok()
+        }
+    }
+    .filter(|&it| kind.contains(it))
+}
+
 /// Interns a possibly-unknown target usize
 pub fn usize_const(value: Option<u64>) -> Const {
+    usize_const_from_scalar(value.map(ConstScalar::Usize).unwrap_or(ConstScalar::Unknown))
+}
+
+fn usize_const_from_scalar(value: ConstScalar) -> Const {
     ConstData {
         ty: TyKind::Scalar(chalk_ir::Scalar::Uint(chalk_ir::UintTy::Usize)).intern(&Interner),
-        value: ConstValue::Concrete(chalk_ir::ConcreteConst {
-            interned: value.map(|value| ConstScalar::Usize(value)).unwrap_or(ConstScalar::Unknown),
-        }),
+        value: ConstValue::Concrete(chalk_ir::ConcreteConst { interned: value }),
     }
     .intern(&Interner)
 }
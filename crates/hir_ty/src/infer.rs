@@ -72,6 +72,16 @@ pub(crate) fn infer_query(db: &dyn HirDatabase, def: DefWithBodyId) -> Arc<Infer
     Arc::new(ctx.resolve_all())
 }
 
+/// Bodies can depend on themselves through constants evaluated during inference, e.g. the array
+/// length in `const N: usize = [0; N].len();`. Such bodies are left uninferred.
+pub(crate) fn infer_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _def: &DefWithBodyId,
+) -> Arc<InferenceResult> {
+    Arc::new(InferenceResult::default())
+}
+
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 enum ExprOrPatId {
     ExprId(ExprId),
@@ -140,6 +150,9 @@ pub struct InferenceResult {
     variant_resolutions: FxHashMap<ExprOrPatId, VariantId>,
     /// For each associated item record what it resolves to
     assoc_resolutions: FxHashMap<ExprOrPatId, AssocItemId>,
+    /// For each method call or field access expr that failed to resolve,
+    /// records the type of the receiver.
+    unresolved_accesses: FxHashMap<ExprId, Ty>,
     diagnostics: Vec<InferenceDiagnostic>,
     pub type_of_expr: ArenaMap<ExprId, Ty>,
     /// For each pattern record the type it resolves to.
@@ -173,6 +186,9 @@ impl InferenceResult {
     pub fn assoc_resolutions_for_pat(&self, id: PatId) -> Option<AssocItemId> {
         self.assoc_resolutions.get(&id.into()).copied()
     }
+    pub fn unresolved_accesses(&self) -> impl Iterator<Item = (ExprId, &Ty)> {
+        self.unresolved_accesses.iter().map(|(&expr, ty)| (expr, ty))
+    }
     pub fn type_mismatch_for_expr(&self, expr: ExprId) -> Option<&TypeMismatch> {
         self.type_mismatches.get(&expr.into())
     }
@@ -299,6 +315,9 @@ impl<'a> InferenceContext<'a> {
         for (_, subst) in result.method_resolutions.values_mut() {
             *subst = self.table.resolve_completely(subst.clone());
         }
+        for ty in result.unresolved_accesses.values_mut() {
+            *ty = self.table.resolve_completely(ty.clone());
+        }
         result
     }
 
@@ -322,6 +341,16 @@ impl<'a> InferenceContext<'a> {
         self.result.assoc_resolutions.insert(id, item);
     }
 
+    /// Records that the method call or field access `expr` couldn't be
+    /// resolved, unless the receiver type isn't known yet.
+    fn write_unresolved_access(&mut self, expr: ExprId, receiver_ty: &Ty) {
+        let receiver_ty = self.resolve_ty_shallow(receiver_ty);
+        if matches!(receiver_ty.kind(&Interner), TyKind::InferenceVar(..) | TyKind::Error) {
+            return;
+        }
+        self.result.unresolved_accesses.insert(expr, receiver_ty);
+    }
+
     fn write_pat_ty(&mut self, pat: PatId, ty: Ty) {
         self.result.type_of_pat.insert(pat, ty);
     }
@@ -247,7 +247,7 @@ impl<'a> InferenceContext<'a> {
                 self.diverges = Diverges::Maybe;
                 TyBuilder::unit()
             }
-            Expr::Lambda { body, args, ret_type, arg_types } => {
+            Expr::Lambda { body, args, ret_type, arg_types, .. } => {
                 assert_eq!(args.len(), arg_types.len());
 
                 let mut sig_tys = Vec::new();
@@ -453,7 +453,7 @@ impl<'a> InferenceContext<'a> {
             }
             Expr::Field { expr, name } => {
                 let receiver_ty = self.infer_expr_inner(*expr, &Expectation::none());
-                let canonicalized = self.canonicalize(receiver_ty);
+                let canonicalized = self.canonicalize(receiver_ty.clone());
                 let ty = autoderef::autoderef(
                     self.db,
                     self.resolver.krate(),
@@ -511,8 +511,14 @@ impl<'a> InferenceContext<'a> {
                         }
                         _ => None,
                     }
-                })
-                .unwrap_or(self.err_ty());
+                });
+                let ty = match ty {
+                    Some(ty) => ty,
+                    None => {
+                        self.write_unresolved_access(tgt_expr, &receiver_ty);
+                        self.err_ty()
+                    }
+                };
                 let ty = self.insert_type_vars(ty);
                 self.normalize_associated_types_in(ty)
             }
@@ -760,8 +766,15 @@ impl<'a> InferenceContext<'a> {
                             ),
                         );
 
-                        let repeat_expr = &self.body.exprs[*repeat];
-                        consteval::eval_usize(repeat_expr)
+                        consteval::eval_usize(
+                            *repeat,
+                            consteval::ConstEvalCtx::new(
+                                self.db,
+                                self.owner,
+                                &self.body,
+                                &self.result,
+                            ),
+                        )
                     }
                 };
 
@@ -820,7 +833,7 @@ impl<'a> InferenceContext<'a> {
     ) -> Ty {
         for stmt in statements {
             match stmt {
-                Statement::Let { pat, type_ref, initializer } => {
+                Statement::Let { pat, type_ref, initializer, else_branch } => {
                     let decl_ty =
                         type_ref.as_ref().map(|tr| self.make_ty(tr)).unwrap_or(self.err_ty());
 
@@ -835,6 +848,13 @@ impl<'a> InferenceContext<'a> {
                         }
                     }
 
+                    if let Some(expr) = else_branch {
+                        self.infer_expr_coerce(
+                            *expr,
+                            &Expectation::has_type(TyKind::Never.intern(&Interner)),
+                        );
+                    }
+
                     self.infer_pat(*pat, &ty, BindingMode::default());
                 }
                 Statement::Expr { expr, .. } => {
@@ -898,11 +918,14 @@ impl<'a> InferenceContext<'a> {
                 self.write_method_resolution(tgt_expr, func, substs.clone());
                 (ty, self.db.value_ty(func.into()), substs)
             }
-            None => (
-                receiver_ty,
-                Binders::empty(&Interner, self.err_ty()),
-                Substitution::empty(&Interner),
-            ),
+            None => {
+                self.write_unresolved_access(tgt_expr, &receiver_ty);
+                (
+                    receiver_ty,
+                    Binders::empty(&Interner, self.err_ty()),
+                    Substitution::empty(&Interner),
+                )
+            }
         };
         let method_ty = method_ty.substitute(&Interner, &substs);
         self.register_obligations_for_call(&method_ty);
@@ -0,0 +1,424 @@
+//! Computes the memory layout of types, following what rustc does for the default target
+//! configuration: sizes, alignments, field offsets, and the niches enums can store their
+//! discriminant in.
+//!
+//! The layout of `repr(Rust)` types is unspecified, so the results are only an approximation of
+//! what the compiler actually does. Fields are reordered by alignment, and enums whose only
+//! dataful variant has invalid values to spare (like `Option<&T>`) don't get a separate tag.
+
+use base_db::CrateId;
+use cfg::{CfgAtom, CfgExpr};
+use chalk_ir::{FloatTy, IntTy, UintTy};
+use either::Either;
+use hir_def::{
+    adt::ReprData, AdtId, AttrDefId, EnumId, EnumVariantId, StructId, UnionId, VariantId,
+};
+use stdx::never;
+
+use crate::{
+    consteval::{self, enum_variant_discriminant},
+    db::HirDatabase,
+    primitive::{int_ty_from_builtin, uint_ty_from_builtin},
+    Interner, Scalar, Substitution, Ty, TyKind,
+};
+
+/// The memory layout of a sized type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub size: u64,
+    pub align: u64,
+    /// The offsets of the fields of a struct, union or tuple, in declaration order. Empty for
+    /// other types.
+    pub field_offsets: Vec<u64>,
+    niche: Option<Niche>,
+}
+
+/// Invalid values of a type, which can encode the discriminant of an enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Niche {
+    offset: u64,
+    /// The number of invalid values.
+    available: u128,
+}
+
+impl Layout {
+    fn scalar(size: u64, niche: Option<u128>) -> Layout {
+        Layout {
+            size,
+            align: size,
+            field_offsets: Vec::new(),
+            niche: niche.map(|available| Niche { offset: 0, available }),
+        }
+    }
+
+    fn zst() -> Layout {
+        Layout { size: 0, align: 1, field_offsets: Vec::new(), niche: None }
+    }
+}
+
+/// Computes the layout of `ty` for the target of `krate`. Returns `None` for unsized types and
+/// types whose layout depends on generic parameters or can't be computed.
+pub fn layout_of_ty(db: &dyn HirDatabase, ty: &Ty, krate: CrateId) -> Option<Layout> {
+    let _p = profile::span("layout_of_ty");
+    LayoutCx { db, pointer_size: target_pointer_size(db, krate), depth: 0 }.layout_of(ty)
+}
+
+/// The pointer width of the target of `krate`, as reported by `rustc --print cfg`.
+fn target_pointer_size(db: &dyn HirDatabase, krate: CrateId) -> u64 {
+    let cfg_options = &db.crate_graph()[krate].cfg_options;
+    let is_enabled = |width: &str| {
+        let atom = CfgAtom::KeyValue { key: "target_pointer_width".into(), value: width.into() };
+        cfg_options.check(&CfgExpr::Atom(atom)) == Some(true)
+    };
+    match () {
+        _ if is_enabled("16") => 2,
+        _ if is_enabled("32") => 4,
+        _ => 8,
+    }
+}
+
+/// Types nested deeper than this are assumed to be infinitely large.
+const MAX_DEPTH: usize = 64;
+
+struct LayoutCx<'a> {
+    db: &'a dyn HirDatabase,
+    pointer_size: u64,
+    depth: usize,
+}
+
+/// Where the fields of a struct-like layout start.
+#[derive(Clone, Copy)]
+enum StructKind {
+    /// Fields start at offset zero.
+    AlwaysSized,
+    /// Fields start after an enum tag of the given size and alignment.
+    Prefixed(u64, u64),
+}
+
+impl LayoutCx<'_> {
+    fn layout_of(&mut self, ty: &Ty) -> Option<Layout> {
+        if self.depth > MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let res = self.layout_of_inner(ty);
+        self.depth -= 1;
+        res
+    }
+
+    fn layout_of_inner(&mut self, ty: &Ty) -> Option<Layout> {
+        let layout = match ty.kind(&Interner) {
+            TyKind::Scalar(scalar) => match scalar {
+                // Only 0 and 1 are valid.
+                Scalar::Bool => Layout::scalar(1, Some(254)),
+                // Values above `char::MAX` are invalid.
+                Scalar::Char => Layout::scalar(4, Some((1 << 32) - 0x11_0000)),
+                Scalar::Int(it) => Layout::scalar(self.int_size(*it), None),
+                Scalar::Uint(it) => Layout::scalar(self.uint_size(*it), None),
+                Scalar::Float(FloatTy::F32) => Layout::scalar(4, None),
+                Scalar::Float(FloatTy::F64) => Layout::scalar(8, None),
+            },
+            TyKind::Never | TyKind::FnDef(..) => Layout::zst(),
+            TyKind::Tuple(_, subst) => {
+                let fields = subst
+                    .iter(&Interner)
+                    .map(|it| self.layout_of(it.assert_ty_ref(&Interner)))
+                    .collect::<Option<Vec<_>>>()?;
+                self.univariant(&fields, &ReprData::default(), StructKind::AlwaysSized)?
+            }
+            TyKind::Array(elem, len) => {
+                let len = consteval::try_const_usize(self.db, len)?;
+                let elem = self.layout_of(elem)?;
+                Layout {
+                    size: elem.size.checked_mul(len)?,
+                    align: elem.align,
+                    field_offsets: Vec::new(),
+                    niche: if len > 0 { elem.niche } else { None },
+                }
+            }
+            TyKind::Ref(_, _, pointee) => {
+                let size = self.pointer_size * self.pointer_words(pointee)?;
+                // References are never null.
+                Layout { align: self.pointer_size, ..Layout::scalar(size, Some(1)) }
+            }
+            TyKind::Raw(_, pointee) => {
+                let size = self.pointer_size * self.pointer_words(pointee)?;
+                Layout { align: self.pointer_size, ..Layout::scalar(size, None) }
+            }
+            // Function pointers are never null.
+            TyKind::Function(_) => Layout::scalar(self.pointer_size, Some(1)),
+            TyKind::Adt(crate::AdtId(adt), subst) => match *adt {
+                AdtId::StructId(it) => self.layout_of_struct(it, subst)?,
+                AdtId::UnionId(it) => self.layout_of_union(it, subst)?,
+                AdtId::EnumId(it) => self.layout_of_enum(it, subst)?,
+            },
+            _ => return None,
+        };
+        Some(layout)
+    }
+
+    fn layout_of_struct(&mut self, id: StructId, subst: &Substitution) -> Option<Layout> {
+        let repr = self.db.struct_data(id).repr.unwrap_or_default();
+        let fields = self.field_layouts(id.into(), subst)?;
+        let mut layout = self.univariant(&fields, &repr, StructKind::AlwaysSized)?;
+        // Used by e.g. `NonZeroU32` and `NonNull` to declare zero invalid.
+        let attrs = self.db.attrs(AttrDefId::AdtId(id.into()));
+        let valid_range_start = attrs
+            .by_key("rustc_layout_scalar_valid_range_start")
+            .tt_values()
+            .find_map(|tt| tt.to_string().trim_matches(|c| c == '(' || c == ')').parse().ok());
+        if let Some(start) = valid_range_start {
+            layout.niche = Some(Niche { offset: 0, available: start });
+        }
+        Some(layout)
+    }
+
+    fn layout_of_union(&mut self, id: UnionId, subst: &Substitution) -> Option<Layout> {
+        let repr = self.db.union_data(id).repr.unwrap_or_default();
+        let fields = self.field_layouts(id.into(), subst)?;
+        let mut size = 0;
+        let mut align = repr.align.unwrap_or(1);
+        for field in &fields {
+            size = size.max(field.size);
+            align = align.max(repr.packed.map_or(field.align, |pack| field.align.min(pack)));
+        }
+        Some(Layout {
+            size: align_to(size, align),
+            align,
+            field_offsets: vec![0; fields.len()],
+            niche: None,
+        })
+    }
+
+    fn layout_of_enum(&mut self, id: EnumId, subst: &Substitution) -> Option<Layout> {
+        let data = self.db.enum_data(id);
+        let repr = data.repr.unwrap_or_default();
+        let variant_ids: Vec<EnumVariantId> = data
+            .variants
+            .iter()
+            .map(|(local_id, _)| EnumVariantId { parent: id, local_id })
+            .collect();
+        let variants = variant_ids
+            .iter()
+            .map(|&it| self.field_layouts(it.into(), subst))
+            .collect::<Option<Vec<_>>>()?;
+
+        if variants.is_empty() {
+            return Some(Layout::zst());
+        }
+        let has_explicit_tag = repr.c || repr.int.is_some();
+        if variants.len() == 1 && !has_explicit_tag {
+            let mut layout = self.univariant(&variants[0], &repr, StructKind::AlwaysSized)?;
+            layout.field_offsets.clear();
+            return Some(layout);
+        }
+
+        if !has_explicit_tag {
+            if let Some(layout) = self.niche_filling_enum(&variants, &repr) {
+                return Some(layout);
+            }
+        }
+
+        let discriminants = variant_ids
+            .iter()
+            .map(|&it| enum_variant_discriminant(self.db, it))
+            .collect::<Option<Vec<_>>>()?;
+        let min = discriminants.iter().copied().min()?;
+        let max = discriminants.iter().copied().max()?;
+        let tag_size = match repr.int {
+            Some(Either::Left(it)) => self.int_size(int_ty_from_builtin(it)),
+            Some(Either::Right(it)) => self.uint_size(uint_ty_from_builtin(it)),
+            // `c_int` on all the targets we care about.
+            None if repr.c => 4,
+            None => {
+                [1, 2, 4, 8, 16].iter().copied().find(|&size| fits_in(min, max, size)).unwrap_or(16)
+            }
+        };
+
+        let mut size = tag_size;
+        let mut align = tag_size.max(repr.align.unwrap_or(1));
+        if repr.c {
+            // A `repr(C)` enum is a struct of the tag and a union of the variants.
+            let mut union_size = 0;
+            let mut union_align = 1;
+            for fields in &variants {
+                let variant = self.univariant(fields, &repr, StructKind::AlwaysSized)?;
+                union_size = union_size.max(variant.size);
+                union_align = union_align.max(variant.align);
+            }
+            size = align_to(tag_size, union_align) + union_size;
+            align = align.max(union_align);
+        } else {
+            for fields in &variants {
+                let prefix = StructKind::Prefixed(tag_size, tag_size);
+                let variant = self.univariant(fields, &repr, prefix)?;
+                size = size.max(variant.size);
+                align = align.max(variant.align);
+            }
+        }
+        let tag_values = 1u128.checked_shl(8 * tag_size as u32).unwrap_or(u128::MAX);
+        Some(Layout {
+            size: align_to(size, align),
+            align,
+            field_offsets: Vec::new(),
+            niche: Some(Niche { offset: 0, available: tag_values - variants.len() as u128 }),
+        })
+    }
+
+    /// Lays out an enum with a single dataful variant without a tag, storing the discriminant
+    /// of the other variants in invalid values of the dataful one, like `Option<&T>` does.
+    fn niche_filling_enum(&mut self, variants: &[Vec<Layout>], repr: &ReprData) -> Option<Layout> {
+        let mut dataful =
+            variants.iter().filter(|fields| fields.iter().any(|field| field.size > 0));
+        let dataful_fields = match (dataful.next(), dataful.next()) {
+            (Some(it), None) => it,
+            _ => return None,
+        };
+        let layout = self.univariant(dataful_fields, repr, StructKind::AlwaysSized)?;
+        let niche = layout.niche?;
+        let other_variants = variants.len() as u128 - 1;
+        if niche.available < other_variants {
+            return None;
+        }
+        let align = variants.iter().flatten().map(|field| field.align).fold(layout.align, u64::max);
+        Some(Layout {
+            size: align_to(layout.size, align),
+            align,
+            field_offsets: Vec::new(),
+            niche: Some(Niche { available: niche.available - other_variants, ..niche }),
+        })
+    }
+
+    /// Lays out `fields` like the fields of a struct.
+    fn univariant(&self, fields: &[Layout], repr: &ReprData, kind: StructKind) -> Option<Layout> {
+        let field_align = |field: &Layout| repr.packed.map_or(field.align, |p| field.align.min(p));
+
+        let mut order: Vec<usize> = (0..fields.len()).collect();
+        if !repr.c && repr.int.is_none() {
+            match kind {
+                // Put ZSTs first, then minimize padding by placing the most aligned fields
+                // first.
+                StructKind::AlwaysSized => order.sort_by_key(|&idx| {
+                    let field = &fields[idx];
+                    (field.size > 0, std::cmp::Reverse(field_align(field)))
+                }),
+                // Sort by ascending alignment, so that the layout stays optimal regardless of the
+                // prefix.
+                StructKind::Prefixed(..) => order.sort_by_key(|&idx| field_align(&fields[idx])),
+            }
+        }
+
+        let mut align = 1;
+        let mut offset = 0;
+        if let StructKind::Prefixed(prefix_size, prefix_align) = kind {
+            let prefix_align = repr.packed.map_or(prefix_align, |p| prefix_align.min(p));
+            align = prefix_align;
+            offset = align_to(prefix_size, prefix_align);
+        }
+        let mut field_offsets = vec![0; fields.len()];
+        let mut niche: Option<Niche> = None;
+        for idx in order {
+            let field = &fields[idx];
+            let field_align = field_align(field);
+            align = align.max(field_align);
+            offset = align_to(offset, field_align);
+            field_offsets[idx] = offset;
+            if let Some(field_niche) = field.niche {
+                if niche.map_or(true, |it| field_niche.available > it.available) {
+                    niche = Some(Niche { offset: offset + field_niche.offset, ..field_niche });
+                }
+            }
+            offset = offset.checked_add(field.size)?;
+        }
+        if let Some(repr_align) = repr.align {
+            align = align.max(repr_align);
+        }
+        Some(Layout { size: align_to(offset, align), align, field_offsets, niche })
+    }
+
+    fn field_layouts(&mut self, variant: VariantId, subst: &Substitution) -> Option<Vec<Layout>> {
+        let field_types = self.db.field_types(variant);
+        field_types
+            .iter()
+            .map(|(_, ty)| self.layout_of(&ty.clone().substitute(&Interner, subst)))
+            .collect()
+    }
+
+    /// The number of pointer-sized words of a pointer to `pointee`, two for pointers with
+    /// metadata.
+    fn pointer_words(&self, pointee: &Ty) -> Option<u64> {
+        Some(if self.is_unsized(pointee, 0)? { 2 } else { 1 })
+    }
+
+    fn is_unsized(&self, ty: &Ty, depth: usize) -> Option<bool> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match ty.kind(&Interner) {
+            TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) => Some(true),
+            // Only the last field of a struct can be unsized.
+            TyKind::Adt(crate::AdtId(AdtId::StructId(id)), subst) => {
+                let field_types = self.db.field_types((*id).into());
+                match field_types.iter().last() {
+                    Some((_, ty)) => {
+                        self.is_unsized(&ty.clone().substitute(&Interner, subst), depth + 1)
+                    }
+                    None => Some(false),
+                }
+            }
+            TyKind::Placeholder(_)
+            | TyKind::BoundVar(_)
+            | TyKind::InferenceVar(..)
+            | TyKind::Alias(_)
+            | TyKind::AssociatedType(..)
+            | TyKind::OpaqueType(..)
+            | TyKind::Foreign(_)
+            | TyKind::Error => None,
+            _ => Some(false),
+        }
+    }
+
+    fn int_size(&self, ty: IntTy) -> u64 {
+        match ty {
+            IntTy::Isize => self.pointer_size,
+            IntTy::I8 => 1,
+            IntTy::I16 => 2,
+            IntTy::I32 => 4,
+            IntTy::I64 => 8,
+            IntTy::I128 => 16,
+        }
+    }
+
+    fn uint_size(&self, ty: UintTy) -> u64 {
+        match ty {
+            UintTy::Usize => self.pointer_size,
+            UintTy::U8 => 1,
+            UintTy::U16 => 2,
+            UintTy::U32 => 4,
+            UintTy::U64 => 8,
+            UintTy::U128 => 16,
+        }
+    }
+}
+
+/// Whether all discriminants in `min..=max` can be represented by an integer of `size` bytes.
+fn fits_in(min: i128, max: i128, size: u64) -> bool {
+    let bits = 8 * size as u32;
+    if bits >= 128 {
+        return true;
+    }
+    if min >= 0 {
+        (max as u128) < (1u128 << bits)
+    } else {
+        let bound = 1i128 << (bits - 1);
+        min >= -bound && max < bound
+    }
+}
+
+fn align_to(offset: u64, align: u64) -> u64 {
+    if align == 0 {
+        never!("alignment is zero");
+        return offset;
+    }
+    (offset + align - 1) / align * align
+}
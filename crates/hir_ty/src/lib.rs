@@ -8,6 +8,7 @@ macro_rules! eprintln {
 
 mod autoderef;
 mod builder;
+pub mod capture;
 mod chalk_db;
 mod chalk_ext;
 pub mod consteval;
@@ -23,6 +24,7 @@ pub mod db;
 pub mod diagnostics;
 pub mod diagnostics_sink;
 pub mod display;
+pub mod layout;
 pub mod method_resolution;
 pub mod primitive;
 pub mod traits;
@@ -62,7 +64,7 @@ pub use mapping::{
     to_foreign_def_id, to_placeholder_idx,
 };
 pub use traits::TraitEnvironment;
-pub use utils::all_super_traits;
+pub use utils::{all_super_traits, direct_super_traits};
 pub use walk::TypeWalk;
 
 pub use chalk_ir::{
@@ -176,7 +176,7 @@ impl<'a> TyLoweringContext<'a> {
             TypeRef::Array(inner, len) => {
                 let inner_ty = self.lower_ty(inner);
 
-                let const_len = consteval::usize_const(len.as_usize());
+                let const_len = consteval::lower_array_len(self.db, &self.resolver, len);
 
                 TyKind::Array(inner_ty, const_len).intern(&Interner)
             }
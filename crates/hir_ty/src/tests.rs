@@ -8,6 +8,8 @@ mod method_resolution;
 mod macros;
 mod display_source_code;
 mod incremental;
+mod consteval;
+mod layout;
 
 use std::{env, sync::Arc};
 
@@ -0,0 +1,207 @@
+use base_db::fixture::WithFixture;
+use hir_def::{db::DefDatabase, ModuleDefId};
+
+use crate::{
+    consteval::{ComputedExpr, ConstEvalError},
+    db::HirDatabase,
+    test_db::TestDB,
+};
+
+use super::check_types;
+
+fn eval_goal(ra_fixture: &str) -> Result<ComputedExpr, ConstEvalError> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let const_id = scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::ConstId(it) if db.const_data(it).name.as_ref()?.to_string() == "GOAL" => {
+                Some(it)
+            }
+            _ => None,
+        })
+        .expect("no `GOAL` const in fixture");
+    db.const_eval(const_id)
+}
+
+fn check_number(ra_fixture: &str, answer: i128) {
+    let value = eval_goal(ra_fixture).unwrap();
+    assert_eq!(value.as_i128(), Some(answer), "{:?}", value);
+}
+
+fn check_fail(ra_fixture: &str, error: ConstEvalError) {
+    assert_eq!(eval_goal(ra_fixture), Err(error));
+}
+
+#[test]
+fn arithmetic() {
+    check_number(r#"const GOAL: usize = 2 + 2 * 3 - 1;"#, 7);
+    check_number(r#"const GOAL: i32 = (5 - 8) / 2 % 7;"#, -1);
+    check_number(r#"const GOAL: u8 = 1 << 7 | 3 & 6 ^ 1;"#, 131);
+    check_number(r#"const GOAL: i64 = -(1 >> 1) - 1;"#, -1);
+    check_number(r#"const GOAL: usize = if 2 > 1 && !(3 == 4) { 10 } else { 20 };"#, 10);
+}
+
+#[test]
+fn locals() {
+    check_number(
+        r#"
+const GOAL: usize = {
+    let a = 3;
+    let b = a * 2;
+    a + b
+};
+"#,
+        9,
+    );
+}
+
+#[test]
+fn const_references() {
+    check_number(
+        r#"
+const A: usize = 4;
+mod m {
+    pub const B: usize = 3;
+}
+const GOAL: usize = A * m::B;
+"#,
+        12,
+    );
+}
+
+#[test]
+fn associated_consts() {
+    check_number(
+        r#"
+struct S;
+impl S {
+    const SIZE: usize = 8;
+}
+const GOAL: usize = S::SIZE + 1;
+"#,
+        9,
+    );
+}
+
+#[test]
+fn casts() {
+    check_number(r#"const GOAL: u8 = 300u32 as u8;"#, 44);
+    check_number(r#"const GOAL: i8 = 255u8 as i8;"#, -1);
+    check_number(r#"const GOAL: usize = true as usize + -1i32 as u16 as usize;"#, 65536);
+}
+
+#[test]
+fn const_fn_calls() {
+    check_number(
+        r#"
+const fn square(x: usize) -> usize { x * x }
+const fn fact(n: u32) -> u32 {
+    if n == 0 { 1 } else { n * fact(n - 1) }
+}
+const GOAL: usize = square(3) + fact(5) as usize;
+"#,
+        129,
+    );
+}
+
+#[test]
+fn errors() {
+    check_fail(r#"const GOAL: u8 = 255 + 1;"#, ConstEvalError::Overflow);
+    check_fail(r#"const GOAL: u8 = 256;"#, ConstEvalError::Overflow);
+    check_fail(
+        r#"const GOAL: i32 = 1 / 0;"#,
+        ConstEvalError::Panic("attempt to divide by zero".to_string()),
+    );
+    check_fail(
+        r#"
+fn not_const() -> usize { 0 }
+const GOAL: usize = not_const();
+"#,
+        ConstEvalError::NotSupported("call to non-const fn"),
+    );
+}
+
+#[test]
+fn cycles() {
+    check_fail(
+        r#"
+const A: usize = GOAL + 1;
+const GOAL: usize = A;
+"#,
+        ConstEvalError::Loop,
+    );
+    check_fail(
+        r#"
+const fn forever(n: usize) -> usize { forever(n + 1) }
+const GOAL: usize = forever(0);
+"#,
+        ConstEvalError::Loop,
+    );
+}
+
+#[test]
+fn array_length_cycles() {
+    check_fail(
+        r#"
+const GOAL: usize = {
+    let a = [0u8; GOAL];
+    1
+};
+"#,
+        ConstEvalError::Loop,
+    );
+    check_fail(
+        r#"
+const A: usize = {
+    let a = [0u8; GOAL];
+    1
+};
+const GOAL: usize = {
+    let a = [0u8; A];
+    1
+};
+"#,
+        ConstEvalError::Loop,
+    );
+}
+
+#[test]
+fn array_lengths() {
+    check_types(
+        r#"
+const N: usize = 2;
+struct S;
+impl S {
+    const SIZE: usize = 3;
+    fn f(self) -> [u8; Self::SIZE] { loop {} }
+}
+fn test(a: [u8; N * 2], b: [u8; (N + 1) << 1]) {
+    a;
+  //^ [u8; 4]
+    b;
+  //^ [u8; 6]
+    S.f();
+  //^^^^^ [u8; 3]
+    [0u8; N * 4];
+  //^^^^^^^^^^^^ [u8; 8]
+    [0u8; S::SIZE];
+  //^^^^^^^^^^^^^^ [u8; 3]
+}
+"#,
+    );
+}
+
+#[test]
+fn array_length_cycle_through_signature() {
+    // Inferring `GOAL` lowers the signature of `f`, which must not evaluate `GOAL` again.
+    check_fail(
+        r#"
+pub const GOAL: usize = f().len();
+pub const fn f() -> [u8; GOAL] { [0; GOAL] }
+"#,
+        ConstEvalError::NotSupported("expression"),
+    );
+}
@@ -0,0 +1,198 @@
+use base_db::fixture::WithFixture;
+use hir_def::{db::DefDatabase, AdtId, GenericDefId, ModuleDefId};
+
+use crate::{
+    db::HirDatabase,
+    layout::{layout_of_ty, Layout},
+    test_db::TestDB,
+    Interner, TyBuilder, TyDefId,
+};
+
+/// Computes the layout of the item called `Goal`, which is either an ADT or a type alias.
+fn layout_of_goal(ra_fixture: &str) -> Option<Layout> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let (ty_def, generic_def): (TyDefId, GenericDefId) = scope
+        .declarations()
+        .find_map(|def| {
+            let (name, ty_def, generic_def) = match def {
+                ModuleDefId::AdtId(adt) => {
+                    let name = match adt {
+                        AdtId::StructId(it) => db.struct_data(it).name.clone(),
+                        AdtId::UnionId(it) => db.union_data(it).name.clone(),
+                        AdtId::EnumId(it) => db.enum_data(it).name.clone(),
+                    };
+                    (name, adt.into(), adt.into())
+                }
+                ModuleDefId::TypeAliasId(it) => {
+                    (db.type_alias_data(it).name.clone(), it.into(), it.into())
+                }
+                _ => return None,
+            };
+            (name.to_string() == "Goal").then(|| (ty_def, generic_def))
+        })
+        .expect("no `Goal` item in fixture");
+    let ty = db.ty(ty_def).substitute(&Interner, &TyBuilder::type_params_subst(&db, generic_def));
+    layout_of_ty(&db, &ty, module_id.krate())
+}
+
+fn check_size_and_align(ra_fixture: &str, size: u64, align: u64) {
+    let layout = layout_of_goal(ra_fixture).expect("no layout");
+    assert_eq!((layout.size, layout.align), (size, align), "{:?}", layout);
+}
+
+fn check_offsets(ra_fixture: &str, offsets: &[u64]) {
+    let layout = layout_of_goal(ra_fixture).expect("no layout");
+    assert_eq!(layout.field_offsets, offsets, "{:?}", layout);
+}
+
+#[test]
+fn scalars_and_tuples() {
+    check_size_and_align(r#"type Goal = bool;"#, 1, 1);
+    check_size_and_align(r#"type Goal = char;"#, 4, 4);
+    check_size_and_align(r#"type Goal = (u8, u32, u16);"#, 8, 4);
+    check_size_and_align(r#"type Goal = ();"#, 0, 1);
+    check_size_and_align(r#"type Goal = [u16; 5];"#, 10, 2);
+    check_size_and_align(r#"type Goal = usize;"#, 8, 8);
+}
+
+#[test]
+fn pointers() {
+    check_size_and_align(r#"type Goal = &'static u8;"#, 8, 8);
+    check_size_and_align(r#"type Goal = &'static [u8];"#, 16, 8);
+    check_size_and_align(r#"type Goal = *const str;"#, 16, 8);
+    check_size_and_align(
+        r#"
+struct Unsized { len: usize, data: [u8] }
+type Goal = &'static Unsized;
+"#,
+        16,
+        8,
+    );
+    check_size_and_align(r#"type Goal = fn(u32) -> u32;"#, 8, 8);
+}
+
+#[test]
+fn target_pointer_width() {
+    check_size_and_align(
+        r#"
+//- /main.rs crate:main cfg:target_pointer_width=32
+struct Goal { ptr: &'static u8, len: usize, flag: bool }
+"#,
+        12,
+        4,
+    );
+}
+
+#[test]
+fn reorders_rust_structs() {
+    check_size_and_align(r#"struct Goal { a: u8, b: u32, c: u8 }"#, 8, 4);
+    check_offsets(r#"struct Goal { a: u8, b: u32, c: u8 }"#, &[4, 0, 5]);
+    check_offsets(r#"struct Goal { a: u8, b: (), c: u16 }"#, &[2, 0, 0]);
+}
+
+#[test]
+fn repr_c() {
+    check_size_and_align(r#"#[repr(C)] struct Goal { a: u8, b: u32, c: u8 }"#, 12, 4);
+    check_offsets(r#"#[repr(C)] struct Goal { a: u8, b: u32, c: u8 }"#, &[0, 4, 8]);
+    check_size_and_align(r#"#[repr(C)] enum Goal { A(u8), B(u64) }"#, 16, 8);
+    check_size_and_align(r#"#[repr(C)] enum Goal { A, B }"#, 4, 4);
+}
+
+#[test]
+fn repr_packed_and_align() {
+    check_size_and_align(r#"#[repr(packed)] struct Goal { a: u8, b: u32 }"#, 5, 1);
+    check_size_and_align(r#"#[repr(C, packed(2))] struct Goal { a: u8, b: u32 }"#, 6, 2);
+    check_offsets(r#"#[repr(C, packed(2))] struct Goal { a: u8, b: u32 }"#, &[0, 2]);
+    check_size_and_align(r#"#[repr(align(16))] struct Goal { a: u8 }"#, 16, 16);
+    check_size_and_align(r#"#[repr(C)] #[repr(align(8))] struct Goal(u8, u16);"#, 8, 8);
+}
+
+#[test]
+fn unions() {
+    check_size_and_align(r#"union Goal { a: u8, b: [u16; 3], c: u32 }"#, 8, 4);
+    check_offsets(r#"union Goal { a: u8, b: u32 }"#, &[0, 0]);
+}
+
+#[test]
+fn fieldless_enums() {
+    check_size_and_align(r#"enum Goal {}"#, 0, 1);
+    check_size_and_align(r#"enum Goal { A }"#, 0, 1);
+    check_size_and_align(r#"enum Goal { A, B, C }"#, 1, 1);
+    check_size_and_align(r#"enum Goal { A = 0, B = 300 }"#, 2, 2);
+    check_size_and_align(r#"enum Goal { A = 255, B }"#, 2, 2);
+    check_size_and_align(r#"enum Goal { A = 254, B }"#, 1, 1);
+    check_size_and_align(r#"#[repr(u32)] enum Goal { A, B }"#, 4, 4);
+    check_size_and_align(r#"enum Goal { A = -1, B }"#, 1, 1);
+    check_size_and_align(r#"enum Goal { A = -129, B }"#, 2, 2);
+    check_size_and_align(r#"#[repr(i8)] enum Goal { A = -128, B = 127 }"#, 1, 1);
+}
+
+#[test]
+fn discriminants_out_of_repr_range() {
+    assert_eq!(layout_of_goal(r#"#[repr(u8)] enum Goal { A = 255, B }"#), None);
+    assert_eq!(layout_of_goal(r#"#[repr(u8)] enum Goal { A = -1 }"#), None);
+    assert_eq!(layout_of_goal(r#"#[repr(i8)] enum Goal { A = 128 }"#), None);
+}
+
+#[test]
+fn tagged_enums() {
+    check_size_and_align(r#"enum Goal { A(u8), B(u32) }"#, 8, 4);
+    check_size_and_align(r#"enum Goal { A(u8, u32), B }"#, 8, 4);
+    check_size_and_align(r#"#[repr(u8)] enum Goal { A(u16), B }"#, 4, 2);
+}
+
+#[test]
+fn niche_optimization() {
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<&'static u32>;
+"#,
+        8,
+        8,
+    );
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<Option<bool>>;
+"#,
+        1,
+        1,
+    );
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<(u64, char)>;
+"#,
+        16,
+        8,
+    );
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+type Goal = Option<u32>;
+"#,
+        8,
+        4,
+    );
+    check_size_and_align(
+        r#"
+#[rustc_layout_scalar_valid_range_start(1)]
+struct NonZeroU32(u32);
+enum Option<T> { None, Some(T) }
+type Goal = Option<NonZeroU32>;
+"#,
+        4,
+        4,
+    );
+}
+
+#[test]
+fn no_layout_for_generic_or_unsized() {
+    assert_eq!(layout_of_goal(r#"struct Goal<T> { a: T }"#), None);
+    assert_eq!(layout_of_goal(r#"type Goal = [u8];"#), None);
+    assert_eq!(layout_of_goal(r#"struct Goal { a: Goal }"#), None);
+}
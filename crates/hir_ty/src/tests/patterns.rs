@@ -916,3 +916,18 @@ fn main() {
         "#]],
     );
 }
+
+#[test]
+fn let_else_pattern() {
+    check_types(
+        r#"
+enum Option<T> { Some(T), None }
+use Option::*;
+fn foo(opt: Option<u32>) -> u32 {
+    let Some(x) = opt else { return 0 };
+    x
+  //^ u32
+}
+"#,
+    );
+}
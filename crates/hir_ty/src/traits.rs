@@ -2,7 +2,7 @@
 
 use std::env::var;
 
-use chalk_ir::GoalData;
+use chalk_ir::{cast::Cast, BoundVar, DebruijnIndex, GoalData, TyVariableKind};
 use chalk_solve::{logging_db::LoggingRustIrDatabase, Solver};
 
 use base_db::CrateId;
@@ -10,8 +10,8 @@ use hir_def::{lang_item::LangItemTarget, TraitId};
 use stdx::panic_context;
 
 use crate::{
-    db::HirDatabase, AliasEq, AliasTy, Canonical, DomainGoal, Goal, Guidance, InEnvironment,
-    Interner, Solution, TraitRefExt, Ty, TyKind, WhereClause,
+    db::HirDatabase, make_canonical, AliasEq, AliasTy, Canonical, DomainGoal, Goal, Guidance,
+    InEnvironment, Interner, Solution, TraitRefExt, Ty, TyKind, WhereClause,
 };
 
 /// This controls how much 'time' we give the Chalk solver before giving up.
@@ -100,6 +100,44 @@ pub(crate) fn trait_solve_query(
     solve(db, krate, &u_canonical)
 }
 
+/// Replaces the associated type projections in `ty` that normalize to a
+/// unique type in `env` with that type.
+pub fn normalize_projections(db: &dyn HirDatabase, env: &TraitEnvironment, ty: Ty) -> Ty {
+    crate::fold_tys(
+        ty,
+        |ty, binders| {
+            let projection = match ty.kind(&Interner) {
+                TyKind::Alias(AliasTy::Projection(it)) if binders == DebruijnIndex::INNERMOST => {
+                    it.clone()
+                }
+                _ => return ty,
+            };
+            let goal = make_canonical(
+                InEnvironment::new(
+                    &env.env,
+                    AliasEq {
+                        alias: AliasTy::Projection(projection),
+                        ty: TyKind::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, 0))
+                            .intern(&Interner),
+                    }
+                    .cast(&Interner),
+                ),
+                [TyVariableKind::General].iter().copied(),
+            );
+            match db.trait_solve(env.krate, goal) {
+                Some(Solution::Unique(solution)) => solution
+                    .value
+                    .subst
+                    .as_slice(&Interner)
+                    .first()
+                    .map_or(ty, |it| it.assert_ty_ref(&Interner).clone()),
+                _ => ty,
+            }
+        },
+        DebruijnIndex::INNERMOST,
+    )
+}
+
 fn solve(
     db: &dyn HirDatabase,
     krate: CrateId,
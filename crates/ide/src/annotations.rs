@@ -1,13 +1,17 @@
 use either::Either;
-use hir::{HasSource, InFile, Semantics};
+use hir::{AsAssocItem, AssocItemContainer, HasSource, InFile, Semantics};
 use ide_db::{
     base_db::{FileId, FilePosition, FileRange},
     helpers::visit_file_defs,
     RootDatabase,
 };
-use syntax::{ast::NameOwner, AstNode, TextRange};
+use syntax::{
+    ast::{self, NameOwner},
+    AstNode, TextRange,
+};
 
 use crate::{
+    display::TryToNav,
     fn_references::find_all_methods,
     goto_implementation::goto_implementation,
     references::find_all_refs,
@@ -18,7 +22,8 @@ use crate::{
 // Feature: Annotations
 //
 // Provides user with annotations above items for looking up references or impl blocks
-// and running/debugging binaries.
+// and running/debugging binaries. Methods of trait impls link back to the trait method they
+// implement, and trait methods with a default body list the impls that override them.
 //
 // image::https://user-images.githubusercontent.com/48062697/113020672-b7c34f00-917a-11eb-8f6e-858735660a0e.png[]
 #[derive(Debug)]
@@ -32,6 +37,8 @@ pub enum AnnotationKind {
     Runnable { debug: bool, runnable: Runnable },
     HasImpls { position: FilePosition, data: Option<Vec<NavigationTarget>> },
     HasReferences { position: FilePosition, data: Option<Vec<FileRange>> },
+    ImplementsTraitItem { position: FilePosition, data: Option<Vec<NavigationTarget>> },
+    HasOverrides { position: FilePosition, data: Option<Vec<NavigationTarget>> },
 }
 
 pub struct AnnotationConfig {
@@ -40,6 +47,7 @@ pub struct AnnotationConfig {
     pub annotate_impls: bool,
     pub annotate_references: bool,
     pub annotate_method_references: bool,
+    pub annotate_trait_methods: bool,
     pub run: bool,
     pub debug: bool,
 }
@@ -98,6 +106,28 @@ pub(crate) fn annotations(
                 }
                 _ => None,
             };
+            if config.annotate_trait_methods {
+                if let hir::ModuleDef::Trait(trait_) = def {
+                    // Only methods with a default body can be overridden.
+                    let methods = trait_.items(db).into_iter().filter_map(|item| match item {
+                        hir::AssocItem::Function(it) if it.has_body(db) => Some(it),
+                        _ => None,
+                    });
+                    for method in methods {
+                        if let Some(range) =
+                            method.source(db).and_then(|node| name_range(&node, file_id))
+                        {
+                            annotations.push(Annotation {
+                                range,
+                                kind: AnnotationKind::HasOverrides {
+                                    position: FilePosition { file_id, offset: range.start() },
+                                    data: None,
+                                },
+                            });
+                        }
+                    }
+                }
+            }
             let (range, offset) = match range {
                 Some(range) => (range, range.start()),
                 None => return,
@@ -121,17 +151,29 @@ pub(crate) fn annotations(
                     },
                 });
             }
-
-            fn name_range<T: NameOwner>(node: &InFile<T>, file_id: FileId) -> Option<TextRange> {
-                if node.file_id == file_id.into() {
-                    node.value.name().map(|it| it.syntax().text_range())
-                } else {
-                    // Node is outside the file we are adding annotations to (e.g. macros).
-                    None
+        }
+        Either::Right(impl_) => {
+            if !config.annotate_trait_methods || impl_.trait_def(db).is_none() {
+                return;
+            }
+            for item in impl_.items(db) {
+                let range = match item {
+                    hir::AssocItem::Function(it) => {
+                        it.source(db).and_then(|node| name_range(&node, file_id))
+                    }
+                    _ => None,
+                };
+                if let Some(range) = range {
+                    annotations.push(Annotation {
+                        range,
+                        kind: AnnotationKind::ImplementsTraitItem {
+                            position: FilePosition { file_id, offset: range.start() },
+                            data: None,
+                        },
+                    });
                 }
             }
         }
-        Either::Right(_) => (),
     });
 
     if config.annotate_method_references {
@@ -146,7 +188,16 @@ pub(crate) fn annotations(
         ));
     }
 
-    annotations
+    return annotations;
+
+    fn name_range<T: NameOwner>(node: &InFile<T>, file_id: FileId) -> Option<TextRange> {
+        if node.file_id == file_id.into() {
+            node.value.name().map(|it| it.syntax().text_range())
+        } else {
+            // Node is outside the file we are adding annotations to (e.g. macros).
+            None
+        }
+    }
 }
 
 pub(crate) fn resolve_annotation(db: &RootDatabase, mut annotation: Annotation) -> Annotation {
@@ -166,12 +217,39 @@ pub(crate) fn resolve_annotation(db: &RootDatabase, mut annotation: Annotation)
                     .collect()
             });
         }
+        AnnotationKind::ImplementsTraitItem { position, ref mut data } => {
+            *data = implemented_trait_item(db, position);
+        }
+        AnnotationKind::HasOverrides { position, ref mut data } => {
+            *data = goto_implementation(db, position).map(|range| range.info);
+        }
         _ => {}
     };
 
     annotation
 }
 
+/// Finds the trait item implemented by the impl method whose name is at `position`.
+fn implemented_trait_item(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(position.file_id);
+    let fn_ =
+        sema.find_node_at_offset_with_descend::<ast::Fn>(source_file.syntax(), position.offset)?;
+    let assoc = sema.to_def(&fn_)?.as_assoc_item(db)?;
+    let trait_ = match assoc.container(db) {
+        AssocItemContainer::Impl(impl_) => impl_.trait_def(db)?,
+        AssocItemContainer::Trait(_) => return None,
+    };
+    let name = assoc.name(db)?;
+    let item = trait_.items(db).into_iter().find(|it| it.name(db).as_ref() == Some(&name))?;
+    let mut nav = item.try_to_nav(db)?;
+    nav.container_name = Some(trait_.name(db).to_string().into());
+    Some(vec![nav])
+}
+
 fn should_skip_runnable(kind: &RunnableKind, binary_target: bool) -> bool {
     match kind {
         RunnableKind::Bin => !binary_target,
@@ -197,6 +275,7 @@ mod tests {
                     annotate_impls: true,
                     annotate_references: true,
                     annotate_method_references: true,
+                    annotate_trait_methods: false,
                     run: true,
                     debug: true,
                 },
@@ -209,6 +288,31 @@ mod tests {
         expect.assert_debug_eq(&annotations);
     }
 
+    fn check_trait_methods(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+
+        let annotations: Vec<Annotation> = analysis
+            .annotations(
+                file_id,
+                AnnotationConfig {
+                    binary_target: false,
+                    annotate_runnables: false,
+                    annotate_impls: false,
+                    annotate_references: false,
+                    annotate_method_references: false,
+                    annotate_trait_methods: true,
+                    run: false,
+                    debug: false,
+                },
+            )
+            .unwrap()
+            .into_iter()
+            .map(|annotation| analysis.resolve_annotation(annotation).unwrap())
+            .collect();
+
+        expect.assert_debug_eq(&annotations);
+    }
+
     #[test]
     fn const_annotations() {
         check(
@@ -995,4 +1099,138 @@ m!();
             "#]],
         );
     }
+
+    #[test]
+    fn trait_method_annotations() {
+        check_trait_methods(
+            r#"
+trait Trait {
+    fn required(&self);
+    fn provided(&self) {}
+}
+
+struct A;
+impl Trait for A {
+    fn required(&self) {}
+    fn provided(&self) {}
+}
+
+struct B;
+impl Trait for B {
+    fn required(&self) {}
+}
+
+impl B {
+    fn inherent(&self) {}
+}
+"#,
+            expect![[r#"
+                [
+                    Annotation {
+                        range: 45..53,
+                        kind: HasOverrides {
+                            position: FilePosition {
+                                file_id: FileId(
+                                    0,
+                                ),
+                                offset: 45,
+                            },
+                            data: Some(
+                                [
+                                    NavigationTarget {
+                                        file_id: FileId(
+                                            0,
+                                        ),
+                                        full_range: 126..147,
+                                        focus_range: 129..137,
+                                        name: "provided",
+                                        kind: Function,
+                                        description: "fn provided(&self)",
+                                    },
+                                ],
+                            ),
+                        },
+                    },
+                    Annotation {
+                        range: 103..111,
+                        kind: ImplementsTraitItem {
+                            position: FilePosition {
+                                file_id: FileId(
+                                    0,
+                                ),
+                                offset: 103,
+                            },
+                            data: Some(
+                                [
+                                    NavigationTarget {
+                                        file_id: FileId(
+                                            0,
+                                        ),
+                                        full_range: 18..37,
+                                        focus_range: 21..29,
+                                        name: "required",
+                                        kind: Function,
+                                        container_name: "Trait",
+                                        description: "fn required(&self)",
+                                    },
+                                ],
+                            ),
+                        },
+                    },
+                    Annotation {
+                        range: 129..137,
+                        kind: ImplementsTraitItem {
+                            position: FilePosition {
+                                file_id: FileId(
+                                    0,
+                                ),
+                                offset: 129,
+                            },
+                            data: Some(
+                                [
+                                    NavigationTarget {
+                                        file_id: FileId(
+                                            0,
+                                        ),
+                                        full_range: 42..63,
+                                        focus_range: 45..53,
+                                        name: "provided",
+                                        kind: Function,
+                                        container_name: "Trait",
+                                        description: "fn provided(&self)",
+                                    },
+                                ],
+                            ),
+                        },
+                    },
+                    Annotation {
+                        range: 187..195,
+                        kind: ImplementsTraitItem {
+                            position: FilePosition {
+                                file_id: FileId(
+                                    0,
+                                ),
+                                offset: 187,
+                            },
+                            data: Some(
+                                [
+                                    NavigationTarget {
+                                        file_id: FileId(
+                                            0,
+                                        ),
+                                        full_range: 18..37,
+                                        focus_range: 21..29,
+                                        name: "required",
+                                        kind: Function,
+                                        container_name: "Trait",
+                                        description: "fn required(&self)",
+                                    },
+                                ],
+                            ),
+                        },
+                    },
+                ]
+            "#]],
+        );
+    }
 }
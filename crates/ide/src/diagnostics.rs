@@ -7,6 +7,8 @@
 mod fixes;
 mod field_shorthand;
 mod unlinked_file;
+mod unused_import;
+mod unused_item;
 
 use std::cell::RefCell;
 
@@ -80,6 +82,8 @@ pub enum Severity {
 pub struct DiagnosticsConfig {
     pub disable_experimental: bool,
     pub disabled: FxHashSet<String>,
+    pub unused_imports: bool,
+    pub unused_items: bool,
 }
 
 pub(crate) fn diagnostics(
@@ -106,6 +110,12 @@ pub(crate) fn diagnostics(
         check_unnecessary_braces_in_use_statement(&mut res, file_id, &node);
         field_shorthand::check(&mut res, file_id, &node);
     }
+    if config.unused_imports && !config.disabled.contains("unused-import") {
+        unused_import::check(&mut res, &sema, file_id);
+    }
+    if config.unused_items && !config.disabled.contains("unused-item") {
+        unused_item::check(&mut res, &sema, file_id);
+    }
     let res = RefCell::new(res);
     let sink_builder = DiagnosticSinkBuilder::new()
         .on::<hir::diagnostics::UnresolvedModule, _>(|d| {
@@ -123,6 +133,12 @@ pub(crate) fn diagnostics(
         .on::<hir::diagnostics::RemoveThisSemicolon, _>(|d| {
             res.borrow_mut().push(diagnostic_with_fix(d, &sema, resolve));
         })
+        .on::<hir::diagnostics::TypeMismatch, _>(|d| {
+            res.borrow_mut().push(diagnostic_with_fix(d, &sema, resolve));
+        })
+        .on::<hir::diagnostics::UnresolvedMethodCall, _>(|d| {
+            res.borrow_mut().push(diagnostic_with_fix(d, &sema, resolve));
+        })
         .on::<hir::diagnostics::IncorrectCase, _>(|d| {
             res.borrow_mut().push(warning_with_fix(d, &sema, resolve));
         })
@@ -168,6 +184,14 @@ pub(crate) fn diagnostics(
             res.borrow_mut()
                 .push(Diagnostic::hint(display_range, d.message()).with_code(Some(d.code())));
         })
+        .on::<hir::diagnostics::MacroError, _>(|d| {
+            // Point at the input token the error is about, if known.
+            let display_range = d
+                .precise_location
+                .unwrap_or_else(|| sema.diagnostics_display_range(d.display_source()).range);
+            res.borrow_mut()
+                .push(Diagnostic::error(display_range, d.message()).with_code(Some(d.code())));
+        })
         .on::<hir::diagnostics::UnresolvedMacroCall, _>(|d| {
             let last_path_segment = sema.db.parse_or_expand(d.file).and_then(|root| {
                 d.node
@@ -320,6 +344,16 @@ mod tests {
     pub(crate) fn check_fix(ra_fixture_before: &str, ra_fixture_after: &str) {
         check_nth_fix(0, ra_fixture_before, ra_fixture_after);
     }
+
+    /// Like [`check_fix`], but with a custom diagnostics config.
+    #[track_caller]
+    pub(crate) fn check_fix_with_config(
+        config: DiagnosticsConfig,
+        ra_fixture_before: &str,
+        ra_fixture_after: &str,
+    ) {
+        check_nth_fix_with_config(config, 0, ra_fixture_before, ra_fixture_after);
+    }
     /// Takes a multi-file input fixture with annotated cursor positions,
     /// and checks that:
     ///  * a diagnostic is produced
@@ -333,15 +367,26 @@ mod tests {
 
     #[track_caller]
     fn check_nth_fix(nth: usize, ra_fixture_before: &str, ra_fixture_after: &str) {
+        check_nth_fix_with_config(
+            DiagnosticsConfig::default(),
+            nth,
+            ra_fixture_before,
+            ra_fixture_after,
+        )
+    }
+
+    #[track_caller]
+    fn check_nth_fix_with_config(
+        config: DiagnosticsConfig,
+        nth: usize,
+        ra_fixture_before: &str,
+        ra_fixture_after: &str,
+    ) {
         let after = trim_indent(ra_fixture_after);
 
         let (analysis, file_position) = fixture::position(ra_fixture_before);
         let diagnostic = analysis
-            .diagnostics(
-                &DiagnosticsConfig::default(),
-                AssistResolveStrategy::All,
-                file_position.file_id,
-            )
+            .diagnostics(&config, AssistResolveStrategy::All, file_position.file_id)
             .unwrap()
             .pop()
             .unwrap();
@@ -366,7 +411,7 @@ mod tests {
         );
     }
     /// Checks that there's a diagnostic *without* fix at `$0`.
-    fn check_no_fix(ra_fixture: &str) {
+    pub(crate) fn check_no_fix(ra_fixture: &str) {
         let (analysis, file_position) = fixture::position(ra_fixture);
         let diagnostic = analysis
             .diagnostics(
@@ -404,10 +449,13 @@ mod tests {
     }
 
     pub(crate) fn check_diagnostics(ra_fixture: &str) {
+        check_diagnostics_with_config(DiagnosticsConfig::default(), ra_fixture)
+    }
+
+    pub(crate) fn check_diagnostics_with_config(config: DiagnosticsConfig, ra_fixture: &str) {
         let (analysis, file_id) = fixture::file(ra_fixture);
-        let diagnostics = analysis
-            .diagnostics(&DiagnosticsConfig::default(), AssistResolveStrategy::All, file_id)
-            .unwrap();
+        let diagnostics =
+            analysis.diagnostics(&config, AssistResolveStrategy::All, file_id).unwrap();
 
         let expected = extract_annotations(&*analysis.file_text(file_id).unwrap());
         let actual = diagnostics.into_iter().map(|d| (d.range, d.message)).collect::<Vec<_>>();
@@ -424,6 +472,23 @@ foo::bar!(92);
         );
     }
 
+    #[test]
+    fn macro_error_points_at_unexpected_token() {
+        check_diagnostics(
+            r#"
+macro_rules! m {
+    ($e:expr, $f:expr) => { $e + $f };
+}
+fn f() {
+    m!(1 2);
+       //^ no rules expected the token `2`; expected `,`
+    m!(1,);
+       //^ unexpected end of macro invocation; expected expr
+}
+"#,
+        );
+    }
+
     #[test]
     fn unresolved_import_in_use_tree() {
         // Only the relevant part of a nested `use` item should be highlighted.
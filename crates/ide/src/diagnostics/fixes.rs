@@ -5,6 +5,8 @@ mod create_field;
 mod fill_missing_fields;
 mod remove_semicolon;
 mod replace_with_find_map;
+mod type_mismatch;
+mod unresolved_method;
 mod unresolved_module;
 mod wrap_tail_expr;
 
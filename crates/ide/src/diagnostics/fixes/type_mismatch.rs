@@ -0,0 +1,311 @@
+use hir::{db::AstDatabase, diagnostics::TypeMismatch, Semantics};
+use ide_assists::{Assist, AssistResolveStrategy};
+use ide_db::{base_db::FileId, helpers::FamousDefs, source_change::SourceChange, RootDatabase};
+use syntax::{ast, AstNode};
+use text_edit::TextEdit;
+
+use crate::diagnostics::{fix, DiagnosticWithFixes};
+
+impl DiagnosticWithFixes for TypeMismatch {
+    fn fixes(
+        &self,
+        sema: &Semantics<RootDatabase>,
+        _resolve: &AssistResolveStrategy,
+    ) -> Option<Vec<Assist>> {
+        let root = sema.db.parse_or_expand(self.file)?;
+        let expr = self.expr.to_node(&root);
+        let file_id = self.file.original_file(sema.db);
+
+        let mut fixes = Vec::new();
+        add_reference(self, &expr, file_id, &mut fixes);
+        wrap_in_some_or_ok(self, sema, &expr, file_id, &mut fixes);
+        add_into(self, sema, &expr, file_id, &mut fixes);
+
+        if fixes.is_empty() {
+            None
+        } else {
+            Some(fixes)
+        }
+    }
+}
+
+fn add_reference(
+    d: &TypeMismatch,
+    expr: &ast::Expr,
+    file_id: FileId,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    if d.expected.remove_ref()? != d.actual {
+        return None;
+    }
+    let (label, prefix) = if d.expected.is_mutable_reference() {
+        ("Add mutable reference here", "&mut ")
+    } else {
+        ("Add reference here", "&")
+    };
+    let range = expr.syntax().text_range();
+    let edit = TextEdit::insert(range.start(), prefix.to_string());
+    let source_change = SourceChange::from_text_edit(file_id, edit);
+    acc.push(fix("add_reference_here", label, source_change, range));
+    Some(())
+}
+
+fn wrap_in_some_or_ok(
+    d: &TypeMismatch,
+    sema: &Semantics<RootDatabase>,
+    expr: &ast::Expr,
+    file_id: FileId,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    let expected_enum = match d.expected.as_adt()? {
+        hir::Adt::Enum(it) => it,
+        _ => return None,
+    };
+    let famous_defs = FamousDefs(sema, sema.scope(expr.syntax()).krate());
+    let variant = if Some(expected_enum) == famous_defs.core_option_Option() {
+        "Some"
+    } else if Some(expected_enum) == famous_defs.core_result_Result() {
+        "Ok"
+    } else {
+        return None;
+    };
+    if d.expected.type_arguments().next()? != d.actual {
+        return None;
+    }
+    let range = expr.syntax().text_range();
+    let edit = TextEdit::replace(range, format!("{}({})", variant, expr.syntax()));
+    let source_change = SourceChange::from_text_edit(file_id, edit);
+    let label = format!("Wrap in `{}`", variant);
+    acc.push(fix("wrap_in_some_or_ok", &label, source_change, range));
+    Some(())
+}
+
+fn add_into(
+    d: &TypeMismatch,
+    sema: &Semantics<RootDatabase>,
+    expr: &ast::Expr,
+    file_id: FileId,
+    acc: &mut Vec<Assist>,
+) -> Option<()> {
+    let famous_defs = FamousDefs(sema, sema.scope(expr.syntax()).krate());
+    let into_trait = famous_defs.core_convert_Into()?;
+    if !d.actual.impls_trait_uniquely(sema.db, into_trait, &[d.expected.clone()]) {
+        return None;
+    }
+    let range = expr.syntax().text_range();
+    let receiver = match expr {
+        ast::Expr::BinExpr(_)
+        | ast::Expr::CastExpr(_)
+        | ast::Expr::ClosureExpr(_)
+        | ast::Expr::PrefixExpr(_)
+        | ast::Expr::RangeExpr(_)
+        | ast::Expr::RefExpr(_) => format!("({})", expr.syntax()),
+        _ => expr.syntax().to_string(),
+    };
+    let edit = TextEdit::replace(range, format!("{}.into()", receiver));
+    let source_change = SourceChange::from_text_edit(file_id, edit);
+    acc.push(fix("add_into", "Convert with `.into()`", source_change, range));
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::tests::{
+        check_diagnostics, check_fix, check_no_diagnostics, check_no_fix,
+    };
+
+    #[test]
+    fn add_reference_to_argument() {
+        check_fix(
+            r#"
+fn foo(x: &i32) {}
+fn main() {
+    let x = 92;
+    foo($0x);
+}
+"#,
+            r#"
+fn foo(x: &i32) {}
+fn main() {
+    let x = 92;
+    foo(&x);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_mutable_reference_to_let() {
+        check_fix(
+            r#"
+fn main() {
+    let mut x = 92;
+    let y: &mut i32 = $0x;
+}
+"#,
+            r#"
+fn main() {
+    let mut x = 92;
+    let y: &mut i32 = &mut x;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wrap_argument_in_some() {
+        check_fix(
+            r#"
+//- /main.rs crate:main deps:core
+use core::option::Option::{self, Some};
+
+fn foo(x: Option<i32>) {}
+fn main() {
+    foo($092);
+}
+//- /core/lib.rs crate:core
+pub mod option {
+    pub enum Option<T> { Some(T), None }
+}
+"#,
+            r#"
+use core::option::Option::{self, Some};
+
+fn foo(x: Option<i32>) {}
+fn main() {
+    foo(Some(92));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wrap_let_initializer_in_ok() {
+        check_fix(
+            r#"
+//- /main.rs crate:main deps:core
+use core::result::Result::{self, Ok};
+
+fn main() {
+    let x: Result<i32, ()> = $092;
+}
+//- /core/lib.rs crate:core
+pub mod result {
+    pub enum Result<T, E> { Ok(T), Err(E) }
+}
+"#,
+            r#"
+use core::result::Result::{self, Ok};
+
+fn main() {
+    let x: Result<i32, ()> = Ok(92);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_with_into() {
+        check_fix(
+            r#"
+//- /main.rs crate:main deps:core
+struct Meters(u32);
+impl core::convert::From<u32> for Meters {
+    fn from(it: u32) -> Meters { Meters(it) }
+}
+
+fn walk(distance: Meters) {}
+fn main() {
+    let x = 1u32;
+    walk($0x + 1);
+}
+//- /core/lib.rs crate:core
+pub mod convert {
+    pub trait From<T> {
+        fn from(t: T) -> Self;
+    }
+    pub trait Into<T> {
+        fn into(self) -> T;
+    }
+    impl<T, U: From<T>> Into<U> for T {
+        fn into(self) -> U { U::from(self) }
+    }
+}
+"#,
+            r#"
+struct Meters(u32);
+impl core::convert::From<u32> for Meters {
+    fn from(it: u32) -> Meters { Meters(it) }
+}
+
+fn walk(distance: Meters) {}
+fn main() {
+    let x = 1u32;
+    walk((x + 1).into());
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_into_without_unique_impl() {
+        check_no_fix(
+            r#"
+//- /main.rs crate:main deps:core
+struct Meters(u32);
+trait Scale<U> {}
+impl Scale<u8> for u32 {}
+impl Scale<u16> for u32 {}
+// `U` can be either integer type, so the solver only finds an ambiguous solution.
+impl<T: Scale<U>, U> core::convert::From<T> for Meters {
+    fn from(_: T) -> Meters { Meters(0) }
+}
+
+fn walk(distance: Meters) {}
+fn main() {
+    let x = 1u32;
+    walk($0x);
+}
+//- /core/lib.rs crate:core
+pub mod convert {
+    pub trait From<T> {
+        fn from(t: T) -> Self;
+    }
+    pub trait Into<T> {
+        fn into(self) -> T;
+    }
+    impl<T, U: From<T>> Into<U> for T {
+        fn into(self) -> U { U::from(self) }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reports_expected_and_actual_types() {
+        check_diagnostics(
+            r#"
+struct Foo;
+fn foo(x: Foo) {}
+fn main() {
+    foo(92);
+      //^^ expected Foo, found i32
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_for_unknown_types() {
+        check_no_diagnostics(
+            r#"
+fn foo(x: Unresolved) {}
+fn main() {
+    foo(92);
+    let x: u32 = unresolved();
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use hir::{db::AstDatabase, diagnostics::UnresolvedMethodCall, HasSource, HirDisplay, Semantics};
+use ide_db::{base_db::SourceDatabaseExt, source_change::SourceChange, RootDatabase};
+use itertools::Itertools;
+use stdx::to_lower_snake_case;
+use syntax::{
+    ast::{self, edit::IndentLevel, ArgListOwner, GenericParamsOwner, NameOwner, TypeBoundsOwner},
+    AstNode,
+};
+use text_edit::TextEdit;
+
+use crate::{
+    diagnostics::{fix, DiagnosticWithFixes},
+    Assist, AssistResolveStrategy,
+};
+
+impl DiagnosticWithFixes for UnresolvedMethodCall {
+    fn fixes(
+        &self,
+        sema: &Semantics<RootDatabase>,
+        _resolve: &AssistResolveStrategy,
+    ) -> Option<Vec<Assist>> {
+        let root = sema.db.parse_or_expand(self.file)?;
+        let call = self.expr.to_node(&root);
+        let adt = self.receiver.autoderef(sema.db).find_map(|ty| ty.as_adt())?;
+        let adt_source = match adt {
+            hir::Adt::Struct(it) => it.source(sema.db)?.map(ast::Adt::Struct),
+            hir::Adt::Union(it) => it.source(sema.db)?.map(ast::Adt::Union),
+            hir::Adt::Enum(it) => it.source(sema.db)?.map(ast::Adt::Enum),
+        };
+        if adt_source.file_id.call_node(sema.db).is_some() {
+            return None;
+        }
+        let def_file_id = adt_source.file_id.original_file(sema.db);
+        if sema.db.source_root(sema.db.file_source_root(def_file_id)).is_library {
+            return None;
+        }
+        let module = adt.module(sema.db);
+
+        let self_param = if self.receiver.is_mutable_reference() { "&mut self" } else { "&self" };
+        let params = method_params(sema, module, &call)?;
+        let visibility =
+            if self.file.original_file(sema.db) == def_file_id { "" } else { "pub(crate) " };
+        let signature = format!("{}fn {}({}{}) {{", visibility, self.name, self_param, params);
+
+        let existing_impl = hir::Impl::all_for_type(sema.db, adt.ty(sema.db))
+            .into_iter()
+            .filter(|it| it.trait_(sema.db).is_none())
+            .filter_map(|it| it.source(sema.db))
+            .filter(|it| {
+                it.file_id.call_node(sema.db).is_none()
+                    && it.file_id.original_file(sema.db) == def_file_id
+            })
+            .find_map(|it| it.value.assoc_item_list());
+        let edit = match existing_impl {
+            Some(items) => add_to_impl(&items, &signature),
+            None => add_new_impl(&adt_source.value, &signature)?,
+        };
+
+        let source_change = SourceChange::from_text_edit(def_file_id, edit);
+        Some(vec![fix(
+            "generate_method",
+            &format!("Generate method `{}`", self.name),
+            source_change,
+            call.syntax().text_range(),
+        )])
+    }
+}
+
+/// Renders the parameters after `self`, taking names and types from the
+/// arguments of the call.
+fn method_params(
+    sema: &Semantics<RootDatabase>,
+    module: hir::Module,
+    call: &ast::MethodCallExpr,
+) -> Option<String> {
+    let mut params = String::new();
+    let mut names = Vec::new();
+    for (idx, arg) in call.arg_list()?.args().enumerate() {
+        let mut name = arg_name(&arg).unwrap_or_else(|| "arg".to_string());
+        if names.contains(&name) {
+            name = format!("{}{}", name, idx);
+        }
+        let ty = sema
+            .type_of_expr(&arg)
+            .filter(|ty| !ty.is_unknown())
+            .and_then(|ty| ty.display_source_code(sema.db, module.into()).ok())
+            .unwrap_or_else(|| "()".to_string());
+        params.push_str(&format!(", {}: {}", name, ty));
+        names.push(name);
+    }
+    Some(params)
+}
+
+fn arg_name(arg: &ast::Expr) -> Option<String> {
+    match arg {
+        ast::Expr::CastExpr(it) => arg_name(&it.expr()?),
+        ast::Expr::RefExpr(it) => arg_name(&it.expr()?),
+        ast::Expr::PathExpr(it) => {
+            let name = it.path()?.segment()?.name_ref()?;
+            Some(to_lower_snake_case(&name.text()))
+        }
+        _ => None,
+    }
+}
+
+fn add_to_impl(items: &ast::AssocItemList, signature: &str) -> TextEdit {
+    let impl_indent = IndentLevel::from_node(items.syntax());
+    let indent = impl_indent + 1;
+    let method = format!("{}\n{}todo!()\n{}}}", signature, indent + 1, indent);
+    match items.assoc_items().last() {
+        Some(last) => {
+            TextEdit::insert(last.syntax().text_range().end(), format!("\n\n{}{}", indent, method))
+        }
+        None => TextEdit::replace(
+            items.syntax().text_range(),
+            format!("{{\n{}{}\n{}}}", indent, method, impl_indent),
+        ),
+    }
+}
+
+fn add_new_impl(adt: &ast::Adt, signature: &str) -> Option<TextEdit> {
+    let indent = IndentLevel::from_node(adt.syntax());
+    let name = adt.name()?;
+    let (params, args) = match adt.generic_param_list() {
+        Some(list) => {
+            // Defaults aren't allowed on impl parameters, so the list is rebuilt.
+            let (params, args): (Vec<_>, Vec<_>) = list
+                .generic_params()
+                .filter_map(|param| match param {
+                    ast::GenericParam::LifetimeParam(it) => {
+                        let lifetime = it.lifetime()?.to_string();
+                        let param = match it.type_bound_list() {
+                            Some(bounds) => format!("{}: {}", lifetime, bounds),
+                            None => lifetime.clone(),
+                        };
+                        Some((param, lifetime))
+                    }
+                    ast::GenericParam::TypeParam(it) => {
+                        let name = it.name()?.to_string();
+                        let param = match it.type_bound_list() {
+                            Some(bounds) => format!("{}: {}", name, bounds),
+                            None => name.clone(),
+                        };
+                        Some((param, name))
+                    }
+                    ast::GenericParam::ConstParam(it) => {
+                        let name = it.name()?.to_string();
+                        Some((format!("const {}: {}", name, it.ty()?), name))
+                    }
+                })
+                .unzip();
+            (format!("<{}>", params.iter().join(", ")), format!("<{}>", args.iter().join(", ")))
+        }
+        None => (String::new(), String::new()),
+    };
+    let text = format!(
+        "\n\n{indent}impl{} {}{} {{\n{}{}\n{}todo!()\n{}}}\n{indent}}}",
+        params,
+        name,
+        args,
+        indent + 1,
+        signature,
+        indent + 2,
+        indent + 1,
+        indent = indent,
+    );
+    let end = adt.syntax().text_range().end();
+    Some(TextEdit::insert(end, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::tests::{check_diagnostics, check_fix, check_no_fix};
+
+    #[test]
+    fn reports_unresolved_method_and_field() {
+        check_diagnostics(
+            r#"
+struct S { field: u32 }
+fn main() {
+    let s = S { field: 0 };
+    s.foo();
+  //^^^^^^^ no method `foo` on type `S`
+    s.bar;
+  //^^^^^ no field `bar` on type `S`
+    s.field;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_for_unknown_receiver() {
+        check_diagnostics(
+            r#"
+fn main() {
+    let x = unknown();
+    x.foo();
+    x.bar;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_for_generic_receiver() {
+        check_diagnostics(
+            r#"
+trait Tr {}
+fn f<T: Tr>(t: T) {
+    t.foo();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_unresolved_field_for_private_field() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S { field: u32 }
+    pub fn make() -> S { S { field: 0 } }
+}
+fn main() {
+    m::make().field;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generates_method_in_existing_impl() {
+        check_fix(
+            r#"
+struct S;
+impl S {
+    fn new() -> S { S }
+}
+fn main() {
+    let count = 1u8;
+    S::new().$0frobnicate(count, "x");
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn new() -> S { S }
+
+    fn frobnicate(&self, count: u8, arg: &str) {
+        todo!()
+    }
+}
+fn main() {
+    let count = 1u8;
+    S::new().frobnicate(count, "x");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generates_new_impl() {
+        check_fix(
+            r#"
+struct S<T> { t: T }
+fn main(s: &mut S<u32>) {
+    s.$0frobnicate();
+}
+"#,
+            r#"
+struct S<T> { t: T }
+
+impl<T> S<T> {
+    fn frobnicate(&mut self) {
+        todo!()
+    }
+}
+fn main(s: &mut S<u32>) {
+    s.frobnicate();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_fix_for_non_adt_receiver() {
+        check_no_fix(
+            r#"
+fn main() {
+    (1, 2).$0frobnicate();
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Flags imports whose names are never used in their scope.
+
+use hir::{diagnostics::DiagnosticCode, Semantics};
+use ide_db::{
+    base_db::FileId,
+    helpers::unused_imports::{find_unused_imports, remove_use_tree},
+    source_change::SourceChange,
+    RootDatabase,
+};
+use syntax::{algo, ast, AstNode};
+use text_edit::TextEdit;
+
+use crate::diagnostics::{fix, Diagnostic};
+
+// Diagnostic: unused-import
+//
+// This diagnostic is shown for imports whose names are never used in their scope. Re-exports, glob
+// imports and imports with attributes are not checked.
+pub(super) fn check(acc: &mut Vec<Diagnostic>, sema: &Semantics<RootDatabase>, file_id: FileId) {
+    let root = sema.parse(file_id).syntax().clone();
+    for tree in find_unused_imports(sema, file_id) {
+        let range = tree.syntax().text_range();
+        let mut edit = TextEdit::builder();
+        let new_root = root.clone_for_update();
+        if let Some(tree) = algo::find_node_at_range::<ast::UseTree>(&new_root, range) {
+            remove_use_tree(&tree);
+            algo::diff(&root, &new_root).into_text_edit(&mut edit);
+        }
+        acc.push(
+            Diagnostic::hint(range, format!("unused import: `{}`", tree.syntax()))
+                .with_unused(true)
+                .with_code(Some(DiagnosticCode("unused-import")))
+                .with_fixes(Some(vec![fix(
+                    "remove_unused_import",
+                    "Remove unused import",
+                    SourceChange::from_text_edit(file_id, edit.finish()),
+                    range,
+                )])),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        diagnostics::tests::{check_diagnostics_with_config, check_fix_with_config},
+        DiagnosticsConfig,
+    };
+
+    fn check(ra_fixture: &str) {
+        let config = DiagnosticsConfig { unused_imports: true, ..DiagnosticsConfig::default() };
+        check_diagnostics_with_config(config, ra_fixture)
+    }
+
+    fn check_fix(ra_fixture_before: &str, ra_fixture_after: &str) {
+        let config = DiagnosticsConfig { unused_imports: true, ..DiagnosticsConfig::default() };
+        check_fix_with_config(config, ra_fixture_before, ra_fixture_after)
+    }
+
+    #[test]
+    fn unused_imports() {
+        check(
+            r#"
+mod foo {
+    pub struct A;
+    pub struct B;
+    pub fn f() {}
+}
+
+use foo::A;
+use foo::{B, f};
+        //^ unused import: `B`
+use foo::B as Renamed;
+  //^^^^^^^^^^^^^^^^^ unused import: `foo::B as Renamed`
+
+fn main() {
+    let _ = A;
+    f();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_imports() {
+        check(
+            r#"
+mod m {
+    pub trait Used {
+        fn method(&self) {}
+    }
+    impl Used for () {}
+    pub trait Assoc {
+        fn assoc() {}
+    }
+    impl Assoc for () {}
+    pub trait Unused {}
+}
+
+use m::Used as _;
+use m::Assoc;
+use m::Unused;
+  //^^^^^^^^^ unused import: `m::Unused`
+
+fn main() {
+    ().method();
+    <()>::assoc();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn usages_in_macros() {
+        check(
+            r#"
+mod m {
+    pub struct S;
+}
+
+use m::S;
+
+macro_rules! id {
+    ($($tt:tt)*) => { $($tt)* };
+}
+
+fn main() {
+    id!(let _ = S;);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_in_blocks_and_modules() {
+        check(
+            r#"
+mod m {
+    pub struct S;
+}
+
+mod inner {
+    use super::m::S;
+      //^^^^^^^^^^^ unused import: `super::m::S`
+}
+
+fn main() {
+    use m::S;
+    let _ = S;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn ignored_imports() {
+        check(
+            r#"
+mod m {
+    pub struct S;
+}
+
+pub use m::S;
+use m::*;
+#[allow(unused_imports)]
+use m::S as T;
+use unresolved::Thing;
+  //^^^^^^^^^^^^^^^^^ unresolved import
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_unused_import() {
+        check_fix(
+            r#"
+mod foo {
+    pub struct A;
+    pub struct B;
+}
+
+use foo::{A, B$0};
+
+fn main() {
+    let _ = A;
+}
+"#,
+            r#"
+mod foo {
+    pub struct A;
+    pub struct B;
+}
+
+use foo::A;
+
+fn main() {
+    let _ = A;
+}
+"#,
+        );
+        check_fix(
+            r#"
+mod foo {
+    pub struct A;
+}
+
+use foo::A$0;
+
+fn main() {}
+"#,
+            r#"
+mod foo {
+    pub struct A;
+}
+
+fn main() {}
+"#,
+        );
+    }
+}
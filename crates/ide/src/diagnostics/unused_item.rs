@@ -0,0 +1,513 @@
+//! Flags private and `pub(crate)` items that are never referenced in their crate.
+
+use cfg::{CfgAtom, CfgExpr};
+use hir::{HasAttrs, Semantics};
+use ide_db::{
+    base_db::FileId,
+    defs::Definition,
+    search::{FileReference, ReferenceAccess, SearchScope},
+    source_change::SourceChange,
+    RootDatabase,
+};
+use syntax::{
+    ast::{self, NameOwner, VisibilityOwner},
+    match_ast, AstNode, Direction, SyntaxKind, SyntaxNode, TextRange, T,
+};
+use text_edit::TextEdit;
+
+use crate::diagnostics::{fix, Diagnostic};
+
+// Diagnostic: unused-item
+//
+// This diagnostic is shown for private and `pub(crate)` functions, structs, constants, enum
+// variants and fields that are never referenced in their crate. Items marked with
+// `#[allow(dead_code)]`, items in `#[cfg(test)]` modules and items of trait impls are not checked.
+pub(super) fn check(acc: &mut Vec<Diagnostic>, sema: &Semantics<RootDatabase>, file_id: FileId) {
+    let module = match sema.to_module_def(file_id) {
+        Some(it) => it,
+        None => return,
+    };
+    if module_chain_is_exempt(sema.db, module) {
+        return;
+    }
+    let ctx = Ctx { sema, file_id, krate: module.krate() };
+
+    let source_file = sema.parse(file_id);
+    for node in source_file.syntax().descendants() {
+        match_ast! {
+            match node {
+                ast::Fn(it) => {
+                    if is_main(sema, &it) || is_in_trait_or_extern(it.syntax()) {
+                        continue;
+                    }
+                    if let Some(def) = sema.to_def(&it) {
+                        ctx.check_item(acc, &it, Definition::ModuleDef(def.into()), "function", "used");
+                    }
+                },
+                ast::Const(it) => {
+                    if is_in_trait_or_extern(it.syntax()) {
+                        continue;
+                    }
+                    if let Some(def) = sema.to_def(&it) {
+                        ctx.check_item(acc, &it, Definition::ModuleDef(def.into()), "constant", "used");
+                    }
+                },
+                ast::Struct(it) => {
+                    let def = match sema.to_def(&it) {
+                        Some(it) => it,
+                        None => continue,
+                    };
+                    let def = Definition::ModuleDef(hir::ModuleDef::Adt(def.into()));
+                    if !ctx.check_item(acc, &it, def, "struct", "constructed") {
+                        continue;
+                    }
+                    // Only look at the fields of structs that are used themselves.
+                    let fields = it.field_list().and_then(|it| match it {
+                        ast::FieldList::RecordFieldList(it) => Some(it),
+                        ast::FieldList::TupleFieldList(_) => None,
+                    });
+                    for field in fields.into_iter().flat_map(|it| it.fields()) {
+                        if let Some(def) = sema.to_def(&field) {
+                            ctx.check_item(acc, &field, Definition::Field(def), "field", "read");
+                        }
+                    }
+                },
+                ast::Enum(it) => {
+                    if !is_crate_visible(it.visibility()) {
+                        continue;
+                    }
+                    for variant in it.variant_list().into_iter().flat_map(|it| it.variants()) {
+                        if let Some(def) = sema.to_def(&variant) {
+                            let def = Definition::ModuleDef(hir::ModuleDef::Variant(def));
+                            ctx.check_item(acc, &variant, def, "variant", "constructed");
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+}
+
+struct Ctx<'a> {
+    sema: &'a Semantics<'a, RootDatabase>,
+    file_id: FileId,
+    krate: hir::Crate,
+}
+
+impl Ctx<'_> {
+    /// Pushes a diagnostic if `item` is unused, returns whether `item` is used or exempt.
+    fn check_item<N: NameOwner + VisibilityOwner>(
+        &self,
+        acc: &mut Vec<Diagnostic>,
+        item: &N,
+        def: Definition,
+        kind: &str,
+        usage: &str,
+    ) -> bool {
+        let name = match item.name() {
+            Some(it) => it,
+            None => return true,
+        };
+        let is_variant = ast::Variant::can_cast(item.syntax().kind());
+        if !is_variant && !is_crate_visible(item.visibility()) {
+            return true;
+        }
+        if name.text().starts_with('_')
+            || item.syntax().ancestors().any(|it| is_exempt(&it) || self.is_test_only(&it))
+        {
+            return true;
+        }
+        let usages = def.usages(self.sema).in_scope(SearchScope::krate(self.sema.db, self.krate));
+        let is_used = match def {
+            // Initializing or assigning a field doesn't make it any less dead.
+            Definition::Field(_) => usages.all().iter().flat_map(|(_, refs)| refs).any(is_read),
+            _ => usages.at_least_one(),
+        };
+        if is_used {
+            return true;
+        }
+
+        let mut edit = TextEdit::builder();
+        edit.delete(removal_range(item.syntax()));
+        let name_range = name.syntax().text_range();
+        acc.push(
+            Diagnostic::hint(name_range, format!("{} `{}` is never {}", kind, name, usage))
+                .with_unused(true)
+                .with_code(Some(hir::diagnostics::DiagnosticCode("unused-item")))
+                .with_fixes(Some(vec![fix(
+                    "remove_unused_item",
+                    &format!("Remove unused {} `{}`", kind, name),
+                    SourceChange::from_text_edit(self.file_id, edit.finish()),
+                    name_range,
+                )])),
+        );
+        false
+    }
+
+    /// Whether `node` is an item only compiled for tests, like a `#[cfg(test)] mod tests`.
+    fn is_test_only(&self, node: &SyntaxNode) -> bool {
+        let sema = self.sema;
+        let attrs = match_ast! {
+            match node {
+                ast::Module(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                ast::Fn(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                ast::Impl(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                ast::Struct(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                ast::Enum(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                ast::Const(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                ast::Variant(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                ast::RecordField(it) => sema.to_def(&it).map(|it| it.attrs(sema.db)),
+                _ => None,
+            }
+        };
+        attrs.and_then(|it| it.cfg()).map_or(false, |cfg| is_test_cfg(&cfg))
+    }
+}
+
+/// Whether a field reference reads the field, rather than initializing or assigning it.
+fn is_read(reference: &FileReference) -> bool {
+    if reference.access == Some(ReferenceAccess::Write) {
+        return false;
+    }
+    match &reference.name {
+        ast::NameLike::NameRef(name_ref) => {
+            ast::RecordExprField::for_field_name(name_ref).is_none()
+        }
+        _ => true,
+    }
+}
+
+/// Whether `cfg` can only be enabled together with `test`.
+fn is_test_cfg(cfg: &CfgExpr) -> bool {
+    let test = CfgAtom::Flag("test".into());
+    cfg.fold(&|atom| *atom != test) == Some(false)
+}
+
+/// Private and `pub(crate)` items can only be used from within their crate.
+fn is_crate_visible(vis: Option<ast::Visibility>) -> bool {
+    match vis {
+        None => true,
+        Some(vis) => vis.syntax().text() == "pub(crate)",
+    }
+}
+
+fn is_main(sema: &Semantics<RootDatabase>, func: &ast::Fn) -> bool {
+    func.name().map_or(false, |it| it.text() == "main")
+        && sema.to_def(func).map_or(false, |it| {
+            let module = it.module(sema.db);
+            module == module.krate().root_module(sema.db)
+        })
+}
+
+/// Trait items and items of trait impls are used through the trait, extern items by foreign code.
+fn is_in_trait_or_extern(item: &SyntaxNode) -> bool {
+    let container = match item.parent() {
+        Some(it) => it,
+        None => return false,
+    };
+    if ast::ExternItemList::can_cast(container.kind()) {
+        return true;
+    }
+    match container.parent() {
+        Some(parent) => {
+            ast::Trait::can_cast(parent.kind())
+                || ast::Impl::cast(parent).map_or(false, |it| it.trait_().is_some())
+        }
+        None => false,
+    }
+}
+
+/// Whether the attributes of `node` opt out of the check.
+fn is_exempt(node: &SyntaxNode) -> bool {
+    node.children().filter_map(ast::Attr::cast).any(|attr| match attr.as_simple_call() {
+        Some((name, args)) => match name.as_str() {
+            "allow" => allows_dead_code(&args.syntax().text().to_string()),
+            _ => false,
+        },
+        None => attr.simple_name().map_or(false, |name| {
+            matches!(name.as_str(), "test" | "bench" | "no_mangle" | "export_name" | "used")
+        }),
+    })
+}
+
+fn module_chain_is_exempt(db: &RootDatabase, module: hir::Module) -> bool {
+    module.path_to_root(db).into_iter().any(|module| {
+        let attrs = module.attrs(db);
+        let is_test = attrs.cfg().map_or(false, |cfg| is_test_cfg(&cfg));
+        is_test || attrs.by_key("allow").tt_values().any(|tt| allows_dead_code(&tt.to_string()))
+    })
+}
+
+fn allows_dead_code(lints: &str) -> bool {
+    lints
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|lint| lint == "dead_code" || lint == "unused")
+}
+
+/// The range to delete to remove `node` along with its separator and surrounding whitespace.
+fn removal_range(node: &SyntaxNode) -> TextRange {
+    let non_trivia_sibling =
+        |direction| node.siblings_with_tokens(direction).skip(1).find(|it| !it.kind().is_trivia());
+    let whitespace_sibling = |direction| {
+        node.siblings_with_tokens(direction).nth(1).filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+    };
+    let mut range = node.text_range();
+    match non_trivia_sibling(Direction::Next) {
+        Some(comma) if comma.kind() == T![,] => {
+            range = range.cover(comma.text_range());
+        }
+        _ => {
+            if let Some(comma) = non_trivia_sibling(Direction::Prev).filter(|it| it.kind() == T![,])
+            {
+                return range.cover(comma.text_range());
+            }
+        }
+    }
+    match whitespace_sibling(Direction::Prev) {
+        Some(ws) => range.cover(ws.text_range()),
+        None => match whitespace_sibling(Direction::Next) {
+            Some(ws) => range.cover(ws.text_range()),
+            None => range,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        diagnostics::tests::{check_diagnostics_with_config, check_fix_with_config},
+        DiagnosticsConfig,
+    };
+
+    fn check(ra_fixture: &str) {
+        let config = DiagnosticsConfig { unused_items: true, ..DiagnosticsConfig::default() };
+        check_diagnostics_with_config(config, ra_fixture)
+    }
+
+    fn check_fix(ra_fixture_before: &str, ra_fixture_after: &str) {
+        let config = DiagnosticsConfig { unused_items: true, ..DiagnosticsConfig::default() };
+        check_fix_with_config(config, ra_fixture_before, ra_fixture_after)
+    }
+
+    #[test]
+    fn unused_items() {
+        check(
+            r#"
+fn main() {
+    used();
+}
+
+fn used() {}
+fn unused() {}
+ //^^^^^^ function `unused` is never used
+pub fn public() {}
+pub(crate) struct Unused;
+                //^^^^^^ struct `Unused` is never constructed
+pub(super) struct Restricted;
+const C: u32 = 0;
+    //^ constant `C` is never used
+fn _ignored() {}
+
+struct S;
+impl S {
+    fn method(&self) {}
+     //^^^^^^ function `method` is never used
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unused_fields_and_variants() {
+        check(
+            r#"
+fn main() {
+    let b = 0;
+    get(&S { a: 0, b, c: 0 });
+    let _ = E::A;
+}
+
+fn get(s: &S) -> u32 {
+    s.a
+}
+
+struct S {
+    a: u32,
+    b: u32,
+  //^ field `b` is never read
+    pub c: u32,
+}
+
+enum E {
+    A,
+    B,
+  //^ variant `B` is never constructed
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fields_of_unused_structs_are_not_reported() {
+        check(
+            r#"
+struct S {
+     //^ struct `S` is never constructed
+    a: u32,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn exempt_items() {
+        check(
+            r#"
+//- /main.rs cfg:test,unix
+trait Trait {
+    fn required();
+}
+
+struct S;
+impl Trait for S {
+    fn required() {}
+}
+
+#[allow(dead_code)]
+fn allowed() {}
+
+#[allow(unused)]
+mod m {
+    fn in_allowed_module() {}
+}
+
+#[test]
+fn test() {}
+
+#[cfg(test)]
+mod tests {
+    fn helper() {}
+}
+
+#[cfg(all(test, unix))]
+fn test_helper() {}
+
+#[no_mangle]
+fn exported() {}
+
+extern "C" {
+    fn foreign();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn crate_level_allow() {
+        check(
+            r#"
+#![allow(dead_code)]
+
+fn unused() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn usages_in_other_files_count() {
+        check(
+            r#"
+//- /main.rs
+mod foo;
+
+pub(crate) fn used_in_foo() {}
+
+fn main() {}
+//- /foo.rs
+fn f() {
+    crate::used_in_foo();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_unused_function() {
+        check_fix(
+            r#"
+fn main() {
+    another();
+}
+
+fn unused$0() {}
+
+fn another() {}
+"#,
+            r#"
+fn main() {
+    another();
+}
+
+fn another() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_unused_field() {
+        check_fix(
+            r#"
+fn get(s: &S) -> u32 {
+    s.a
+}
+
+pub fn main() {
+    let _ = get;
+}
+
+struct S {
+    a: u32,
+    b$0: u32,
+}
+"#,
+            r#"
+fn get(s: &S) -> u32 {
+    s.a
+}
+
+pub fn main() {
+    let _ = get;
+}
+
+struct S {
+    a: u32,
+}
+"#,
+        );
+        check_fix(
+            r#"
+fn get(s: &S) -> u32 {
+    s.b
+}
+
+pub fn main() {
+    let _ = get;
+}
+
+struct S { a$0: u32, b: u32 }
+"#,
+            r#"
+fn get(s: &S) -> u32 {
+    s.b
+}
+
+pub fn main() {
+    let _ = get;
+}
+
+struct S { b: u32 }
+"#,
+        );
+    }
+}
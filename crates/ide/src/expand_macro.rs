@@ -1,7 +1,9 @@
 use std::iter;
 
-use hir::Semantics;
-use ide_db::RootDatabase;
+use either::Either;
+use hir::{HasSource, MetaVarBinding, Semantics};
+use ide_db::{helpers::insert_whitespace, RootDatabase};
+use itertools::Itertools;
 use syntax::{
     algo::find_node_at_offset, ast, ted, AstNode, NodeOrToken, SyntaxKind, SyntaxKind::*,
     SyntaxNode, WalkEvent, T,
@@ -63,6 +65,138 @@ fn expand_macro_recur(
     Some(expanded)
 }
 
+/// One macro call expanded while expanding a macro call step by step.
+#[derive(Debug)]
+pub struct MacroExpansionStep {
+    /// How deeply the call is nested in expansions of other calls, zero for the call at the
+    /// cursor.
+    pub depth: usize,
+    pub name: String,
+    /// The tokens the macro was called with.
+    pub input: String,
+    /// The rules the input was matched against, in order. Empty for builtin and proc macros.
+    pub rules: Vec<MacroRuleMatch>,
+    /// The index in `rules` of the rule used for the expansion.
+    pub used_rule: Option<usize>,
+    pub expansion: String,
+}
+
+/// How the input of a macro call matched one rule of a `macro_rules!` macro.
+#[derive(Debug)]
+pub struct MacroRuleMatch {
+    /// The pattern of the rule, as written in the macro definition.
+    pub pattern: String,
+    /// The tokens bound to each meta variable. Repetitions are shown as `[a, b, c]`.
+    pub bindings: Vec<(String, String)>,
+    /// Why the rule didn't match, `None` if it did.
+    pub error: Option<String>,
+}
+
+// Feature: Expand Macro Step By Step
+//
+// Shows each step of the recursive expansion of the macro at current cursor: for every macro
+// call that gets expanded, which `macro_rules!` rule matched, what each `$var` was bound to and
+// the resulting tokens. Rules that were tried before it are listed with the reason they didn't
+// match.
+pub(crate) fn expand_macro_steps(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<Vec<MacroExpansionStep>> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let mac = find_node_at_offset::<ast::MacroCall>(file.syntax(), position.offset)?;
+
+    let mut steps = Vec::new();
+    trace_macro_recur(&sema, &mac, 0, &mut steps)?;
+    Some(steps)
+}
+
+fn trace_macro_recur(
+    sema: &Semantics<RootDatabase>,
+    macro_call: &ast::MacroCall,
+    depth: usize,
+    steps: &mut Vec<MacroExpansionStep>,
+) -> Option<()> {
+    let expanded = sema.expand(macro_call)?;
+    let name = macro_call.path()?.segment()?.name_ref()?;
+    let input = macro_call.token_tree().map_or_else(String::new, |it| render(it.syntax()));
+
+    let (rules, used_rule) = match sema.trace_expand(macro_call) {
+        Some(trace) => {
+            let patterns = rule_patterns(sema, macro_call);
+            let rules = trace
+                .rules
+                .iter()
+                .enumerate()
+                .map(|(idx, rule)| MacroRuleMatch {
+                    pattern: patterns.get(idx).cloned().unwrap_or_default(),
+                    bindings: rule
+                        .bindings
+                        .iter()
+                        .map(|(name, binding)| (name.to_string(), render_binding(binding)))
+                        .collect(),
+                    error: rule.err.as_ref().map(|err| err.to_string()),
+                })
+                .collect();
+            (rules, trace.used_rule)
+        }
+        None => (Vec::new(), None),
+    };
+
+    steps.push(MacroExpansionStep {
+        depth,
+        name: name.to_string(),
+        input,
+        rules,
+        used_rule,
+        expansion: render(&expanded),
+    });
+
+    for child in expanded.descendants().filter_map(ast::MacroCall::cast) {
+        trace_macro_recur(sema, &child, depth + 1, steps);
+    }
+    Some(())
+}
+
+/// Returns the source text of the patterns of the rules of the macro called by `macro_call`.
+fn rule_patterns(sema: &Semantics<RootDatabase>, macro_call: &ast::MacroCall) -> Vec<String> {
+    let source = match sema.resolve_macro_call(macro_call).and_then(|it| it.source(sema.db)) {
+        Some(it) => it.value,
+        None => return Vec::new(),
+    };
+    let rules = match source {
+        Either::Left(ast::Macro::MacroRules(it)) => it.token_tree(),
+        Either::Left(ast::Macro::MacroDef(it)) => {
+            let mut token_trees = it.syntax().children().filter_map(ast::TokenTree::cast);
+            match (token_trees.next(), token_trees.next()) {
+                // `macro m($pattern) { ... }` has a single rule.
+                (Some(pattern), Some(_)) => return vec![pattern.syntax().text().to_string()],
+                (body, _) => body,
+            }
+        }
+        Either::Right(_) => None,
+    };
+    // Rules look like `(pattern) => { template };`, so every other token tree is a pattern.
+    rules
+        .into_iter()
+        .flat_map(|it| it.syntax().children().filter_map(ast::TokenTree::cast).step_by(2))
+        .map(|it| it.syntax().text().to_string())
+        .collect()
+}
+
+fn render_binding(binding: &MetaVarBinding) -> String {
+    match binding {
+        MetaVarBinding::Fragment(tt) => tt.to_string(),
+        MetaVarBinding::Nested(it) => format!("[{}]", it.iter().map(render_binding).join(", ")),
+        MetaVarBinding::Empty => "[]".to_string(),
+    }
+}
+
+/// Renders the syntax of a step, formatted like source code.
+fn render(syn: &SyntaxNode) -> String {
+    insert_whitespace::insert_whitespaces(syn, "  ")
+}
+
 // FIXME: It would also be cool to share logic here and in the mbe tests,
 // which are pretty unreadable at the moment.
 fn insert_whitespaces(syn: SyntaxNode) -> String {
@@ -148,6 +282,29 @@ mod tests {
         expect.assert_eq(&actual);
     }
 
+    fn check_steps(ra_fixture: &str, expect: Expect) {
+        let (analysis, pos) = fixture::position(ra_fixture);
+        let steps = analysis.expand_macro_steps(pos).unwrap().unwrap();
+        let mut actual = String::new();
+        for step in steps {
+            let indent = "  ".repeat(step.depth);
+            actual += &format!("{}{}!{}\n", indent, step.name, step.input);
+            for (idx, rule) in step.rules.iter().enumerate() {
+                let status = match &rule.error {
+                    Some(err) => format!("failed: {}", err),
+                    None if step.used_rule == Some(idx) => "used".to_string(),
+                    None => "matched".to_string(),
+                };
+                actual += &format!("{}  rule {} {}: {}\n", indent, idx, rule.pattern, status);
+                for (name, value) in &rule.bindings {
+                    actual += &format!("{}    ${} = {}\n", indent, name, value);
+                }
+            }
+            actual += &format!("{}  => {}\n", indent, step.expansion);
+        }
+        expect.assert_eq(&actual);
+    }
+
     #[test]
     fn macro_expand_recursive_expansion() {
         check(
@@ -297,4 +454,63 @@ fn main() {
                 0 "#]],
         );
     }
+
+    #[test]
+    fn macro_expand_steps() {
+        check_steps(
+            r#"
+macro_rules! add {
+    ($e:expr) => { $e };
+    ($e:expr, $($rest:expr),+) => { $e + add!($($rest),+) };
+}
+macro_rules! sum {
+    ($($e:expr),*) => { add!($($e),*) };
+}
+fn main() {
+    let x = s$0um!(1, 2, 3);
+}
+"#,
+            expect![[r#"
+                sum!(1, 2, 3)
+                  rule 0 ($($e:expr),*): used
+                    $e = [1, 2, 3]
+                  => add!(1, 2, 3)
+                  add!(1, 2, 3)
+                    rule 0 ($e:expr): failed: no rules expected the token `,`
+                      $e = 1
+                    rule 1 ($e:expr, $($rest:expr),+): used
+                      $e = 1
+                      $rest = [2, 3]
+                    => 1 + add!(2, 3)
+                    add!(2, 3)
+                      rule 0 ($e:expr): failed: no rules expected the token `,`
+                        $e = 2
+                      rule 1 ($e:expr, $($rest:expr),+): used
+                        $e = 2
+                        $rest = [3]
+                      => 2 + add!(3)
+                      add!(3)
+                        rule 0 ($e:expr): used
+                          $e = 3
+                        => 3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn macro_expand_steps_builtin() {
+        check_steps(
+            r#"
+#[rustc_builtin_macro]
+macro_rules! concat {}
+fn main() {
+    let x = conc$0at!("a", 1);
+}
+"#,
+            expect![[r#"
+                concat!("a", 1)
+                  => "a1"
+            "#]],
+        );
+    }
 }
@@ -1,7 +1,9 @@
+use std::fmt::Display;
+
 use either::Either;
 use hir::{
-    AsAssocItem, AssocItemContainer, GenericParam, HasAttrs, HasSource, HirDisplay, InFile, Module,
-    ModuleDef, Semantics,
+    AsAssocItem, AssocItemContainer, GenericParam, HasAttrs, HasSource, HirDisplay, InFile, Layout,
+    Module, ModuleDef, Semantics, StructKind,
 };
 use ide_db::{
     base_db::SourceDatabase,
@@ -34,6 +36,7 @@ pub struct HoverConfig {
     pub goto_type_def: bool,
     pub links_in_hover: bool,
     pub markdown: bool,
+    pub memory_layout: bool,
 }
 
 impl HoverConfig {
@@ -45,6 +48,7 @@ impl HoverConfig {
         goto_type_def: false,
         links_in_hover: true,
         markdown: true,
+        memory_layout: true,
     };
 
     pub fn any(&self) -> bool {
@@ -86,12 +90,15 @@ pub struct HoverResult {
 // Shows additional information, like type of an expression or documentation for definition when "focusing" code.
 // Focusing is usually hovering with a mouse, but can also be triggered with a shortcut.
 //
+// Hovering over a struct, union or enum shows its size and alignment, and hovering over a
+// struct or union field also shows its offset. These are computed for the target of the
+// crate, and approximate what the compiler does for `repr(Rust)` types.
+//
 // image::https://user-images.githubusercontent.com/48062697/113020658-b5f98b80-917a-11eb-9f88-3dbc27320c95.gif[]
 pub(crate) fn hover(
     db: &RootDatabase,
     position: FilePosition,
-    links_in_hover: bool,
-    markdown: bool,
+    config: &HoverConfig,
 ) -> Option<RangeInfo<HoverResult>> {
     let sema = Semantics::new(db);
     let file = sema.parse(position.file_id).syntax().clone();
@@ -145,8 +152,16 @@ pub(crate) fn hover(
             }
             _ => None,
         };
-        if let Some(markup) = hover_for_definition(db, definition, famous_defs.as_ref()) {
-            res.markup = process_markup(sema.db, definition, &markup, links_in_hover, markdown);
+        if let Some(markup) =
+            hover_for_definition(db, definition, famous_defs.as_ref(), config.memory_layout)
+        {
+            res.markup = process_markup(
+                sema.db,
+                definition,
+                &markup,
+                config.links_in_hover,
+                config.markdown,
+            );
             if let Some(action) = show_implementations_action(db, definition) {
                 res.actions.push(action);
             }
@@ -173,7 +188,8 @@ pub(crate) fn hover(
         return None;
     }
 
-    if let res @ Some(_) = hover_for_keyword(&sema, links_in_hover, markdown, &token) {
+    if let res @ Some(_) = hover_for_keyword(&sema, config.links_in_hover, config.markdown, &token)
+    {
         return res;
     }
 
@@ -192,7 +208,7 @@ pub(crate) fn hover(
         }
     };
 
-    res.markup = if markdown {
+    res.markup = if config.markdown {
         Markup::fenced_block(&ty.display(db))
     } else {
         ty.display(db).to_string().into()
@@ -376,6 +392,7 @@ fn hover_for_definition(
     db: &RootDatabase,
     def: Definition,
     famous_defs: Option<&FamousDefs>,
+    memory_layout: bool,
 ) -> Option<Markup> {
     let mod_path = definition_mod_path(db, &def);
     return match def {
@@ -389,14 +406,39 @@ fn hover_for_definition(
                 None
             }
         },
-        Definition::Field(def) => from_hir_fmt(db, def, mod_path),
+        Definition::Field(def) => {
+            let layout = def.layout(db).filter(|_| memory_layout).map(|layout| {
+                let mut comment = layout_comment(&layout);
+                if let Some(offset) = def.offset(db) {
+                    format_to!(comment, ", offset = {}", offset);
+                }
+                comment
+            });
+            from_hir_fmt_with_layout(db, def, layout, mod_path)
+        }
         Definition::ModuleDef(it) => match it {
             ModuleDef::Module(it) => from_hir_fmt(db, it, mod_path),
             ModuleDef::Function(it) => from_hir_fmt(db, it, mod_path),
-            ModuleDef::Adt(it) => from_hir_fmt(db, it, mod_path),
-            ModuleDef::Variant(it) => from_hir_fmt(db, it, mod_path),
-            ModuleDef::Const(it) => from_hir_fmt(db, it, mod_path),
-            ModuleDef::Static(it) => from_hir_fmt(db, it, mod_path),
+            ModuleDef::Adt(it) => {
+                let layout = it.layout(db).filter(|_| memory_layout);
+                from_hir_fmt_with_layout(db, it, layout.as_ref().map(layout_comment), mod_path)
+            }
+            ModuleDef::Variant(it) => {
+                // Only fieldless enums have discriminants that can be observed.
+                let is_fieldless = it
+                    .parent_enum(db)
+                    .variants(db)
+                    .into_iter()
+                    .all(|variant| variant.kind(db) == StructKind::Unit);
+                let value = if is_fieldless { it.discriminant(db) } else { None };
+                from_hir_fmt_with_value(db, it, value, mod_path)
+            }
+            ModuleDef::Const(it) => from_hir_fmt_with_value(db, it, it.eval(db).ok(), mod_path),
+            ModuleDef::Static(it) => {
+                // The initializer of a `static mut` isn't necessarily its current value.
+                let value = if it.is_mut(db) { None } else { it.eval(db).ok() };
+                from_hir_fmt_with_value(db, it, value, mod_path)
+            }
             ModuleDef::Trait(it) => from_hir_fmt(db, it, mod_path),
             ModuleDef::TypeAlias(it) => from_hir_fmt(db, it, mod_path),
             ModuleDef::BuiltinType(it) => famous_defs
@@ -419,6 +461,42 @@ fn hover_for_definition(
         from_def_source_labeled(db, def, Some(label), mod_path)
     }
 
+    fn from_hir_fmt_with_value<D>(
+        db: &RootDatabase,
+        def: D,
+        value: Option<impl Display>,
+        mod_path: Option<String>,
+    ) -> Option<Markup>
+    where
+        D: HasAttrs + HirDisplay,
+    {
+        let mut label = def.display(db).to_string();
+        if let Some(value) = value {
+            format_to!(label, " = {}", value);
+        }
+        from_def_source_labeled(db, def, Some(label), mod_path)
+    }
+
+    fn from_hir_fmt_with_layout<D>(
+        db: &RootDatabase,
+        def: D,
+        layout: Option<String>,
+        mod_path: Option<String>,
+    ) -> Option<Markup>
+    where
+        D: HasAttrs + HirDisplay,
+    {
+        let label = match layout {
+            Some(layout) => format!("{}\n{}", layout, def.display(db)),
+            None => def.display(db).to_string(),
+        };
+        from_def_source_labeled(db, def, Some(label), mod_path)
+    }
+
+    fn layout_comment(layout: &Layout) -> String {
+        format!("// size = {}, align = {}", layout.size, layout.align)
+    }
+
     fn from_def_source_labeled<D>(
         db: &RootDatabase,
         def: D,
@@ -519,14 +597,17 @@ mod tests {
 
     use super::*;
 
+    const HOVER_BASE_CONFIG: HoverConfig =
+        HoverConfig { memory_layout: false, ..HoverConfig::NO_ACTIONS };
+
     fn check_hover_no_result(ra_fixture: &str) {
         let (analysis, position) = fixture::position(ra_fixture);
-        assert!(analysis.hover(position, true, true).unwrap().is_none());
+        assert!(analysis.hover(position, &HOVER_BASE_CONFIG).unwrap().is_none());
     }
 
     fn check(ra_fixture: &str, expect: Expect) {
         let (analysis, position) = fixture::position(ra_fixture);
-        let hover = analysis.hover(position, true, true).unwrap().unwrap();
+        let hover = analysis.hover(position, &HOVER_BASE_CONFIG).unwrap().unwrap();
 
         let content = analysis.db.file_text(position.file_id);
         let hovered_element = &content[hover.range];
@@ -537,7 +618,10 @@ mod tests {
 
     fn check_hover_no_links(ra_fixture: &str, expect: Expect) {
         let (analysis, position) = fixture::position(ra_fixture);
-        let hover = analysis.hover(position, false, true).unwrap().unwrap();
+        let hover = analysis
+            .hover(position, &HoverConfig { links_in_hover: false, ..HOVER_BASE_CONFIG })
+            .unwrap()
+            .unwrap();
 
         let content = analysis.db.file_text(position.file_id);
         let hovered_element = &content[hover.range];
@@ -548,7 +632,22 @@ mod tests {
 
     fn check_hover_no_markdown(ra_fixture: &str, expect: Expect) {
         let (analysis, position) = fixture::position(ra_fixture);
-        let hover = analysis.hover(position, true, false).unwrap().unwrap();
+        let hover = analysis
+            .hover(position, &HoverConfig { markdown: false, ..HOVER_BASE_CONFIG })
+            .unwrap()
+            .unwrap();
+
+        let content = analysis.db.file_text(position.file_id);
+        let hovered_element = &content[hover.range];
+
+        let actual = format!("*{}*\n{}\n", hovered_element, hover.info.markup);
+        expect.assert_eq(&actual)
+    }
+
+    fn check_hover_memory_layout(ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let config = HoverConfig { memory_layout: true, ..HOVER_BASE_CONFIG };
+        let hover = analysis.hover(position, &config).unwrap().unwrap();
 
         let content = analysis.db.file_text(position.file_id);
         let hovered_element = &content[hover.range];
@@ -559,7 +658,7 @@ mod tests {
 
     fn check_actions(ra_fixture: &str, expect: Expect) {
         let (analysis, position) = fixture::position(ra_fixture);
-        let hover = analysis.hover(position, true, true).unwrap().unwrap();
+        let hover = analysis.hover(position, &HOVER_BASE_CONFIG).unwrap().unwrap();
         expect.assert_debug_eq(&hover.info.actions)
     }
 
@@ -852,7 +951,7 @@ fn main() {
                 ```
 
                 ```rust
-                const foo: u32
+                const foo: u32 = 123
                 ```
             "#]],
         );
@@ -866,7 +965,134 @@ fn main() {
                 ```
 
                 ```rust
-                static foo: u32
+                static foo: u32 = 456
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_const_static_evaluated_value() {
+        check(
+            r#"
+const SHIFT: usize = 12;
+const MAX$0: usize = 1 << SHIFT;
+"#,
+            expect![[r#"
+                *MAX*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const MAX: usize = 4096
+                ```
+            "#]],
+        );
+        check(
+            r#"
+struct S;
+impl S {
+    const SIZE: u8 = 2 * 3;
+}
+fn f() { S::SIZE$0; }
+"#,
+            expect![[r#"
+                *SIZE*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const SIZE: u8 = 6
+                ```
+            "#]],
+        );
+        check(
+            r#"
+static mut COUNTER$0: u32 = 0;
+"#,
+            expect![[r#"
+                *COUNTER*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                static mut COUNTER: u32
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_const_eval_failure_shows_no_value() {
+        check(
+            r#"
+fn not_const() -> u32 { 0 }
+const BAD$0: u8 = 255 + not_const();
+"#,
+            expect![[r#"
+                *BAD*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const BAD: u8
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_enum_variant_discriminant() {
+        check(
+            r#"
+const BASE: isize = 8;
+enum E {
+    A,
+    B = BASE * 2,
+    C$0,
+}
+"#,
+            expect![[r#"
+                *C*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                C = 17
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_negative_enum_variant_discriminant() {
+        check(
+            r#"
+#[repr(i8)]
+enum E {
+    A = -2,
+    B$0,
+}
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B = -1
                 ```
             "#]],
         );
@@ -947,7 +1173,7 @@ enum Option<T> {
                 ```
 
                 ```rust
-                None
+                None = 0
                 ```
 
                 ---
@@ -1135,7 +1361,7 @@ fn main() {
                 ```
 
                 ```rust
-                const C: u32
+                const C: u32 = 1
                 ```
             "#]],
         )
@@ -3698,7 +3924,7 @@ fn foo() {
                 ```
 
                 ```rust
-                const FOO: usize
+                const FOO: usize = 3
                 ```
 
                 ---
@@ -4004,4 +4230,199 @@ pub fn foo() {}
             "#]],
         )
     }
+
+    #[test]
+    fn hover_shows_struct_layout() {
+        check_hover_memory_layout(
+            r#"
+struct Foo$0 { a: u8, b: u64, c: &'static str }
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 32, align = 8
+                struct Foo
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_shows_field_offset() {
+        check_hover_memory_layout(
+            r#"
+#[repr(C)]
+struct Foo { a: u8, b: u64, c$0: u32 }
+"#,
+            expect![[r#"
+                *c*
+
+                ```rust
+                test::Foo
+                ```
+
+                ```rust
+                // size = 4, align = 4, offset = 16
+                c: u32
+                ```
+            "#]],
+        );
+        check_hover_memory_layout(
+            r#"
+struct Foo { a: u8, b: u64, c$0: u32 }
+"#,
+            expect![[r#"
+                *c*
+
+                ```rust
+                test::Foo
+                ```
+
+                ```rust
+                // size = 4, align = 4, offset = 8
+                c: u32
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_shows_packed_and_aligned_layout() {
+        check_hover_memory_layout(
+            r#"
+#[repr(packed)]
+struct Foo$0 { a: u8, b: u64 }
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 9, align = 1
+                struct Foo
+                ```
+            "#]],
+        );
+        check_hover_memory_layout(
+            r#"
+#[repr(C, align(16))]
+struct Foo$0 { a: u8, b: u64 }
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 16, align = 16
+                struct Foo
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_shows_enum_layout_with_niche() {
+        check_hover_memory_layout(
+            r#"
+enum MaybeRef$0<'a> { None, Some(&'a u32) }
+"#,
+            expect![[r#"
+                *MaybeRef*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 8, align = 8
+                enum MaybeRef<'a>
+                ```
+            "#]],
+        );
+        check_hover_memory_layout(
+            r#"
+enum Tagged$0 { A(u8), B(u32) }
+"#,
+            expect![[r#"
+                *Tagged*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 8, align = 4
+                enum Tagged
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_no_offset_for_generic_or_variant_fields() {
+        check_hover_memory_layout(
+            r#"
+struct Foo<T> { a$0: T }
+"#,
+            expect![[r#"
+                *a*
+
+                ```rust
+                test::Foo
+                ```
+
+                ```rust
+                a: T
+                ```
+            "#]],
+        );
+        check_hover_memory_layout(
+            r#"
+enum Foo { A { a$0: u32 } }
+"#,
+            expect![[r#"
+                *a*
+
+                ```rust
+                test::A
+                ```
+
+                ```rust
+                // size = 4, align = 4
+                a: u32
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_layout_respects_config() {
+        check(
+            r#"
+struct Foo$0 { a: u8 }
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                struct Foo
+                ```
+            "#]],
+        );
+    }
 }
@@ -4,8 +4,9 @@ use ide_db::helpers::FamousDefs;
 use ide_db::RootDatabase;
 use stdx::to_lower_snake_case;
 use syntax::{
-    ast::{self, ArgListOwner, AstNode, NameOwner},
-    match_ast, Direction, NodeOrToken, SmolStr, SyntaxKind, TextRange, T,
+    ast::{self, ArgListOwner, AstNode, GenericParamsOwner, NameOwner},
+    match_ast, Direction, NodeOrToken, SmolStr, SyntaxKind, SyntaxNode, SyntaxToken, TextRange,
+    WalkEvent, T,
 };
 
 use crate::FileId;
@@ -15,6 +16,10 @@ pub struct InlayHintsConfig {
     pub type_hints: bool,
     pub parameter_hints: bool,
     pub chaining_hints: bool,
+    pub lifetime_elision_hints: bool,
+    pub param_names_for_lifetime_elision_hints: bool,
+    pub closure_return_type_hints: bool,
+    pub closure_capture_hints: bool,
     pub max_length: Option<usize>,
 }
 
@@ -23,6 +28,9 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    LifetimeHint,
+    ClosureReturnTypeHint,
+    ClosureCaptureHint,
 }
 
 #[derive(Debug)]
@@ -42,6 +50,9 @@ pub struct InlayHint {
 // * types of local variables
 // * names of function arguments
 // * types of chained expressions
+// * elided lifetimes in function signatures (disabled by default)
+// * return types of closures with block bodies (disabled by default)
+// * what closures capture and how, e.g. `(a, &b, &mut c)` (disabled by default)
 //
 // **Note:** VS Code does not have native support for inlay hints https://github.com/microsoft/vscode/issues/16221[yet] and the hints are implemented using decorations.
 // This approach has limitations, the caret movement and bracket highlighting near the edges of the hint may be weird:
@@ -74,6 +85,8 @@ pub(crate) fn inlay_hints(
                 ast::CallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::MethodCallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::IdentPat(it) => { get_bind_pat_hints(&mut res, &sema, config, it); },
+                ast::Fn(it) => { get_lifetime_elision_hints(&mut res, &sema, config, it); },
+                ast::ClosureExpr(it) => { get_closure_hints(&mut res, &sema, config, it); },
                 _ => (),
             }
         }
@@ -204,6 +217,347 @@ fn get_bind_pat_hints(
     Some(())
 }
 
+fn get_closure_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    closure: ast::ClosureExpr,
+) -> Option<()> {
+    if !config.closure_return_type_hints && !config.closure_capture_hints {
+        return None;
+    }
+    let param_list = closure.param_list()?;
+    let ty = sema.type_of_expr(&closure.clone().into())?;
+
+    if config.closure_capture_hints {
+        if let Some(hint) = closure_capture_hint(sema, &ty, &param_list) {
+            acc.push(hint);
+        }
+    }
+
+    if config.closure_return_type_hints
+        && closure.ret_type().is_none()
+        && matches!(closure.body(), Some(ast::Expr::BlockExpr(_)))
+    {
+        let ret_ty = ty.as_callable(sema.db)?.return_type();
+        if ret_ty.is_unknown() || ret_ty.is_unit() {
+            return None;
+        }
+        acc.push(InlayHint {
+            range: param_list.syntax().text_range(),
+            kind: InlayKind::ClosureReturnTypeHint,
+            label: ret_ty.display_truncated(sema.db, config.max_length).to_string().into(),
+        });
+    }
+    Some(())
+}
+
+/// Lists the captured locals in parentheses, each prefixed with how it is
+/// captured. The `move` keyword, if any, is already written right before.
+fn closure_capture_hint(
+    sema: &Semantics<RootDatabase>,
+    ty: &hir::Type,
+    param_list: &ast::ParamList,
+) -> Option<InlayHint> {
+    let captures = ty.as_closure(sema.db)?.captured_items(sema.db);
+    if captures.is_empty() {
+        return None;
+    }
+    let captures = captures
+        .iter()
+        .map(|capture| {
+            let prefix = match capture.kind() {
+                hir::CaptureKind::ByRef => "&",
+                hir::CaptureKind::ByMutRef => "&mut ",
+                hir::CaptureKind::ByValue => "",
+            };
+            Some(format!("{}{}", prefix, capture.local().name(sema.db)?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(InlayHint {
+        range: param_list.syntax().text_range(),
+        kind: InlayKind::ClosureCaptureHint,
+        label: format!("({})", captures.join(", ")).into(),
+    })
+}
+
+/// Renders the lifetimes elided in a function signature, following the
+/// elision rules: every elided input lifetime becomes a fresh generic
+/// parameter, and elided output lifetimes take the lifetime of `self` if it is
+/// a reference, or the only input lifetime if there is exactly one.
+fn get_lifetime_elision_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    func: ast::Fn,
+) -> Option<()> {
+    if !config.lifetime_elision_hints {
+        return None;
+    }
+    let param_list = func.param_list()?;
+    let generic_param_list = func.generic_param_list();
+    let mut used_names: Vec<SmolStr> = generic_param_list
+        .iter()
+        .flat_map(|it| it.lifetime_params())
+        .filter_map(|it| it.lifetime())
+        .map(|it| SmolStr::new(it.text()))
+        .collect();
+
+    let mut fresh_names: Vec<SmolStr> = Vec::new();
+    let mut next_index = 0;
+    let mut new_name = |used_names: &mut Vec<SmolStr>, param_name: Option<String>| {
+        let name = match param_name.map(|it| SmolStr::from(format!("'{}", it))) {
+            Some(name) if !used_names.contains(&name) => name,
+            _ => {
+                next_index += 1;
+                SmolStr::from(format!("'{}", next_index - 1))
+            }
+        };
+        used_names.push(name.clone());
+        name
+    };
+
+    let mut hints = Vec::new();
+    let mut input_lifetimes = Vec::new();
+    let mut self_lifetime = None;
+
+    if let Some(self_param) = param_list.self_param() {
+        let param_name = config.param_names_for_lifetime_elision_hints.then(|| "self".to_string());
+        match (self_param.amp_token(), self_param.lifetime(), self_param.ty()) {
+            (Some(_), Some(lifetime), _) => {
+                let name = SmolStr::new(lifetime.text());
+                input_lifetimes.push(name.clone());
+                self_lifetime = Some(name);
+            }
+            (Some(amp), None, _) => {
+                let name = new_name(&mut used_names, param_name);
+                hints.push(lifetime_hint(amp.text_range(), format!("{} ", name)));
+                input_lifetimes.push(name.clone());
+                fresh_names.push(name.clone());
+                self_lifetime = Some(name);
+            }
+            (None, _, Some(ty)) => {
+                let positions = lifetime_positions(sema, ty.syntax());
+                let elided = positions.iter().filter(|it| it.is_elided()).count();
+                let mut param_name = param_name.filter(|_| elided == 1);
+                for position in positions {
+                    let name = match position {
+                        LifetimePosition::Named(name) => name,
+                        LifetimePosition::Elided(elided) => {
+                            let name = new_name(&mut used_names, param_name.take());
+                            hints.push(elided.hint(&name));
+                            fresh_names.push(name.clone());
+                            name
+                        }
+                    };
+                    if matches!(ty, ast::Type::RefType(_)) && input_lifetimes.is_empty() {
+                        self_lifetime = Some(name.clone());
+                    }
+                    input_lifetimes.push(name);
+                }
+            }
+            (None, _, None) => (),
+        }
+    }
+
+    for param in param_list.params() {
+        let ty = match param.ty() {
+            Some(it) => it,
+            None => continue,
+        };
+        let positions = lifetime_positions(sema, ty.syntax());
+        let elided = positions.iter().filter(|it| it.is_elided()).count();
+        let mut param_name = match param.pat() {
+            Some(ast::Pat::IdentPat(it))
+                if config.param_names_for_lifetime_elision_hints && elided == 1 =>
+            {
+                it.name().map(|it| it.text().trim_start_matches("r#").to_string())
+            }
+            _ => None,
+        };
+        for position in positions {
+            let name = match position {
+                LifetimePosition::Named(name) => name,
+                LifetimePosition::Elided(elided) => {
+                    let name = new_name(&mut used_names, param_name.take());
+                    hints.push(elided.hint(&name));
+                    fresh_names.push(name.clone());
+                    name
+                }
+            };
+            input_lifetimes.push(name);
+        }
+    }
+
+    let output_lifetime = match (self_lifetime, &*input_lifetimes) {
+        (Some(it), _) => Some(it),
+        (None, [it]) => Some(it.clone()),
+        _ => None,
+    };
+    if let (Some(output_lifetime), Some(ret_type)) = (output_lifetime, func.ret_type()) {
+        for position in lifetime_positions(sema, ret_type.syntax()) {
+            if let LifetimePosition::Elided(elided) = position {
+                hints.push(elided.hint(&output_lifetime));
+            }
+        }
+    }
+
+    if !fresh_names.is_empty() {
+        let fresh_names = fresh_names.join(", ");
+        let generic_hint = match &generic_param_list {
+            Some(list) => {
+                let l_angle = list.l_angle_token()?;
+                let label = if list.generic_params().next().is_some() {
+                    format!("{}, ", fresh_names)
+                } else {
+                    fresh_names
+                };
+                lifetime_hint(l_angle.text_range(), label)
+            }
+            None => lifetime_hint(func.name()?.syntax().text_range(), format!("<{}>", fresh_names)),
+        };
+        acc.push(generic_hint);
+    }
+    acc.extend(hints);
+    Some(())
+}
+
+fn lifetime_hint(range: TextRange, label: String) -> InlayHint {
+    InlayHint { range, kind: InlayKind::LifetimeHint, label: label.into() }
+}
+
+/// A place in a type where a lifetime is, or could be, written.
+enum LifetimePosition {
+    Named(SmolStr),
+    Elided(ElidedLifetime),
+}
+
+impl LifetimePosition {
+    fn is_elided(&self) -> bool {
+        matches!(self, LifetimePosition::Elided(_))
+    }
+}
+
+enum ElidedLifetime {
+    /// `&T`, the lifetime goes right after the `&`.
+    Ref(SyntaxToken),
+    /// `'_`, the name is shown next to it.
+    Placeholder(ast::Lifetime),
+    /// A lifetime parameter of a path's type that isn't written at all, like the
+    /// one of `Foo` for `struct Foo<'a>`. The hint goes after `range`, between
+    /// `prefix` and `suffix` which add the `<`, `>` and commas as needed.
+    Hidden { range: TextRange, prefix: &'static str, suffix: &'static str },
+}
+
+impl ElidedLifetime {
+    fn hint(&self, name: &str) -> InlayHint {
+        match self {
+            ElidedLifetime::Ref(amp) => lifetime_hint(amp.text_range(), format!("{} ", name)),
+            ElidedLifetime::Placeholder(lifetime) => {
+                lifetime_hint(lifetime.syntax().text_range(), format!(" = {}", name))
+            }
+            ElidedLifetime::Hidden { range, prefix, suffix } => {
+                lifetime_hint(*range, format!("{}{}{}", prefix, name, suffix))
+            }
+        }
+    }
+}
+
+/// Collects lifetime positions in source order. Lifetimes of function pointers,
+/// `Fn` sugar and `for<'a>` types are bound there and not by the function.
+fn lifetime_positions(sema: &Semantics<RootDatabase>, node: &SyntaxNode) -> Vec<LifetimePosition> {
+    let mut res = Vec::new();
+    let mut preorder = node.preorder();
+    while let Some(event) = preorder.next() {
+        let node = match event {
+            WalkEvent::Enter(node) => node,
+            WalkEvent::Leave(_) => continue,
+        };
+        if matches!(node.kind(), SyntaxKind::FN_PTR_TYPE | SyntaxKind::FOR_TYPE) {
+            preorder.skip_subtree();
+            continue;
+        }
+        match_ast! {
+            match node {
+                ast::PathType(it) => {
+                    res.extend(hidden_lifetimes(sema, &it).map(LifetimePosition::Elided));
+                },
+                ast::PathSegment(it) => {
+                    if it.param_list().is_some() {
+                        preorder.skip_subtree();
+                    }
+                },
+                ast::RefType(it) => {
+                    if let (Some(amp), None) = (it.amp_token(), it.lifetime()) {
+                        res.push(LifetimePosition::Elided(ElidedLifetime::Ref(amp)));
+                    }
+                },
+                ast::Lifetime(it) => {
+                    if it.text() == "'_" {
+                        res.push(LifetimePosition::Elided(ElidedLifetime::Placeholder(it)));
+                    } else {
+                        res.push(LifetimePosition::Named(SmolStr::new(it.text())));
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+    res
+}
+
+/// The lifetime parameters of the type a path refers to that the path leaves out.
+/// Like type lowering, only the last segment's generic arguments are considered.
+fn hidden_lifetimes(
+    sema: &Semantics<RootDatabase>,
+    path_type: &ast::PathType,
+) -> impl Iterator<Item = ElidedLifetime> {
+    let hidden = (|| {
+        let path = path_type.path()?;
+        let segment = path.segment()?;
+        let def: hir::GenericDef = match sema.resolve_path(&path)? {
+            hir::PathResolution::Def(hir::ModuleDef::Adt(it)) => it.into(),
+            hir::PathResolution::Def(hir::ModuleDef::TypeAlias(it)) => it.into(),
+            _ => return None,
+        };
+        let count = def
+            .params(sema.db)
+            .into_iter()
+            .filter(|it| matches!(it, hir::GenericParam::LifetimeParam(_)))
+            .count();
+        let arg_list = segment.generic_arg_list();
+        let has_lifetime_args = arg_list
+            .iter()
+            .flat_map(|it| it.generic_args())
+            .any(|it| matches!(it, ast::GenericArg::LifetimeArg(_)));
+        if count == 0 || has_lifetime_args {
+            return None;
+        }
+        Some((segment, arg_list, count))
+    })();
+    hidden
+        .into_iter()
+        .flat_map(|(segment, arg_list, count)| {
+            let (range, has_args) = match &arg_list {
+                Some(list) => {
+                    (list.l_angle_token()?.text_range(), list.generic_args().next().is_some())
+                }
+                None => (segment.syntax().text_range(), false),
+            };
+            Some((0..count).map(move |idx| {
+                let first = idx == 0;
+                let last = idx + 1 == count;
+                let (prefix, suffix) = match (&arg_list, has_args) {
+                    (None, _) => (if first { "<" } else { ", " }, if last { ">" } else { "" }),
+                    (Some(_), true) => ("", ", "),
+                    (Some(_), false) => (if first { "" } else { ", " }, ""),
+                };
+                ElidedLifetime::Hidden { range, prefix, suffix }
+            }))
+        })
+        .flatten()
+}
+
 /// Checks if the type is an Iterator from std::iter and replaces its hint with an `impl Iterator<Item = Ty>`.
 fn hint_iterator(
     sema: &Semantics<RootDatabase>,
@@ -435,14 +789,21 @@ fn get_callable(
 mod tests {
     use expect_test::{expect, Expect};
     use ide_db::helpers::FamousDefs;
-    use test_utils::extract_annotations;
+    use test_utils::{assert_eq_text, extract_annotations};
 
-    use crate::{fixture, inlay_hints::InlayHintsConfig};
+    use crate::{
+        fixture,
+        inlay_hints::{InlayHintsConfig, InlayKind},
+    };
 
     const TEST_CONFIG: InlayHintsConfig = InlayHintsConfig {
         type_hints: true,
         parameter_hints: true,
         chaining_hints: true,
+        lifetime_elision_hints: false,
+        param_names_for_lifetime_elision_hints: false,
+        closure_return_type_hints: false,
+        closure_capture_hints: false,
         max_length: None,
     };
 
@@ -456,6 +817,10 @@ mod tests {
                 parameter_hints: true,
                 type_hints: false,
                 chaining_hints: false,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             ra_fixture,
@@ -468,6 +833,10 @@ mod tests {
                 parameter_hints: false,
                 type_hints: true,
                 chaining_hints: false,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             ra_fixture,
@@ -480,6 +849,10 @@ mod tests {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             ra_fixture,
@@ -497,6 +870,19 @@ mod tests {
         assert_eq!(expected, actual, "\nExpected:\n{:#?}\n\nActual:\n{:#?}", expected, actual);
     }
 
+    /// Renders lifetime hints into the source text, each right after the range it's attached to.
+    fn check_lifetimes(config: InlayHintsConfig, ra_fixture: &str, expected: &str) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let mut text = analysis.file_text(file_id).unwrap().to_string();
+        let mut inlay_hints = analysis.inlay_hints(file_id, &config).unwrap();
+        inlay_hints.sort_by_key(|it| it.range.end());
+        for hint in inlay_hints.into_iter().rev() {
+            assert_eq!(hint.kind, InlayKind::LifetimeHint);
+            text.insert_str(hint.range.end().into(), &hint.label);
+        }
+        assert_eq_text!(expected.trim(), text.trim());
+    }
+
     fn check_expect(config: InlayHintsConfig, ra_fixture: &str, expect: Expect) {
         let ra_fixture =
             format!("//- /main.rs crate:main deps:core\n{}\n{}", ra_fixture, FamousDefs::FIXTURE);
@@ -512,6 +898,10 @@ mod tests {
                 type_hints: false,
                 parameter_hints: false,
                 chaining_hints: false,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1202,6 +1592,290 @@ fn main() {
         );
     }
 
+    // Closure hint tests
+
+    const CLOSURE_CONFIG: InlayHintsConfig = InlayHintsConfig {
+        type_hints: false,
+        parameter_hints: false,
+        chaining_hints: false,
+        lifetime_elision_hints: false,
+        param_names_for_lifetime_elision_hints: false,
+        closure_return_type_hints: false,
+        closure_capture_hints: false,
+        max_length: None,
+    };
+
+    #[test]
+    fn closure_return_type_hints() {
+        check_with_config(
+            InlayHintsConfig { closure_return_type_hints: true, ..CLOSURE_CONFIG },
+            r#"
+fn main() {
+    let a = |x: u32| { x + 1 };
+          //^^^^^^^^ u32
+    let b = |x: u32| x;
+    let c = || -> u32 { 0 };
+    let d = || {};
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_capture_hints() {
+        check_with_config(
+            InlayHintsConfig { closure_capture_hints: true, ..CLOSURE_CONFIG },
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+struct S;
+impl S {
+    fn consume(self) {}
+    fn peek(&self) {}
+    fn poke(&mut self) {}
+}
+fn main() {
+    let a = S;
+    let b = S;
+    let mut c = S;
+    let n = 0;
+    let f = || {
+          //^^ (&b, &mut c, &n, a)
+        b.peek();
+        c.poke();
+        let m = n + 1;
+        a.consume();
+        m
+    };
+    let g = move || b.peek();
+               //^^ (b)
+    let h = |x: S| x.consume();
+    let i = || {
+          //^^ (&mut c)
+        let j = || c.poke();
+              //^^ (&mut c)
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_capture_and_return_type_hints() {
+        check_expect(
+            InlayHintsConfig {
+                closure_return_type_hints: true,
+                closure_capture_hints: true,
+                ..CLOSURE_CONFIG
+            },
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+fn main() {
+    let n = 0;
+    let u = unknown();
+    let f = || { n + 1 };
+    let g = || { u };
+}
+"#,
+            expect![[r#"
+                [
+                    InlayHint {
+                        range: 115..117,
+                        kind: ClosureCaptureHint,
+                        label: "(&n)",
+                    },
+                    InlayHint {
+                        range: 115..117,
+                        kind: ClosureReturnTypeHint,
+                        label: "i32",
+                    },
+                    InlayHint {
+                        range: 141..143,
+                        kind: ClosureCaptureHint,
+                        label: "(u)",
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn closure_capture_hints_places() {
+        check_with_config(
+            InlayHintsConfig { closure_capture_hints: true, ..CLOSURE_CONFIG },
+            r#"
+#[lang = "copy"]
+trait Copy {}
+impl Copy for u32 {}
+struct P { x: u32, s: S }
+struct S;
+fn main() {
+    let mut p = P { x: 0, s: S };
+    let r = &mut p;
+    let a = || { let y = p.x; };
+          //^^ (&p)
+    let b = || { let s = p.s; };
+          //^^ (p)
+    let c = || { p.x = 1; };
+          //^^ (&mut p)
+    let d = || { let P { ref s, .. } = p; };
+          //^^ (&p)
+    let e = || { r.x += 1; };
+          //^^ (&mut r)
+}
+"#,
+        );
+    }
+
+    // Lifetime elision hint tests
+
+    const LIFETIME_CONFIG: InlayHintsConfig = InlayHintsConfig {
+        type_hints: false,
+        parameter_hints: false,
+        chaining_hints: false,
+        lifetime_elision_hints: true,
+        param_names_for_lifetime_elision_hints: false,
+        closure_return_type_hints: false,
+        closure_capture_hints: false,
+        max_length: None,
+    };
+
+    #[test]
+    fn lifetime_hints_disabled_by_default() {
+        check_with_config(TEST_CONFIG, r#"fn f(x: &str) -> &str { x }"#);
+    }
+
+    #[test]
+    fn lifetime_hints_single_input() {
+        check_lifetimes(
+            LIFETIME_CONFIG,
+            r#"
+fn no_refs(x: u32) -> u32 { x }
+fn single(x: &str) -> &str { x }
+fn generic<T>(x: &mut T) -> &T { x }
+fn explicit<'a>(x: &'a str, y: u32) -> &str { x }
+fn placeholder(x: Wrap<'_>) -> Wrap<'_> { x }
+"#,
+            r#"
+fn no_refs(x: u32) -> u32 { x }
+fn single<'0>(x: &'0 str) -> &'0 str { x }
+fn generic<'0, T>(x: &'0 mut T) -> &'0 T { x }
+fn explicit<'a>(x: &'a str, y: u32) -> &'a str { x }
+fn placeholder<'0>(x: Wrap<'_ = '0>) -> Wrap<'_ = '0> { x }
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_hints_multiple_inputs() {
+        check_lifetimes(
+            LIFETIME_CONFIG,
+            r#"
+fn two(x: &str, y: &(&u8, u8)) {}
+fn ambiguous(x: &str, y: &str) -> &str { x }
+fn empty_generics<>(x: &str) {}
+"#,
+            r#"
+fn two<'0, '1, '2>(x: &'0 str, y: &'1 (&'2 u8, u8)) {}
+fn ambiguous<'0, '1>(x: &'0 str, y: &'1 str) -> &str { x }
+fn empty_generics<'0>(x: &'0 str) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_hints_self() {
+        check_lifetimes(
+            LIFETIME_CONFIG,
+            r#"
+struct S;
+impl S {
+    fn by_ref(&self, x: &str) -> &str { x }
+    fn by_mut(&mut self) -> &mut u8 { loop {} }
+    fn named<'a>(&'a self, x: &str) -> &u8 { loop {} }
+    fn typed(self: &Self, x: &str) -> &u8 { loop {} }
+    fn by_value(self, x: &str) -> &str { x }
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn by_ref<'0, '1>(&'0 self, x: &'1 str) -> &'0 str { x }
+    fn by_mut<'0>(&'0 mut self) -> &'0 mut u8 { loop {} }
+    fn named<'0, 'a>(&'a self, x: &'0 str) -> &'a u8 { loop {} }
+    fn typed<'0, '1>(self: &'0 Self, x: &'1 str) -> &'0 u8 { loop {} }
+    fn by_value<'0>(self, x: &'0 str) -> &'0 str { x }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_hints_hidden_in_paths() {
+        check_lifetimes(
+            LIFETIME_CONFIG,
+            r#"
+struct Foo<'a>(&'a u8);
+struct Pair<'a, 'b, T>(&'a T, &'b T);
+type Alias<'a> = Foo<'a>;
+fn single(x: Foo) -> Foo { x }
+fn with_args(x: Pair<u8>, y: Pair<>) {}
+fn alias(x: &Alias) {}
+fn written(x: Foo<'_>) -> Foo { x }
+"#,
+            r#"
+struct Foo<'a>(&'a u8);
+struct Pair<'a, 'b, T>(&'a T, &'b T);
+type Alias<'a> = Foo<'a>;
+fn single<'0>(x: Foo<'0>) -> Foo<'0> { x }
+fn with_args<'0, '1, '2, '3>(x: Pair<'0, '1, u8>, y: Pair<'2, '3>) {}
+fn alias<'0, '1>(x: &'0 Alias<'1>) {}
+fn written<'0>(x: Foo<'_ = '0>) -> Foo<'0> { x }
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_hints_skip_higher_ranked() {
+        check_lifetimes(
+            LIFETIME_CONFIG,
+            r#"
+fn fn_ptr(f: fn(&u8) -> &u8) {}
+fn fn_trait(f: impl Fn(&u8) -> &u8, x: &u8) -> &u8 { x }
+fn hrtb(f: for<'a> fn(&'a u8)) {}
+"#,
+            r#"
+fn fn_ptr(f: fn(&u8) -> &u8) {}
+fn fn_trait<'0>(f: impl Fn(&u8) -> &u8, x: &'0 u8) -> &'0 u8 { x }
+fn hrtb(f: for<'a> fn(&'a u8)) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_hints_param_names() {
+        check_lifetimes(
+            InlayHintsConfig { param_names_for_lifetime_elision_hints: true, ..LIFETIME_CONFIG },
+            r#"
+struct S;
+impl S {
+    fn f(&self, key: &str, pair: (&u8, &u8)) -> &str { key }
+}
+fn g<'key>(key: &str, other: &'key u8) {}
+"#,
+            r#"
+struct S;
+impl S {
+    fn f<'self, 'key, '0, '1>(&'self self, key: &'key str, pair: (&'0 u8, &'1 u8)) -> &'self str { key }
+}
+fn g<'0, 'key>(key: &'0 str, other: &'key u8) {}
+"#,
+        );
+    }
+
     // Chaining hint tests
 
     #[test]
@@ -1211,6 +1885,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1267,6 +1945,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1311,6 +1993,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
@@ -1356,6 +2042,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                lifetime_elision_hints: false,
+                param_names_for_lifetime_elision_hints: false,
+                closure_return_type_hints: false,
+                closure_capture_hints: false,
                 max_length: None,
             },
             r#"
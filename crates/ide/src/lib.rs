@@ -38,6 +38,7 @@ mod inlay_hints;
 mod join_lines;
 mod matching_brace;
 mod move_item;
+mod normalize_imports;
 mod parent_module;
 mod references;
 mod fn_references;
@@ -46,6 +47,7 @@ mod ssr;
 mod status;
 mod syntax_highlighting;
 mod syntax_tree;
+mod type_hierarchy;
 mod typing;
 mod markdown_remove;
 mod doc_links;
@@ -61,6 +63,7 @@ use ide_db::base_db::{
     Env, FileLoader, FileSet, SourceDatabase, VfsPath,
 };
 use ide_db::{
+    helpers::insert_use::InsertUseConfig,
     symbol_index::{self, FileSymbol},
     LineIndexDatabase,
 };
@@ -73,7 +76,7 @@ pub use crate::{
     call_hierarchy::CallItem,
     diagnostics::{Diagnostic, DiagnosticsConfig, Severity},
     display::navigation_target::NavigationTarget,
-    expand_macro::ExpandedMacro,
+    expand_macro::{ExpandedMacro, MacroExpansionStep, MacroRuleMatch},
     file_structure::{StructureNode, StructureNodeKind},
     folding_ranges::{Fold, FoldKind},
     hover::{HoverAction, HoverConfig, HoverGotoTypeData, HoverResult},
@@ -85,7 +88,7 @@ pub use crate::{
     runnables::{Runnable, RunnableKind, TestId},
     syntax_highlighting::{
         tags::{Highlight, HlMod, HlMods, HlOperator, HlPunct, HlTag},
-        HlRange,
+        HighlightConfig, HlRange, InjectedLanguage,
     },
 };
 pub use hir::{Documentation, Semantics};
@@ -94,7 +97,7 @@ pub use ide_assists::{
 };
 pub use ide_completion::{
     CompletionConfig, CompletionItem, CompletionItemKind, CompletionRelevance, ImportEdit,
-    InsertTextFormat,
+    InsertTextFormat, Snippet, SnippetScope,
 };
 pub use ide_db::{
     base_db::{
@@ -254,6 +257,12 @@ impl Analysis {
         self.with_db(|db| db.source_root(db.file_source_root(file_id)).is_library)
     }
 
+    /// Returns the source root the file belongs to.
+    pub fn file_source_root(&self, file_id: FileId) -> Cancellable<SourceRootId> {
+        use ide_db::base_db::SourceDatabaseExt;
+        self.with_db(|db| db.file_source_root(file_id))
+    }
+
     /// Gets the file's `LineIndex`: data structure to convert between absolute
     /// offsets and line/column representation.
     pub fn file_line_index(&self, file_id: FileId) -> Cancellable<Arc<LineIndex>> {
@@ -302,6 +311,14 @@ impl Analysis {
         self.with_db(|db| expand_macro::expand_macro(db, position))
     }
 
+    /// Returns each step of the recursive expansion of the macro call at `position`.
+    pub fn expand_macro_steps(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<Vec<MacroExpansionStep>>> {
+        self.with_db(|db| expand_macro::expand_macro_steps(db, position))
+    }
+
     /// Returns an edit to remove all newlines in the range, cleaning up minor
     /// stuff like trailing commas.
     pub fn join_lines(&self, frange: FileRange) -> Cancellable<TextEdit> {
@@ -406,10 +423,9 @@ impl Analysis {
     pub fn hover(
         &self,
         position: FilePosition,
-        links_in_hover: bool,
-        markdown: bool,
+        config: &HoverConfig,
     ) -> Cancellable<Option<RangeInfo<HoverResult>>> {
-        self.with_db(|db| hover::hover(db, position, links_in_hover, markdown))
+        self.with_db(|db| hover::hover(db, position, config))
     }
 
     /// Return URL(s) for the documentation of the symbol under the cursor.
@@ -443,6 +459,24 @@ impl Analysis {
         self.with_db(|db| call_hierarchy::outgoing_calls(db, position))
     }
 
+    /// Computes the type hierarchy item (a trait or an ADT) at the given position.
+    pub fn prepare_type_hierarchy(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| type_hierarchy::prepare_type_hierarchy(db, position))
+    }
+
+    /// Computes supertraits of a trait, or traits implemented by an ADT.
+    pub fn supertypes(&self, position: FilePosition) -> Cancellable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| type_hierarchy::supertypes(db, position))
+    }
+
+    /// Computes types implementing the trait at the given position.
+    pub fn subtypes(&self, position: FilePosition) -> Cancellable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| type_hierarchy::subtypes(db, position))
+    }
+
     /// Returns a `mod name;` declaration which created the current module.
     pub fn parent_module(&self, position: FilePosition) -> Cancellable<Vec<NavigationTarget>> {
         self.with_db(|db| parent_module::parent_module(db, position))
@@ -478,14 +512,22 @@ impl Analysis {
     }
 
     /// Computes syntax highlighting for the given file
-    pub fn highlight(&self, file_id: FileId) -> Cancellable<Vec<HlRange>> {
-        self.with_db(|db| syntax_highlighting::highlight(db, file_id, None, false))
+    pub fn highlight(
+        &self,
+        config: &HighlightConfig,
+        file_id: FileId,
+    ) -> Cancellable<Vec<HlRange>> {
+        self.with_db(|db| syntax_highlighting::highlight(db, config, file_id, None, false))
     }
 
     /// Computes syntax highlighting for the given file range.
-    pub fn highlight_range(&self, frange: FileRange) -> Cancellable<Vec<HlRange>> {
+    pub fn highlight_range(
+        &self,
+        config: &HighlightConfig,
+        frange: FileRange,
+    ) -> Cancellable<Vec<HlRange>> {
         self.with_db(|db| {
-            syntax_highlighting::highlight(db, frange.file_id, Some(frange.range), false)
+            syntax_highlighting::highlight(db, config, frange.file_id, Some(frange.range), false)
         })
     }
 
@@ -508,19 +550,10 @@ impl Analysis {
         &self,
         config: &CompletionConfig,
         position: FilePosition,
-        full_import_path: &str,
-        imported_name: String,
+        imports: impl IntoIterator<Item = (String, String)> + std::panic::UnwindSafe,
     ) -> Cancellable<Vec<TextEdit>> {
         Ok(self
-            .with_db(|db| {
-                ide_completion::resolve_completion_edits(
-                    db,
-                    config,
-                    position,
-                    full_import_path,
-                    imported_name,
-                )
-            })?
+            .with_db(|db| ide_completion::resolve_completion_edits(db, config, position, imports))?
             .unwrap_or_default())
     }
 
@@ -627,6 +660,11 @@ impl Analysis {
         })
     }
 
+    /// Rewrites the imports of all local files according to `config`.
+    pub fn normalize_imports(&self, config: &InsertUseConfig) -> Cancellable<SourceChange> {
+        self.with_db(|db| normalize_imports::normalize_imports(db, config))
+    }
+
     pub fn annotations(
         &self,
         file_id: FileId,
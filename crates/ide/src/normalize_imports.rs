@@ -0,0 +1,210 @@
+use ide_db::{
+    base_db::{FileId, SourceDatabase, SourceDatabaseExt},
+    helpers::{
+        insert_use::{ImportScope, InsertUseConfig},
+        organize_imports::organize_imports,
+    },
+    source_change::SourceChange,
+    symbol_index::SymbolsDatabase,
+    RootDatabase,
+};
+use syntax::{algo, ast, AstNode};
+use text_edit::TextEdit;
+
+// Feature: Normalize Imports
+//
+// Rewrites the `use` items of all workspace files to the given import granularity, sorting them
+// and optionally separating std, external crate and local imports into groups. Imports are not
+// removed, even if they are unused.
+//
+// The same rewrite is available from the command line as `rust-analyzer normalize-imports`.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **Rust Analyzer: Normalize Imports**
+// |===
+pub(crate) fn normalize_imports(db: &RootDatabase, config: &InsertUseConfig) -> SourceChange {
+    let _p = profile::span("normalize_imports");
+    let mut res = SourceChange::default();
+    for &root in db.local_roots().iter() {
+        let source_root = db.source_root(root);
+        for file_id in source_root.iter() {
+            let is_rust_file = source_root
+                .path_for_file(&file_id)
+                .and_then(|path| path.name_and_extension())
+                .map_or(false, |(_, ext)| ext == Some("rs"));
+            if !is_rust_file {
+                continue;
+            }
+            if let Some(edit) = normalize_file(db, file_id, config) {
+                res.insert_source_edit(file_id, edit);
+            }
+        }
+    }
+    res
+}
+
+fn normalize_file(
+    db: &RootDatabase,
+    file_id: FileId,
+    config: &InsertUseConfig,
+) -> Option<TextEdit> {
+    let root = db.parse(file_id).tree().syntax().clone();
+    let new_root = root.clone_for_update();
+    // Scopes are collected up front, as organizing them changes the tree.
+    let scopes: Vec<_> = new_root
+        .descendants()
+        .filter(|it| ast::SourceFile::can_cast(it.kind()) || ast::Module::can_cast(it.kind()))
+        .filter_map(ImportScope::from)
+        .collect();
+    for scope in &scopes {
+        organize_imports(scope, config);
+    }
+
+    let mut edit = TextEdit::builder();
+    algo::diff(&root, &new_root).into_text_edit(&mut edit);
+    let edit = edit.finish();
+    if edit.is_empty() {
+        None
+    } else {
+        Some(edit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::{
+        base_db::SourceDatabaseExt,
+        helpers::insert_use::{ImportGranularity, InsertUseConfig},
+    };
+
+    use crate::fixture;
+
+    fn check(granularity: ImportGranularity, ra_fixture: &str, expect: Expect) {
+        let config = InsertUseConfig {
+            granularity,
+            enforce_granularity: true,
+            prefix_kind: hir::PrefixKind::Plain,
+            group: true,
+        };
+        let (analysis, _) = fixture::files(ra_fixture);
+        let source_change = analysis.normalize_imports(&config).unwrap();
+
+        let mut edits: Vec<_> = source_change.source_file_edits.into_iter().collect();
+        edits.sort_by_key(|(file_id, _)| *file_id);
+        let mut actual = String::new();
+        for (file_id, edit) in edits {
+            let mut text = analysis.with_db(|db| db.file_text(file_id).to_string()).unwrap();
+            edit.apply(&mut text);
+            actual += &format!("{:?}\n{}\n", file_id, text);
+        }
+        expect.assert_eq(&actual);
+    }
+
+    const FIXTURE: &str = r#"
+//- /main.rs crate:main deps:dep
+mod a;
+mod b;
+mod c;
+//- /a.rs
+use dep::{foo::{A, B}, bar::C};
+use crate::b::Local;
+use dep::foo::D;
+//- /b.rs
+use dep::foo::A;
+use dep::foo::B;
+
+pub struct Local;
+//- /c.rs
+use dep::foo::{A, B};
+//- /lib.rs crate:dep
+pub mod foo { pub struct A; pub struct B; pub struct D; }
+pub mod bar { pub struct C; }
+"#;
+
+    #[test]
+    fn normalize_to_module() {
+        check(
+            ImportGranularity::Module,
+            FIXTURE,
+            expect![[r#"
+                FileId(1)
+                use dep::bar::C;
+                use dep::foo::{A, B, D};
+
+                use crate::b::Local;
+
+                FileId(2)
+                use dep::foo::{A, B};
+
+                pub struct Local;
+
+            "#]],
+        );
+    }
+
+    #[test]
+    fn normalize_to_crate() {
+        check(
+            ImportGranularity::Crate,
+            FIXTURE,
+            expect![[r#"
+                FileId(1)
+                use dep::{bar::C, foo::{A, B, D}};
+
+                use crate::b::Local;
+
+                FileId(2)
+                use dep::foo::{A, B};
+
+                pub struct Local;
+
+            "#]],
+        );
+    }
+
+    #[test]
+    fn normalize_to_item() {
+        check(
+            ImportGranularity::Item,
+            FIXTURE,
+            expect![[r#"
+                FileId(1)
+                use dep::bar::C;
+                use dep::foo::A;
+                use dep::foo::B;
+                use dep::foo::D;
+
+                use crate::b::Local;
+
+                FileId(3)
+                use dep::foo::A;
+                use dep::foo::B;
+
+            "#]],
+        );
+    }
+
+    #[test]
+    fn flattens_self_and_globs() {
+        check(
+            ImportGranularity::Item,
+            r#"
+//- /main.rs crate:main deps:dep
+pub(crate) use dep::{foo::{self, *}, bar::C as Renamed};
+//- /lib.rs crate:dep
+pub mod foo { pub struct A; }
+pub mod bar { pub struct C; }
+"#,
+            expect![[r#"
+                FileId(0)
+                pub(crate) use dep::bar::C as Renamed;
+                pub(crate) use dep::foo;
+                pub(crate) use dep::foo::*;
+
+            "#]],
+        );
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! All reference and file rename requests go through here where the corresponding [`SourceChange`]s
 //! will be calculated.
+mod conflicts;
+
 use std::fmt::{self, Display};
 
 use either::Either;
@@ -219,6 +221,7 @@ fn rename_mod(
     }
     let def = Definition::ModuleDef(ModuleDef::Module(module));
     let usages = def.usages(sema).all();
+    conflicts::check_conflicts(sema, def, new_name, &usages)?;
     let ref_edits = usages.iter().map(|(&file_id, references)| {
         (file_id, source_edit_from_references(references, def, new_name))
     });
@@ -280,6 +283,9 @@ fn rename_reference(
         cov_mark::hit!(rename_underscore_multiple);
         bail!("Cannot rename reference to `_` as it is being referenced multiple times");
     }
+    if ident_kind == IdentifierKind::Ident {
+        conflicts::check_conflicts(sema, def, new_name, &usages)?;
+    }
     let mut source_change = SourceChange::default();
     source_change.extend(usages.iter().map(|(&file_id, references)| {
         (file_id, source_edit_from_references(&references, def, new_name))
@@ -1907,4 +1913,212 @@ impl Fo0 where Self: {}
 "#,
         );
     }
+
+    #[test]
+    fn test_rename_local_shadowed_by_inner_binding() {
+        check(
+            "y",
+            r#"
+fn main() {
+    let x$0 = 1;
+    let y = 2;
+    x + y;
+}
+"#,
+            r#"
+error: Cannot rename to `y`, the new name conflicts with:
+/main.rs:4:5: this reference would refer to an inner `y`"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_local_captures_references() {
+        check(
+            "y",
+            r#"
+fn main() {
+    let y = 1;
+    let x$0 = 2;
+    y + x;
+}
+"#,
+            r#"
+error: Cannot rename to `y`, the new name conflicts with:
+/main.rs:4:5: this reference would refer to the renamed binding"#,
+        );
+        check(
+            "y",
+            r#"
+fn y() {}
+fn main() {
+    let x$0 = 2;
+    y();
+}
+"#,
+            r#"
+error: Cannot rename to `y`, the new name conflicts with:
+/main.rs:4:5: this reference would refer to the renamed binding"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_local_to_const_name() {
+        check(
+            "Y",
+            r#"
+const Y: i32 = 0;
+fn main() {
+    let x$0 = 1;
+}
+"#,
+            r#"
+error: Cannot rename to `Y`, the new name conflicts with:
+/main.rs:1:7: bindings cannot shadow this item"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_local_shadowing_outer_binding() {
+        check(
+            "y",
+            r#"
+fn main() {
+    let y = 1;
+    let x$0 = 2;
+    x;
+}
+"#,
+            r#"
+fn main() {
+    let y = 1;
+    let y = 2;
+    y;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_item_collision() {
+        check(
+            "bar",
+            r#"
+fn foo$0() {}
+fn bar() {}
+"#,
+            r#"
+error: Cannot rename to `bar`, the new name conflicts with:
+/main.rs:2:4: an item with this name already exists in the module"#,
+        );
+        check(
+            "Bar",
+            r#"
+fn foo$0() {}
+struct Bar {}
+"#,
+            r#"
+fn Bar() {}
+struct Bar {}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_item_collision_through_import() {
+        check(
+            "bar",
+            r#"
+mod a {
+    pub fn foo$0() {}
+}
+mod b {
+    use crate::a::foo;
+    fn bar() {}
+}
+"#,
+            r#"
+error: Cannot rename to `bar`, the new name conflicts with:
+/main.rs:6:8: an item with this name already exists in the module"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_item_reference_shadowed_by_local() {
+        check(
+            "bar",
+            r#"
+fn foo$0() {}
+fn main() {
+    let bar = 1;
+    foo();
+}
+"#,
+            r#"
+error: Cannot rename to `bar`, the new name conflicts with:
+/main.rs:4:5: this reference would refer to another `bar`"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_method_collision_in_impl() {
+        check(
+            "bar",
+            r#"
+struct S;
+impl S {
+    fn foo$0(&self) {}
+    fn bar(&self) {}
+}
+"#,
+            r#"
+error: Cannot rename to `bar`, the new name conflicts with:
+/main.rs:4:8: an item with this name already exists in the impl"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_method_shadowing_trait_method() {
+        check(
+            "bar",
+            r#"
+struct S;
+trait T { fn bar(&self); }
+impl T for S { fn bar(&self) {} }
+impl S { fn foo$0(&self) {} }
+"#,
+            r#"
+error: Cannot rename to `bar`, the new name conflicts with:
+/main.rs:2:14: method calls resolving to this trait method would resolve to the renamed method"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_trait_method_ambiguity() {
+        check(
+            "bar",
+            r#"
+trait A { fn foo$0(&self); }
+trait B { fn bar(&self); }
+struct S;
+impl A for S { fn foo(&self) {} }
+impl B for S { fn bar(&self) {} }
+"#,
+            r#"
+error: Cannot rename to `bar`, the new name conflicts with:
+/main.rs:2:14: method calls would be ambiguous with this trait method"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_field_collision() {
+        check(
+            "b",
+            r#"
+struct S { a$0: i32, b: i32 }
+"#,
+            r#"
+error: Cannot rename to `b`, the new name conflicts with:
+/main.rs:1:20: a field with this name already exists"#,
+        );
+    }
 }
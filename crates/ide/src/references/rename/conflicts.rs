@@ -0,0 +1,493 @@
+//! Detects renames that would silently change the meaning of the code or make
+//! it fail to compile: references that would resolve to a different binding or
+//! item after the rename, and names that would collide with existing ones.
+
+use either::Either;
+use hir::{
+    AsAssocItem, AssocItem, AssocItemContainer, GenericParam, Impl, InFile, Local, ModuleDef,
+    ScopeDef, Semantics, StructKind,
+};
+use ide_db::{
+    base_db::{FileRange, SourceDatabaseExt},
+    defs::{Definition, NameRefClass},
+    search::UsageSearchResult,
+    LineIndexDatabase, RootDatabase,
+};
+use stdx::format_to;
+use syntax::{ast, AstNode, SyntaxKind, TextSize};
+
+use crate::display::TryToNav;
+
+use super::{RenameError, RenameResult};
+
+struct Conflict {
+    range: FileRange,
+    reason: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Namespace {
+    Types,
+    Values,
+    Macros,
+}
+
+/// Checks that renaming `def` to `new_name` keeps every reference in `usages`
+/// pointing at `def` and doesn't introduce duplicate definitions.
+pub(super) fn check_conflicts(
+    sema: &Semantics<RootDatabase>,
+    def: Definition,
+    new_name: &str,
+    usages: &UsageSearchResult,
+) -> RenameResult<()> {
+    let mut conflicts = Vec::new();
+    match def {
+        Definition::Local(local) => {
+            local_conflicts(sema, local, new_name, usages, &mut conflicts);
+        }
+        Definition::Field(field) => {
+            let siblings = field.parent_def(sema.db).fields(sema.db);
+            for sibling in siblings.into_iter().filter(|it| *it != field) {
+                if sibling.name(sema.db).to_string() == new_name {
+                    push_def(
+                        sema,
+                        Definition::Field(sibling),
+                        "a field with this name already exists",
+                        &mut conflicts,
+                    );
+                }
+            }
+        }
+        Definition::ModuleDef(ModuleDef::Variant(variant)) => {
+            let siblings = variant.parent_enum(sema.db).variants(sema.db);
+            for sibling in siblings.into_iter().filter(|it| *it != variant) {
+                if sibling.name(sema.db).to_string() == new_name {
+                    push_def(
+                        sema,
+                        Definition::ModuleDef(sibling.into()),
+                        "a variant with this name already exists",
+                        &mut conflicts,
+                    );
+                }
+            }
+        }
+        Definition::ModuleDef(module_def) => match module_def.as_assoc_item(sema.db) {
+            Some(assoc) => assoc_item_conflicts(sema, assoc, new_name, &mut conflicts),
+            None => item_conflicts(sema, def, new_name, usages, &mut conflicts),
+        },
+        Definition::Macro(_) => item_conflicts(sema, def, new_name, usages, &mut conflicts),
+        Definition::SelfType(_) | Definition::GenericParam(_) | Definition::Label(_) => {}
+    }
+
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    conflicts.sort_by_key(|it| (it.range.file_id, it.range.range.start()));
+    conflicts.dedup_by_key(|it| it.range);
+
+    let db = sema.db;
+    let mut message = format!("Cannot rename to `{}`, the new name conflicts with:", new_name);
+    for Conflict { range, reason } in conflicts {
+        let source_root = db.source_root(db.file_source_root(range.file_id));
+        let line_col = db.line_index(range.file_id).line_col(range.range.start());
+        match source_root.path_for_file(&range.file_id) {
+            Some(path) => format_to!(message, "\n{}", path),
+            None => format_to!(message, "\n{:?}", range.file_id),
+        }
+        format_to!(message, ":{}:{}: {}", line_col.line + 1, line_col.col + 1, reason);
+    }
+    Err(RenameError(message))
+}
+
+/// Renaming a local must not make its references resolve to an inner binding,
+/// make other references resolve to it, or turn it into a path pattern.
+fn local_conflicts(
+    sema: &Semantics<RootDatabase>,
+    local: Local,
+    new_name: &str,
+    usages: &UsageSearchResult,
+    acc: &mut Vec<Conflict>,
+) {
+    let db = sema.db;
+    let local_offset = match local_offset(sema, local) {
+        Some(it) => it,
+        None => return,
+    };
+
+    for (&file_id, references) in usages.iter() {
+        for reference in references {
+            let name_ref = match &reference.name {
+                ast::NameLike::NameRef(it) if it.syntax().text_range() == reference.range => it,
+                _ => continue,
+            };
+            let mut shadowed = false;
+            sema.scope(name_ref.syntax()).process_all_names(&mut |name, def| match def {
+                ScopeDef::Local(other) if other != local && name.to_string() == new_name => {
+                    shadowed |= local_offset_in(sema, other, file_id) > Some(local_offset);
+                }
+                _ => {}
+            });
+            if shadowed {
+                acc.push(Conflict {
+                    range: FileRange { file_id, range: reference.range },
+                    reason: format!("this reference would refer to an inner `{}`", new_name),
+                });
+            }
+        }
+    }
+
+    let source = local.source(db);
+    let file_id = source.file_id.original_file(db);
+    if source.file_id != file_id.into() {
+        return;
+    }
+    let pat = match source.value {
+        Either::Left(pat) => pat,
+        Either::Right(_) => return,
+    };
+
+    sema.scope(pat.syntax()).process_all_names(&mut |name, def| {
+        if name.to_string() != new_name {
+            return;
+        }
+        if let ScopeDef::ModuleDef(module_def) = def {
+            if !matches!(module_def, ModuleDef::Function(_))
+                && module_def_namespaces(db, module_def).contains(&Namespace::Values)
+            {
+                push_def(
+                    sema,
+                    Definition::ModuleDef(module_def),
+                    "bindings cannot shadow this item",
+                    acc,
+                );
+            }
+        }
+    });
+
+    let old_name = match local.name(db) {
+        Some(it) => it,
+        None => return,
+    };
+    let body = pat
+        .syntax()
+        .ancestors()
+        .find(|it| matches!(it.kind(), SyntaxKind::FN | SyntaxKind::CONST | SyntaxKind::STATIC));
+    let name_refs = body
+        .iter()
+        .flat_map(|it| it.descendants())
+        .filter_map(ast::NameRef::cast)
+        .filter(|it| it.text() == new_name);
+    for name_ref in name_refs {
+        if !is_unqualified_path_expr(&name_ref) {
+            continue;
+        }
+        let mut visible = false;
+        sema.scope(name_ref.syntax()).process_all_names(&mut |name, def| {
+            visible |= name == old_name && def == ScopeDef::Local(local);
+        });
+        if !visible {
+            continue;
+        }
+        let still_resolves = match NameRefClass::classify(sema, &name_ref) {
+            Some(NameRefClass::Definition(Definition::Local(other))) => {
+                local_offset_in(sema, other, file_id) > Some(local_offset)
+            }
+            _ => false,
+        };
+        if !still_resolves {
+            acc.push(Conflict {
+                range: FileRange { file_id, range: name_ref.syntax().text_range() },
+                reason: "this reference would refer to the renamed binding".to_owned(),
+            });
+        }
+    }
+}
+
+/// Renaming an item must not collide with an item of the same namespace in its
+/// module, nor make references resolve to a closer definition.
+fn item_conflicts(
+    sema: &Semantics<RootDatabase>,
+    def: Definition,
+    new_name: &str,
+    usages: &UsageSearchResult,
+    acc: &mut Vec<Conflict>,
+) {
+    let db = sema.db;
+    let namespaces = def_namespaces(db, def);
+    let def_module = def.module(db);
+    if let Some(module) = def_module {
+        declared_conflicts(sema, module, def, &namespaces, new_name, acc);
+    }
+
+    for (&file_id, references) in usages.iter() {
+        for reference in references {
+            let name_ref = match &reference.name {
+                ast::NameLike::NameRef(it) if it.syntax().text_range() == reference.range => it,
+                _ => continue,
+            };
+            let path = match name_ref.syntax().ancestors().find_map(ast::Path::cast) {
+                Some(it) => it,
+                None => continue,
+            };
+
+            if let Some(use_tree) = path.syntax().parent().and_then(ast::UseTree::cast) {
+                // `use foo::old;` becomes `use foo::new;`, which must not clash
+                // with the module importing it.
+                if use_tree.rename().is_none() {
+                    if let Some(module) = sema.scope(use_tree.syntax()).module() {
+                        if Some(module) != def_module {
+                            declared_conflicts(sema, module, def, &namespaces, new_name, acc);
+                        }
+                    }
+                }
+                continue;
+            }
+            if path.qualifier().is_some() {
+                continue;
+            }
+
+            let scope = sema.scope(name_ref.syntax());
+            let scope_module = scope.module();
+            let mut shadowed = false;
+            scope.process_all_names(&mut |name, scope_def| {
+                if name.to_string() != new_name
+                    || !scope_def_namespaces(db, &scope_def)
+                        .iter()
+                        .any(|it| namespaces.contains(it))
+                {
+                    return;
+                }
+                shadowed |= match scope_def {
+                    ScopeDef::Local(_) | ScopeDef::GenericParam(_) => true,
+                    ScopeDef::ModuleDef(it) => {
+                        Definition::ModuleDef(it) != def
+                            && it.module(db) == scope_module
+                            && scope_module != def_module
+                    }
+                    ScopeDef::MacroDef(it) => {
+                        Definition::Macro(it) != def
+                            && it.module(db) == scope_module
+                            && scope_module != def_module
+                    }
+                    _ => false,
+                };
+            });
+            if shadowed {
+                acc.push(Conflict {
+                    range: FileRange { file_id, range: reference.range },
+                    reason: format!("this reference would refer to another `{}`", new_name),
+                });
+            }
+        }
+    }
+}
+
+/// Renaming an associated item must not collide with the other items of its
+/// impl or trait, and must not make method calls resolve differently or become
+/// ambiguous.
+fn assoc_item_conflicts(
+    sema: &Semantics<RootDatabase>,
+    assoc: AssocItem,
+    new_name: &str,
+    acc: &mut Vec<Conflict>,
+) {
+    let db = sema.db;
+    let namespace = assoc_item_namespace(assoc);
+    let is_fn = matches!(assoc, AssocItem::Function(_));
+    let push_items = |items: Vec<AssocItem>, reason: &str, acc: &mut Vec<Conflict>| {
+        for item in items {
+            if item != assoc
+                && assoc_item_namespace(item) == namespace
+                && item.name(db).map_or(false, |it| it.to_string() == new_name)
+            {
+                push_def(sema, Definition::ModuleDef(assoc_item_def(item)), reason, acc);
+            }
+        }
+    };
+
+    match assoc.container(db) {
+        AssocItemContainer::Impl(impl_) => {
+            push_items(impl_.items(db), "an item with this name already exists in the impl", acc);
+            if impl_.trait_(db).is_some() {
+                return;
+            }
+            for other in Impl::all_for_type(db, impl_.self_ty(db)) {
+                if other == impl_ {
+                    continue;
+                }
+                match other.trait_def(db) {
+                    None if other.trait_(db).is_none() => push_items(
+                        other.items(db),
+                        "an item with this name already exists in another impl of the type",
+                        acc,
+                    ),
+                    Some(trait_) if is_fn => push_items(
+                        trait_.items(db),
+                        "method calls resolving to this trait method would resolve to the renamed method",
+                        acc,
+                    ),
+                    _ => {}
+                }
+            }
+        }
+        AssocItemContainer::Trait(trait_) => {
+            push_items(trait_.items(db), "an item with this name already exists in the trait", acc);
+            if !is_fn {
+                return;
+            }
+            for supertrait in trait_.direct_supertraits(db) {
+                push_items(
+                    supertrait.items(db),
+                    "method calls would be ambiguous with this supertrait method",
+                    acc,
+                );
+            }
+            for impl_ in Impl::all_for_trait(db, trait_) {
+                for other in Impl::all_for_type(db, impl_.self_ty(db)) {
+                    match other.trait_def(db) {
+                        Some(other_trait) if other_trait == trait_ => {}
+                        Some(other_trait) => push_items(
+                            other_trait.items(db),
+                            "method calls would be ambiguous with this trait method",
+                            acc,
+                        ),
+                        None if other.trait_(db).is_none() => push_items(
+                            other.items(db),
+                            "this inherent method would take precedence over the renamed method",
+                            acc,
+                        ),
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Pushes a conflict for every item declared in `module` named `new_name` in
+/// one of `namespaces`.
+fn declared_conflicts(
+    sema: &Semantics<RootDatabase>,
+    module: hir::Module,
+    def: Definition,
+    namespaces: &[Namespace],
+    new_name: &str,
+    acc: &mut Vec<Conflict>,
+) {
+    let db = sema.db;
+    for (name, scope_def) in module.scope(db, None) {
+        if name.to_string() != new_name
+            || !scope_def_namespaces(db, &scope_def).iter().any(|it| namespaces.contains(it))
+        {
+            continue;
+        }
+        let other = match scope_def {
+            ScopeDef::ModuleDef(it) if it.module(db) == Some(module) => Definition::ModuleDef(it),
+            ScopeDef::MacroDef(it) if it.module(db) == Some(module) => Definition::Macro(it),
+            _ => continue,
+        };
+        if other != def {
+            push_def(sema, other, "an item with this name already exists in the module", acc);
+        }
+    }
+}
+
+fn push_def(
+    sema: &Semantics<RootDatabase>,
+    def: Definition,
+    reason: &str,
+    acc: &mut Vec<Conflict>,
+) {
+    if let Some(nav) = def.try_to_nav(sema.db) {
+        acc.push(Conflict {
+            range: FileRange { file_id: nav.file_id, range: nav.focus_or_full_range() },
+            reason: reason.to_owned(),
+        });
+    }
+}
+
+fn is_unqualified_path_expr(name_ref: &ast::NameRef) -> bool {
+    let path = match name_ref.syntax().ancestors().find_map(ast::Path::cast) {
+        Some(it) => it,
+        None => return false,
+    };
+    path.qualifier().is_none()
+        && path.syntax().parent().map_or(false, |it| ast::PathExpr::can_cast(it.kind()))
+}
+
+fn local_offset(sema: &Semantics<RootDatabase>, local: Local) -> Option<TextSize> {
+    let file_id = local.source(sema.db).file_id.original_file(sema.db);
+    local_offset_in(sema, local, file_id)
+}
+
+/// The start of `local`'s binding, if it is defined in `file_id`. Within a
+/// body, a binding declared later shadows the visible ones declared before it.
+fn local_offset_in(
+    sema: &Semantics<RootDatabase>,
+    local: Local,
+    file_id: ide_db::base_db::FileId,
+) -> Option<TextSize> {
+    let InFile { file_id: source_file, value } = local.source(sema.db);
+    if source_file != file_id.into() {
+        return None;
+    }
+    let node = value.either(|it| it.syntax().clone(), |it| it.syntax().clone());
+    Some(node.text_range().start())
+}
+
+fn def_namespaces(db: &RootDatabase, def: Definition) -> Vec<Namespace> {
+    match def {
+        Definition::ModuleDef(it) => module_def_namespaces(db, it),
+        Definition::Macro(_) => vec![Namespace::Macros],
+        Definition::Local(_) => vec![Namespace::Values],
+        _ => Vec::new(),
+    }
+}
+
+fn scope_def_namespaces(db: &RootDatabase, def: &ScopeDef) -> Vec<Namespace> {
+    match def {
+        ScopeDef::ModuleDef(it) => module_def_namespaces(db, *it),
+        ScopeDef::MacroDef(_) => vec![Namespace::Macros],
+        ScopeDef::GenericParam(GenericParam::TypeParam(_)) => vec![Namespace::Types],
+        ScopeDef::GenericParam(GenericParam::ConstParam(_)) => vec![Namespace::Values],
+        ScopeDef::ImplSelfType(_) | ScopeDef::AdtSelfType(_) => vec![Namespace::Types],
+        ScopeDef::Local(_) => vec![Namespace::Values],
+        ScopeDef::GenericParam(GenericParam::LifetimeParam(_))
+        | ScopeDef::Label(_)
+        | ScopeDef::Unknown => Vec::new(),
+    }
+}
+
+fn module_def_namespaces(db: &RootDatabase, def: ModuleDef) -> Vec<Namespace> {
+    let struct_like = |kind| match kind {
+        StructKind::Record => vec![Namespace::Types],
+        StructKind::Tuple | StructKind::Unit => vec![Namespace::Types, Namespace::Values],
+    };
+    match def {
+        ModuleDef::Adt(hir::Adt::Struct(it)) => struct_like(it.kind(db)),
+        ModuleDef::Variant(it) => struct_like(it.kind(db)),
+        ModuleDef::Function(_) | ModuleDef::Const(_) | ModuleDef::Static(_) => {
+            vec![Namespace::Values]
+        }
+        ModuleDef::Module(_)
+        | ModuleDef::Adt(_)
+        | ModuleDef::Trait(_)
+        | ModuleDef::TypeAlias(_)
+        | ModuleDef::BuiltinType(_) => vec![Namespace::Types],
+    }
+}
+
+fn assoc_item_namespace(item: AssocItem) -> Namespace {
+    match item {
+        AssocItem::Function(_) | AssocItem::Const(_) => Namespace::Values,
+        AssocItem::TypeAlias(_) => Namespace::Types,
+    }
+}
+
+fn assoc_item_def(item: AssocItem) -> ModuleDef {
+    match item {
+        AssocItem::Function(it) => it.into(),
+        AssocItem::Const(it) => it.into(),
+        AssocItem::TypeAlias(it) => it.into(),
+    }
+}
@@ -7,6 +7,7 @@ mod highlight;
 mod format;
 mod macro_;
 mod inject;
+mod embedded;
 
 mod html;
 #[cfg(test)]
@@ -39,6 +40,19 @@ pub struct HlRange {
     pub binding_hash: Option<u64>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HighlightConfig {
+    /// Paths of functions and macros, like `regex::Regex::new`, whose string literal argument is
+    /// highlighted as code of the given language.
+    pub injections: Vec<(String, InjectedLanguage)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectedLanguage {
+    Regex,
+    Sql,
+}
+
 // Feature: Semantic Syntax Highlighting
 //
 // rust-analyzer highlights the code semantically.
@@ -134,7 +148,7 @@ pub struct HlRange {
 // controlFlow:: Emitted for control-flow related tokens, this includes the `?` operator.
 // declaration:: Emitted for names of definitions, like `foo` in `fn foo() {}`.
 // documentation:: Emitted for documentation comments.
-// injected:: Emitted for doc-string injected highlighting like rust source blocks in documentation.
+// injected:: Emitted for doc-string injected highlighting like rust source blocks in documentation, and for embedded languages in strings.
 // intraDocLink:: Emitted for intra doc links in doc-strings.
 // library:: Emitted for items that are defined outside of the current crate.
 // mutable:: Emitted for mutable locals and statics.
@@ -147,6 +161,7 @@ pub struct HlRange {
 // image::https://user-images.githubusercontent.com/48062697/113187625-f7f50100-9250-11eb-825e-91c58f236071.png[]
 pub(crate) fn highlight(
     db: &RootDatabase,
+    config: &HighlightConfig,
     file_id: FileId,
     range_to_highlight: Option<TextRange>,
     syntactic_name_ref_highlighting: bool,
@@ -173,6 +188,7 @@ pub(crate) fn highlight(
     traverse(
         &mut hl,
         &sema,
+        config,
         InFile::new(file_id.into(), &root),
         sema.scope(&root).krate(),
         range_to_highlight,
@@ -184,6 +200,7 @@ pub(crate) fn highlight(
 fn traverse(
     hl: &mut Highlights,
     sema: &Semantics<RootDatabase>,
+    config: &HighlightConfig,
     root: InFile<&SyntaxNode>,
     krate: Option<hir::Crate>,
     range_to_highlight: TextRange,
@@ -319,6 +336,9 @@ fn traverse(
 
         if let Some(string) = element_to_highlight.as_token().cloned().and_then(ast::String::cast) {
             highlight_format_string(hl, &string, range);
+            if let Some(original) = element.as_token() {
+                inject::embedded_language(hl, sema, config, original, &string, range);
+            }
             // Highlight escape sequences
             if let Some(char_ranges) = string.char_ranges() {
                 for (piece_range, _) in char_ranges.iter().filter(|(_, char)| char.is_ok()) {
@@ -0,0 +1,311 @@
+//! Tiny lexers for regular expressions and SQL, used to highlight such code in string literals.
+//!
+//! The lexers only return the tokens worth highlighting: plain characters of a regex and
+//! identifiers of a query are left out. They never fail, unknown input is skipped.
+use ide_db::SymbolKind;
+use syntax::{TextRange, TextSize};
+
+use crate::{HlOperator, HlPunct, HlTag};
+
+pub(super) fn lex_regex(text: &str) -> Vec<(TextRange, HlTag)> {
+    let mut res = Vec::new();
+    let mut cursor = Cursor::new(text);
+    let mut in_class = false;
+    while let Some(c) = cursor.bump() {
+        let start = cursor.pos - c.len_utf8();
+        let tag = match c {
+            '\\' => {
+                // `\d`, `\.` or `\p{Greek}`
+                if let Some(c) = cursor.bump() {
+                    if matches!(c, 'p' | 'P') && cursor.peek() == Some('{') {
+                        cursor.eat_until('}');
+                    }
+                }
+                HlTag::EscapeSequence
+            }
+            '[' if !in_class => {
+                in_class = true;
+                res.push((cursor.range(start), HlTag::Punctuation(HlPunct::Bracket)));
+                if cursor.peek() == Some('^') {
+                    let start = cursor.pos;
+                    cursor.bump();
+                    res.push((cursor.range(start), HlTag::Operator(HlOperator::Logical)));
+                }
+                continue;
+            }
+            ']' if in_class => {
+                in_class = false;
+                HlTag::Punctuation(HlPunct::Bracket)
+            }
+            '-' if in_class && !matches!(cursor.peek(), None | Some(']')) => {
+                HlTag::Operator(HlOperator::Other)
+            }
+            _ if in_class => continue,
+            '(' => {
+                res.push((cursor.range(start), HlTag::Punctuation(HlPunct::Parenthesis)));
+                if cursor.peek() == Some('?') {
+                    lex_group_flags(&mut cursor, &mut res);
+                }
+                continue;
+            }
+            ')' => HlTag::Punctuation(HlPunct::Parenthesis),
+            '{' if is_repetition(cursor.rest()) => {
+                res.push((cursor.range(start), HlTag::Punctuation(HlPunct::Brace)));
+                while let Some(c) = cursor.peek() {
+                    let start = cursor.pos;
+                    let tag = match c {
+                        '0'..='9' => {
+                            cursor.eat_while(|it| it.is_ascii_digit());
+                            HlTag::NumericLiteral
+                        }
+                        ',' => {
+                            cursor.bump();
+                            HlTag::Punctuation(HlPunct::Comma)
+                        }
+                        _ => break,
+                    };
+                    res.push((cursor.range(start), tag));
+                }
+                let start = cursor.pos;
+                cursor.bump();
+                res.push((cursor.range(start), HlTag::Punctuation(HlPunct::Brace)));
+                continue;
+            }
+            '|' => HlTag::Operator(HlOperator::Logical),
+            '*' | '+' | '?' | '.' | '^' | '$' => HlTag::Operator(HlOperator::Other),
+            _ => continue,
+        };
+        res.push((cursor.range(start), tag));
+    }
+    res
+}
+
+/// Lexes the part of a group after `(`, like `?:`, `?i:` or `?P<name>`.
+fn lex_group_flags(cursor: &mut Cursor, res: &mut Vec<(TextRange, HlTag)>) {
+    let start = cursor.pos;
+    cursor.bump();
+    let rest = cursor.rest();
+    let named = rest.starts_with("P<")
+        || (rest.starts_with('<') && !rest[1..].starts_with(|it| matches!(it, '=' | '!')));
+    if named {
+        cursor.eat_until('<');
+        res.push((cursor.range(start), HlTag::Operator(HlOperator::Other)));
+        let start = cursor.pos;
+        cursor.eat_while(|it| it != '>');
+        res.push((cursor.range(start), HlTag::Symbol(SymbolKind::Local)));
+        let start = cursor.pos;
+        cursor.bump();
+        res.push((cursor.range(start), HlTag::Operator(HlOperator::Other)));
+    } else {
+        cursor.eat_while(|it| it.is_ascii_alphabetic() || matches!(it, '-' | '<' | '=' | '!'));
+        if cursor.peek() == Some(':') {
+            cursor.bump();
+        }
+        res.push((cursor.range(start), HlTag::Operator(HlOperator::Other)));
+    }
+}
+
+/// Whether `rest`, which follows a `{`, is a repetition like `2}`, `2,}` or `2,5}`.
+fn is_repetition(rest: &str) -> bool {
+    match rest.find('}') {
+        Some(end) => {
+            let inner = &rest[..end];
+            inner.starts_with(|it: char| it.is_ascii_digit())
+                && inner.chars().all(|it| it.is_ascii_digit() || it == ',')
+                && inner.matches(',').count() <= 1
+        }
+        None => false,
+    }
+}
+
+pub(super) fn lex_sql(text: &str) -> Vec<(TextRange, HlTag)> {
+    let mut res = Vec::new();
+    let mut cursor = Cursor::new(text);
+    while let Some(c) = cursor.bump() {
+        let start = cursor.pos - c.len_utf8();
+        let tag = match c {
+            '-' if cursor.peek() == Some('-') => {
+                cursor.eat_while(|it| it != '\n');
+                HlTag::Comment
+            }
+            '/' if cursor.peek() == Some('*') => {
+                cursor.bump();
+                while cursor.bump().is_some() && !cursor.text[..cursor.pos].ends_with("*/") {}
+                HlTag::Comment
+            }
+            '\'' => {
+                // `''` is an escaped quote and simply lexed as two strings.
+                cursor.eat_until('\'');
+                HlTag::StringLiteral
+            }
+            '"' | '`' => {
+                // Quoted identifier.
+                cursor.eat_until(c);
+                continue;
+            }
+            '0'..='9' => {
+                cursor.eat_while(|it| it.is_ascii_digit() || it == '.');
+                HlTag::NumericLiteral
+            }
+            '$' if cursor.peek().map_or(false, |it| it.is_ascii_digit()) => {
+                cursor.eat_while(|it| it.is_ascii_digit());
+                HlTag::Symbol(SymbolKind::ValueParam)
+            }
+            ':' | '@' if cursor.peek().map_or(false, is_ident_start) => {
+                cursor.eat_while(is_ident_continue);
+                HlTag::Symbol(SymbolKind::ValueParam)
+            }
+            '?' => HlTag::Symbol(SymbolKind::ValueParam),
+            c if is_ident_start(c) => {
+                cursor.eat_while(is_ident_continue);
+                let word = &cursor.text[start..cursor.pos];
+                if !SQL_KEYWORDS.iter().any(|it| it.eq_ignore_ascii_case(word)) {
+                    continue;
+                }
+                HlTag::Keyword
+            }
+            '<' | '>' | '!' | '=' => {
+                cursor.eat_while(|it| matches!(it, '<' | '>' | '='));
+                HlTag::Operator(HlOperator::Comparison)
+            }
+            '+' | '-' | '*' | '/' | '%' => HlTag::Operator(HlOperator::Arithmetic),
+            '|' if cursor.peek() == Some('|') => {
+                cursor.bump();
+                HlTag::Operator(HlOperator::Other)
+            }
+            '(' | ')' => HlTag::Punctuation(HlPunct::Parenthesis),
+            ',' => HlTag::Punctuation(HlPunct::Comma),
+            ';' => HlTag::Punctuation(HlPunct::Semi),
+            '.' => HlTag::Punctuation(HlPunct::Dot),
+            _ => continue,
+        };
+        res.push((cursor.range(start), tag));
+    }
+    res
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+const SQL_KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CAST",
+    "COLUMN",
+    "COMMIT",
+    "CONFLICT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXCEPT",
+    "EXISTS",
+    "FALSE",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "ILIKE",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NOT",
+    "NOTHING",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "PRIMARY",
+    "REFERENCES",
+    "RETURNING",
+    "RIGHT",
+    "ROLLBACK",
+    "SELECT",
+    "SET",
+    "TABLE",
+    "THEN",
+    "TRUE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+    "WITH",
+];
+
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Cursor<'a> {
+        Cursor { text, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.peek().map_or(false, &f) {
+            self.bump();
+        }
+    }
+
+    /// Eats everything up to and including `c`.
+    fn eat_until(&mut self, c: char) {
+        self.eat_while(|it| it != c);
+        self.bump();
+    }
+
+    fn range(&self, start: usize) -> TextRange {
+        TextRange::new(TextSize::from(start as u32), TextSize::from(self.pos as u32))
+    }
+}
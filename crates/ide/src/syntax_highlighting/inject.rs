@@ -1,21 +1,23 @@
-//! "Recursive" Syntax highlighting for code in doctests and fixtures.
+//! "Recursive" Syntax highlighting for code in doctests, fixtures and embedded languages.
 
 use std::mem;
 
 use either::Either;
-use hir::{InFile, Semantics};
+use hir::{
+    AsAssocItem, AssocItemContainer, CallableKind, InFile, ModuleDef, PathResolution, Semantics,
+};
 use ide_db::{call_info::ActiveParameter, helpers::rust_doc::is_rust_fence, SymbolKind};
 use syntax::{
-    ast::{self, AstNode, IsString},
+    ast::{self, AstNode, HasFormatSpecifier, IsString},
     AstToken, NodeOrToken, SyntaxNode, SyntaxToken, TextRange, TextSize,
 };
 
 use crate::{
     doc_links::{doc_attributes, extract_definitions_from_markdown, resolve_doc_path_for_def},
-    Analysis, HlMod, HlRange, HlTag, RootDatabase,
+    Analysis, HighlightConfig, HlMod, HlRange, HlTag, InjectedLanguage, RootDatabase,
 };
 
-use super::{highlights::Highlights, injector::Injector};
+use super::{embedded, highlights::Highlights, injector::Injector};
 
 pub(super) fn ra_fixture(
     hl: &mut Highlights,
@@ -61,7 +63,7 @@ pub(super) fn ra_fixture(
 
     let (analysis, tmp_file_id) = Analysis::from_single_file(inj.text().to_string());
 
-    for mut hl_range in analysis.highlight(tmp_file_id).unwrap() {
+    for mut hl_range in analysis.highlight(&HighlightConfig::default(), tmp_file_id).unwrap() {
         for range in inj.map_range_up(hl_range.range) {
             if let Some(range) = literal.map_range_up(range) {
                 hl_range.range = range;
@@ -77,6 +79,192 @@ pub(super) fn ra_fixture(
     Some(())
 }
 
+// Feature: Embedded Language Highlighting
+//
+// String literals containing regular expressions or SQL queries are highlighted as such if they
+// are passed to a parameter whose name starts with `ra_regex` or `ra_sql`, or as the first
+// argument of a function or macro listed in the `rust-analyzer.highlighting.injections` setting:
+//
+// [source,json]
+// ----
+// {
+//   "rust-analyzer.highlighting.injections": {
+//     "regex::Regex::new": "regex",
+//     "sqlx::query": "sql"
+//   }
+// }
+// ----
+//
+// The configured paths are resolved from the calling module, so they also match calls through
+// re-exports and imports.
+pub(super) fn embedded_language(
+    hl: &mut Highlights,
+    sema: &Semantics<RootDatabase>,
+    config: &HighlightConfig,
+    original: &SyntaxToken,
+    literal: &ast::String,
+    range: TextRange,
+) -> Option<()> {
+    let language = macro_injection(sema, config, original)
+        .or_else(|| fn_injection(sema, config, literal.syntax()))?;
+
+    let text = literal.text();
+    let mut inj = Injector::default();
+    if literal.is_raw() {
+        let text_range =
+            literal.text_range_between_quotes()? - literal.syntax().text_range().start();
+        inj.add(&text[text_range], text_range);
+    } else {
+        // The source range of the unescaped characters not added to `inj` yet.
+        let mut run: Option<TextRange> = None;
+        for (char_range, char) in literal.char_ranges()? {
+            match char {
+                Ok(char) if TextSize::of(char) == char_range.len() => {
+                    run = Some(run.map_or(char_range, |it| it.cover(char_range)));
+                    continue;
+                }
+                _ => (),
+            }
+            if let Some(run) = run.take() {
+                inj.add(&text[run], run);
+            }
+            // Characters written as an escape, like the `\\` in `"\\d"`, can't be mapped back,
+            // but the escape itself is highlighted already.
+            if let Ok(char) = char {
+                inj.add_unmapped(char.encode_utf8(&mut [0; 4]));
+            }
+        }
+        if let Some(run) = run {
+            inj.add(&text[run], run);
+        }
+    }
+
+    let tokens = match language {
+        InjectedLanguage::Regex => embedded::lex_regex(inj.text()),
+        InjectedLanguage::Sql => embedded::lex_sql(inj.text()),
+    };
+    for (token_range, tag) in tokens {
+        for token_range in inj.map_range_up(token_range) {
+            hl.add(HlRange {
+                range: token_range + range.start(),
+                highlight: tag | HlMod::Injected,
+                binding_hash: None,
+            });
+        }
+    }
+
+    Some(())
+}
+
+/// The language of the first string literal in a call of a configured macro, like `sqlx::query!`.
+fn macro_injection(
+    sema: &Semantics<RootDatabase>,
+    config: &HighlightConfig,
+    original: &SyntaxToken,
+) -> Option<InjectedLanguage> {
+    if config.injections.is_empty() {
+        return None;
+    }
+    let token_tree = original.parent().and_then(ast::TokenTree::cast)?;
+    let macro_call = token_tree.syntax().parent().and_then(ast::MacroCall::cast)?;
+    let first_literal = token_tree
+        .syntax()
+        .children_with_tokens()
+        .find_map(|it| it.into_token().and_then(ast::String::cast))?;
+    if first_literal.syntax() != original {
+        return None;
+    }
+
+    let mac = sema.resolve_macro_call(&macro_call)?;
+    let scope = sema.scope(macro_call.syntax());
+    configured_language(
+        config,
+        |path| matches!(scope.speculative_resolve(path), Some(PathResolution::Macro(it)) if it == mac),
+    )
+}
+
+/// The language of a string literal passed as a function argument, either given by the name of
+/// the parameter or by the configured path of the function.
+fn fn_injection(
+    sema: &Semantics<RootDatabase>,
+    config: &HighlightConfig,
+    literal: &SyntaxToken,
+) -> Option<InjectedLanguage> {
+    let arg = literal.parent().and_then(ast::Literal::cast)?;
+    let arg_list = arg.syntax().parent().and_then(ast::ArgList::cast)?;
+
+    let active_parameter = ActiveParameter::at_token(sema, literal.clone())?;
+    if let Some(name) = active_parameter.ident() {
+        let name = name.text();
+        if name.starts_with("ra_regex") {
+            return Some(InjectedLanguage::Regex);
+        }
+        if name.starts_with("ra_sql") {
+            return Some(InjectedLanguage::Sql);
+        }
+    }
+
+    if config.injections.is_empty() || arg_list.args().next()?.syntax() != arg.syntax() {
+        return None;
+    }
+    let callable = match arg_list.syntax().parent()? {
+        it if ast::CallExpr::can_cast(it.kind()) => {
+            sema.type_of_expr(&ast::CallExpr::cast(it)?.expr()?)?.as_callable(sema.db)?
+        }
+        it => sema.resolve_method_call_as_callable(&ast::MethodCallExpr::cast(it)?)?,
+    };
+    let func = match callable.kind() {
+        CallableKind::Function(it) => it,
+        _ => return None,
+    };
+
+    let db = sema.db;
+    let scope = sema.scope(arg_list.syntax());
+    let container = func.as_assoc_item(db).map(|it| it.container(db));
+    configured_language(config, |path| {
+        let container = match &container {
+            Some(it) => it,
+            None => {
+                return matches!(
+                    scope.speculative_resolve(path),
+                    Some(PathResolution::Def(ModuleDef::Function(it))) if it == func
+                )
+            }
+        };
+        // Associated functions like `Regex::new` are found through their type or trait.
+        let name_matches = path
+            .segment()
+            .and_then(|it| it.name_ref())
+            .map_or(false, |it| it.text() == func.name(db).to_string().as_str());
+        let qualifier = match path.qualifier() {
+            Some(it) if name_matches => it,
+            _ => return false,
+        };
+        match (container, scope.speculative_resolve(&qualifier)) {
+            (AssocItemContainer::Impl(impl_), Some(PathResolution::Def(ModuleDef::Adt(adt)))) => {
+                impl_.self_ty(db).as_adt() == Some(adt)
+            }
+            (
+                AssocItemContainer::Trait(trait_),
+                Some(PathResolution::Def(ModuleDef::Trait(it))),
+            ) => *trait_ == it,
+            _ => false,
+        }
+    })
+}
+
+/// The language configured for the first path in `config.injections` that resolves to the callee.
+fn configured_language(
+    config: &HighlightConfig,
+    mut resolves_to_callee: impl FnMut(&ast::Path) -> bool,
+) -> Option<InjectedLanguage> {
+    config
+        .injections
+        .iter()
+        .find(|(path, _)| ast::Path::parse(path).map_or(false, |it| resolves_to_callee(&it)))
+        .map(|&(_, language)| language)
+}
+
 const RUSTDOC_FENCE: &'static str = "```";
 
 /// Injection of syntax highlighting of doctests.
@@ -194,8 +382,9 @@ pub(super) fn doc_comment(
 
     let (analysis, tmp_file_id) = Analysis::from_single_file(inj.text().to_string());
 
-    for HlRange { range, highlight, binding_hash } in
-        analysis.with_db(|db| super::highlight(db, tmp_file_id, None, true)).unwrap()
+    for HlRange { range, highlight, binding_hash } in analysis
+        .with_db(|db| super::highlight(db, &HighlightConfig::default(), tmp_file_id, None, true))
+        .unwrap()
     {
         for range in inj.map_range_up(range) {
             hl.add(HlRange { range, highlight: highlight | HlMod::Injected, binding_hash });
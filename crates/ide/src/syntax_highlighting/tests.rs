@@ -1,10 +1,10 @@
 use std::time::Instant;
 
-use expect_test::{expect_file, ExpectFile};
+use expect_test::{expect, expect_file, ExpectFile};
 use ide_db::SymbolKind;
 use test_utils::{bench, bench_fixture, skip_slow_tests, AssertLinear};
 
-use crate::{fixture, FileRange, HlTag, TextRange};
+use crate::{fixture, FileRange, HighlightConfig, HlMod, HlTag, InjectedLanguage, TextRange};
 
 #[test]
 fn test_highlighting() {
@@ -308,7 +308,7 @@ fn benchmark_syntax_highlighting_long_struct() {
     let hash = {
         let _pt = bench("syntax highlighting long struct");
         analysis
-            .highlight(file_id)
+            .highlight(&HighlightConfig::default(), file_id)
             .unwrap()
             .iter()
             .filter(|it| it.highlight.tag == HlTag::Symbol(SymbolKind::Struct))
@@ -334,7 +334,7 @@ fn syntax_highlighting_not_quadratic() {
             let time = Instant::now();
 
             let hash = analysis
-                .highlight(file_id)
+                .highlight(&HighlightConfig::default(), file_id)
                 .unwrap()
                 .iter()
                 .filter(|it| it.highlight.tag == HlTag::Symbol(SymbolKind::Struct))
@@ -359,7 +359,7 @@ fn benchmark_syntax_highlighting_parser() {
     let hash = {
         let _pt = bench("syntax highlighting parser");
         analysis
-            .highlight(file_id)
+            .highlight(&HighlightConfig::default(), file_id)
             .unwrap()
             .iter()
             .filter(|it| it.highlight.tag == HlTag::Symbol(SymbolKind::Function))
@@ -382,7 +382,10 @@ struct Foo {
 
     // The "x"
     let highlights = &analysis
-        .highlight_range(FileRange { file_id, range: TextRange::at(45.into(), 1.into()) })
+        .highlight_range(
+            &HighlightConfig::default(),
+            FileRange { file_id, range: TextRange::at(45.into(), 1.into()) },
+        )
         .unwrap();
 
     assert_eq!(&highlights[0].highlight.to_string(), "field.declaration");
@@ -418,7 +421,7 @@ macro_rules! test {}
 }"#
         .trim(),
     );
-    let _ = analysis.highlight(file_id).unwrap();
+    let _ = analysis.highlight(&HighlightConfig::default(), file_id).unwrap();
 }
 
 #[test]
@@ -771,6 +774,128 @@ fn foo() {
     );
 }
 
+#[test]
+fn test_embedded_languages() {
+    check_highlighting(
+        r##"
+fn regex(ra_regex: &str) {}
+fn query(ra_sql: &str, id: u32) {}
+fn main() {
+    regex(r"^(?P<year>\d{4})-(?:[^a-z]+|\.)*$");
+    regex("\\w+ \u{e9}+");
+    query(
+        "SELECT name, count(*) FROM users -- active only
+        WHERE id = $1 AND name <> 'root' LIMIT 10",
+        92,
+    );
+}
+    "##,
+        expect_file!["./test_data/highlight_embedded_languages.html"],
+        false,
+    );
+}
+
+#[test]
+fn test_configured_injections() {
+    let (analysis, file_id) = fixture::file(
+        r#"
+//- /main.rs crate:main deps:regex,sqlx
+fn main() {
+    regex::Regex::new("a+");
+    regex::escape("a+");
+    sqlx::query!("DELETE FROM users", 92);
+}
+//- /regex/lib.rs crate:regex
+pub struct Regex;
+impl Regex {
+    pub fn new(re: &str) -> Regex { Regex }
+}
+pub fn escape(text: &str) -> String { loop {} }
+//- /sqlx/lib.rs crate:sqlx
+#[macro_export]
+macro_rules! query {
+    ($query:expr $(, $args:expr)*) => {};
+}
+"#,
+    );
+    let config = HighlightConfig {
+        injections: vec![
+            ("regex::Regex::new".to_string(), InjectedLanguage::Regex),
+            ("sqlx::query".to_string(), InjectedLanguage::Sql),
+        ],
+    };
+    let text = analysis.file_text(file_id).unwrap();
+    let injected: Vec<String> = analysis
+        .highlight(&config, file_id)
+        .unwrap()
+        .into_iter()
+        .filter(|it| it.highlight.mods.contains(HlMod::Injected))
+        .map(|it| format!("{} {}", &text[it.range], it.highlight))
+        .collect();
+    expect![[r#"
+        [
+            "+ operator.injected",
+            "DELETE keyword.injected",
+            "FROM keyword.injected",
+        ]
+    "#]]
+    .assert_debug_eq(&injected);
+}
+
+#[test]
+fn test_configured_injections_through_reexports() {
+    let (analysis, file_id) = fixture::file(
+        r#"
+//- /main.rs crate:main deps:regex,sqlx
+use regex::Regex;
+fn main() {
+    Regex::new("a+");
+    regex::re_unicode::Regex::new("b+");
+    sqlx::macros::query!("DELETE FROM users");
+}
+//- /regex/lib.rs crate:regex
+pub mod re_unicode {
+    pub struct Regex;
+    impl Regex {
+        pub fn new(re: &str) -> Regex { Regex }
+    }
+}
+pub use crate::re_unicode::Regex;
+//- /sqlx/lib.rs crate:sqlx
+pub mod macros {
+    #[macro_export]
+    macro_rules! query_impl {
+        ($query:expr) => {};
+    }
+    pub use query_impl as query;
+}
+"#,
+    );
+    let config = HighlightConfig {
+        injections: vec![
+            ("regex::Regex::new".to_string(), InjectedLanguage::Regex),
+            ("sqlx::macros::query".to_string(), InjectedLanguage::Sql),
+        ],
+    };
+    let text = analysis.file_text(file_id).unwrap();
+    let injected: Vec<String> = analysis
+        .highlight(&config, file_id)
+        .unwrap()
+        .into_iter()
+        .filter(|it| it.highlight.mods.contains(HlMod::Injected))
+        .map(|it| format!("{} {}", &text[it.range], it.highlight))
+        .collect();
+    expect![[r#"
+        [
+            "+ operator.injected",
+            "+ operator.injected",
+            "DELETE keyword.injected",
+            "FROM keyword.injected",
+        ]
+    "#]]
+    .assert_debug_eq(&injected);
+}
+
 /// Highlights the code given by the `ra_fixture` argument, renders the
 /// result as HTML, and compares it with the HTML file given as `snapshot`.
 /// Note that the `snapshot` file is overwritten by the rendered HTML.
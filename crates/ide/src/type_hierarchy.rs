@@ -0,0 +1,220 @@
+//! Entry point for type-hierarchy
+
+use hir::{Impl, Semantics};
+use ide_db::{
+    defs::{Definition, NameClass, NameRefClass},
+    RootDatabase,
+};
+use syntax::{ast, AstNode};
+
+use crate::{display::TryToNav, FilePosition, NavigationTarget, RangeInfo};
+
+// Feature: Type Hierarchy
+//
+// Shows the supertraits of a trait and the types implementing it, as well as
+// the traits implemented by a struct, enum or union.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **Show Type Hierarchy**
+// |===
+pub(crate) fn prepare_type_hierarchy(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantics::new(db);
+    let (range, def) = type_def_at(&sema, position)?;
+    let nav = match def {
+        TypeDef::Trait(it) => it.try_to_nav(db),
+        TypeDef::Adt(it) => it.try_to_nav(db),
+    }?;
+    Some(RangeInfo { range, info: vec![nav] })
+}
+
+/// For a trait, returns its direct supertraits. For an ADT, returns the traits
+/// it implements.
+pub(crate) fn supertypes(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let (_, def) = type_def_at(&sema, position)?;
+    let traits = match def {
+        TypeDef::Trait(trait_) => trait_.direct_supertraits(db),
+        TypeDef::Adt(adt) => Impl::all_for_type(db, adt.ty(db))
+            .into_iter()
+            .filter_map(|imp| imp.trait_def(db))
+            .collect(),
+    };
+    Some(dedup_navs(traits.into_iter().filter_map(|it| it.try_to_nav(db))))
+}
+
+/// For a trait, returns the types implementing it across all crates that can
+/// see it. Impls whose self type is not an ADT (primitives, references,
+/// blanket impls) are returned as the impl blocks themselves.
+pub(crate) fn subtypes(db: &RootDatabase, position: FilePosition) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let (_, def) = type_def_at(&sema, position)?;
+    let trait_ = match def {
+        TypeDef::Trait(it) => it,
+        TypeDef::Adt(_) => return Some(Vec::new()),
+    };
+    let navs = Impl::all_for_trait(db, trait_).into_iter().filter_map(|imp| {
+        match imp.self_ty(db).as_adt() {
+            Some(adt) => adt.try_to_nav(db),
+            None => imp.try_to_nav(db),
+        }
+    });
+    Some(dedup_navs(navs))
+}
+
+#[derive(Clone, Copy)]
+enum TypeDef {
+    Trait(hir::Trait),
+    Adt(hir::Adt),
+}
+
+fn type_def_at(
+    sema: &Semantics<RootDatabase>,
+    position: FilePosition,
+) -> Option<(syntax::TextRange, TypeDef)> {
+    let file = sema.parse(position.file_id);
+    let node = sema.find_node_at_offset_with_descend(file.syntax(), position.offset)?;
+    let def = match &node {
+        ast::NameLike::Name(name) => {
+            NameClass::classify(sema, name).map(|class| class.referenced_or_defined(sema.db))
+        }
+        ast::NameLike::NameRef(name_ref) => {
+            NameRefClass::classify(sema, name_ref).map(|class| class.referenced(sema.db))
+        }
+        ast::NameLike::Lifetime(_) => None,
+    }?;
+    let def = match def {
+        Definition::ModuleDef(hir::ModuleDef::Trait(it)) => TypeDef::Trait(it),
+        Definition::ModuleDef(hir::ModuleDef::Adt(it)) => TypeDef::Adt(it),
+        _ => return None,
+    };
+    Some((node.syntax().text_range(), def))
+}
+
+fn dedup_navs(navs: impl Iterator<Item = NavigationTarget>) -> Vec<NavigationTarget> {
+    let mut res: Vec<NavigationTarget> = Vec::new();
+    for nav in navs {
+        if !res.contains(&nav) {
+            res.push(nav);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::base_db::FilePosition;
+
+    use crate::fixture;
+
+    fn check_hierarchy(
+        ra_fixture: &str,
+        expected: &str,
+        expected_supertypes: &[&str],
+        expected_subtypes: &[&str],
+    ) {
+        let (analysis, pos) = fixture::position(ra_fixture);
+
+        let mut navs = analysis.prepare_type_hierarchy(pos).unwrap().unwrap().info;
+        assert_eq!(navs.len(), 1);
+        let nav = navs.pop().unwrap();
+        nav.assert_match(expected);
+
+        let item_pos =
+            FilePosition { file_id: nav.file_id, offset: nav.focus_or_full_range().start() };
+        let supertypes = analysis.supertypes(item_pos).unwrap().unwrap();
+        let supertypes: Vec<_> = supertypes.iter().map(|it| it.debug_render()).collect();
+        assert_eq!(supertypes, expected_supertypes);
+
+        let subtypes = analysis.subtypes(item_pos).unwrap().unwrap();
+        let subtypes: Vec<_> = subtypes.iter().map(|it| it.debug_render()).collect();
+        assert_eq!(subtypes, expected_subtypes);
+    }
+
+    #[test]
+    fn test_type_hierarchy_on_trait() {
+        check_hierarchy(
+            r#"
+trait Base {}
+trait Other {}
+trait Tr$0ait: Base where Self: Other {}
+struct S;
+enum E {}
+impl Trait for S {}
+impl Trait for E {}
+impl Trait for u32 {}
+"#,
+            "Trait Trait FileId(0) 29..67 35..40",
+            &["Base Trait FileId(0) 0..13 6..10", "Other Trait FileId(0) 14..28 20..25"],
+            &[
+                "S Struct FileId(0) 68..77 75..76",
+                "E Enum FileId(0) 78..87 83..84",
+                "impl Impl FileId(0) 128..149 143..146",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_on_struct() {
+        check_hierarchy(
+            r#"
+trait A {}
+trait B {}
+struct S$0;
+impl S {}
+impl A for S {}
+impl B for &S {}
+"#,
+            "S Struct FileId(0) 22..31 29..30",
+            &["A Trait FileId(0) 0..10 6..7", "B Trait FileId(0) 11..21 17..18"],
+            &[],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_on_reference() {
+        check_hierarchy(
+            r#"
+trait Base {}
+trait Trait: Base {}
+struct S;
+impl Trait for S {}
+fn f(_: &dyn Tra$0it) {}
+"#,
+            "Trait Trait FileId(0) 14..34 20..25",
+            &["Base Trait FileId(0) 0..13 6..10"],
+            &["S Struct FileId(0) 35..44 42..43"],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_across_crates() {
+        check_hierarchy(
+            r#"
+//- /main.rs crate:main deps:lib
+struct Local;
+impl lib::Trait for Local {}
+//- /lib.rs crate:lib
+pub trait Tra$0it {}
+pub struct S;
+impl Trait for S {}
+"#,
+            "Trait Trait FileId(1) 0..18 10..15",
+            &[],
+            &["Local Struct FileId(0) 0..13 7..12", "S Struct FileId(1) 19..32 30..31"],
+        );
+    }
+
+    #[test]
+    fn test_no_type_hierarchy_for_functions() {
+        let (analysis, pos) = fixture::position(r#"fn f$0() {}"#);
+        assert!(analysis.prepare_type_hierarchy(pos).unwrap().is_none());
+    }
+}
@@ -287,6 +287,10 @@ impl AssistBuilder {
         self.source_change.is_snippet = true;
         self.replace(range, snippet);
     }
+    /// Marks the change as a snippet, for tab stops written directly into a mutable tree.
+    pub(crate) fn mark_snippet(&mut self, _cap: SnippetCap) {
+        self.source_change.is_snippet = true;
+    }
     pub(crate) fn replace_ast<N: AstNode>(&mut self, old: N, new: N) {
         algo::diff(old.syntax(), new.syntax()).into_text_edit(&mut self.edit)
     }
@@ -217,7 +217,7 @@ fn main() {
             add_explicit_type,
             r#"
 fn main() {
-    let $0l = [0.0; 2+2];
+    let $0l = [0.0; unknown_len()];
 }
 "#,
         );
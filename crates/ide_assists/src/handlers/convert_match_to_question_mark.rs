@@ -0,0 +1,547 @@
+use hir::Semantics;
+use ide_db::{helpers::FamousDefs, ty_filter::TryEnum, RootDatabase};
+use itertools::Itertools;
+use syntax::{
+    ast::{self, edit::IndentLevel, ArgListOwner, AstNode, NameOwner},
+    SyntaxKind,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_match_to_question_mark
+//
+// Replaces a `match` that returns early on `None` or `Err` with the `?` operator.
+//
+// ```
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn f(opt: Option<i32>) -> Option<i32> {
+//     let x = $0match opt {
+//         Some(it) => it,
+//         None => return None,
+//     };
+//     Some(x + 1)
+// }
+// ```
+// ->
+// ```
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn f(opt: Option<i32>) -> Option<i32> {
+//     let x = opt?;
+//     Some(x + 1)
+// }
+// ```
+pub(crate) fn convert_match_to_question_mark(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let match_expr = ctx.find_node_at_offset::<ast::MatchExpr>()?;
+    let scrutinee = match_expr.expr()?;
+    let scrutinee_ty = ctx.sema.type_of_expr(&scrutinee)?;
+    let try_enum = TryEnum::from_ty(&ctx.sema, &scrutinee_ty)?;
+    let ret_ty = enclosing_fn_ret_type(&ctx.sema, match_expr.syntax())?;
+    if TryEnum::from_ty(&ctx.sema, &ret_ty) != Some(try_enum) {
+        return None;
+    }
+
+    let (first, second) = match_expr.match_arm_list()?.arms().collect_tuple()?;
+    if first.guard().is_some() || second.guard().is_some() {
+        return None;
+    }
+    let (happy, sad) =
+        if is_happy_arm(&first, try_enum) { (first, second) } else { (second, first) };
+    if !is_happy_arm(&happy, try_enum) || !is_sad_arm(&sad, try_enum) {
+        return None;
+    }
+    if let TryEnum::Result = try_enum {
+        let from_err = scrutinee_ty.type_arguments().nth(1)?;
+        let to_err = ret_ty.type_arguments().nth(1)?;
+        let convertible = from_err == to_err
+            || FamousDefs(&ctx.sema, Some(ctx.sema.scope(match_expr.syntax()).krate()?))
+                .core_convert_From()
+                .map_or(false, |from| to_err.impls_trait_uniquely(ctx.db(), from, &[from_err]));
+        if !convertible {
+            cov_mark::hit!(convert_match_to_question_mark_no_from_impl);
+            return None;
+        }
+    }
+
+    let replacement = if is_postfix_operand(&scrutinee) {
+        format!("{}?", scrutinee)
+    } else {
+        format!("({})?", scrutinee)
+    };
+    let target = match_expr.syntax().text_range();
+    acc.add(
+        AssistId("convert_match_to_question_mark", AssistKind::RefactorRewrite),
+        "Replace match with `?`",
+        target,
+        |builder| builder.replace(target, replacement),
+    )
+}
+
+// Assist: convert_question_mark_to_match
+//
+// Expands the `?` operator into a `match` returning early on `None` or `Err`.
+//
+// ```
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn f(opt: Option<i32>) -> Option<i32> {
+//     let x = opt?$0;
+//     Some(x + 1)
+// }
+// ```
+// ->
+// ```
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn f(opt: Option<i32>) -> Option<i32> {
+//     let x = match opt {
+//         Some(it) => it,
+//         None => return None,
+//     };
+//     Some(x + 1)
+// }
+// ```
+pub(crate) fn convert_question_mark_to_match(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let try_expr = ctx.find_node_at_offset::<ast::TryExpr>()?;
+    let operand = try_expr.expr()?;
+    let operand_ty = ctx.sema.type_of_expr(&operand)?;
+    let try_enum = TryEnum::from_ty(&ctx.sema, &operand_ty)?;
+    let ret_ty = enclosing_fn_ret_type(&ctx.sema, try_expr.syntax())?;
+    if TryEnum::from_ty(&ctx.sema, &ret_ty) != Some(try_enum) {
+        return None;
+    }
+
+    let sad_arm = match try_enum {
+        TryEnum::Option => "None => return None".to_string(),
+        TryEnum::Result => {
+            let from_err = operand_ty.type_arguments().nth(1);
+            let to_err = ret_ty.type_arguments().nth(1);
+            let value = if from_err.is_some() && from_err == to_err { "err" } else { "err.into()" };
+            format!("Err(err) => return Err({})", value)
+        }
+    };
+    let indent = IndentLevel::from_node(try_expr.syntax());
+    let mut replacement = format!(
+        "match {} {{\n{}{}(it) => it,\n{}{},\n{}}}",
+        operand,
+        indent + 1,
+        try_enum.happy_case(),
+        indent + 1,
+        sad_arm,
+        indent
+    );
+    if is_postfix_receiver(try_expr.syntax()) {
+        replacement = format!("({})", replacement);
+    }
+    let target = try_expr.syntax().text_range();
+    acc.add(
+        AssistId("convert_question_mark_to_match", AssistKind::RefactorRewrite),
+        "Replace `?` with match",
+        target,
+        |builder| builder.replace(target, replacement),
+    )
+}
+
+/// Returns the return type of the function `node` returns from, unless it's in a closure or an
+/// async block, where `return` and `?` behave differently.
+fn enclosing_fn_ret_type(
+    sema: &Semantics<RootDatabase>,
+    node: &syntax::SyntaxNode,
+) -> Option<hir::Type> {
+    for ancestor in node.ancestors() {
+        match ancestor.kind() {
+            SyntaxKind::FN => {
+                let func = sema.to_def(&ast::Fn::cast(ancestor)?)?;
+                return Some(func.ret_type(sema.db));
+            }
+            SyntaxKind::CLOSURE_EXPR => return None,
+            SyntaxKind::EFFECT_EXPR => {
+                let block = ast::EffectExpr::cast(ancestor)?;
+                if block.async_token().is_some() || block.try_token().is_some() {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Checks for `Some(x) => x` or `Ok(x) => x`.
+fn is_happy_arm(arm: &ast::MatchArm, try_enum: TryEnum) -> bool {
+    let binding = match arm.pat() {
+        Some(ast::Pat::TupleStructPat(pat)) if path_is(pat.path(), try_enum.happy_case()) => {
+            match pat.fields().collect_tuple() {
+                Some((ast::Pat::IdentPat(binding),)) => binding,
+                _ => return false,
+            }
+        }
+        _ => return false,
+    };
+    if binding.ref_token().is_some() || binding.mut_token().is_some() || binding.pat().is_some() {
+        return false;
+    }
+    match (binding.name(), arm.expr().and_then(unwrap_trivial_block)) {
+        (Some(name), Some(ast::Expr::PathExpr(path))) => path
+            .path()
+            .and_then(|it| it.as_single_name_ref())
+            .map_or(false, |it| it.text() == name.text()),
+        _ => false,
+    }
+}
+
+/// Checks for `None => return None` or `Err(e) => return Err(e)`, where the error may also be
+/// converted with `e.into()`, `Into::into(e)` or `From::from(e)`.
+fn is_sad_arm(arm: &ast::MatchArm, try_enum: TryEnum) -> bool {
+    let returned = match arm.expr().and_then(unwrap_trivial_block) {
+        Some(ast::Expr::ReturnExpr(ret)) => match ret.expr() {
+            Some(it) => it,
+            None => return false,
+        },
+        _ => return false,
+    };
+    match try_enum {
+        TryEnum::Option => {
+            let pat_is_none = match arm.pat() {
+                Some(ast::Pat::IdentPat(pat)) => pat.syntax().text() == "None",
+                Some(ast::Pat::PathPat(pat)) => path_is(pat.path(), "None"),
+                _ => false,
+            };
+            let returns_none = match returned {
+                ast::Expr::PathExpr(path) => path_is(path.path(), "None"),
+                _ => false,
+            };
+            pat_is_none && returns_none
+        }
+        TryEnum::Result => {
+            let err = match arm.pat() {
+                Some(ast::Pat::TupleStructPat(pat)) if path_is(pat.path(), "Err") => {
+                    match pat.fields().collect_tuple() {
+                        Some((ast::Pat::IdentPat(binding),)) if binding.pat().is_none() => {
+                            match binding.name() {
+                                Some(name) => name.text().to_string(),
+                                None => return false,
+                            }
+                        }
+                        _ => return false,
+                    }
+                }
+                _ => return false,
+            };
+            let arg = match returned {
+                ast::Expr::CallExpr(call) if matches!(call.expr(), Some(ast::Expr::PathExpr(path)) if path_is(path.path(), "Err")) => {
+                    match call.arg_list().and_then(|it| it.args().collect_tuple()) {
+                        Some((arg,)) => arg,
+                        None => return false,
+                    }
+                }
+                _ => return false,
+            };
+            let is_err = |expr: &ast::Expr| match expr {
+                ast::Expr::PathExpr(path) => path_is(path.path(), &err),
+                _ => false,
+            };
+            match &arg {
+                ast::Expr::MethodCallExpr(call) => {
+                    call.name_ref().map_or(false, |it| it.text() == "into")
+                        && call.arg_list().map_or(false, |it| it.args().next().is_none())
+                        && call.receiver().map_or(false, |it| is_err(&it))
+                }
+                ast::Expr::CallExpr(call) => {
+                    let is_conversion = match call.expr() {
+                        Some(ast::Expr::PathExpr(path)) => matches!(
+                            path.path().map(|it| it.to_string()).as_deref(),
+                            Some("From::from") | Some("Into::into")
+                        ),
+                        _ => false,
+                    };
+                    is_conversion
+                        && matches!(
+                            call.arg_list().and_then(|it| it.args().collect_tuple()),
+                            Some((it,)) if is_err(&it)
+                        )
+                }
+                _ => is_err(&arg),
+            }
+        }
+    }
+}
+
+fn unwrap_trivial_block(expr: ast::Expr) -> Option<ast::Expr> {
+    match expr {
+        ast::Expr::BlockExpr(block) if block.statements().next().is_none() => block.tail_expr(),
+        expr => Some(expr),
+    }
+}
+
+fn path_is(path: Option<ast::Path>, name: &str) -> bool {
+    path.and_then(|it| it.as_single_name_ref()).map_or(false, |it| it.text() == name)
+}
+
+fn is_postfix_operand(expr: &ast::Expr) -> bool {
+    matches!(
+        expr,
+        ast::Expr::PathExpr(_)
+            | ast::Expr::CallExpr(_)
+            | ast::Expr::MethodCallExpr(_)
+            | ast::Expr::FieldExpr(_)
+            | ast::Expr::IndexExpr(_)
+            | ast::Expr::TryExpr(_)
+            | ast::Expr::ParenExpr(_)
+            | ast::Expr::TupleExpr(_)
+            | ast::Expr::MacroCall(_)
+    )
+}
+
+/// Whether `node` is the operand of a postfix expression, like the receiver of a method call.
+fn is_postfix_receiver(node: &syntax::SyntaxNode) -> bool {
+    let parent = match node.parent() {
+        Some(it) => it,
+        None => return false,
+    };
+    matches!(
+        parent.kind(),
+        SyntaxKind::METHOD_CALL_EXPR
+            | SyntaxKind::FIELD_EXPR
+            | SyntaxKind::CALL_EXPR
+            | SyntaxKind::INDEX_EXPR
+            | SyntaxKind::TRY_EXPR
+            | SyntaxKind::AWAIT_EXPR
+    ) && parent.first_child().as_ref() == Some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    fn check_to_question_mark(ra_fixture_before: &str, ra_fixture_after: &str) {
+        let before = format!(
+            "//- /main.rs crate:main deps:core{}{}",
+            ra_fixture_before,
+            FamousDefs::FIXTURE
+        );
+        check_assist(convert_match_to_question_mark, &before, ra_fixture_after);
+    }
+
+    fn check_to_question_mark_not_applicable(ra_fixture: &str) {
+        let before =
+            format!("//- /main.rs crate:main deps:core{}{}", ra_fixture, FamousDefs::FIXTURE);
+        check_assist_not_applicable(convert_match_to_question_mark, &before);
+    }
+
+    fn check_to_match(ra_fixture_before: &str, ra_fixture_after: &str) {
+        let before = format!(
+            "//- /main.rs crate:main deps:core{}{}",
+            ra_fixture_before,
+            FamousDefs::FIXTURE
+        );
+        check_assist(convert_question_mark_to_match, &before, ra_fixture_after);
+    }
+
+    #[test]
+    fn option_match_to_question_mark() {
+        check_to_question_mark(
+            r#"
+fn f(v: Vec<i32>) -> Option<i32> {
+    let x = $0match v.first() {
+        Some(x) => x,
+        None => return None,
+    };
+    Some(*x)
+}
+"#,
+            r#"
+fn f(v: Vec<i32>) -> Option<i32> {
+    let x = v.first()?;
+    Some(*x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn result_match_to_question_mark_with_conversion() {
+        check_to_question_mark(
+            r#"
+struct MyErr;
+impl From<u8> for MyErr {
+    fn from(_: u8) -> MyErr { MyErr }
+}
+fn f(res: Result<i32, u8>) -> Result<i32, MyErr> {
+    let x = $0match res {
+        Err(e) => {
+            return Err(e.into())
+        }
+        Ok(x) => x,
+    };
+    Ok(x)
+}
+"#,
+            r#"
+struct MyErr;
+impl From<u8> for MyErr {
+    fn from(_: u8) -> MyErr { MyErr }
+}
+fn f(res: Result<i32, u8>) -> Result<i32, MyErr> {
+    let x = res?;
+    Ok(x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_from_impl() {
+        cov_mark::check!(convert_match_to_question_mark_no_from_impl);
+        check_to_question_mark_not_applicable(
+            r#"
+struct MyErr;
+fn f(res: Result<i32, u8>) -> Result<i32, MyErr> {
+    let x = $0match res {
+        Ok(x) => x,
+        Err(e) => return Err(From::from(e)),
+    };
+    Ok(x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_other_sad_arms() {
+        check_to_question_mark_not_applicable(
+            r#"
+fn f(res: Result<i32, u8>) -> Result<i32, u8> {
+    let x = $0match res {
+        Ok(x) => x,
+        Err(e) => return Err(e + 1),
+    };
+    Ok(x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_closures() {
+        check_to_question_mark_not_applicable(
+            r#"
+fn f(opt: Option<i32>) -> Option<i32> {
+    let g = || {
+        let x = $0match opt {
+            Some(x) => x,
+            None => return None,
+        };
+        Some(x)
+    };
+    None
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_mismatched_return_type() {
+        check_to_question_mark_not_applicable(
+            r#"
+fn f(opt: Option<i32>) -> Result<i32, ()> {
+    let x = $0match opt {
+        Some(x) => x,
+        None => return None,
+    };
+    Ok(x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn question_mark_to_match() {
+        check_to_match(
+            r#"
+fn f(res: Result<i32, u8>) -> Result<i32, u8> {
+    if true {
+        let x = res$0?;
+    }
+    Ok(0)
+}
+"#,
+            r#"
+fn f(res: Result<i32, u8>) -> Result<i32, u8> {
+    if true {
+        let x = match res {
+            Ok(it) => it,
+            Err(err) => return Err(err),
+        };
+    }
+    Ok(0)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn question_mark_to_match_with_conversion() {
+        check_to_match(
+            r#"
+struct MyErr;
+impl From<u8> for MyErr {
+    fn from(_: u8) -> MyErr { MyErr }
+}
+fn f(res: Result<i32, u8>) -> Result<i32, MyErr> {
+    Ok(res?$0 + 1)
+}
+"#,
+            r#"
+struct MyErr;
+impl From<u8> for MyErr {
+    fn from(_: u8) -> MyErr { MyErr }
+}
+fn f(res: Result<i32, u8>) -> Result<i32, MyErr> {
+    Ok(match res {
+        Ok(it) => it,
+        Err(err) => return Err(err.into()),
+    } + 1)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn question_mark_to_match_in_method_chain() {
+        check_to_match(
+            r#"
+struct S;
+impl S {
+    fn len(&self) -> usize { 0 }
+}
+fn get() -> Option<S> { None }
+fn f() -> Option<usize> {
+    let n = get()$0?.len();
+    Some(n)
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn len(&self) -> usize { 0 }
+}
+fn get() -> Option<S> { None }
+fn f() -> Option<usize> {
+    let n = (match get() {
+        Some(it) => it,
+        None => return None,
+    }).len();
+    Some(n)
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,596 @@
+use hir::Semantics;
+use ide_db::{base_db::Edition, ty_filter::TryEnum, RootDatabase};
+use itertools::Itertools;
+use syntax::{
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        AstNode, NameOwner,
+    },
+    ted, TextRange,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_to_let_else
+//
+// Converts a `let` whose initializer is a `match` or `if let` bailing out in one of its branches
+// into a `let ... else` statement.
+//
+// ```
+// //- /main.rs crate:main edition:2021
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn main() {
+//     let opt: Option<i32> = None;
+//     let x = $0match opt {
+//         Some(it) => it,
+//         None => return,
+//     };
+// }
+// ```
+// ->
+// ```
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn main() {
+//     let opt: Option<i32> = None;
+//     let Some(x) = opt else { return };
+// }
+// ```
+pub(crate) fn convert_to_let_else(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let let_stmt = ctx.find_node_at_offset::<ast::LetStmt>()?;
+    if let_stmt.ty().is_some() || let_stmt.let_else().is_some() {
+        return None;
+    }
+    let krate = ctx.sema.scope(let_stmt.syntax()).module()?.krate();
+    if krate.edition(ctx.db()) < Edition::Edition2021 {
+        // `let ... else` needs a newer toolchain than the one of older editions.
+        cov_mark::hit!(convert_to_let_else_old_edition);
+        return None;
+    }
+    let outer_pat = let_stmt.pat()?;
+    let initializer = let_stmt.initializer()?;
+
+    let (happy_pat, sad_pat, scrutinee, happy_expr, sad_expr) = match &initializer {
+        ast::Expr::MatchExpr(match_expr) => {
+            let (first, second) = match_expr.match_arm_list()?.arms().collect_tuple()?;
+            if first.guard().is_some() || second.guard().is_some() {
+                return None;
+            }
+            let (happy, sad) = if diverges(&ctx.sema, &second.expr()?) {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            if !diverges(&ctx.sema, &sad.expr()?) || diverges(&ctx.sema, &happy.expr()?) {
+                return None;
+            }
+            let sad_pat = sad.pat()?;
+            let sad_expr = sad.expr()?;
+            if binds_used_names(&ctx.sema, &sad_pat, &sad_expr) {
+                cov_mark::hit!(convert_to_let_else_sad_arm_uses_bindings);
+                return None;
+            }
+            (happy.pat()?, Some(sad_pat), match_expr.expr()?, happy.expr()?, sad_expr)
+        }
+        ast::Expr::IfExpr(if_expr) => {
+            let condition = if_expr.condition()?;
+            let else_block = match if_expr.else_branch()? {
+                ast::ElseBranch::Block(block) => block,
+                ast::ElseBranch::IfExpr(_) => return None,
+            };
+            let sad_expr = ast::Expr::BlockExpr(else_block);
+            if !diverges(&ctx.sema, &sad_expr) {
+                return None;
+            }
+            let then_expr = ast::Expr::BlockExpr(if_expr.then_branch()?);
+            (condition.pat()?, None, condition.expr()?, then_expr, sad_expr)
+        }
+        _ => return None,
+    };
+
+    // The `else` has to run exactly when the `match` took the diverging arm, and an irrefutable
+    // pattern would not be accepted by `let ... else` at all.
+    let try_enum = TryEnum::from_ty(&ctx.sema, &ctx.sema.type_of_expr(&scrutinee)?)?;
+    if !is_happy_pattern(&happy_pat, try_enum)
+        || !sad_pat.map_or(true, |it| is_sad_pattern(&it, try_enum))
+    {
+        cov_mark::hit!(convert_to_let_else_not_opposite_patterns);
+        return None;
+    }
+
+    let new_pat = substitute_bindings(&ctx.sema, &happy_pat, &happy_expr, &outer_pat)?;
+    let scrutinee =
+        if scrutinee.is_block_like() { format!("({})", scrutinee) } else { scrutinee.to_string() };
+    let indent = IndentLevel::from_node(let_stmt.syntax());
+    let sad_block = match sad_expr {
+        ast::Expr::BlockExpr(block) => block.reset_indent().indent(indent).to_string(),
+        expr => format!("{{ {} }}", expr),
+    };
+
+    let target = let_stmt.syntax().text_range();
+    let range = TextRange::new(
+        outer_pat.syntax().text_range().start(),
+        initializer.syntax().text_range().end(),
+    );
+    acc.add(
+        AssistId("convert_to_let_else", AssistKind::RefactorRewrite),
+        "Convert to let-else",
+        target,
+        |builder| builder.replace(range, format!("{} = {} else {}", new_pat, scrutinee, sad_block)),
+    )
+}
+
+// Assist: convert_let_else_to_match
+//
+// Converts a `let ... else` statement into a `let` with a `match` initializer.
+//
+// ```
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn main() {
+//     let opt: Option<i32> = None;
+//     let Some(x) = opt $0else { return };
+// }
+// ```
+// ->
+// ```
+// enum Option<T> { Some(T), None }
+// use Option::*;
+//
+// fn main() {
+//     let opt: Option<i32> = None;
+//     let x = match opt {
+//         Some(x) => x,
+//         None => return,
+//     };
+// }
+// ```
+pub(crate) fn convert_let_else_to_match(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let let_stmt = ctx.find_node_at_offset::<ast::LetStmt>()?;
+    let let_else = let_stmt.let_else()?;
+    let else_block = let_else.block_expr()?;
+    let pat = let_stmt.pat()?;
+    let scrutinee = let_stmt.initializer()?;
+
+    let bindings = bindings(&ctx.sema, &pat);
+    if bindings.is_empty() {
+        return None;
+    }
+    // `mut` moves from the pattern to the new `let`, where the binding ends up.
+    let new_pat = pat.clone_for_update();
+    let mut outer_pats = Vec::new();
+    let mut names = Vec::new();
+    let mut mut_tokens = Vec::new();
+    for binding in &bindings {
+        let name = binding.name()?.to_string();
+        if binding.mut_token().is_some() && binding.ref_token().is_none() {
+            outer_pats.push(format!("mut {}", name));
+            mut_tokens.push(find_same_node(&new_pat, &pat, binding)?.mut_token()?);
+        } else {
+            outer_pats.push(name.clone());
+        }
+        names.push(name);
+    }
+    for mut_token in mut_tokens {
+        if let Some(ws) = mut_token.next_token().filter(|it| it.kind().is_trivia()) {
+            ted::remove(ws);
+        }
+        ted::remove(mut_token);
+    }
+    let (outer_pat, happy_expr) = match (outer_pats.as_slice(), names.as_slice()) {
+        ([outer_pat], [name]) => (outer_pat.clone(), name.clone()),
+        _ => (format!("({})", outer_pats.join(", ")), format!("({})", names.join(", "))),
+    };
+
+    let sad_pat = ctx
+        .sema
+        .type_of_expr(&scrutinee)
+        .and_then(|ty| TryEnum::from_ty(&ctx.sema, &ty))
+        .filter(|try_enum| is_happy_pattern(&pat, *try_enum))
+        .map_or_else(|| "_".to_string(), |try_enum| try_enum.sad_pattern().to_string());
+    let indent = IndentLevel::from_node(let_stmt.syntax());
+    let sad_expr = match (else_block.statements().next(), else_block.tail_expr()) {
+        (None, Some(tail)) if !tail.syntax().text().contains_char('\n') => {
+            format!("{},", tail)
+        }
+        _ => else_block.reset_indent().indent(indent + 1).to_string(),
+    };
+    let arm_indent = indent + 1;
+    let replacement = format!(
+        "{} = match {} {{\n{}{} => {},\n{}{} => {}\n{}}}",
+        outer_pat,
+        scrutinee,
+        arm_indent,
+        new_pat,
+        happy_expr,
+        arm_indent,
+        sad_pat,
+        sad_expr,
+        indent,
+    );
+
+    let target = let_stmt.syntax().text_range();
+    let range =
+        TextRange::new(pat.syntax().text_range().start(), else_block.syntax().text_range().end());
+    acc.add(
+        AssistId("convert_let_else_to_match", AssistKind::RefactorRewrite),
+        "Convert let-else to match",
+        target,
+        |builder| builder.replace(range, replacement),
+    )
+}
+
+fn diverges(sema: &Semantics<RootDatabase>, expr: &ast::Expr) -> bool {
+    match expr {
+        // Blocks ending in a diverging statement get the type expected from them, not `!`.
+        ast::Expr::BlockExpr(block) => match block.tail_expr() {
+            Some(tail) => diverges(sema, &tail),
+            None => match block.statements().last() {
+                Some(ast::Stmt::ExprStmt(stmt)) => {
+                    stmt.expr().map_or(false, |it| diverges(sema, &it))
+                }
+                _ => false,
+            },
+        },
+        _ => sema.type_of_expr(expr).map_or(false, |ty| ty.is_never()),
+    }
+}
+
+/// Returns the bindings introduced by `pat`, skipping identifiers that refer to constants or
+/// unit variants.
+fn bindings(sema: &Semantics<RootDatabase>, pat: &ast::Pat) -> Vec<ast::IdentPat> {
+    pat.syntax()
+        .descendants()
+        .filter_map(ast::IdentPat::cast)
+        .filter(|it| sema.resolve_bind_pat_to_const(it).is_none())
+        .collect()
+}
+
+fn binds_used_names(sema: &Semantics<RootDatabase>, pat: &ast::Pat, expr: &ast::Expr) -> bool {
+    let names: Vec<_> = bindings(sema, pat).iter().filter_map(|it| it.name()).collect();
+    expr.syntax()
+        .descendants()
+        .filter_map(ast::NameRef::cast)
+        .any(|name_ref| names.iter().any(|name| name.text() == name_ref.text()))
+}
+
+/// Rewrites `happy_pat` so that it binds what `outer_pat` bound from `happy_expr`, which must
+/// consist of nothing but the bindings of `happy_pat`.
+fn substitute_bindings(
+    sema: &Semantics<RootDatabase>,
+    happy_pat: &ast::Pat,
+    happy_expr: &ast::Expr,
+    outer_pat: &ast::Pat,
+) -> Option<ast::Pat> {
+    let happy_expr = match happy_expr {
+        ast::Expr::BlockExpr(block) if block.statements().next().is_none() => block.tail_expr()?,
+        expr => expr.clone(),
+    };
+    let (values, outer_pats) = match (happy_expr, outer_pat) {
+        (ast::Expr::TupleExpr(tuple), ast::Pat::TuplePat(outer)) => {
+            let values: Vec<_> = tuple.fields().collect();
+            let outer_pats: Vec<_> = outer.fields().collect();
+            if values.len() != outer_pats.len() {
+                return None;
+            }
+            (values, outer_pats)
+        }
+        (expr, outer) => (vec![expr], vec![outer.clone()]),
+    };
+
+    let bindings = bindings(sema, happy_pat);
+    if bindings.len() != values.len() {
+        return None;
+    }
+    let new_pat = happy_pat.clone_for_update();
+    let mut replacements = Vec::new();
+    let mut seen = Vec::new();
+    for (value, outer) in values.iter().zip(outer_pats) {
+        let name = match value {
+            ast::Expr::PathExpr(path) => path.path()?.as_single_name_ref()?,
+            _ => return None,
+        };
+        if seen.contains(&name.text().to_string()) {
+            return None;
+        }
+        seen.push(name.text().to_string());
+        let binding =
+            bindings.iter().find(|it| it.name().map_or(false, |it| it.text() == name.text()))?;
+        let has_modifiers = binding.ref_token().is_some() || binding.mut_token().is_some();
+        if binding.pat().is_some() {
+            return None;
+        }
+        let binding = find_same_node(&new_pat, happy_pat, binding)?;
+        match outer {
+            ast::Pat::IdentPat(outer) if has_modifiers => {
+                if outer.ref_token().is_some()
+                    || outer.mut_token().is_some()
+                    || outer.pat().is_some()
+                {
+                    return None;
+                }
+                replacements
+                    .push((binding.name()?.syntax().clone(), outer.name()?.syntax().clone()));
+            }
+            _ if has_modifiers => return None,
+            outer => replacements.push((binding.syntax().clone(), outer.syntax().clone())),
+        }
+    }
+    for (old, new) in replacements {
+        ted::replace(old, new.clone_for_update());
+    }
+    Some(new_pat)
+}
+
+/// Finds the copy of `node`, a descendant of `root`, in `copy`, a mutable copy of `root`.
+fn find_same_node<N: AstNode>(copy: &impl AstNode, root: &impl AstNode, node: &N) -> Option<N> {
+    let range = node.syntax().text_range() - root.syntax().text_range().start();
+    let offset = copy.syntax().text_range().start();
+    copy.syntax()
+        .descendants()
+        .filter_map(N::cast)
+        .find(|it| it.syntax().text_range() - offset == range)
+}
+
+fn is_happy_pattern(pat: &ast::Pat, try_enum: TryEnum) -> bool {
+    match pat {
+        ast::Pat::TupleStructPat(pat) => pat
+            .path()
+            .and_then(|path| path.as_single_name_ref())
+            .map_or(false, |name| name.text() == try_enum.happy_case()),
+        _ => false,
+    }
+}
+
+fn is_sad_pattern(pat: &ast::Pat, try_enum: TryEnum) -> bool {
+    let name = match pat {
+        ast::Pat::WildcardPat(_) => return true,
+        ast::Pat::IdentPat(pat) if pat.pat().is_none() => {
+            pat.name().map(|it| it.text().to_string())
+        }
+        ast::Pat::TupleStructPat(pat) => {
+            pat.path().and_then(|path| path.as_single_name_ref()).map(|it| it.text().to_string())
+        }
+        _ => None,
+    };
+    let sad_case = match try_enum {
+        TryEnum::Result => "Err",
+        TryEnum::Option => "None",
+    };
+    name.map_or(false, |name| name == sad_case)
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::helpers::FamousDefs;
+
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    fn check_to_let_else(ra_fixture_before: &str, ra_fixture_after: &str) {
+        let before = format!(
+            "//- /main.rs crate:main deps:core edition:2021{}{}",
+            ra_fixture_before,
+            FamousDefs::FIXTURE
+        );
+        check_assist(convert_to_let_else, &before, ra_fixture_after);
+    }
+
+    fn check_to_let_else_not_applicable(ra_fixture: &str) {
+        let before = format!(
+            "//- /main.rs crate:main deps:core edition:2021{}{}",
+            ra_fixture,
+            FamousDefs::FIXTURE
+        );
+        check_assist_not_applicable(convert_to_let_else, &before);
+    }
+
+    fn check_to_match(ra_fixture_before: &str, ra_fixture_after: &str) {
+        let before = format!(
+            "//- /main.rs crate:main deps:core{}{}",
+            ra_fixture_before,
+            FamousDefs::FIXTURE
+        );
+        check_assist(convert_let_else_to_match, &before, ra_fixture_after);
+    }
+
+    #[test]
+    fn match_to_let_else() {
+        check_to_let_else(
+            r#"
+fn f(opt: Option<i32>) -> i32 {
+    let x = $0match opt {
+        Some(it) => it,
+        None => return 0,
+    };
+    x
+}
+"#,
+            r#"
+fn f(opt: Option<i32>) -> i32 {
+    let Some(x) = opt else { return 0 };
+    x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn match_with_sad_arm_first_and_block() {
+        check_to_let_else(
+            r#"
+fn f(res: Result<(i32, u8), ()>) {
+    let (mut a, b) = $0match res {
+        Err(()) => {
+            log();
+            return;
+        }
+        Ok((x, y)) => (x, y),
+    };
+}
+fn log() {}
+"#,
+            r#"
+fn f(res: Result<(i32, u8), ()>) {
+    let Ok((mut a, b)) = res else {
+        log();
+        return;
+    };
+}
+fn log() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn if_let_to_let_else() {
+        check_to_let_else(
+            r#"
+fn f(v: &[i32]) {
+    for it in v {
+        let n = i$0f let Some(n) = it.checked_add(1) { n } else { continue };
+    }
+}
+"#,
+            r#"
+fn f(v: &[i32]) {
+    for it in v {
+        let Some(n) = it.checked_add(1) else { continue };
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_sad_arm_uses_bindings() {
+        cov_mark::check!(convert_to_let_else_sad_arm_uses_bindings);
+        check_to_let_else_not_applicable(
+            r#"
+fn f(res: Result<i32, i32>) -> i32 {
+    let x = $0match res {
+        Ok(x) => x,
+        Err(e) => return e,
+    };
+    x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_divergence() {
+        check_to_let_else_not_applicable(
+            r#"
+fn f(opt: Option<i32>) {
+    let x = $0match opt {
+        Some(x) => x,
+        None => 0,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_happy_arm_computes() {
+        check_to_let_else_not_applicable(
+            r#"
+fn f(opt: Option<i32>) {
+    let x = $0match opt {
+        Some(x) => x + 1,
+        None => return,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_old_editions() {
+        cov_mark::check!(convert_to_let_else_old_edition);
+        check_assist_not_applicable(
+            convert_to_let_else,
+            r#"
+//- /main.rs crate:main edition:2018
+fn f(opt: Option<i32>) {
+    let x = $0match opt {
+        Some(x) => x,
+        None => return,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_irrefutable_happy_pattern() {
+        cov_mark::check!(convert_to_let_else_not_opposite_patterns);
+        check_to_let_else_not_applicable(
+            r#"
+fn f(opt: Option<i32>) {
+    let x = $0match opt {
+        None => return,
+        o => o,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn let_else_to_match() {
+        check_to_match(
+            r#"
+fn f(opt: Option<i32>) -> i32 {
+    let Some(x) = opt $0else { return 0 };
+    x
+}
+"#,
+            r#"
+fn f(opt: Option<i32>) -> i32 {
+    let x = match opt {
+        Some(x) => x,
+        None => return 0,
+    };
+    x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn let_else_to_match_several_bindings() {
+        check_to_match(
+            r#"
+struct S { a: i32, b: i32 }
+fn f(s: Option<S>) {
+    let$0 Some(S { a, mut b }) = s else {
+        log();
+        return;
+    };
+}
+fn log() {}
+"#,
+            r#"
+struct S { a: i32, b: i32 }
+fn f(s: Option<S>) {
+    let (a, mut b) = match s {
+        Some(S { a, b }) => (a, b),
+        None => {
+            log();
+            return;
+        }
+    };
+}
+fn log() {}
+"#,
+        );
+    }
+}
@@ -0,0 +1,954 @@
+use std::{collections::BTreeMap, iter};
+
+use hir::{Module, ModuleDef, PathResolution, ScopeDef, Semantics};
+use ide_db::{
+    base_db::{AnchoredPathBuf, FileId},
+    defs::Definition,
+    helpers::{
+        insert_use::{insert_use, ImportScope},
+        mod_path_to_ast,
+    },
+    RootDatabase,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::{
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        make, AstNode,
+    },
+    ted, NodeOrToken, SyntaxKind, SyntaxNode, SyntaxToken, SyntaxTreeBuilder, TextRange, T,
+};
+
+use crate::{assist_context::AssistBuilder, AssistContext, AssistId, AssistKind, Assists};
+
+const MODULE_NAME: &str = "modname";
+
+// Assist: extract_module
+//
+// Extracts the selected items into a new inline module. Items used from outside of it are made
+// visible, and imports are added to keep both the moved items and their users compiling.
+//
+// ```
+// $0fn foo() {}
+//
+// fn bar() {}$0
+//
+// fn main() {
+//     foo();
+// }
+// ```
+// ->
+// ```
+// use self::${0:modname}::foo;
+//
+// mod ${0:modname} {
+//     pub(super) fn foo() {}
+//
+//     fn bar() {}
+// }
+//
+// fn main() {
+//     foo();
+// }
+// ```
+pub(crate) fn extract_module(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let extraction = Extraction::new(ctx)?;
+    acc.add(
+        AssistId("extract_module", AssistKind::RefactorExtract),
+        "Extract into module",
+        extraction.range,
+        |builder| extraction.apply(ctx, builder, false),
+    )
+}
+
+// Assist: extract_module_to_file
+//
+// Extracts the selected items into a new module in a separate file.
+//
+// ```
+// $0fn foo() {}$0
+// ```
+// ->
+// ```
+// mod modname;
+// ```
+pub(crate) fn extract_module_to_file(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let extraction = Extraction::new(ctx)?;
+    // Extracting out of an inline module would need a directory for it.
+    if extraction.container.kind() != SyntaxKind::SOURCE_FILE {
+        return None;
+    }
+    acc.add(
+        AssistId("extract_module_to_file", AssistKind::RefactorExtract),
+        "Extract into module file",
+        extraction.range,
+        |builder| extraction.apply(ctx, builder, true),
+    )
+}
+
+/// Visibilities given to extracted items used from outside of the new module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Visibility {
+    Super,
+    Crate,
+}
+
+impl Visibility {
+    fn as_str(self) -> &'static str {
+        match self {
+            Visibility::Super => "pub(super)",
+            Visibility::Crate => "pub(crate)",
+        }
+    }
+}
+
+struct Extraction {
+    /// The `SourceFile` or module `ItemList` holding the selected items.
+    container: SyntaxNode,
+    items: Vec<ast::Item>,
+    range: TextRange,
+    /// The module the items are extracted from.
+    parent: Module,
+}
+
+impl Extraction {
+    fn new(ctx: &AssistContext) -> Option<Extraction> {
+        let selection = ctx.frange.range;
+        if selection.is_empty() {
+            return None;
+        }
+        let container = match ctx.covering_element() {
+            NodeOrToken::Node(it) => it,
+            NodeOrToken::Token(it) => it.parent()?,
+        }
+        .ancestors()
+        .find(|it| matches!(it.kind(), SyntaxKind::SOURCE_FILE | SyntaxKind::ITEM_LIST))?;
+
+        let items = container
+            .children()
+            .filter(|it| it.text_range().intersect(selection).map_or(false, |it| !it.is_empty()))
+            .map(ast::Item::cast)
+            .collect::<Option<Vec<_>>>()?;
+        let (first, last) = (items.first()?, items.last()?);
+        if items
+            .iter()
+            .any(|it| !selection.contains_range(it.syntax().text_range()) || !is_movable(it))
+        {
+            return None;
+        }
+
+        let range =
+            TextRange::new(first.syntax().text_range().start(), last.syntax().text_range().end());
+        let parent = ctx.sema.scope(first.syntax()).module()?;
+        Some(Extraction { container, items, range, parent })
+    }
+
+    fn apply(&self, ctx: &AssistContext, builder: &mut AssistBuilder, to_file: bool) {
+        let db = ctx.db();
+        let file_id = ctx.frange.file_id;
+        let moved: FxHashSet<ModuleDef> =
+            self.items.iter().filter_map(|it| item_def(&ctx.sema, it)).collect();
+
+        let mut edits = Vec::new();
+        for (def, node) in self.visibility_targets(&ctx.sema) {
+            if let Some(vis) = self.needed_visibility(ctx, def) {
+                edits.extend(visibility_edit(&node, vis));
+            }
+        }
+        let imports = self.fix_inner_paths(ctx, &moved, &mut edits);
+        let (qualified_refs, parent_imports) = self.outer_references(ctx, &moved);
+
+        let indent = IndentLevel::from_node(self.items[0].syntax());
+        let body = {
+            let file = ast::SourceFile::parse(&self.body_text(edits)).tree();
+            let file = file.dedent(indent).clone_for_update();
+            let scope = ImportScope::File(file.clone());
+            imports.into_iter().for_each(|it| insert_use(&scope, it, ctx.config.insert_use));
+            file
+        };
+        let new_module = if to_file {
+            let dir = match self.parent.name(db) {
+                Some(name) if !self.parent.is_mod_rs(db) => format!("{}/", name),
+                _ => String::new(),
+            };
+            let path = format!("./{}{}.rs", dir, MODULE_NAME);
+            builder.create_file(AnchoredPathBuf { anchor: file_id, path }, format!("{}\n", body));
+            parse_module(&format!("mod {};", MODULE_NAME))
+        } else {
+            let body = body.indent(indent + 1);
+            parse_module(&format!("mod {} {{\n{}{}\n{}}}", MODULE_NAME, indent + 1, body, indent))
+        };
+
+        // Edit the other files first, the current one is committed last.
+        let mut current_file_refs = Vec::new();
+        let mut other_files_edited = false;
+        for (ref_file_id, paths) in qualified_refs {
+            if ref_file_id == file_id {
+                current_file_refs = paths;
+                continue;
+            }
+            other_files_edited = true;
+            builder.edit_file(ref_file_id);
+            let paths: Vec<_> = paths.into_iter().map(|it| builder.make_mut(it)).collect();
+            paths.iter().for_each(qualify_with_module);
+        }
+
+        builder.edit_file(file_id);
+        let paths: Vec<_> = current_file_refs.into_iter().map(|it| builder.make_mut(it)).collect();
+        let container = builder.make_syntax_mut(self.container.clone());
+        let first = builder.make_syntax_mut(self.items[0].syntax().clone());
+        let last = builder.make_syntax_mut(self.items[self.items.len() - 1].syntax().clone());
+        let root = container.ancestors().last().unwrap();
+        let existing_names: FxHashSet<_> = module_name_tokens(&root).collect();
+
+        paths.iter().for_each(qualify_with_module);
+        ted::replace_all(first.into()..=last.into(), vec![new_module.syntax().clone().into()]);
+        if let Some(scope) = ImportScope::find_insert_use_container(&container) {
+            for name in parent_imports {
+                let path = make::path_from_text(&format!("self::{}::{}", MODULE_NAME, name));
+                insert_use(&scope, path, ctx.config.insert_use);
+            }
+        }
+
+        // Every mention of the name gets its own placeholder, which the client turns into a
+        // cursor each. Snippets can't span files, so this is skipped when other files changed.
+        let cap = match ctx.config.snippet_cap {
+            Some(cap) if !to_file && !other_files_edited => cap,
+            _ => return,
+        };
+        let body = new_module.item_list().map(|it| it.syntax().clone());
+        let new_names: Vec<_> = module_name_tokens(&root)
+            .filter(|it| !existing_names.contains(it))
+            .filter(|it| !it.ancestors().any(|it| Some(&it) == body.as_ref()))
+            .collect();
+        for name in new_names {
+            ted::replace(name, snippet_placeholder(MODULE_NAME));
+        }
+        builder.mark_snippet(cap);
+    }
+
+    /// Returns the definitions whose visibility may have to be raised, along with their syntax.
+    fn visibility_targets(&self, sema: &Semantics<RootDatabase>) -> Vec<(Definition, SyntaxNode)> {
+        let mut res = Vec::new();
+        let mut push_fields = |field_list: Option<ast::FieldList>| match field_list {
+            Some(ast::FieldList::RecordFieldList(it)) => {
+                for field in it.fields() {
+                    if let Some(def) = sema.to_def(&field) {
+                        res.push((Definition::Field(def), field.syntax().clone()));
+                    }
+                }
+            }
+            Some(ast::FieldList::TupleFieldList(it)) => {
+                for field in it.fields() {
+                    if let Some(def) = sema.to_def(&field) {
+                        res.push((Definition::Field(def), field.syntax().clone()));
+                    }
+                }
+            }
+            None => {}
+        };
+        for item in &self.items {
+            match item {
+                ast::Item::Struct(it) => push_fields(it.field_list()),
+                ast::Item::Union(it) => {
+                    push_fields(it.record_field_list().map(ast::FieldList::RecordFieldList))
+                }
+                _ => {}
+            }
+        }
+
+        for item in &self.items {
+            match item {
+                ast::Item::Impl(it) if it.trait_().is_none() => {
+                    for assoc_item in
+                        it.assoc_item_list().into_iter().flat_map(|it| it.assoc_items())
+                    {
+                        let def: Option<ModuleDef> = match &assoc_item {
+                            ast::AssocItem::Fn(it) => sema.to_def(it).map(Into::into),
+                            ast::AssocItem::Const(it) => sema.to_def(it).map(Into::into),
+                            ast::AssocItem::TypeAlias(it) => sema.to_def(it).map(Into::into),
+                            ast::AssocItem::MacroCall(_) => None,
+                        };
+                        if let Some(def) = def {
+                            res.push((Definition::ModuleDef(def), assoc_item.syntax().clone()));
+                        }
+                    }
+                }
+                ast::Item::ExternBlock(it) => {
+                    for extern_item in
+                        it.extern_item_list().into_iter().flat_map(|it| it.extern_items())
+                    {
+                        let def: Option<ModuleDef> = match &extern_item {
+                            ast::ExternItem::Fn(it) => sema.to_def(it).map(Into::into),
+                            ast::ExternItem::Static(it) => sema.to_def(it).map(Into::into),
+                            ast::ExternItem::TypeAlias(_) | ast::ExternItem::MacroCall(_) => None,
+                        };
+                        if let Some(def) = def {
+                            res.push((Definition::ModuleDef(def), extern_item.syntax().clone()));
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(def) = item_def(sema, item) {
+                        res.push((Definition::ModuleDef(def), item.syntax().clone()));
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// Returns the smallest visibility `def` needs to keep its users outside of the new module
+    /// compiling, if any.
+    fn needed_visibility(&self, ctx: &AssistContext, def: Definition) -> Option<Visibility> {
+        let mut res = None;
+        for (file_id, refs) in def.usages(&ctx.sema).all() {
+            for reference in refs {
+                if file_id == ctx.frange.file_id && self.range.contains_range(reference.range) {
+                    continue;
+                }
+                let module = ctx.sema.scope(reference.name.syntax()).module();
+                let vis = match module {
+                    Some(module) if is_descendant_of(ctx.db(), module, self.parent) => {
+                        Visibility::Super
+                    }
+                    _ => Visibility::Crate,
+                };
+                res = res.max(Some(vis));
+            }
+        }
+        res
+    }
+
+    /// Records the edits fixing `self` and `super` paths in the moved items, and returns the
+    /// imports the new module needs for the names it uses from the parent module's scope.
+    fn fix_inner_paths(
+        &self,
+        ctx: &AssistContext,
+        moved: &FxHashSet<ModuleDef>,
+        edits: &mut Vec<(TextRange, String)>,
+    ) -> Vec<ast::Path> {
+        let db = ctx.db();
+        let parent_scope = self.parent.scope(db, None);
+        let mut imports = BTreeMap::new();
+
+        let paths = self
+            .items
+            .iter()
+            .flat_map(|it| it.syntax().descendants().filter_map(ast::Path::cast))
+            .filter(|it| it.qualifier().is_none())
+            // Visibilities are handled separately.
+            .filter(|it| !it.syntax().ancestors().any(|it| ast::Visibility::can_cast(it.kind())));
+        for path in paths {
+            match path.segment().and_then(|it| it.kind()) {
+                Some(ast::PathSegmentKind::SelfKw) | Some(ast::PathSegmentKind::SuperKw) => {
+                    if self.points_outside(ctx, &path, moved) {
+                        edits.push(relative_path_edit(&path));
+                    }
+                }
+                Some(ast::PathSegmentKind::Name(name_ref)) => {
+                    if path.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind())) {
+                        continue;
+                    }
+                    let def = match ctx.sema.resolve_path(&path) {
+                        Some(PathResolution::Def(def)) if !moved.contains(&def) => def,
+                        _ => continue,
+                    };
+                    let name = name_ref.text().to_string();
+                    let in_parent_scope = parent_scope.iter().any(|(scope_name, scope_def)| {
+                        scope_name.to_string() == name && *scope_def == ScopeDef::ModuleDef(def)
+                    });
+                    if in_parent_scope {
+                        imports.entry(name).or_insert(def);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        imports.into_iter().map(|(name, def)| self.import_path(db, def, &name)).collect()
+    }
+
+    /// Whether the `self`/`super` path `path` leads out of the items moved into the new module.
+    fn points_outside(
+        &self,
+        ctx: &AssistContext,
+        path: &ast::Path,
+        moved: &FxHashSet<ModuleDef>,
+    ) -> bool {
+        let mut keywords = path.clone();
+        while let Some(parent_path) = keywords.parent_path() {
+            match parent_path.segment().and_then(|it| it.kind()) {
+                Some(ast::PathSegmentKind::SuperKw) => keywords = parent_path,
+                _ => break,
+            }
+        }
+        let module = match ctx.sema.resolve_path(&keywords) {
+            Some(PathResolution::Def(ModuleDef::Module(it))) => it,
+            _ => return false,
+        };
+        if module != self.parent {
+            return is_descendant_of(ctx.db(), self.parent, module);
+        }
+        match keywords.parent_path().and_then(|it| ctx.sema.resolve_path(&it)) {
+            Some(PathResolution::Def(def)) => !moved.contains(&def),
+            _ => true,
+        }
+    }
+
+    /// The path importing `def`, known as `name` in the parent module, into the new module.
+    fn import_path(&self, db: &RootDatabase, def: ModuleDef, name: &str) -> ast::Path {
+        // Prefer the canonical path of items from dependencies over re-importing them from the
+        // parent module.
+        let def_krate = def.module(db).map(|it| it.krate());
+        let dependency = self
+            .parent
+            .krate()
+            .dependencies(db)
+            .into_iter()
+            .find(|dep| Some(dep.krate) == def_krate);
+        if let Some(dependency) = dependency {
+            if let Some(path) = dependency.krate.root_module(db).find_use_path(db, def) {
+                if path.kind == hir::PathKind::Plain
+                    && path.segments().last().map_or(false, |it| it.to_string() == name)
+                {
+                    let segments =
+                        iter::once(dependency.name).chain(path.segments().iter().cloned());
+                    return mod_path_to_ast(&hir::ModPath::from_segments(
+                        hir::PathKind::Plain,
+                        segments,
+                    ));
+                }
+            }
+        }
+        make::path_from_text(&format!("super::{}", name))
+    }
+
+    /// Collects the references to the moved items from outside of the selection. Qualified
+    /// paths are grouped by file, to get the new module inserted. Names used unqualified are
+    /// returned to be imported into the parent module.
+    fn outer_references(
+        &self,
+        ctx: &AssistContext,
+        moved: &FxHashSet<ModuleDef>,
+    ) -> (FxHashMap<FileId, Vec<ast::Path>>, Vec<String>) {
+        let mut qualified: FxHashMap<FileId, Vec<ast::Path>> = FxHashMap::default();
+        let mut unqualified = Vec::new();
+        let mut modules_importing = FxHashSet::default();
+
+        for &def in moved {
+            for (file_id, refs) in Definition::ModuleDef(def).usages(&ctx.sema).all() {
+                for reference in refs {
+                    if file_id == ctx.frange.file_id && self.range.contains_range(reference.range) {
+                        continue;
+                    }
+                    let name_ref = match reference.name {
+                        ast::NameLike::NameRef(it) => it,
+                        _ => continue,
+                    };
+                    let path = match name_ref.syntax().parent().and_then(ast::PathSegment::cast) {
+                        Some(segment) => segment.parent_path(),
+                        None => continue,
+                    };
+                    let module = ctx.sema.scope(name_ref.syntax()).module();
+                    let in_use_tree = path
+                        .syntax()
+                        .parent()
+                        .map_or(false, |it| ast::UseTree::can_cast(it.kind()));
+                    if path.qualifier().is_some() || in_use_tree {
+                        modules_importing.extend(module);
+                        qualified.entry(file_id).or_default().push(path);
+                    } else {
+                        unqualified.push((module, name_ref.text().to_string()));
+                    }
+                }
+            }
+        }
+
+        // Unqualified uses in the parent module, or in its submodules through glob imports,
+        // are kept working by importing the moved items into the parent module.
+        let mut parent_imports: Vec<String> = unqualified
+            .into_iter()
+            .filter(|(module, _)| match module {
+                Some(module) if *module == self.parent => true,
+                Some(module) => !modules_importing.contains(module),
+                None => false,
+            })
+            .map(|(_, name)| name)
+            .collect();
+        parent_imports.sort();
+        parent_imports.dedup();
+        (qualified, parent_imports)
+    }
+
+    /// Returns the text of the moved items with `edits` applied, dedented to the top level.
+    fn body_text(&self, mut edits: Vec<(TextRange, String)>) -> String {
+        edits.sort_by_key(|(range, _)| range.start());
+        let text = self.container.text().to_string();
+        let base = self.container.text_range().start();
+        let slice = |range: TextRange| &text[range - base];
+
+        let mut body = String::new();
+        let mut offset = self.range.start();
+        for (range, replacement) in edits {
+            body.push_str(slice(TextRange::new(offset, range.start())));
+            body.push_str(&replacement);
+            offset = range.end();
+        }
+        body.push_str(slice(TextRange::new(offset, self.range.end())));
+        body
+    }
+}
+
+fn is_movable(item: &ast::Item) -> bool {
+    match item {
+        // `use`s may still be needed by the parent module, and `macro_rules!` are textually
+        // scoped.
+        ast::Item::Use(_)
+        | ast::Item::ExternCrate(_)
+        | ast::Item::MacroCall(_)
+        | ast::Item::MacroRules(_)
+        | ast::Item::MacroDef(_) => false,
+        ast::Item::Module(it) => it.item_list().is_some(),
+        _ => true,
+    }
+}
+
+fn item_def(sema: &Semantics<RootDatabase>, item: &ast::Item) -> Option<ModuleDef> {
+    let def = match item {
+        ast::Item::Const(it) => sema.to_def(it)?.into(),
+        ast::Item::Enum(it) => sema.to_def(it)?.into(),
+        ast::Item::Fn(it) => sema.to_def(it)?.into(),
+        ast::Item::Module(it) => sema.to_def(it)?.into(),
+        ast::Item::Static(it) => sema.to_def(it)?.into(),
+        ast::Item::Struct(it) => sema.to_def(it)?.into(),
+        ast::Item::Trait(it) => sema.to_def(it)?.into(),
+        ast::Item::TypeAlias(it) => sema.to_def(it)?.into(),
+        ast::Item::Union(it) => sema.to_def(it)?.into(),
+        _ => return None,
+    };
+    Some(def)
+}
+
+fn is_descendant_of(db: &RootDatabase, module: Module, ancestor: Module) -> bool {
+    std::iter::successors(Some(module), |it| it.parent(db)).any(|it| it == ancestor)
+}
+
+/// Returns the edit raising the visibility of the item, field or associated item `node` to
+/// `vis`, unless it's visible enough already.
+fn visibility_edit(node: &SyntaxNode, vis: Visibility) -> Option<(TextRange, String)> {
+    match node.children().find_map(ast::Visibility::cast) {
+        None => {
+            let anchor = node.children_with_tokens().find(|it| {
+                !matches!(
+                    it.kind(),
+                    SyntaxKind::ATTR | SyntaxKind::COMMENT | SyntaxKind::WHITESPACE
+                )
+            })?;
+            Some((TextRange::empty(anchor.text_range().start()), format!("{} ", vis.as_str())))
+        }
+        Some(existing) => {
+            let existing_text = existing.syntax().text().to_string().replace(' ', "");
+            let is_weaker = match existing_text.as_str() {
+                "pub(self)" => true,
+                "pub(super)" => vis == Visibility::Crate,
+                _ => false,
+            };
+            is_weaker.then(|| (existing.syntax().text_range(), vis.as_str().to_string()))
+        }
+    }
+}
+
+/// Makes the `self` or `super` path `path` go through the new module's parent.
+fn relative_path_edit(path: &ast::Path) -> (TextRange, String) {
+    let range = path.syntax().text_range();
+    match path.segment().and_then(|it| it.kind()) {
+        Some(ast::PathSegmentKind::SelfKw) => (range, "super".to_string()),
+        _ => (TextRange::empty(range.start()), "super::".to_string()),
+    }
+}
+
+/// Inserts the new module before the last segment of `path`.
+fn qualify_with_module(path: &ast::Path) {
+    if let Some(segment) = path.segment() {
+        ted::insert_all_raw(
+            ted::Position::before(segment.syntax()),
+            vec![
+                make::name_ref(MODULE_NAME).syntax().clone_for_update().into(),
+                make::token(T![::]).into(),
+            ],
+        );
+    }
+}
+
+/// Returns the identifiers spelling the new module's name.
+fn module_name_tokens(node: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> {
+    node.descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() == SyntaxKind::IDENT && it.text() == MODULE_NAME)
+}
+
+/// Makes a detached identifier whose text is a `${0:name}` snippet placeholder.
+fn snippet_placeholder(name: &str) -> SyntaxToken {
+    let mut builder = SyntaxTreeBuilder::default();
+    builder.start_node(SyntaxKind::NAME_REF);
+    builder.token(SyntaxKind::IDENT, &format!("${{0:{}}}", name));
+    builder.finish_node();
+    let name_ref = builder.finish().syntax_node().clone_for_update();
+    let token = name_ref.first_token().unwrap();
+    ted::remove(&token);
+    token
+}
+
+fn parse_module(text: &str) -> ast::Module {
+    let file = ast::SourceFile::parse(text).tree();
+    let module = file.syntax().descendants().find_map(ast::Module::cast).unwrap();
+    module.clone_subtree().clone_for_update()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn extracts_items_into_module() {
+        check_assist(
+            extract_module,
+            r#"
+struct Unused;
+
+$0fn foo() {}
+
+fn bar() {
+    foo();
+}$0
+
+fn main() {
+    bar();
+}
+"#,
+            r#"
+use self::${0:modname}::bar;
+
+struct Unused;
+
+mod ${0:modname} {
+    fn foo() {}
+
+    pub(super) fn bar() {
+        foo();
+    }
+}
+
+fn main() {
+    bar();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn raises_visibility_of_fields_and_methods() {
+        check_assist(
+            extract_module,
+            r#"
+$0struct S {
+    x: u32,
+    pub(crate) y: u32,
+    z: u32,
+}
+
+impl S {
+    fn new() -> S {
+        S { x: 0, y: 0, z: 0 }
+    }
+
+    fn internal(&self) {}
+}$0
+
+fn f() -> u32 {
+    let s = S::new();
+    s.x + s.y
+}
+"#,
+            r#"
+use self::${0:modname}::S;
+
+mod ${0:modname} {
+    pub(super) struct S {
+        pub(super) x: u32,
+        pub(crate) y: u32,
+        z: u32,
+    }
+
+    impl S {
+        pub(super) fn new() -> S {
+            S { x: 0, y: 0, z: 0 }
+        }
+
+        fn internal(&self) {}
+    }
+}
+
+fn f() -> u32 {
+    let s = S::new();
+    s.x + s.y
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_parent_items_into_module() {
+        check_assist(
+            extract_module,
+            r#"
+mod outer {
+    struct Kept;
+    fn helper() {}
+
+    $0fn foo(_: Kept) -> Kept {
+        helper();
+        self::helper();
+        super::other();
+        Kept
+    }$0
+}
+fn other() {}
+"#,
+            r#"
+mod outer {
+    struct Kept;
+    fn helper() {}
+
+    mod ${0:modname} {
+        use super::{Kept, helper};
+
+        fn foo(_: Kept) -> Kept {
+            helper();
+            super::helper();
+            super::super::other();
+            Kept
+        }
+    }
+}
+fn other() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_items_of_other_crates_by_their_path() {
+        check_assist(
+            extract_module,
+            r#"
+//- /main.rs crate:main deps:dep
+use dep::Thing;
+
+$0fn foo() -> Thing {
+    Thing
+}$0
+//- /dep.rs crate:dep
+pub struct Thing;
+"#,
+            r#"
+use dep::Thing;
+
+mod ${0:modname} {
+    use dep::Thing;
+
+    fn foo() -> Thing {
+        Thing
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rewrites_qualified_paths() {
+        check_assist(
+            extract_module,
+            r#"
+mod a {
+    $0pub(super) struct Foo;$0
+
+    pub(super) fn make() -> Foo {
+        Foo
+    }
+}
+mod b {
+    use super::a::Foo;
+
+    fn f(_: crate::a::Foo) {}
+}
+"#,
+            r#"
+mod a {
+    use self::${0:modname}::Foo;
+
+    mod ${0:modname} {
+        pub(crate) struct Foo;
+    }
+
+    pub(super) fn make() -> Foo {
+        Foo
+    }
+}
+mod b {
+    use super::a::${0:modname}::Foo;
+
+    fn f(_: crate::a::${0:modname}::Foo) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_glob_imports_in_submodules_working() {
+        check_assist(
+            extract_module,
+            r#"
+$0fn foo() {}$0
+
+mod tests {
+    use super::*;
+
+    fn test() {
+        foo();
+    }
+}
+"#,
+            r#"
+use self::${0:modname}::foo;
+
+mod ${0:modname} {
+    pub(super) fn foo() {}
+}
+
+mod tests {
+    use super::*;
+
+    fn test() {
+        foo();
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_multi_line_strings_unchanged() {
+        check_assist(
+            extract_module,
+            r##"
+mod a {
+    $0fn text() -> (&'static str, &'static str) {
+        let s = "first
+second";
+        let r = r#"
+    raw
+"#;
+        (s, r)
+    }$0
+}
+"##,
+            r##"
+mod a {
+    mod ${0:modname} {
+        fn text() -> (&'static str, &'static str) {
+            let s = "first
+second";
+            let r = r#"
+    raw
+"#;
+            (s, r)
+        }
+    }
+}
+"##,
+        );
+    }
+
+    #[test]
+    fn extracts_into_file() {
+        check_assist(
+            extract_module_to_file,
+            r#"
+//- /main.rs
+mod foo;
+//- /foo.rs
+struct Kept;
+
+$0fn bar(_: Kept) {}$0
+
+fn baz() {
+    bar(Kept);
+}
+"#,
+            r#"
+//- /foo.rs
+use self::modname::bar;
+
+struct Kept;
+
+mod modname;
+
+fn baz() {
+    bar(Kept);
+}
+//- /foo/modname.rs
+use super::Kept;
+
+pub(super) fn bar(_: Kept) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_whole_items() {
+        check_assist_not_applicable(extract_module, r#"fn f$0oo() {}"#);
+        check_assist_not_applicable(extract_module, r#"fn f$0oo() {}$0"#);
+        check_assist_not_applicable(
+            extract_module,
+            r#"
+fn foo() {
+    $0let x = 1;$0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_uses_and_macros() {
+        check_assist_not_applicable(
+            extract_module,
+            r#"
+$0use std::fmt;
+fn foo() {}$0
+"#,
+        );
+        check_assist_not_applicable(
+            extract_module,
+            r#"
+$0macro_rules! m { () => {} }
+fn foo() {}$0
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_to_file_not_applicable_in_inline_module() {
+        check_assist_not_applicable(
+            extract_module_to_file,
+            r#"
+mod m {
+    $0fn foo() {}$0
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use hir::MacroKind;
+use ide_db::{
+    defs::{Definition, NameRefClass},
+    helpers::insert_whitespace::insert_whitespaces,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::{
+    ast::{self, edit::IndentLevel, make, AstNode, NameOwner},
+    ted, SyntaxKind, SyntaxNode,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: inline_macro_call
+//
+// Replaces a `macro_rules!` macro call with its expansion.
+//
+// ```
+// macro_rules! double {
+//     ($e:expr) => { $e * 2 };
+// }
+//
+// fn main() {
+//     let x = $0double!(21);
+// }
+// ```
+// ->
+// ```
+// macro_rules! double {
+//     ($e:expr) => { $e * 2 };
+// }
+//
+// fn main() {
+//     let x = 21 * 2;
+// }
+// ```
+pub(crate) fn inline_macro_call(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let macro_call = ctx.find_node_at_offset::<ast::MacroCall>()?;
+    let path = macro_call.path()?;
+    if !path.syntax().text_range().contains_range(ctx.frange.range) {
+        // Don't offer the assist when the cursor is in the macro's arguments.
+        return None;
+    }
+    let def = ctx.sema.resolve_macro_call(&macro_call)?;
+    if def.kind() != MacroKind::Declarative {
+        return None;
+    }
+    let token_tree = macro_call.token_tree()?;
+    let expanded = ctx.sema.expand(&macro_call)?;
+    let call_module = ctx.sema.scope(macro_call.syntax()).module()?;
+
+    let input_range = token_tree.syntax().text_range();
+    let from_input = |node: &SyntaxNode| {
+        let range = ctx.sema.original_range(node);
+        range.file_id == ctx.frange.file_id && input_range.contains_range(range.range)
+    };
+
+    // Locals introduced by the macro definition are hygienic: they can't clash with names from the
+    // call site. Once inlined they can, so give them fresh names where needed.
+    let mut taken: FxHashSet<String> = FxHashSet::default();
+    ctx.sema.scope(&expanded).process_all_names(&mut |name, _| {
+        taken.insert(name.to_string());
+    });
+    taken.extend(
+        token_tree
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| it.kind() == SyntaxKind::IDENT)
+            .map(|it| it.text().to_string()),
+    );
+    // The fresh name of each renamed local. Locals with the same name share the fresh name.
+    let mut renames: Vec<(hir::Local, String)> = Vec::new();
+    let mut fresh_names: FxHashMap<String, String> = FxHashMap::default();
+    for pat in expanded.descendants().filter_map(ast::IdentPat::cast) {
+        let name = match pat.name() {
+            Some(name) => name,
+            None => continue,
+        };
+        let text = name.text().to_string();
+        if from_input(name.syntax()) || !taken.contains(&text) {
+            continue;
+        }
+        let local = match ctx.sema.to_def(&pat) {
+            Some(local) => local,
+            None => continue,
+        };
+        let new_name = match fresh_names.get(&text) {
+            Some(it) => it.clone(),
+            None => {
+                let new_name =
+                    (1..).map(|idx| format!("{}_{}", text, idx)).find(|it| !taken.contains(it))?;
+                taken.insert(new_name.clone());
+                fresh_names.insert(text, new_name.clone());
+                new_name
+            }
+        };
+        renames.push((local, new_name));
+    }
+    let renamed = |local: hir::Local| {
+        renames.iter().find(|(it, _)| *it == local).map(|(_, new)| new.as_str())
+    };
+
+    // `$crate` becomes the name the calling crate knows the macro's crate by.
+    let def_krate = def.module(ctx.db())?.krate();
+    let crate_path = if def_krate == call_module.krate() {
+        "crate".to_string()
+    } else {
+        let dep = call_module
+            .krate()
+            .dependencies(ctx.db())
+            .into_iter()
+            .find(|dep| dep.krate == def_krate)?;
+        dep.name.to_string()
+    };
+
+    let mut edits: Vec<(SyntaxNode, SyntaxNode)> = Vec::new();
+    for node in expanded.descendants() {
+        let new_node = if let Some(name) = ast::Name::cast(node.clone()) {
+            name.syntax()
+                .parent()
+                .and_then(ast::IdentPat::cast)
+                .and_then(|pat| ctx.sema.to_def(&pat))
+                .and_then(renamed)
+                .map(|it| make::name(it).syntax().clone())
+        } else if let Some(name_ref) = ast::NameRef::cast(node.clone()) {
+            if name_ref.text() == "$crate" {
+                Some(make::name_ref(&crate_path).syntax().clone())
+            } else {
+                match NameRefClass::classify(&ctx.sema, &name_ref) {
+                    Some(NameRefClass::Definition(Definition::Local(local))) => {
+                        renamed(local).map(|it| make::name_ref(it).syntax().clone())
+                    }
+                    _ => None,
+                }
+            }
+        } else {
+            None
+        };
+        match new_node {
+            Some(new_node) if !from_input(&node) => edits.push((node, new_node)),
+            _ => {}
+        }
+    }
+    // `sema` only knows the immutable tree, so the edits are mapped onto a mutable copy.
+    let expanded = expanded.clone_for_update();
+    let edits: Vec<_> = edits
+        .into_iter()
+        .filter_map(|(old, new)| {
+            let old = expanded
+                .descendants()
+                .find(|it| it.kind() == old.kind() && it.text_range() == old.text_range())?;
+            Some((old, new.clone_for_update()))
+        })
+        .collect();
+    for (old, new) in edits {
+        ted::replace(old, new);
+    }
+
+    let indent = IndentLevel::from_node(macro_call.syntax());
+    let mut replacement =
+        insert_whitespaces(&expanded, "    ").replace('\n', &format!("\n{}", indent));
+    let needs_parens = ast::Expr::cast(expanded).map_or(false, |expr| !is_atom(&expr))
+        && macro_call.syntax().parent().map_or(false, |it| {
+            matches!(
+                it.kind(),
+                SyntaxKind::BIN_EXPR
+                    | SyntaxKind::PREFIX_EXPR
+                    | SyntaxKind::REF_EXPR
+                    | SyntaxKind::CAST_EXPR
+                    | SyntaxKind::TRY_EXPR
+                    | SyntaxKind::AWAIT_EXPR
+                    | SyntaxKind::RANGE_EXPR
+                    | SyntaxKind::CALL_EXPR
+                    | SyntaxKind::METHOD_CALL_EXPR
+                    | SyntaxKind::FIELD_EXPR
+                    | SyntaxKind::INDEX_EXPR
+            )
+        });
+    if needs_parens {
+        replacement = format!("({})", replacement);
+    }
+
+    let target = macro_call.syntax().text_range();
+    acc.add(
+        AssistId("inline_macro_call", AssistKind::RefactorInline),
+        "Inline macro call",
+        target,
+        |builder| builder.replace(target, replacement),
+    )
+}
+
+fn is_atom(expr: &ast::Expr) -> bool {
+    matches!(
+        expr,
+        ast::Expr::Literal(_)
+            | ast::Expr::PathExpr(_)
+            | ast::Expr::ParenExpr(_)
+            | ast::Expr::TupleExpr(_)
+            | ast::Expr::ArrayExpr(_)
+            | ast::Expr::BlockExpr(_)
+            | ast::Expr::CallExpr(_)
+            | ast::Expr::MethodCallExpr(_)
+            | ast::Expr::FieldExpr(_)
+            | ast::Expr::IndexExpr(_)
+            | ast::Expr::MacroCall(_)
+            | ast::Expr::RecordExpr(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn inline_expr_macro() {
+        check_assist(
+            inline_macro_call,
+            r#"
+macro_rules! add { ($a:expr, $b:expr) => { $a + $b } }
+fn f() {
+    let x = 2 * $0add!(1, 2);
+}
+"#,
+            r#"
+macro_rules! add { ($a:expr, $b:expr) => { $a + $b } }
+fn f() {
+    let x = 2 * (1 + 2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_block_macro_is_reindented() {
+        check_assist(
+            inline_macro_call,
+            r#"
+macro_rules! check {
+    ($e:expr) => { if !$e { panic!("failed") } else { 0 } };
+}
+fn f() {
+    let x = {
+        $0check!(true)
+    };
+}
+"#,
+            r#"
+macro_rules! check {
+    ($e:expr) => { if !$e { panic!("failed") } else { 0 } };
+}
+fn f() {
+    let x = {
+        if !true {
+            panic!("failed")
+        } else {
+            0
+        }
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_item_macro() {
+        check_assist(
+            inline_macro_call,
+            r#"
+macro_rules! unit { ($name:ident) => { struct $name; impl $name { fn new() -> Self { $name } } } }
+$0unit!(Foo);
+"#,
+            r#"
+macro_rules! unit { ($name:ident) => { struct $name; impl $name { fn new() -> Self { $name } } } }
+struct Foo;
+impl Foo {
+    fn new() -> Self {
+        Foo
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn hygienic_locals_are_renamed() {
+        check_assist(
+            inline_macro_call,
+            r#"
+macro_rules! double { ($e:expr) => {{ let tmp = $e; tmp + tmp }} }
+fn f() {
+    let tmp = 1;
+    let x = $0double!(tmp);
+}
+"#,
+            r#"
+macro_rules! double { ($e:expr) => {{ let tmp = $e; tmp + tmp }} }
+fn f() {
+    let tmp = 1;
+    let x = {
+        let tmp_1 = tmp;
+        tmp_1 + tmp_1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn dollar_crate_is_replaced() {
+        check_assist(
+            inline_macro_call,
+            r#"
+//- /main.rs crate:main deps:dep
+fn f() {
+    let x = dep::$0make!();
+}
+//- /dep.rs crate:dep
+pub struct S;
+#[macro_export]
+macro_rules! make { () => { $crate::S } }
+"#,
+            r#"
+fn f() {
+    let x = dep::S;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn only_references_to_renamed_locals_are_renamed() {
+        check_assist(
+            inline_macro_call,
+            r#"
+struct V;
+impl V { fn len(&self) -> usize { 0 } }
+macro_rules! total { ($v:expr) => {{ let len = $v.len(); len + len }} }
+fn f(v: V) {
+    let len = 1;
+    let x = $0total!(v);
+}
+"#,
+            r#"
+struct V;
+impl V { fn len(&self) -> usize { 0 } }
+macro_rules! total { ($v:expr) => {{ let len = $v.len(); len + len }} }
+fn f(v: V) {
+    let len = 1;
+    let x = {
+        let len_1 = v.len();
+        len_1 + len_1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn dollar_crate_uses_the_dependency_name() {
+        check_assist(
+            inline_macro_call,
+            r#"
+//- /main.rs crate:main deps:my-dep
+fn f() {
+    let x = my_dep::$0make!();
+}
+//- /dep.rs crate:my-dep
+pub struct S;
+#[macro_export]
+macro_rules! make { () => { $crate::S } }
+"#,
+            r#"
+fn f() {
+    let x = my_dep::S;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_arguments() {
+        check_assist_not_applicable(
+            inline_macro_call,
+            r#"
+macro_rules! id { ($e:expr) => { $e } }
+fn f() {
+    let x = id!(1$0);
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,563 @@
+use hir::{AsAssocItem, HasSource, PathResolution, SemanticsScope};
+use ide_db::{base_db::FileId, defs::Definition, helpers::mod_path_to_ast, RootDatabase};
+use rustc_hash::FxHashMap;
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, AstNode, GenericParamsOwner, NameOwner},
+    SyntaxKind, TextRange, TextSize,
+};
+
+use crate::{
+    assist_context::{AssistContext, Assists},
+    AssistId, AssistKind,
+};
+
+// Assist: inline_type_alias
+//
+// Replace a type alias with its concrete type.
+//
+// ```
+// type A<T = u32> = Vec<T>;
+//
+// fn main() {
+//     let a: $0A;
+// }
+// ```
+// ->
+// ```
+// type A<T = u32> = Vec<T>;
+//
+// fn main() {
+//     let a: Vec<u32>;
+// }
+// ```
+pub(crate) fn inline_type_alias(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let path_type = ctx.find_node_at_offset::<ast::PathType>()?;
+    let alias = match ctx.sema.resolve_path(&path_type.path()?)? {
+        PathResolution::Def(hir::ModuleDef::TypeAlias(alias)) => alias,
+        _ => return None,
+    };
+    let inliner = AliasInliner::new(ctx, alias, &alias_source(ctx, alias)?, false)?;
+    let replacement = inliner.inline(&path_type)?;
+
+    let target = path_type.syntax().text_range();
+    acc.add(
+        AssistId("inline_type_alias", AssistKind::RefactorInline),
+        "Inline type alias",
+        target,
+        |builder| builder.replace(target, replacement),
+    )
+}
+
+// Assist: inline_type_alias_uses
+//
+// Inline a type alias into all of its uses and remove the alias.
+//
+// ```
+// type $0A = i32;
+// fn id(x: A) -> A {
+//     x
+// }
+// ```
+// ->
+// ```
+// fn id(x: i32) -> i32 {
+//     x
+// }
+// ```
+pub(crate) fn inline_type_alias_uses(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let name = ctx.find_node_at_offset::<ast::Name>()?;
+    let alias_def = ast::TypeAlias::cast(name.syntax().parent()?)?;
+    let alias = ctx.sema.to_def(&alias_def)?;
+    let inliner = AliasInliner::new(ctx, alias, &alias_def, true)?;
+
+    let usages = Definition::ModuleDef(alias.into()).usages(&ctx.sema).all();
+    let mut replacements: Vec<(FileId, Vec<(TextRange, String)>)> = Vec::new();
+    for (file_id, references) in usages.references {
+        let mut path_types = Vec::new();
+        for reference in references {
+            let name_ref = reference.name.as_name_ref()?.clone();
+            let path_type = name_ref.syntax().ancestors().find_map(ast::PathType::cast);
+            match path_type {
+                Some(path_type)
+                    if path_type.path().and_then(|path| path.segment()?.name_ref())
+                        == Some(name_ref) =>
+                {
+                    path_types.push(path_type)
+                }
+                _ => {
+                    // E.g. the alias is imported or used in an expression.
+                    cov_mark::hit!(inline_type_alias_uses_not_a_type);
+                    return None;
+                }
+            }
+        }
+        // Uses nested within the generic arguments of other uses are inlined along with them.
+        let outermost: Vec<_> = path_types
+            .iter()
+            .filter(|path_type| {
+                !path_types.iter().any(|other| {
+                    other != *path_type
+                        && other
+                            .syntax()
+                            .text_range()
+                            .contains_range(path_type.syntax().text_range())
+                })
+            })
+            .collect();
+        let mut file_replacements = Vec::new();
+        for path_type in outermost {
+            file_replacements.push((path_type.syntax().text_range(), inliner.inline(path_type)?));
+        }
+        replacements.push((file_id, file_replacements));
+    }
+
+    let alias_range = alias_def.syntax().text_range();
+    let delete_range = match alias_def.syntax().next_sibling_or_token() {
+        Some(syntax::NodeOrToken::Token(ws)) if ws.kind() == SyntaxKind::WHITESPACE => {
+            TextRange::new(alias_range.start(), ws.text_range().end())
+        }
+        _ => alias_range,
+    };
+    acc.add(
+        AssistId("inline_type_alias_uses", AssistKind::RefactorInline),
+        "Inline type alias into all uses",
+        name.syntax().text_range(),
+        |builder| {
+            for (file_id, file_replacements) in replacements {
+                builder.edit_file(file_id);
+                for (range, replacement) in file_replacements {
+                    builder.replace(range, replacement);
+                }
+            }
+            builder.edit_file(ctx.frange.file_id);
+            builder.delete(delete_range);
+        },
+    )
+}
+
+/// Returns the definition of `alias`, within a tree known to `ctx.sema`.
+fn alias_source(ctx: &AssistContext, alias: hir::TypeAlias) -> Option<ast::TypeAlias> {
+    let src = alias.source(ctx.db())?;
+    let file_id = src.file_id.original_file(ctx.db());
+    if src.file_id != file_id.into() {
+        // Aliases defined by macros have no source we could copy.
+        return None;
+    }
+    let file = ctx.sema.parse(file_id);
+    find_node_at_range(file.syntax(), src.value.syntax().text_range())
+}
+
+struct GenericParam {
+    name: String,
+    default: Option<String>,
+}
+
+/// Renders uses of a type alias with the alias's type, with generic arguments substituted and
+/// paths qualified for the use site.
+struct AliasInliner<'a> {
+    sema: &'a hir::Semantics<'a, RootDatabase>,
+    alias: hir::TypeAlias,
+    ty: ast::Type,
+    /// The scope the alias is defined in, which paths in `ty` are resolved in.
+    source_scope: SemanticsScope<'a>,
+    lifetime_params: Vec<String>,
+    /// Type and const parameters, which share a namespace and are given positionally.
+    params: Vec<GenericParam>,
+    /// Whether to inline uses of the alias within the generic arguments of a use.
+    inline_nested: bool,
+}
+
+impl<'a> AliasInliner<'a> {
+    fn new(
+        ctx: &'a AssistContext,
+        alias: hir::TypeAlias,
+        alias_def: &ast::TypeAlias,
+        inline_nested: bool,
+    ) -> Option<AliasInliner<'a>> {
+        if alias.as_assoc_item(ctx.db()).is_some() {
+            cov_mark::hit!(inline_type_alias_assoc_type);
+            return None;
+        }
+        let ty = alias_def.ty()?;
+        let mut lifetime_params = Vec::new();
+        let mut params = Vec::new();
+        for param in alias_def.generic_param_list().into_iter().flat_map(|it| it.generic_params()) {
+            match param {
+                ast::GenericParam::LifetimeParam(param) => {
+                    lifetime_params.push(param.lifetime()?.to_string())
+                }
+                ast::GenericParam::TypeParam(param) => params.push(GenericParam {
+                    name: param.name()?.to_string(),
+                    default: param.default_type().map(|it| it.to_string()),
+                }),
+                ast::GenericParam::ConstParam(param) => params.push(GenericParam {
+                    name: param.name()?.to_string(),
+                    default: param.default_val().map(|it| it.to_string()),
+                }),
+            }
+        }
+        let source_scope = ctx.sema.scope(ty.syntax());
+        Some(AliasInliner {
+            sema: &ctx.sema,
+            alias,
+            ty,
+            source_scope,
+            lifetime_params,
+            params,
+            inline_nested,
+        })
+    }
+
+    /// Returns the text to replace the use of the alias at `path_type` with.
+    fn inline(&self, path_type: &ast::PathType) -> Option<String> {
+        let db = self.sema.db;
+        let target_module = self.sema.scope(path_type.syntax()).module()?;
+
+        let mut lifetime_args = Vec::new();
+        let mut args = Vec::new();
+        let generic_args = path_type.path()?.segment()?.generic_arg_list();
+        for arg in generic_args.into_iter().flat_map(|it| it.generic_args()) {
+            match arg {
+                ast::GenericArg::LifetimeArg(arg) => lifetime_args.push(arg.to_string()),
+                ast::GenericArg::TypeArg(arg) => args.push(self.render_arg(&arg.ty()?)?),
+                ast::GenericArg::ConstArg(arg) => args.push(arg.to_string()),
+                ast::GenericArg::AssocTypeArg(_) => {}
+            }
+        }
+        let lifetimes: FxHashMap<&str, String> = self
+            .lifetime_params
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                (name.as_str(), lifetime_args.get(idx).cloned().unwrap_or_else(|| "'_".to_string()))
+            })
+            .collect();
+        let mut substs: FxHashMap<&str, String> = FxHashMap::default();
+        for (idx, param) in self.params.iter().enumerate() {
+            let arg = match args.get(idx) {
+                Some(arg) => arg.clone(),
+                None => {
+                    // Defaults can refer to earlier parameters, but it's rare enough that we don't
+                    // bother substituting them.
+                    param.default.clone()?
+                }
+            };
+            substs.insert(param.name.as_str(), arg);
+        }
+
+        let mut edits: Vec<(TextRange, String)> = Vec::new();
+        for path in self.ty.syntax().descendants().filter_map(ast::Path::cast) {
+            if path.qualifier().is_some() {
+                continue;
+            }
+            let segment = path.segment()?;
+            if segment.param_list().is_some() {
+                // Don't try to qualify `Fn(Foo) -> Bar` paths, they are in prelude anyway.
+                continue;
+            }
+            match self.source_scope.speculative_resolve(&path) {
+                Some(PathResolution::TypeParam(param)) => {
+                    let subst = substs.get(param.name(db).to_string().as_str())?;
+                    let is_qualifier = path
+                        .syntax()
+                        .parent()
+                        .map_or(false, |parent| parent.kind() == SyntaxKind::PATH);
+                    let subst = if is_qualifier && ast::Path::parse(subst).is_err() {
+                        format!("<{}>", subst)
+                    } else {
+                        subst.clone()
+                    };
+                    edits.push((path_or_parent_range(&path), subst));
+                }
+                Some(PathResolution::ConstParam(param)) => {
+                    let subst = substs.get(param.name(db).to_string().as_str())?;
+                    edits.push((path_or_parent_range(&path), subst.clone()));
+                }
+                Some(PathResolution::Def(def)) => {
+                    let name_ref = segment.name_ref()?;
+                    let found_path = target_module.find_use_path(db, def)?;
+                    let new_path = mod_path_to_ast(&found_path).to_string();
+                    if new_path != name_ref.text() {
+                        edits.push((name_ref.syntax().text_range(), new_path));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for token in self.ty.syntax().descendants_with_tokens().filter_map(|it| it.into_token()) {
+            if token.kind() == SyntaxKind::LIFETIME_IDENT {
+                if let Some(arg) = lifetimes.get(token.text()) {
+                    edits.push((token.text_range(), arg.clone()));
+                }
+            }
+        }
+
+        let mut res = splice(&self.ty.to_string(), self.ty.syntax().text_range().start(), edits);
+        let needs_parens = match &self.ty {
+            ast::Type::DynTraitType(it) => has_several_bounds(it.type_bound_list()),
+            ast::Type::ImplTraitType(it) => has_several_bounds(it.type_bound_list()),
+            _ => false,
+        } && path_type.syntax().parent().map_or(false, |parent| {
+            matches!(parent.kind(), SyntaxKind::REF_TYPE | SyntaxKind::PTR_TYPE)
+        });
+        if needs_parens {
+            res = format!("({})", res);
+        }
+        Some(res)
+    }
+
+    /// Renders a generic argument of a use of the alias, inlining any nested uses if requested.
+    fn render_arg(&self, ty: &ast::Type) -> Option<String> {
+        if !self.inline_nested {
+            return Some(ty.to_string());
+        }
+        let mut edits = Vec::new();
+        let mut nested = ty.syntax().descendants().filter_map(ast::PathType::cast);
+        while let Some(path_type) = nested.next() {
+            let is_alias = path_type.path().and_then(|path| self.sema.resolve_path(&path))
+                == Some(PathResolution::Def(self.alias.into()));
+            if is_alias {
+                let range = path_type.syntax().text_range();
+                edits.push((range, self.inline(&path_type)?));
+                // Uses within this one were handled by the recursive call.
+                while nested
+                    .next()
+                    .map_or(false, |it| range.contains_range(it.syntax().text_range()))
+                {
+                }
+            }
+        }
+        Some(splice(&ty.to_string(), ty.syntax().text_range().start(), edits))
+    }
+}
+
+/// Returns the range of the `PathType` or `PathExpr` that `path` forms, or of `path` itself.
+fn path_or_parent_range(path: &ast::Path) -> TextRange {
+    match path.syntax().parent() {
+        Some(parent) if matches!(parent.kind(), SyntaxKind::PATH_TYPE | SyntaxKind::PATH_EXPR) => {
+            parent.text_range()
+        }
+        _ => path.syntax().text_range(),
+    }
+}
+
+fn has_several_bounds(bounds: Option<ast::TypeBoundList>) -> bool {
+    bounds.map_or(false, |it| it.bounds().count() > 1)
+}
+
+/// Applies non-overlapping `edits` to `text`, which starts at `offset` in the file.
+fn splice(text: &str, offset: TextSize, mut edits: Vec<(TextRange, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start());
+    let mut res = String::new();
+    let mut pos = 0;
+    for (range, replacement) in edits {
+        let start = usize::from(range.start() - offset);
+        if start < pos {
+            continue;
+        }
+        res.push_str(&text[pos..start]);
+        res.push_str(&replacement);
+        pos = usize::from(range.end() - offset);
+    }
+    res.push_str(&text[pos..]);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn inline_simple_alias() {
+        check_assist(
+            inline_type_alias,
+            r#"
+type A = u32;
+fn f(a: $0A) {}
+"#,
+            r#"
+type A = u32;
+fn f(a: u32) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_generic_alias() {
+        check_assist(
+            inline_type_alias,
+            r#"
+struct Pair<A, B>(A, B);
+type P<'a, T, const N: usize> = Pair<&'a [T; N], T>;
+fn f(p: $0P<'static, u8, 3>) {}
+"#,
+            r#"
+struct Pair<A, B>(A, B);
+type P<'a, T, const N: usize> = Pair<&'a [T; N], T>;
+fn f(p: Pair<&'static [u8; 3], u8>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_alias_with_defaults_and_elided_lifetimes() {
+        check_assist(
+            inline_type_alias,
+            r#"
+struct S<'a, T>(&'a T);
+type A<'a, T = i32> = S<'a, T>;
+fn f(a: $0A) {}
+"#,
+            r#"
+struct S<'a, T>(&'a T);
+type A<'a, T = i32> = S<'a, T>;
+fn f(a: S<'_, i32>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_alias_qualifies_paths() {
+        check_assist(
+            inline_type_alias,
+            r#"
+mod m {
+    pub struct Inner;
+    pub struct Outer<T>(T);
+    pub type A<T> = Outer<(Inner, T)>;
+}
+fn f(a: m::$0A<u8>) {}
+"#,
+            r#"
+mod m {
+    pub struct Inner;
+    pub struct Outer<T>(T);
+    pub type A<T> = Outer<(Inner, T)>;
+}
+fn f(a: m::Outer<(m::Inner, u8)>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_alias_qualifier_position() {
+        check_assist(
+            inline_type_alias,
+            r#"
+trait Tr { type Out; }
+type A<T> = Option<T::Out>;
+fn f(a: $0A<&dyn Tr<Out = u8>>) {}
+"#,
+            r#"
+trait Tr { type Out; }
+type A<T> = Option<T::Out>;
+fn f(a: Option<<&dyn Tr<Out = u8>>::Out>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_dyn_alias_behind_reference() {
+        check_assist(
+            inline_type_alias,
+            r#"
+trait Tr {}
+type A = dyn Tr + Send;
+fn f(a: &$0A) {}
+"#,
+            r#"
+trait Tr {}
+type A = dyn Tr + Send;
+fn f(a: &(dyn Tr + Send)) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_assoc_types() {
+        cov_mark::check!(inline_type_alias_assoc_type);
+        check_assist_not_applicable(
+            inline_type_alias_uses,
+            r#"
+trait Tr { type Out; }
+struct S;
+impl Tr for S { type $0Out = u8; }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_required_args() {
+        check_assist_not_applicable(
+            inline_type_alias,
+            r#"
+type A<T> = Vec<T>;
+fn f(a: $0A) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_all_uses() {
+        check_assist(
+            inline_type_alias_uses,
+            r#"
+struct Wrap<T>(T);
+type $0A<T> = Wrap<T>;
+fn f(a: A<u8>, b: A<A<u16>>) -> Option<A<()>> {
+    let c: A<i8>;
+    None
+}
+"#,
+            r#"
+struct Wrap<T>(T);
+fn f(a: Wrap<u8>, b: Wrap<Wrap<u16>>) -> Option<Wrap<()>> {
+    let c: Wrap<i8>;
+    None
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_all_uses_across_files() {
+        check_assist(
+            inline_type_alias_uses,
+            r#"
+//- /main.rs
+mod foo;
+pub struct S;
+pub type $0A = S;
+fn f(a: A) {}
+//- /foo.rs
+fn g(a: crate::A) {}
+"#,
+            r#"
+//- /main.rs
+mod foo;
+pub struct S;
+fn f(a: S) {}
+//- /foo.rs
+fn g(a: crate::S) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_all_uses_not_applicable_with_imports() {
+        cov_mark::check!(inline_type_alias_uses_not_a_type);
+        check_assist_not_applicable(
+            inline_type_alias_uses,
+            r#"
+mod m {
+    pub type $0A = u32;
+}
+use m::A;
+fn f(a: A) {}
+"#,
+        );
+    }
+}
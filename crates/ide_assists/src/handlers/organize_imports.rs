@@ -0,0 +1,240 @@
+use ide_db::helpers::{
+    insert_use::ImportScope,
+    organize_imports::organize_imports as organize_scope,
+    unused_imports::{find_unused_imports, remove_use_tree},
+};
+use syntax::{ast, AstNode};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: organize_imports
+//
+// Removes unused imports of the file, merges the remaining ones according to the configured
+// import granularity and sorts them into std, external crate and local groups.
+//
+// ```
+// mod foo { pub struct Foo; pub struct Bar; pub struct Baz; }
+// use foo::Foo;$0
+// use foo::Baz;
+// use foo::Bar;
+//
+// fn f(_: Foo, _: Bar) {}
+// ```
+// ->
+// ```
+// mod foo { pub struct Foo; pub struct Bar; pub struct Baz; }
+// use foo::{Bar, Foo};
+//
+// fn f(_: Foo, _: Bar) {}
+// ```
+pub(crate) fn organize_imports(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let source_file = ctx.sema.parse(ctx.frange.file_id);
+    let scopes: Vec<_> = source_file
+        .syntax()
+        .descendants()
+        .filter(|it| ast::SourceFile::can_cast(it.kind()) || ast::Module::can_cast(it.kind()))
+        .filter_map(ImportScope::from)
+        .filter(|scope| scope.as_syntax_node().children().any(|it| ast::Use::can_cast(it.kind())))
+        .map(|scope| scope.as_syntax_node().clone())
+        .collect();
+    if scopes.is_empty() {
+        return None;
+    }
+
+    acc.add(
+        AssistId("organize_imports", AssistKind::SourceOrganizeImports),
+        "Organize imports",
+        source_file.syntax().text_range(),
+        |builder| {
+            let unused = find_unused_imports(&ctx.sema, ctx.frange.file_id);
+            // All nodes have to be mapped into the mutable tree before it is changed.
+            let unused: Vec<_> = unused.into_iter().map(|it| builder.make_mut(it)).collect();
+            let scopes: Vec<_> = scopes
+                .into_iter()
+                .filter_map(|it| ImportScope::from(builder.make_syntax_mut(it)))
+                .collect();
+            for tree in unused {
+                remove_use_tree(&tree);
+            }
+            for scope in scopes {
+                organize_scope(&scope, &ctx.config.insert_use);
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::helpers::insert_use::ImportGranularity;
+
+    use crate::tests::{
+        check_assist, check_assist_not_applicable, check_assist_with_config, TEST_CONFIG,
+    };
+
+    use super::*;
+
+    #[test]
+    fn not_applicable_without_imports() {
+        check_assist_not_applicable(
+            organize_imports,
+            r#"
+mod foo {
+    fn f() {$0}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_unused_imports() {
+        check_assist(
+            organize_imports,
+            r#"
+mod foo { pub struct Foo; pub struct Bar; pub trait Tr { fn m(&self) {} } }
+use foo::{Bar, Foo};$0
+use foo::Tr;
+
+fn f(_: Foo) {}
+"#,
+            r#"
+mod foo { pub struct Foo; pub struct Bar; pub trait Tr { fn m(&self) {} } }
+use foo::Foo;
+
+fn f(_: Foo) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_trait_imports_used_by_methods() {
+        check_assist(
+            organize_imports,
+            r#"
+mod foo { pub struct Foo; pub trait Tr { fn m(&self) {} } impl Tr for Foo {} }
+use foo::Tr;$0
+use foo::Foo;
+
+fn f(foo: Foo) {
+    foo.m();
+}
+"#,
+            r#"
+mod foo { pub struct Foo; pub trait Tr { fn m(&self) {} } impl Tr for Foo {} }
+use foo::{Foo, Tr};
+
+fn f(foo: Foo) {
+    foo.m();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn groups_and_sorts_imports() {
+        check_assist(
+            organize_imports,
+            r#"
+//- /main.rs crate:main deps:dep,std
+mod foo { pub struct Foo; }
+use crate::foo::Foo;$0
+use dep::Dep;
+use std::fmt;
+use std::collections::HashMap;
+
+fn f(_: Foo, _: Dep, _: fmt::Error, _: HashMap) {}
+//- /dep.rs crate:dep
+pub struct Dep;
+//- /std.rs crate:std
+pub mod fmt { pub struct Error; }
+pub mod collections { pub struct HashMap; }
+"#,
+            r#"
+mod foo { pub struct Foo; }
+use std::{collections::HashMap, fmt};
+
+use dep::Dep;
+
+use crate::foo::Foo;
+
+fn f(_: Foo, _: Dep, _: fmt::Error, _: HashMap) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn module_granularity() {
+        let mut config = TEST_CONFIG;
+        config.insert_use.granularity = ImportGranularity::Module;
+        check_assist_with_config(
+            organize_imports,
+            config,
+            r#"
+mod foo { pub mod bar { pub struct Bar; } pub struct Foo; pub struct Baz; }
+use foo::bar::Bar;$0
+use foo::Foo;
+use foo::Baz;
+
+fn f(_: Foo, _: Bar, _: Baz) {}
+"#,
+            r#"
+mod foo { pub mod bar { pub struct Bar; } pub struct Foo; pub struct Baz; }
+use foo::bar::Bar;
+use foo::{Baz, Foo};
+
+fn f(_: Foo, _: Bar, _: Baz) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn organizes_inline_modules() {
+        check_assist(
+            organize_imports,
+            r#"
+mod foo { pub struct Foo; pub struct Bar; }
+mod inner {
+    use crate::foo::Foo;
+    use crate::foo::Bar;
+
+    fn f(_: Foo, _: Bar) {}
+}
+
+fn main() {$0}
+"#,
+            r#"
+mod foo { pub struct Foo; pub struct Bar; }
+mod inner {
+    use crate::foo::{Bar, Foo};
+
+    fn f(_: Foo, _: Bar) {}
+}
+
+fn main() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_imports_with_attributes() {
+        check_assist(
+            organize_imports,
+            r#"
+mod foo { pub struct Foo; pub struct Bar; pub struct Baz; }
+use foo::Foo;$0
+#[cfg(test)]
+use foo::Bar;
+use foo::Baz;
+
+fn f(_: Foo, _: Baz) {}
+"#,
+            r#"
+mod foo { pub struct Foo; pub struct Bar; pub struct Baz; }
+use foo::{Baz, Foo};
+#[cfg(test)]
+use foo::Bar;
+
+fn f(_: Foo, _: Baz) {}
+"#,
+        );
+    }
+}
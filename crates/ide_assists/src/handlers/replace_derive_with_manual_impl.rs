@@ -10,15 +10,16 @@ use syntax::{
 use crate::{
     assist_context::{AssistBuilder, AssistContext, Assists},
     utils::{
-        add_trait_assoc_items_to_impl, filter_assoc_items, generate_trait_impl_text,
-        render_snippet, Cursor, DefaultMethods,
+        add_trait_assoc_items_to_impl, filter_assoc_items, gen_trait_fn_body::gen_trait_fn_body,
+        generate_trait_impl_text, render_snippet, Cursor, DefaultMethods,
     },
     AssistId, AssistKind,
 };
 
 // Assist: replace_derive_with_manual_impl
 //
-// Converts a `derive` impl into a manual one.
+// Converts a `derive` impl into a manual one. Methods of derivable std traits get the body
+// the derive would have generated.
 //
 // ```
 // # trait Debug { fn fmt(&self, f: &mut Formatter) -> Result<()>; }
@@ -111,7 +112,7 @@ fn add_assist(
         |builder| {
             let insert_pos = adt.syntax().text_range().end();
             let impl_def_with_items =
-                impl_def_from_trait(&ctx.sema, &annotated_name, trait_, trait_path);
+                impl_def_from_trait(&ctx.sema, adt, &annotated_name, trait_, trait_path);
             update_attribute(builder, &input, &trait_name, &attr);
             let trait_path = format!("{}", trait_path);
             match (ctx.config.snippet_cap, impl_def_with_items) {
@@ -149,6 +150,7 @@ fn add_assist(
 
 fn impl_def_from_trait(
     sema: &hir::Semantics<ide_db::RootDatabase>,
+    adt: &ast::Adt,
     annotated_name: &ast::Name,
     trait_: Option<hir::Trait>,
     trait_path: &ast::Path,
@@ -163,6 +165,20 @@ fn impl_def_from_trait(
         make::impl_trait(trait_path.clone(), make::ext::ident_path(&annotated_name.text()));
     let (impl_def, first_assoc_item) =
         add_trait_assoc_items_to_impl(sema, trait_items, trait_, impl_def, target_scope);
+
+    // Derivable std traits get the bodies the derive would have expanded to.
+    let adt_def = match adt {
+        ast::Adt::Struct(it) => sema.to_def(it).map(hir::Adt::Struct),
+        ast::Adt::Enum(it) => sema.to_def(it).map(hir::Adt::Enum),
+        ast::Adt::Union(it) => sema.to_def(it).map(hir::Adt::Union),
+    };
+    if let (Some(adt_def), Some(items)) = (adt_def, impl_def.assoc_item_list()) {
+        for item in items.assoc_items() {
+            if let ast::AssocItem::Fn(func) = item {
+                gen_trait_fn_body(sema, &func, trait_, adt_def);
+            }
+        }
+    }
     Some((impl_def, first_assoc_item))
 }
 
@@ -202,6 +218,8 @@ fn update_attribute(
 
 #[cfg(test)]
 mod tests {
+    use ide_db::helpers::FamousDefs;
+
     use crate::tests::{check_assist, check_assist_not_applicable};
 
     use super::*;
@@ -247,6 +265,437 @@ impl fmt::Debug for Foo {
 "#,
         )
     }
+    #[test]
+    fn add_custom_impl_debug_record_struct() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Debu$0g)]
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+
+impl core::fmt::Debug for Foo {
+    $0fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Foo").field("bar", &self.bar).field("baz", &self.baz).finish()
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_debug_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Debu$0g)]
+enum Foo {
+    Bar { x: i32 },
+    Baz(i32, String),
+    Qux,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar { x: i32 },
+    Baz(i32, String),
+    Qux,
+}
+
+impl core::fmt::Debug for Foo {
+    $0fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bar { x } => f.debug_struct("Bar").field("x", x).finish(),
+            Self::Baz(arg0, arg1) => f.debug_tuple("Baz").field(arg0).field(arg1).finish(),
+            Self::Qux => f.write_str("Qux"),
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_clone_tuple_struct() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Clo$0ne)]
+struct Foo(usize, String);
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+struct Foo(usize, String);
+
+impl core::clone::Clone for Foo {
+    $0fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1.clone())
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_clone_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Clo$0ne)]
+enum Foo {
+    Bar { x: i32 },
+    Baz(String),
+    Qux,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar { x: i32 },
+    Baz(String),
+    Qux,
+}
+
+impl core::clone::Clone for Foo {
+    $0fn clone(&self) -> Self {
+        match self {
+            Self::Bar { x } => Self::Bar { x: x.clone() },
+            Self::Baz(arg0) => Self::Baz(arg0.clone()),
+            Self::Qux => Self::Qux,
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_default_record_struct() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Defau$0lt)]
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+
+impl Default for Foo {
+    $0fn default() -> Self {
+        Self { bar: Default::default(), baz: Default::default() }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_default_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Defau$0lt)]
+enum Foo {
+    Bar(usize),
+    #[default]
+    Baz,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar(usize),
+    #[default]
+    Baz,
+}
+
+impl Default for Foo {
+    $0fn default() -> Self {
+        Self::Baz
+    }
+}
+"#,
+        );
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Defau$0lt)]
+enum Foo {
+    Bar(usize),
+    Baz,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar(usize),
+    Baz,
+}
+
+impl Default for Foo {
+    fn default() -> Self {
+        ${0:todo!()}
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_partial_eq_record_struct() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(PartialE$0q)]
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+
+impl core::cmp::PartialEq for Foo {
+    $0fn eq(&self, other: &Self) -> bool {
+        self.bar == other.bar && self.baz == other.baz
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_partial_eq_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(PartialE$0q)]
+enum Foo {
+    Bar { x: i32, y: i32 },
+    Baz(String),
+    Qux,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar { x: i32, y: i32 },
+    Baz(String),
+    Qux,
+}
+
+impl core::cmp::PartialEq for Foo {
+    $0fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bar { x: l_x, y: l_y }, Self::Bar { x: r_x, y: r_y }) => l_x == r_x && l_y == r_y,
+            (Self::Baz(l0), Self::Baz(r0)) => l0 == r0,
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_partial_eq_fieldless_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(PartialE$0q)]
+enum Foo {
+    Bar,
+    Baz,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar,
+    Baz,
+}
+
+impl core::cmp::PartialEq for Foo {
+    $0fn eq(&self, other: &Self) -> bool {
+        core::mem::discriminant(self) == core::mem::discriminant(other)
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_hash_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Has$0h)]
+enum Foo {
+    Bar { x: i32, y: i32 },
+    Baz(String),
+    Qux,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar { x: i32, y: i32 },
+    Baz(String),
+    Qux,
+}
+
+impl core::hash::Hash for Foo {
+    $0fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::hash::Hash::hash(&core::mem::discriminant(self), state);
+        match self {
+            Self::Bar { x, y } => {
+                core::hash::Hash::hash(x, state);
+                core::hash::Hash::hash(y, state);
+            }
+            Self::Baz(arg0) => core::hash::Hash::hash(arg0, state),
+            _ => {}
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_partial_ord_record_struct() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(PartialOr$0d)]
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+struct Foo {
+    bar: String,
+    baz: usize,
+}
+
+impl core::cmp::PartialOrd for Foo {
+    $0fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        match self.bar.partial_cmp(&other.bar) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        self.baz.partial_cmp(&other.baz)
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_ord_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            &format!(
+                r#"//- /main.rs crate:main deps:core{}{}"#,
+                r#"
+#[derive(Or$0d)]
+enum Foo {
+    Bar { x: i32, y: i32 },
+    Baz(String),
+    Qux,
+}
+"#,
+                FamousDefs::FIXTURE
+            ),
+            r#"
+enum Foo {
+    Bar { x: i32, y: i32 },
+    Baz(String),
+    Qux,
+}
+
+impl Ord for Foo {
+    $0fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        let index = |it: &Self| match it {
+            Self::Bar { .. } => 0,
+            Self::Baz(..) => 1,
+            Self::Qux => 2,
+        };
+        match (self, other) {
+            (Self::Bar { x: l_x, y: l_y }, Self::Bar { x: r_x, y: r_y }) => {
+                match l_x.cmp(r_x) {
+                    core::cmp::Ordering::Equal => {}
+                    ord => return ord,
+                }
+                l_y.cmp(r_y)
+            }
+            (Self::Baz(l0), Self::Baz(r0)) => l0.cmp(r0),
+            _ => index(self).cmp(&index(other)),
+        }
+    }
+}
+"#,
+        )
+    }
+
     #[test]
     fn add_custom_impl_all() {
         check_assist(
@@ -38,6 +38,8 @@ pub enum AssistKind {
     RefactorExtract,
     RefactorInline,
     RefactorRewrite,
+    Source,
+    SourceOrganizeImports,
 }
 
 impl AssistKind {
@@ -54,6 +56,7 @@ impl AssistKind {
                 | AssistKind::RefactorRewrite => return true,
                 _ => return false,
             },
+            AssistKind::Source => other == AssistKind::SourceOrganizeImports,
             _ => return false,
         }
     }
@@ -67,6 +70,8 @@ impl AssistKind {
             AssistKind::RefactorExtract => "RefactorExtract",
             AssistKind::RefactorInline => "RefactorInline",
             AssistKind::RefactorRewrite => "RefactorRewrite",
+            AssistKind::Source => "Source",
+            AssistKind::SourceOrganizeImports => "SourceOrganizeImports",
         }
     }
 }
@@ -83,6 +88,8 @@ impl FromStr for AssistKind {
             "RefactorExtract" => Ok(AssistKind::RefactorExtract),
             "RefactorInline" => Ok(AssistKind::RefactorInline),
             "RefactorRewrite" => Ok(AssistKind::RefactorRewrite),
+            "Source" => Ok(AssistKind::Source),
+            "SourceOrganizeImports" => Ok(AssistKind::SourceOrganizeImports),
             unknown => Err(format!("Unknown AssistKind: '{}'", unknown)),
         }
     }
@@ -184,11 +191,14 @@ mod handlers {
     mod convert_integer_literal;
     mod convert_comment_block;
     mod convert_iter_for_each_to_for;
+    mod convert_match_to_question_mark;
     mod convert_into_to_from;
+    mod convert_to_let_else;
     mod convert_tuple_struct_to_named_struct;
     mod early_return;
     mod expand_glob_import;
     mod extract_function;
+    mod extract_module;
     mod extract_struct_from_enum_variant;
     mod extract_type_alias;
     mod extract_variable;
@@ -213,6 +223,8 @@ mod handlers {
     mod infer_function_return_type;
     mod inline_function;
     mod inline_local_variable;
+    mod inline_macro_call;
+    mod inline_type_alias;
     mod introduce_named_lifetime;
     mod invert_if;
     mod merge_imports;
@@ -220,6 +232,7 @@ mod handlers {
     mod move_bounds;
     mod move_guard;
     mod move_module_to_file;
+    mod organize_imports;
     mod pull_assignment_up;
     mod qualify_path;
     mod raw_string;
@@ -255,9 +268,15 @@ mod handlers {
             convert_comment_block::convert_comment_block,
             convert_iter_for_each_to_for::convert_iter_for_each_to_for,
             convert_into_to_from::convert_into_to_from,
+            convert_match_to_question_mark::convert_match_to_question_mark,
+            convert_match_to_question_mark::convert_question_mark_to_match,
+            convert_to_let_else::convert_to_let_else,
+            convert_to_let_else::convert_let_else_to_match,
             convert_tuple_struct_to_named_struct::convert_tuple_struct_to_named_struct,
             early_return::convert_to_guarded_return,
             expand_glob_import::expand_glob_import,
+            extract_module::extract_module,
+            extract_module::extract_module_to_file,
             extract_struct_from_enum_variant::extract_struct_from_enum_variant,
             extract_type_alias::extract_type_alias,
             fill_match_arms::fill_match_arms,
@@ -283,6 +302,9 @@ mod handlers {
             infer_function_return_type::infer_function_return_type,
             inline_function::inline_function,
             inline_local_variable::inline_local_variable,
+            inline_macro_call::inline_macro_call,
+            inline_type_alias::inline_type_alias,
+            inline_type_alias::inline_type_alias_uses,
             introduce_named_lifetime::introduce_named_lifetime,
             invert_if::invert_if,
             merge_imports::merge_imports,
@@ -291,6 +313,7 @@ mod handlers {
             move_guard::move_arm_cond_to_match_guard,
             move_guard::move_guard_to_arm_body,
             move_module_to_file::move_module_to_file,
+            organize_imports::organize_imports,
             pull_assignment_up::pull_assignment_up,
             qualify_path::qualify_path,
             raw_string::add_hash,
@@ -266,6 +266,90 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_let_else_to_match() {
+    check_doc_test(
+        "convert_let_else_to_match",
+        r#####"
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn main() {
+    let opt: Option<i32> = None;
+    let Some(x) = opt $0else { return };
+}
+"#####,
+        r#####"
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn main() {
+    let opt: Option<i32> = None;
+    let x = match opt {
+        Some(x) => x,
+        None => return,
+    };
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_match_to_question_mark() {
+    check_doc_test(
+        "convert_match_to_question_mark",
+        r#####"
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn f(opt: Option<i32>) -> Option<i32> {
+    let x = $0match opt {
+        Some(it) => it,
+        None => return None,
+    };
+    Some(x + 1)
+}
+"#####,
+        r#####"
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn f(opt: Option<i32>) -> Option<i32> {
+    let x = opt?;
+    Some(x + 1)
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_question_mark_to_match() {
+    check_doc_test(
+        "convert_question_mark_to_match",
+        r#####"
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn f(opt: Option<i32>) -> Option<i32> {
+    let x = opt?$0;
+    Some(x + 1)
+}
+"#####,
+        r#####"
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn f(opt: Option<i32>) -> Option<i32> {
+    let x = match opt {
+        Some(it) => it,
+        None => return None,
+    };
+    Some(x + 1)
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_to_guarded_return() {
     check_doc_test(
@@ -290,6 +374,35 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_to_let_else() {
+    check_doc_test(
+        "convert_to_let_else",
+        r#####"
+//- /main.rs crate:main edition:2021
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn main() {
+    let opt: Option<i32> = None;
+    let x = $0match opt {
+        Some(it) => it,
+        None => return,
+    };
+}
+"#####,
+        r#####"
+enum Option<T> { Some(T), None }
+use Option::*;
+
+fn main() {
+    let opt: Option<i32> = None;
+    let Some(x) = opt else { return };
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_tuple_struct_to_named_struct() {
     check_doc_test(
@@ -385,6 +498,48 @@ fn $0fun_name(n: i32) {
     )
 }
 
+#[test]
+fn doctest_extract_module() {
+    check_doc_test(
+        "extract_module",
+        r#####"
+$0fn foo() {}
+
+fn bar() {}$0
+
+fn main() {
+    foo();
+}
+"#####,
+        r#####"
+use self::${0:modname}::foo;
+
+mod ${0:modname} {
+    pub(super) fn foo() {}
+
+    fn bar() {}
+}
+
+fn main() {
+    foo();
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_extract_module_to_file() {
+    check_doc_test(
+        "extract_module_to_file",
+        r#####"
+$0fn foo() {}$0
+"#####,
+        r#####"
+mod modname;
+"#####,
+    )
+}
+
 #[test]
 fn doctest_extract_struct_from_enum_variant() {
     check_doc_test(
@@ -967,6 +1122,70 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_inline_macro_call() {
+    check_doc_test(
+        "inline_macro_call",
+        r#####"
+macro_rules! double {
+    ($e:expr) => { $e * 2 };
+}
+
+fn main() {
+    let x = $0double!(21);
+}
+"#####,
+        r#####"
+macro_rules! double {
+    ($e:expr) => { $e * 2 };
+}
+
+fn main() {
+    let x = 21 * 2;
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_inline_type_alias() {
+    check_doc_test(
+        "inline_type_alias",
+        r#####"
+type A<T = u32> = Vec<T>;
+
+fn main() {
+    let a: $0A;
+}
+"#####,
+        r#####"
+type A<T = u32> = Vec<T>;
+
+fn main() {
+    let a: Vec<u32>;
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_inline_type_alias_uses() {
+    check_doc_test(
+        "inline_type_alias_uses",
+        r#####"
+type $0A = i32;
+fn id(x: A) -> A {
+    x
+}
+"#####,
+        r#####"
+fn id(x: i32) -> i32 {
+    x
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_introduce_named_lifetime() {
     check_doc_test(
@@ -1171,6 +1390,27 @@ mod foo;
     )
 }
 
+#[test]
+fn doctest_organize_imports() {
+    check_doc_test(
+        "organize_imports",
+        r#####"
+mod foo { pub struct Foo; pub struct Bar; pub struct Baz; }
+use foo::Foo;$0
+use foo::Baz;
+use foo::Bar;
+
+fn f(_: Foo, _: Bar) {}
+"#####,
+        r#####"
+mod foo { pub struct Foo; pub struct Bar; pub struct Baz; }
+use foo::{Bar, Foo};
+
+fn f(_: Foo, _: Bar) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_pull_assignment_up() {
     check_doc_test(
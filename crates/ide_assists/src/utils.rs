@@ -1,6 +1,7 @@
 //! Assorted functions shared by several assists.
 
 pub(crate) mod suggest_name;
+pub(crate) mod gen_trait_fn_body;
 
 use std::ops;
 
@@ -0,0 +1,391 @@
+//! Generates the bodies of the methods of derivable std traits, as `#[derive]` would.
+
+use hir::{HasAttrs, StructKind};
+use ide_db::{helpers::FamousDefs, RootDatabase};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::AstNodeEdit, edit::IndentLevel, AstNode, NameOwner},
+    ted,
+};
+
+/// Replaces the body of `func`, a method of `trait_` implemented for `adt`, with what the derive
+/// of `trait_` would do. Returns `None` if `trait_` isn't a derivable std trait, or can't be
+/// derived for `adt`.
+pub(crate) fn gen_trait_fn_body(
+    sema: &hir::Semantics<RootDatabase>,
+    func: &ast::Fn,
+    trait_: hir::Trait,
+    adt: hir::Adt,
+) -> Option<()> {
+    let db = sema.db;
+    let famous_defs = FamousDefs(sema, Some(adt.module(db).krate()));
+    let shapes = Shapes::new(db, adt)?;
+    let fn_name = func.name()?.to_string();
+    let is = |famous: Option<hir::Trait>, method: &str| famous == Some(trait_) && fn_name == method;
+    let body = if is(famous_defs.core_clone_Clone(), "clone") {
+        gen_clone(&shapes)
+    } else if is(famous_defs.core_fmt_Debug(), "fmt") {
+        gen_debug(&shapes)
+    } else if is(famous_defs.core_default_Default(), "default") {
+        gen_default(db, &shapes)?
+    } else if is(famous_defs.core_hash_Hash(), "hash") {
+        gen_hash(&shapes)
+    } else if is(famous_defs.core_cmp_PartialEq(), "eq") {
+        gen_partial_eq(&shapes)
+    } else if is(famous_defs.core_cmp_PartialOrd(), "partial_cmp") {
+        gen_ord(&shapes, "partial_cmp", "Some(core::cmp::Ordering::Equal)")
+    } else if is(famous_defs.core_cmp_Ord(), "cmp") {
+        gen_ord(&shapes, "cmp", "core::cmp::Ordering::Equal")
+    } else {
+        return None;
+    };
+
+    let body = ast::SourceFile::parse(&format!("fn f() {}", body))
+        .tree()
+        .syntax()
+        .descendants()
+        .find_map(ast::BlockExpr::cast)?
+        .indent(IndentLevel(1));
+    ted::replace(func.body()?.syntax(), body.clone_for_update().syntax());
+    Some(())
+}
+
+/// The struct, or the variants of the enum, the trait is implemented for.
+enum Shapes {
+    Struct(Shape),
+    Enum(Vec<(hir::Variant, Shape)>),
+}
+
+struct Shape {
+    /// `Self` or `Self::Variant`.
+    path: String,
+    name: String,
+    kind: StructKind,
+    fields: Vec<String>,
+}
+
+impl Shapes {
+    fn new(db: &RootDatabase, adt: hir::Adt) -> Option<Shapes> {
+        let field_names = |fields: Vec<hir::Field>| {
+            fields.into_iter().map(|it| it.name(db).to_string()).collect::<Vec<_>>()
+        };
+        let res = match adt {
+            hir::Adt::Struct(it) => Shapes::Struct(Shape {
+                path: "Self".to_string(),
+                name: it.name(db).to_string(),
+                kind: it.kind(db),
+                fields: field_names(it.fields(db)),
+            }),
+            hir::Adt::Enum(it) => Shapes::Enum(
+                it.variants(db)
+                    .into_iter()
+                    .map(|variant| {
+                        let shape = Shape {
+                            path: format!("Self::{}", variant.name(db)),
+                            name: variant.name(db).to_string(),
+                            kind: variant.kind(db),
+                            fields: field_names(variant.fields(db)),
+                        };
+                        (variant, shape)
+                    })
+                    .collect(),
+            ),
+            hir::Adt::Union(_) => return None,
+        };
+        Some(res)
+    }
+}
+
+impl Shape {
+    /// The names the fields are bound to by `self.pat(prefix)`.
+    fn bindings(&self, prefix: &str) -> Vec<String> {
+        match self.kind {
+            StructKind::Record if prefix.is_empty() => self.fields.clone(),
+            StructKind::Record => {
+                self.fields.iter().map(|it| format!("{}_{}", prefix, it)).collect()
+            }
+            StructKind::Tuple => {
+                let prefix = if prefix.is_empty() { "arg" } else { prefix };
+                (0..self.fields.len()).map(|idx| format!("{}{}", prefix, idx)).collect()
+            }
+            StructKind::Unit => Vec::new(),
+        }
+    }
+
+    fn pat(&self, prefix: &str) -> String {
+        let bindings = self.bindings(prefix);
+        match self.kind {
+            StructKind::Record if prefix.is_empty() => {
+                format!("{} {{ {} }}", self.path, bindings.join(", "))
+            }
+            StructKind::Record => {
+                let fields =
+                    self.fields.iter().zip(&bindings).map(|(f, b)| format!("{}: {}", f, b));
+                format!("{} {{ {} }}", self.path, fields.format(", "))
+            }
+            StructKind::Tuple => format!("{}({})", self.path, bindings.join(", ")),
+            StructKind::Unit => self.path.clone(),
+        }
+    }
+
+    /// A pattern matching the shape without binding anything.
+    fn wildcard_pat(&self) -> String {
+        match self.kind {
+            StructKind::Record => format!("{} {{ .. }}", self.path),
+            StructKind::Tuple => format!("{}(..)", self.path),
+            StructKind::Unit => self.path.clone(),
+        }
+    }
+
+    /// Constructs the shape from `values`, one per field.
+    fn construct(&self, values: impl Iterator<Item = String>) -> String {
+        match self.kind {
+            StructKind::Record => {
+                let fields = self.fields.iter().zip(values).map(|(f, v)| format!("{}: {}", f, v));
+                format!("{} {{ {} }}", self.path, fields.format(", "))
+            }
+            StructKind::Tuple => format!("{}({})", self.path, values.format(", ")),
+            StructKind::Unit => self.path.clone(),
+        }
+    }
+
+    fn has_fields(&self) -> bool {
+        !self.fields.is_empty()
+    }
+}
+
+fn gen_clone(shapes: &Shapes) -> String {
+    match shapes {
+        Shapes::Struct(shape) => {
+            let values = shape.fields.iter().map(|it| format!("self.{}.clone()", it));
+            format!("{{\n    {}\n}}", shape.construct(values))
+        }
+        Shapes::Enum(variants) => {
+            let mut buf = String::from("{\n    match self {\n");
+            for (_, shape) in variants {
+                let values = shape.bindings("").into_iter().map(|it| format!("{}.clone()", it));
+                format_to!(buf, "        {} => {},\n", shape.pat(""), shape.construct(values));
+            }
+            buf.push_str("    }\n}");
+            buf
+        }
+    }
+}
+
+fn gen_debug(shapes: &Shapes) -> String {
+    let debug = |shape: &Shape, values: Vec<String>| match shape.kind {
+        StructKind::Record => {
+            let fields = shape
+                .fields
+                .iter()
+                .zip(values)
+                .map(|(f, v)| format!(".field(\"{}\", {})", f, v))
+                .collect::<String>();
+            format!("f.debug_struct(\"{}\"){}.finish()", shape.name, fields)
+        }
+        StructKind::Tuple => {
+            let fields = values.iter().map(|v| format!(".field({})", v)).collect::<String>();
+            format!("f.debug_tuple(\"{}\"){}.finish()", shape.name, fields)
+        }
+        StructKind::Unit => format!("f.write_str(\"{}\")", shape.name),
+    };
+    match shapes {
+        Shapes::Struct(shape) => {
+            let values = shape.fields.iter().map(|it| format!("&self.{}", it)).collect();
+            format!("{{\n    {}\n}}", debug(shape, values))
+        }
+        Shapes::Enum(variants) => {
+            let mut buf = String::from("{\n    match self {\n");
+            for (_, shape) in variants {
+                format_to!(
+                    buf,
+                    "        {} => {},\n",
+                    shape.pat(""),
+                    debug(shape, shape.bindings(""))
+                );
+            }
+            buf.push_str("    }\n}");
+            buf
+        }
+    }
+}
+
+fn gen_default(db: &RootDatabase, shapes: &Shapes) -> Option<String> {
+    let value = match shapes {
+        Shapes::Struct(shape) => {
+            shape.construct(shape.fields.iter().map(|_| "Default::default()".to_string()))
+        }
+        Shapes::Enum(variants) => {
+            // Enums can only derive `Default` with a unit variant marked as `#[default]`.
+            let (_, shape) = variants
+                .iter()
+                .find(|(variant, _)| variant.attrs(db).by_key("default").exists())?;
+            if shape.has_fields() {
+                return None;
+            }
+            shape.path.clone()
+        }
+    };
+    Some(format!("{{\n    {}\n}}", value))
+}
+
+fn gen_hash(shapes: &Shapes) -> String {
+    match shapes {
+        Shapes::Struct(shape) => {
+            let mut buf = String::from("{\n");
+            for field in &shape.fields {
+                format_to!(buf, "    core::hash::Hash::hash(&self.{}, state);\n", field);
+            }
+            buf.push('}');
+            buf
+        }
+        Shapes::Enum(variants) => {
+            let mut buf = String::from(
+                "{\n    core::hash::Hash::hash(&core::mem::discriminant(self), state);\n",
+            );
+            if variants.iter().any(|(_, shape)| shape.has_fields()) {
+                buf.push_str("    match self {\n");
+                for (_, shape) in variants.iter().filter(|(_, shape)| shape.has_fields()) {
+                    let bindings = shape.bindings("");
+                    if let [binding] = bindings.as_slice() {
+                        format_to!(
+                            buf,
+                            "        {} => core::hash::Hash::hash({}, state),\n",
+                            shape.pat(""),
+                            binding
+                        );
+                    } else {
+                        format_to!(buf, "        {} => {{\n", shape.pat(""));
+                        for binding in bindings {
+                            format_to!(
+                                buf,
+                                "            core::hash::Hash::hash({}, state);\n",
+                                binding
+                            );
+                        }
+                        buf.push_str("        }\n");
+                    }
+                }
+                if variants.iter().any(|(_, shape)| !shape.has_fields()) {
+                    buf.push_str("        _ => {}\n");
+                }
+                buf.push_str("    }\n");
+            }
+            buf.push('}');
+            buf
+        }
+    }
+}
+
+fn gen_partial_eq(shapes: &Shapes) -> String {
+    let eq_all = |lhs: Vec<String>, rhs: Vec<String>| {
+        if lhs.is_empty() {
+            return "true".to_string();
+        }
+        lhs.iter().zip(rhs).map(|(l, r)| format!("{} == {}", l, r)).join(" && ")
+    };
+    match shapes {
+        Shapes::Struct(shape) => {
+            let lhs = shape.fields.iter().map(|it| format!("self.{}", it)).collect();
+            let rhs = shape.fields.iter().map(|it| format!("other.{}", it)).collect();
+            format!("{{\n    {}\n}}", eq_all(lhs, rhs))
+        }
+        Shapes::Enum(variants) => {
+            let discriminants_eq =
+                "core::mem::discriminant(self) == core::mem::discriminant(other)";
+            let with_fields: Vec<_> =
+                variants.iter().map(|(_, shape)| shape).filter(|it| it.has_fields()).collect();
+            if with_fields.is_empty() {
+                return format!("{{\n    {}\n}}", discriminants_eq);
+            }
+            let mut buf = String::from("{\n    match (self, other) {\n");
+            for shape in &with_fields {
+                format_to!(
+                    buf,
+                    "        ({}, {}) => {},\n",
+                    shape.pat("l"),
+                    shape.pat("r"),
+                    eq_all(shape.bindings("l"), shape.bindings("r"))
+                );
+            }
+            if variants.len() > 1 {
+                format_to!(buf, "        _ => {},\n", discriminants_eq);
+            }
+            buf.push_str("    }\n}");
+            buf
+        }
+    }
+}
+
+/// Generates `partial_cmp` or `cmp`, comparing fields in order and variants by their position.
+fn gen_ord(shapes: &Shapes, method: &str, equal: &str) -> String {
+    // Compares the fields pairwise, returning the first non-equal ordering.
+    let cmp_all = |lhs: Vec<String>, rhs: Vec<String>, indent: &str| {
+        let mut pairs: Vec<_> = lhs.into_iter().zip(rhs).collect();
+        let (last_lhs, last_rhs) = match pairs.pop() {
+            Some(it) => it,
+            None => return equal.to_string(),
+        };
+        let mut buf = String::new();
+        for (l, r) in pairs {
+            format_to!(buf, "match {}.{}({}) {{\n", l, method, r);
+            format_to!(buf, "{}    {} => {{}}\n", indent, equal);
+            format_to!(buf, "{}    ord => return ord,\n", indent);
+            format_to!(buf, "{}}}\n{}", indent, indent);
+        }
+        format_to!(buf, "{}.{}({})", last_lhs, method, last_rhs);
+        buf
+    };
+    match shapes {
+        Shapes::Struct(shape) => {
+            let lhs = shape.fields.iter().map(|it| format!("self.{}", it)).collect();
+            let rhs = shape.fields.iter().map(|it| format!("&other.{}", it)).collect();
+            format!("{{\n    {}\n}}", cmp_all(lhs, rhs, "    "))
+        }
+        Shapes::Enum(variants) => {
+            let mut buf = String::from("{\n");
+            if variants.len() > 1 {
+                buf.push_str("    let index = |it: &Self| match it {\n");
+                for (idx, (_, shape)) in variants.iter().enumerate() {
+                    format_to!(buf, "        {} => {},\n", shape.wildcard_pat(), idx);
+                }
+                buf.push_str("    };\n");
+            }
+            let by_index = format!("index(self).{}(&index(other))", method);
+            let with_fields: Vec<_> =
+                variants.iter().map(|(_, shape)| shape).filter(|it| it.has_fields()).collect();
+            if with_fields.is_empty() {
+                let value = if variants.len() > 1 { by_index } else { equal.to_string() };
+                format_to!(buf, "    {}\n}}", value);
+                return buf;
+            }
+            buf.push_str("    match (self, other) {\n");
+            for shape in &with_fields {
+                let cmp = cmp_all(shape.bindings("l"), shape.bindings("r"), "            ");
+                if cmp.contains('\n') {
+                    format_to!(
+                        buf,
+                        "        ({}, {}) => {{\n            {}\n        }}\n",
+                        shape.pat("l"),
+                        shape.pat("r"),
+                        cmp
+                    );
+                } else {
+                    format_to!(
+                        buf,
+                        "        ({}, {}) => {},\n",
+                        shape.pat("l"),
+                        shape.pat("r"),
+                        cmp
+                    );
+                }
+            }
+            if variants.len() > 1 {
+                format_to!(buf, "        _ => {},\n", by_index);
+            }
+            buf.push_str("    }\n}");
+            buf
+        }
+    }
+}
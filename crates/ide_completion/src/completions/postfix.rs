@@ -2,7 +2,10 @@
 
 mod format_like;
 
-use ide_db::{helpers::SnippetCap, ty_filter::TryEnum};
+use ide_db::{
+    helpers::{insert_use::ImportScope, SnippetCap},
+    ty_filter::TryEnum,
+};
 use syntax::{
     ast::{self, AstNode, AstToken},
     SyntaxKind::{BLOCK_EXPR, EXPR_STMT},
@@ -129,6 +132,10 @@ pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
     let dot_receiver = include_references(dot_receiver);
     let receiver_text = get_receiver_text(&dot_receiver, receiver_is_ambiguous_float_literal);
 
+    if !ctx.config.snippets.is_empty() {
+        add_custom_postfix_completions(acc, ctx, cap, &dot_receiver, &receiver_text);
+    }
+
     match try_enum {
         Some(try_enum) => match try_enum {
             TryEnum::Result => {
@@ -283,6 +290,36 @@ fn include_references(initial_element: &ast::Expr) -> ast::Expr {
     resulting_element
 }
 
+fn add_custom_postfix_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    cap: SnippetCap,
+    receiver: &ast::Expr,
+    receiver_text: &str,
+) -> Option<()> {
+    let import_scope =
+        ImportScope::find_insert_use_container_with_macros(&ctx.token.parent()?, &ctx.sema)?;
+    ctx.config.postfix_snippets().for_each(|(trigger, snip)| {
+        let imports = match snip.imports(ctx, &import_scope) {
+            Some(imports) => imports,
+            None => return,
+        };
+        let mut builder = postfix_snippet(
+            ctx,
+            cap,
+            receiver,
+            trigger,
+            snip.description.as_deref().unwrap_or_default(),
+            &snip.postfix_snippet(receiver_text),
+        );
+        for import in imports {
+            builder.add_import(Some(import));
+        }
+        builder.add_to(acc);
+    });
+    None
+}
+
 fn postfix_snippet(
     ctx: &CompletionContext,
     cap: SnippetCap,
@@ -307,8 +344,8 @@ mod tests {
     use expect_test::{expect, Expect};
 
     use crate::{
-        test_utils::{check_edit, completion_list},
-        CompletionKind,
+        test_utils::{check_edit, check_edit_with_config, completion_list, TEST_CONFIG},
+        CompletionConfig, CompletionKind, Snippet, SnippetScope,
     };
 
     fn check(ra_fixture: &str, expect: Expect) {
@@ -575,4 +612,41 @@ fn main() {
             r#"fn main() { log::error!("{}", 2+2) }"#,
         );
     }
+
+    #[test]
+    fn custom_postfix_completion() {
+        check_edit_with_config(
+            CompletionConfig {
+                snippets: vec![Snippet::new(
+                    &[],
+                    &["arc".to_owned()],
+                    &["Arc::new(${receiver})".to_owned()],
+                    "Wrap in an `Arc`",
+                    &["std::sync::Arc".to_owned()],
+                    SnippetScope::Expr,
+                    true,
+                )
+                .unwrap()],
+                ..TEST_CONFIG
+            },
+            "arc",
+            r#"
+//- /main.rs crate:main deps:std
+fn main() {
+    let s = String::new().$0
+}
+//- /std.rs crate:std
+pub mod sync {
+    pub struct Arc<T>(T);
+}
+"#,
+            r#"
+use std::sync::Arc;
+
+fn main() {
+    let s = Arc::new(String::new())
+}
+"#,
+        );
+    }
 }
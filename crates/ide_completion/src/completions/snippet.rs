@@ -1,10 +1,10 @@
 //! This file provides snippet completions, like `pd` => `eprintln!(...)`.
 
-use ide_db::helpers::SnippetCap;
+use ide_db::helpers::{insert_use::ImportScope, SnippetCap};
 
 use crate::{
     item::Builder, CompletionContext, CompletionItem, CompletionItemKind, CompletionKind,
-    Completions,
+    Completions, SnippetScope,
 };
 
 fn snippet(ctx: &CompletionContext, cap: SnippetCap, label: &str, snippet: &str) -> Builder {
@@ -22,6 +22,10 @@ pub(crate) fn complete_expr_snippet(acc: &mut Completions, ctx: &CompletionConte
         None => return,
     };
 
+    if !ctx.config.snippets.is_empty() {
+        add_custom_completions(acc, ctx, cap, SnippetScope::Expr);
+    }
+
     if ctx.can_be_stmt {
         snippet(ctx, cap, "pd", "eprintln!(\"$0 = {:?}\", $0);").add_to(acc);
         snippet(ctx, cap, "ppd", "eprintln!(\"$0 = {:#?}\", $0);").add_to(acc);
@@ -37,6 +41,10 @@ pub(crate) fn complete_item_snippet(acc: &mut Completions, ctx: &CompletionConte
         None => return,
     };
 
+    if !ctx.config.snippets.is_empty() {
+        add_custom_completions(acc, ctx, cap, SnippetScope::Item);
+    }
+
     let mut item = snippet(
         ctx,
         cap,
@@ -72,17 +80,74 @@ fn ${1:feature}() {
     item.add_to(acc);
 }
 
+fn add_custom_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    cap: SnippetCap,
+    scope: SnippetScope,
+) -> Option<()> {
+    let import_scope =
+        ImportScope::find_insert_use_container_with_macros(&ctx.token.parent()?, &ctx.sema)?;
+    ctx.config.prefix_snippets().filter(|(_, snip)| snip.scope == scope).for_each(
+        |(trigger, snip)| {
+            let imports = match snip.imports(ctx, &import_scope) {
+                Some(imports) => imports,
+                None => return,
+            };
+            let mut builder = snippet(ctx, cap, trigger, &snip.snippet());
+            for import in imports {
+                builder.add_import(Some(import));
+            }
+            builder.set_detail(snip.description.clone());
+            builder.add_to(acc);
+        },
+    );
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::{expect, Expect};
 
-    use crate::{test_utils::completion_list, CompletionKind};
+    use crate::{
+        test_utils::{
+            check_edit_with_config, completion_list, completion_list_with_config, TEST_CONFIG,
+        },
+        CompletionConfig, CompletionKind, Snippet, SnippetScope,
+    };
 
     fn check(ra_fixture: &str, expect: Expect) {
         let actual = completion_list(ra_fixture, CompletionKind::Snippet);
         expect.assert_eq(&actual)
     }
 
+    fn config_with_snippet(
+        requires: &[&str],
+        scope: SnippetScope,
+        require_resolved: bool,
+    ) -> CompletionConfig {
+        let requires: Vec<String> = requires.iter().map(|it| it.to_string()).collect();
+        let snippet = Snippet::new(
+            &["arcmut".to_owned()],
+            &[],
+            &["Arc::new(Mutex::new($0))".to_owned()],
+            "Arc<Mutex<_>>",
+            &requires,
+            scope,
+            require_resolved,
+        )
+        .unwrap();
+        CompletionConfig { snippets: vec![snippet], ..TEST_CONFIG }
+    }
+
+    const SYNC_FIXTURE: &str = r#"
+//- /std.rs crate:std
+pub mod sync {
+    pub struct Arc<T>(T);
+    pub struct Mutex<T>(T);
+}
+"#;
+
     #[test]
     fn completes_snippets_in_expressions() {
         check(
@@ -116,4 +181,140 @@ mod tests {
             "#]],
         )
     }
+
+    #[test]
+    fn custom_snippet_adds_imports() {
+        check_edit_with_config(
+            config_with_snippet(&["std::sync::Arc", "std::sync::Mutex"], SnippetScope::Expr, true),
+            "arcmut",
+            &format!(
+                r#"
+//- /main.rs crate:main deps:std
+fn main() {{
+    let x = $0
+}}{}"#,
+                SYNC_FIXTURE
+            ),
+            r#"
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let x = Arc::new(Mutex::new($0))
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn custom_snippet_needing_imports_requires_imports_on_the_fly() {
+        let fixture = format!(
+            r#"
+//- /main.rs crate:main deps:std
+use std::sync::Arc;
+
+fn main() {{ $0 }}{}"#,
+            SYNC_FIXTURE
+        );
+        let config = CompletionConfig {
+            enable_imports_on_the_fly: false,
+            ..config_with_snippet(&["std::sync::Arc", "std::sync::Mutex"], SnippetScope::Expr, true)
+        };
+        let actual = completion_list_with_config(config, &fixture, CompletionKind::Snippet);
+        expect![[r#"
+            sn pd
+            sn ppd
+        "#]]
+        .assert_eq(&actual);
+
+        let config = CompletionConfig {
+            enable_imports_on_the_fly: false,
+            ..config_with_snippet(&["std::sync::Arc"], SnippetScope::Expr, true)
+        };
+        let actual = completion_list_with_config(config, &fixture, CompletionKind::Snippet);
+        expect![[r#"
+            sn arcmut Arc<Mutex<_>>
+            sn pd
+            sn ppd
+        "#]]
+        .assert_eq(&actual);
+    }
+
+    #[test]
+    fn custom_snippet_skips_imports_in_scope() {
+        check_edit_with_config(
+            config_with_snippet(&["std::sync::Arc", "std::sync::Mutex"], SnippetScope::Expr, true),
+            "arcmut",
+            &format!(
+                r#"
+//- /main.rs crate:main deps:std
+use shared::Arc;
+
+mod shared {{
+    pub use std::sync::Arc;
+}}
+
+fn main() {{
+    let x = $0
+}}{}"#,
+                SYNC_FIXTURE
+            ),
+            r#"
+use std::sync::Mutex;
+
+use shared::Arc;
+
+mod shared {
+    pub use std::sync::Arc;
+}
+
+fn main() {
+    let x = Arc::new(Mutex::new($0))
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn custom_snippet_requires_resolved_paths() {
+        let fixture = r#"fn main() { $0 }"#;
+        let config = config_with_snippet(&["std::sync::Arc"], SnippetScope::Expr, true);
+        let actual = completion_list_with_config(config, fixture, CompletionKind::Snippet);
+        expect![[r#"
+            sn pd
+            sn ppd
+        "#]]
+        .assert_eq(&actual);
+
+        let config = config_with_snippet(&["std::sync::Arc"], SnippetScope::Expr, false);
+        let actual = completion_list_with_config(config, fixture, CompletionKind::Snippet);
+        expect![[r#"
+            sn arcmut Arc<Mutex<_>>
+            sn pd
+            sn ppd
+        "#]]
+        .assert_eq(&actual);
+    }
+
+    #[test]
+    fn custom_snippet_respects_scope() {
+        let config = config_with_snippet(&[], SnippetScope::Item, true);
+        let actual = completion_list_with_config(
+            config.clone(),
+            "fn main() { $0 }",
+            CompletionKind::Snippet,
+        );
+        expect![[r#"
+            sn pd
+            sn ppd
+        "#]]
+        .assert_eq(&actual);
+        let actual = completion_list_with_config(config, "struct S;\n$0", CompletionKind::Snippet);
+        expect![[r#"
+            sn arcmut           Arc<Mutex<_>>
+            sn tmod (Test module)
+            sn tfn (Test function)
+            sn macro_rules
+        "#]]
+        .assert_eq(&actual);
+    }
 }
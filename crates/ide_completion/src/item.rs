@@ -76,8 +76,8 @@ pub struct CompletionItem {
     /// possible match.
     ref_match: Option<Mutability>,
 
-    /// The import data to add to completion's edits.
-    import_to_add: Option<ImportEdit>,
+    /// The imports to add to completion's edits.
+    imports_to_add: Vec<ImportEdit>,
 }
 
 // We use custom debug for CompletionItem to make snapshot tests more readable.
@@ -300,7 +300,7 @@ impl CompletionItem {
             trigger_call_info: None,
             relevance: CompletionRelevance::default(),
             ref_match: None,
-            import_to_add: None,
+            imports_to_add: Vec::new(),
         }
     }
 
@@ -359,8 +359,8 @@ impl CompletionItem {
         self.ref_match.map(|mutability| (mutability, relevance))
     }
 
-    pub fn import_to_add(&self) -> Option<&ImportEdit> {
-        self.import_to_add.as_ref()
+    pub fn imports_to_add(&self) -> &[ImportEdit] {
+        &self.imports_to_add
     }
 }
 
@@ -393,7 +393,7 @@ impl ImportEdit {
 pub(crate) struct Builder {
     source_range: TextRange,
     completion_kind: CompletionKind,
-    import_to_add: Option<ImportEdit>,
+    imports_to_add: Vec<ImportEdit>,
     label: String,
     insert_text: Option<String>,
     insert_text_format: InsertTextFormat,
@@ -416,10 +416,9 @@ impl Builder {
         let mut lookup = self.lookup;
         let mut insert_text = self.insert_text;
 
-        if let Some(original_path) = self
-            .import_to_add
-            .as_ref()
-            .and_then(|import_edit| import_edit.import.original_path.as_ref())
+        if let [ImportEdit {
+            import: LocatedImport { original_path: Some(original_path), .. }, ..
+        }] = self.imports_to_add.as_slice()
         {
             lookup = lookup.or_else(|| Some(label.clone()));
             insert_text = insert_text.or_else(|| Some(label.clone()));
@@ -453,7 +452,7 @@ impl Builder {
             trigger_call_info: self.trigger_call_info.unwrap_or(false),
             relevance: self.relevance,
             ref_match: self.ref_match,
-            import_to_add: self.import_to_add,
+            imports_to_add: self.imports_to_add,
         }
     }
     pub(crate) fn lookup_by(&mut self, lookup: impl Into<String>) -> &mut Builder {
@@ -521,7 +520,7 @@ impl Builder {
         self
     }
     pub(crate) fn add_import(&mut self, import_to_add: Option<ImportEdit>) -> &mut Builder {
-        self.import_to_add = import_to_add;
+        self.imports_to_add.extend(import_to_add);
         self
     }
     pub(crate) fn ref_match(&mut self, mutability: Mutability) -> &mut Builder {
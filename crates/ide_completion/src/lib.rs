@@ -8,6 +8,7 @@ mod generated_lint_completions;
 #[cfg(test)]
 mod test_utils;
 mod render;
+mod snippet;
 
 mod completions;
 
@@ -15,11 +16,13 @@ use completions::flyimport::position_for_import;
 use ide_db::{
     base_db::FilePosition,
     helpers::{
-        import_assets::{LocatedImport, NameToImport},
-        insert_use::ImportScope,
+        import_assets::NameToImport,
+        insert_use::{self, ImportScope},
+        mod_path_to_ast,
     },
     items_locator, RootDatabase,
 };
+use syntax::algo;
 use text_edit::TextEdit;
 
 use crate::{completions::Completions, context::CompletionContext, item::CompletionKind};
@@ -27,6 +30,7 @@ use crate::{completions::Completions, context::CompletionContext, item::Completi
 pub use crate::{
     config::CompletionConfig,
     item::{CompletionItem, CompletionItemKind, CompletionRelevance, ImportEdit, InsertTextFormat},
+    snippet::{Snippet, SnippetScope},
 };
 
 //FIXME: split the following feature into fine-grained features.
@@ -174,8 +178,7 @@ pub fn resolve_completion_edits(
     db: &RootDatabase,
     config: &CompletionConfig,
     position: FilePosition,
-    full_import_path: &str,
-    imported_name: String,
+    imports: impl IntoIterator<Item = (String, String)>,
 ) -> Option<Vec<TextEdit>> {
     let ctx = CompletionContext::new(db, position, config)?;
     let position_for_import = position_for_import(&ctx, None)?;
@@ -183,24 +186,29 @@ pub fn resolve_completion_edits(
 
     let current_module = ctx.sema.scope(position_for_import).module()?;
     let current_crate = current_module.krate();
+    let new_ast = scope.clone_for_update();
+    let mut import_insert = TextEdit::builder();
 
-    let (import_path, item_to_import) = items_locator::items_with_name(
-        &ctx.sema,
-        current_crate,
-        NameToImport::Exact(imported_name),
-        items_locator::AssocItemSearch::Include,
-        Some(items_locator::DEFAULT_QUERY_SEARCH_LIMIT),
-    )
-    .filter_map(|candidate| {
-        current_module
-            .find_use_path_prefixed(db, candidate, config.insert_use.prefix_kind)
-            .zip(Some(candidate))
-    })
-    .find(|(mod_path, _)| mod_path.to_string() == full_import_path)?;
-    let import =
-        LocatedImport::new(import_path.clone(), item_to_import, item_to_import, Some(import_path));
+    // All imports go into the same tree, so that their edits can't overlap.
+    for (full_import_path, imported_name) in imports {
+        let (import_path, _) = items_locator::items_with_name(
+            &ctx.sema,
+            current_crate,
+            NameToImport::Exact(imported_name),
+            items_locator::AssocItemSearch::Include,
+            Some(items_locator::DEFAULT_QUERY_SEARCH_LIMIT),
+        )
+        .filter_map(|candidate| {
+            current_module
+                .find_use_path_prefixed(db, candidate, config.insert_use.prefix_kind)
+                .zip(Some(candidate))
+        })
+        .find(|(mod_path, _)| mod_path.to_string() == full_import_path)?;
+        insert_use::insert_use(&new_ast, mod_path_to_ast(&import_path), config.insert_use);
+    }
 
-    ImportEdit { import, scope }.to_text_edit(config.insert_use).map(|edit| vec![edit])
+    algo::diff(scope.as_syntax_node(), new_ast.as_syntax_node()).into_text_edit(&mut import_insert);
+    Some(vec![import_insert.finish()])
 }
 
 #[cfg(test)]
@@ -0,0 +1,170 @@
+//! User-defined (postfix) snippets.
+//!
+//! The completions themselves are produced in [`crate::completions::snippet`]
+//! and [`crate::completions::postfix`].
+
+// Feature: User Snippet Completions
+//
+// rust-analyzer allows the user to define custom (postfix)-snippets that may depend on items to be accessible for the current scope to be applicable.
+//
+// A custom snippet can be defined by adding it to the `rust-analyzer.completion.snippets` object respectively.
+//
+// [source,json]
+// ----
+// {
+//   "rust-analyzer.completion.snippets": {
+//     "thread spawn": {
+//       "prefix": ["spawn", "tspawn"],
+//       "body": [
+//         "thread::spawn(move || {",
+//         "\t$0",
+//         "});",
+//       ],
+//       "description": "Insert a thread::spawn call",
+//       "requires": "std::thread",
+//       "scope": "expr",
+//     }
+//   }
+// }
+// ----
+//
+// In the example above:
+//
+// * `"thread spawn"` is the name of the snippet.
+//
+// * `prefix` defines one or more trigger words that will trigger the snippets completion.
+// Using `postfix` will instead create a postfix snippet.
+//
+// * `body` is one or more lines of content joined via newlines for the final output.
+//
+// * `description` is an optional description of the snippet, if unset the snippet name will be used.
+//
+// * `requires` is an optional list of item paths that have to be resolvable in the current crate where the completion is rendered.
+// On failure of resolution the snippet won't be applicable, unless `requireResolved` is set to `false`, in which case the snippet
+// is offered without the imports that failed to resolve.
+// Resolved paths are imported into the file when the snippet is completed.
+// As with auto-imports, this needs `rust-analyzer.completion.autoimport.enable` and a client that can resolve completion edits,
+// otherwise snippets that need imports are not offered.
+//
+// * `scope` is an optional filter for when the snippet should be applicable. Possible values are:
+// ** for Snippet-Scopes: `expr`, `item` (default: `expr`)
+// ** for Postfix-Snippet-Scopes: `expr` (default: `expr`)
+//
+// The `body` field also has access to placeholders as visible in the example as `$0`.
+// These placeholders take the form of `$number` or `${number:placeholder_text}` which can be traversed as tabstop in ascending order starting from 1,
+// with `$0` being a special case that always comes last.
+//
+// There is also a special placeholder, `${receiver}`, which will be replaced by the receiver expression for postfix snippets, or nothing in case of normal snippets.
+// It does not act as a tabstop.
+
+use hir::PathResolution;
+use ide_db::helpers::{import_assets::LocatedImport, insert_use::ImportScope};
+use syntax::ast;
+
+use crate::{context::CompletionContext, ImportEdit};
+
+/// Where a user snippet can be completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnippetScope {
+    Item,
+    Expr,
+}
+
+/// A user-defined snippet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snippet {
+    pub postfix_triggers: Box<[String]>,
+    pub prefix_triggers: Box<[String]>,
+    pub scope: SnippetScope,
+    pub description: Option<String>,
+    /// Whether the snippet is hidden when one of the `requires` paths doesn't
+    /// resolve.
+    pub require_resolved: bool,
+    snippet: String,
+    requires: Box<[String]>,
+}
+
+impl Snippet {
+    /// Returns `None` if the snippet has no triggers, has postfix triggers but
+    /// isn't an expression snippet, or requires something that isn't a path.
+    pub fn new(
+        prefix_triggers: &[String],
+        postfix_triggers: &[String],
+        snippet: &[String],
+        description: &str,
+        requires: &[String],
+        scope: SnippetScope,
+        require_resolved: bool,
+    ) -> Option<Self> {
+        if prefix_triggers.is_empty() && postfix_triggers.is_empty() {
+            return None;
+        }
+        if !postfix_triggers.is_empty() && scope != SnippetScope::Expr {
+            return None;
+        }
+        if requires.iter().any(|path| ast::Path::parse(path).is_err()) {
+            return None;
+        }
+        let description = description.trim();
+        Some(Snippet {
+            postfix_triggers: postfix_triggers.into(),
+            prefix_triggers: prefix_triggers.into(),
+            scope,
+            description: if description.is_empty() { None } else { Some(description.to_owned()) },
+            require_resolved,
+            snippet: snippet.join("\n"),
+            requires: requires.into(),
+        })
+    }
+
+    /// Returns the imports to add for this snippet, or `None` if the snippet
+    /// isn't applicable at the completion position or needs imports that
+    /// can't be added.
+    pub(crate) fn imports(
+        &self,
+        ctx: &CompletionContext,
+        import_scope: &ImportScope,
+    ) -> Option<Vec<ImportEdit>> {
+        let mut res = Vec::with_capacity(self.requires.len());
+        for path in self.requires.iter() {
+            match resolve_import(ctx, path) {
+                // The item is already in scope.
+                Some(None) => {}
+                Some(Some(import)) => res.push(ImportEdit { import, scope: import_scope.clone() }),
+                None if self.require_resolved => return None,
+                None => {}
+            }
+        }
+        // Imports are only added when the completion is resolved, without
+        // that the snippet would insert code that doesn't compile.
+        if !res.is_empty() && !ctx.config.enable_imports_on_the_fly {
+            return None;
+        }
+        Some(res)
+    }
+
+    pub(crate) fn snippet(&self) -> String {
+        self.snippet.replace("${receiver}", "")
+    }
+
+    pub(crate) fn postfix_snippet(&self, receiver: &str) -> String {
+        self.snippet.replace("${receiver}", receiver)
+    }
+}
+
+fn resolve_import(ctx: &CompletionContext, path: &str) -> Option<Option<LocatedImport>> {
+    let path = ast::Path::parse(path).ok()?;
+    let item: hir::ItemInNs = match ctx.scope.speculative_resolve(&path)? {
+        PathResolution::Macro(mac) => mac.into(),
+        PathResolution::Def(def) => def.into(),
+        _ => return None,
+    };
+    let module = ctx.scope.module()?;
+    // Prefixed paths never stop at a name that is already in scope, so check that first.
+    if module.find_use_path(ctx.db, item)?.len() == 1 {
+        return Some(None);
+    }
+    let import_path =
+        module.find_use_path_prefixed(ctx.db, item, ctx.config.insert_use.prefix_kind)?;
+    Some(Some(LocatedImport::new(import_path, item, item, None)))
+}
@@ -4,15 +4,16 @@ use hir::{PrefixKind, Semantics};
 use ide_db::{
     base_db::{fixture::ChangeFixture, FileLoader, FilePosition},
     helpers::{
-        insert_use::{ImportGranularity, InsertUseConfig},
-        SnippetCap,
+        insert_use::{self, ImportGranularity, InsertUseConfig},
+        mod_path_to_ast, SnippetCap,
     },
     RootDatabase,
 };
 use itertools::Itertools;
 use stdx::{format_to, trim_indent};
-use syntax::{AstNode, NodeOrToken, SyntaxElement};
+use syntax::{algo, AstNode, NodeOrToken, SyntaxElement};
 use test_utils::assert_eq_text;
+use text_edit::TextEdit;
 
 use crate::{item::CompletionKind, CompletionConfig, CompletionItem};
 
@@ -29,6 +30,7 @@ pub(crate) const TEST_CONFIG: CompletionConfig = CompletionConfig {
         enforce_granularity: true,
         group: true,
     },
+    snippets: Vec::new(),
 };
 
 /// Creates analysis from a multi-file fixture, returns positions marked with $0.
@@ -119,10 +121,19 @@ pub(crate) fn check_edit_with_config(
     let mut actual = db.file_text(position.file_id).to_string();
 
     let mut combined_edit = completion.text_edit().to_owned();
-    if let Some(import_text_edit) =
-        completion.import_to_add().and_then(|edit| edit.to_text_edit(config.insert_use))
-    {
-        combined_edit.union(import_text_edit).expect(
+    if let Some(import_scope) = completion.imports_to_add().first().map(|edit| &edit.scope) {
+        let new_ast = import_scope.clone_for_update();
+        for import_edit in completion.imports_to_add() {
+            insert_use::insert_use(
+                &new_ast,
+                mod_path_to_ast(&import_edit.import.import_path),
+                config.insert_use,
+            );
+        }
+        let mut import_text_edit = TextEdit::builder();
+        algo::diff(import_scope.as_syntax_node(), new_ast.as_syntax_node())
+            .into_text_edit(&mut import_text_edit);
+        combined_edit.union(import_text_edit.finish()).expect(
             "Failed to apply completion resolve changes: change ranges overlap, but should not",
         )
     }
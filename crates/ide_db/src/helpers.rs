@@ -1,8 +1,11 @@
 //! A module with ide helpers for high-level ide features.
 pub mod import_assets;
 pub mod insert_use;
+pub mod insert_whitespace;
 pub mod merge_imports;
+pub mod organize_imports;
 pub mod rust_doc;
+pub mod unused_imports;
 
 use std::collections::VecDeque;
 
@@ -91,6 +94,26 @@ impl FamousDefs<'_, '_> {
         self.find_trait("core:cmp:Ord")
     }
 
+    pub fn core_cmp_PartialOrd(&self) -> Option<Trait> {
+        self.find_trait("core:cmp:PartialOrd")
+    }
+
+    pub fn core_cmp_PartialEq(&self) -> Option<Trait> {
+        self.find_trait("core:cmp:PartialEq")
+    }
+
+    pub fn core_clone_Clone(&self) -> Option<Trait> {
+        self.find_trait("core:clone:Clone")
+    }
+
+    pub fn core_fmt_Debug(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:Debug")
+    }
+
+    pub fn core_hash_Hash(&self) -> Option<Trait> {
+        self.find_trait("core:hash:Hash")
+    }
+
     pub fn core_convert_From(&self) -> Option<Trait> {
         self.find_trait("core:convert:From")
     }
@@ -103,6 +126,10 @@ impl FamousDefs<'_, '_> {
         self.find_enum("core:option:Option")
     }
 
+    pub fn core_result_Result(&self) -> Option<Enum> {
+        self.find_enum("core:result:Result")
+    }
+
     pub fn core_default_Default(&self) -> Option<Trait> {
         self.find_trait("core:default:Default")
     }
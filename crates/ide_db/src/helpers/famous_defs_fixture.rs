@@ -1,12 +1,44 @@
 //- /libcore.rs crate:core
 //! Signatures of traits, types and functions from the core lib for use in tests.
+pub mod clone {
+    pub trait Clone {
+        fn clone(&self) -> Self;
+    }
+}
+
 pub mod cmp {
+    use crate::option::Option;
 
-    pub trait Ord {
+    pub enum Ordering {
+        Less,
+        Equal,
+        Greater,
+    }
+
+    pub trait PartialEq<Rhs: ?Sized = Self> {
+        fn eq(&self, other: &Rhs) -> bool;
+        fn ne(&self, other: &Rhs) -> bool {
+            !self.eq(other)
+        }
+    }
+
+    pub trait Eq: PartialEq<Self> {}
+
+    pub trait PartialOrd<Rhs: ?Sized = Self>: PartialEq<Rhs> {
+        fn partial_cmp(&self, other: &Rhs) -> Option<Ordering>;
+    }
+
+    pub trait Ord: Eq + PartialOrd<Self> {
         fn cmp(&self, other: &Self) -> Ordering;
-        fn max(self, other: Self) -> Self;
-        fn min(self, other: Self) -> Self;
-        fn clamp(self, min: Self, max: Self) -> Self;
+        fn max(self, other: Self) -> Self {
+            self
+        }
+        fn min(self, other: Self) -> Self {
+            self
+        }
+        fn clamp(self, min: Self, max: Self) -> Self {
+            self
+        }
     }
 }
 
@@ -20,6 +52,24 @@ pub mod convert {
     }
 }
 
+pub mod fmt {
+    pub struct Error;
+    pub type Result = crate::result::Result<(), Error>;
+    pub struct Formatter<'a>;
+
+    pub trait Debug {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+}
+
+pub mod hash {
+    pub trait Hasher {}
+
+    pub trait Hash {
+        fn hash<H: Hasher>(&self, state: &mut H);
+    }
+}
+
 pub mod default {
     pub trait Default {
         fn default() -> Self;
@@ -127,6 +177,13 @@ pub mod option {
     }
 }
 
+pub mod result {
+    pub enum Result<T, E> {
+        Ok(T),
+        Err(E),
+    }
+}
+
 pub mod prelude {
     pub mod rust_2018 {
         pub use crate::{
@@ -136,8 +193,12 @@ pub mod prelude {
             iter::{IntoIterator, Iterator},
             ops::{Fn, FnMut, FnOnce},
             option::Option::{self, *},
+            result::Result::{self, *},
         };
     }
+    pub mod rust_2021 {
+        pub use super::rust_2018::*;
+    }
 }
 #[prelude_import]
 pub use prelude::rust_2018::*;
@@ -184,8 +184,8 @@ pub fn insert_use<'a>(scope: &ImportScope, path: ast::Path, cfg: InsertUseConfig
     insert_use_(scope, path, cfg.group, use_item);
 }
 
-#[derive(Eq, PartialEq, PartialOrd, Ord)]
-enum ImportGroup {
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+pub(super) enum ImportGroup {
     // the order here defines the order of new group inserts
     Std,
     ExternCrate,
@@ -195,7 +195,7 @@ enum ImportGroup {
 }
 
 impl ImportGroup {
-    fn new(path: &ast::Path) -> ImportGroup {
+    pub(super) fn new(path: &ast::Path) -> ImportGroup {
         let default = ImportGroup::ExternCrate;
 
         let first_segment = match path.first_segment() {
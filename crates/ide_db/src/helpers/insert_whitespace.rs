@@ -0,0 +1,193 @@
+//! Renders syntax trees that have lost their whitespace, such as macro expansions, as readable
+//! source text.
+
+use std::iter;
+
+use syntax::{
+    NodeOrToken,
+    SyntaxKind::{self, *},
+    SyntaxNode, SyntaxToken, WalkEvent, T,
+};
+
+/// Renders `syn`, inserting whitespace between its tokens. Nested blocks are indented by
+/// `indent_unit` per level. Any whitespace already in the tree is ignored.
+// FIXME: It would also be cool to share logic here and in the mbe tests,
+// which are pretty unreadable at the moment.
+pub fn insert_whitespaces(syn: &SyntaxNode, indent_unit: &str) -> String {
+    let mut res = String::new();
+    let mut token_iter = syn
+        .preorder_with_tokens()
+        .filter_map(|event| match event {
+            WalkEvent::Enter(NodeOrToken::Token(token)) if !token.kind().is_trivia() => Some(token),
+            _ => None,
+        })
+        .peekable();
+
+    let mut indent = 0;
+    // Number of unclosed `(` and `[` in each block, since `;` and `,` don't start a new line
+    // within them.
+    let mut nesting = vec![0usize];
+    let mut last: Option<SyntaxKind> = None;
+
+    while let Some(token) = token_iter.next() {
+        let next = token_iter.peek().map(|it| it.kind());
+        let is_next =
+            |f: fn(SyntaxKind) -> bool, default| -> bool { next.map(f).unwrap_or(default) };
+        let is_last =
+            |f: fn(SyntaxKind) -> bool, default| -> bool { last.map(f).unwrap_or(default) };
+        let newline = |res: &mut String, indent: usize| {
+            res.push('\n');
+            res.extend(iter::repeat(indent_unit).take(indent));
+        };
+        let in_parens = nesting.last().map_or(false, |&it| it > 0);
+
+        match token.kind() {
+            k if is_text(k) && is_next(|it| !it.is_punct(), true) => {
+                res.push_str(token.text());
+                res.push(' ');
+            }
+            k if k.is_keyword()
+                && !matches!(k, T![self] | T![super] | T![crate])
+                && is_next(|it| !is_tight_punct(it), false) =>
+            {
+                res.push_str(token.text());
+                res.push(' ');
+            }
+            T![|] if is_closure_param_list_end(&token) => res.push_str("| "),
+            T!['('] | T!['['] => {
+                *nesting.last_mut().unwrap() += 1;
+                res.push_str(token.text());
+            }
+            T![')'] | T![']'] => {
+                let depth = nesting.last_mut().unwrap();
+                *depth = depth.saturating_sub(1);
+                res.push_str(token.text());
+                if is_next(is_text, false) {
+                    res.push(' ');
+                }
+            }
+            L_CURLY if is_next(|it| it != R_CURLY, true) => {
+                indent += 1;
+                nesting.push(0);
+                if is_last(|it| !matches!(it, T!['('] | T!['['] | T![!]), false)
+                    && !res.ends_with(' ')
+                {
+                    res.push(' ');
+                }
+                res.push('{');
+                newline(&mut res, indent);
+            }
+            L_CURLY => {
+                nesting.push(0);
+                if is_last(|it| !matches!(it, T!['('] | T!['['] | T![!]), false)
+                    && !res.ends_with(' ')
+                {
+                    res.push(' ');
+                }
+                res.push('{');
+            }
+            R_CURLY => {
+                if nesting.len() > 1 {
+                    nesting.pop();
+                }
+                if is_last(|it| it != L_CURLY, true) {
+                    indent = indent.saturating_sub(1);
+                    trim_trailing_indent(&mut res);
+                    newline(&mut res, indent);
+                }
+                res.push('}');
+                if is_next(|it| it == T![else], false) {
+                    res.push(' ');
+                } else if is_next(
+                    |it| !matches!(it, T![;] | T![,] | T![')'] | T![']'] | T![.] | T![?]),
+                    false,
+                ) {
+                    newline(&mut res, indent);
+                }
+            }
+            T![;] if in_parens => res.push_str("; "),
+            T![;] => {
+                res.push(';');
+                if is_next(|it| it != R_CURLY, false) {
+                    newline(&mut res, indent);
+                }
+            }
+            T![,] if !in_parens && is_block_list(&token) => {
+                res.push(',');
+                if is_next(|it| it != R_CURLY, false) {
+                    newline(&mut res, indent);
+                }
+            }
+            T![,] => {
+                res.push(',');
+                if is_next(|it| !matches!(it, T![')'] | T![']'] | T![>]), false) {
+                    res.push(' ');
+                }
+            }
+            T![:] => res.push_str(": "),
+            LIFETIME_IDENT if is_next(|it| it == IDENT || it.is_keyword(), true) => {
+                res.push_str(token.text());
+                res.push(' ');
+            }
+            T![->] | T![=] | T![=>] => {
+                if !res.ends_with(' ') {
+                    res.push(' ');
+                }
+                res.push_str(token.text());
+                res.push(' ');
+            }
+            _ if is_binary_operator(&token) => {
+                if !res.ends_with(' ') {
+                    res.push(' ');
+                }
+                res.push_str(token.text());
+                res.push(' ');
+            }
+            _ => res.push_str(token.text()),
+        }
+
+        last = Some(token.kind());
+    }
+
+    res.truncate(res.trim_end().len());
+    return res;
+
+    fn is_text(k: SyntaxKind) -> bool {
+        k.is_keyword() || k.is_literal() || k == IDENT
+    }
+}
+
+/// Returns whether `token` separates the items of a list that's written one item per line.
+fn is_block_list(token: &SyntaxToken) -> bool {
+    token.parent().map_or(false, |parent| {
+        matches!(
+            parent.kind(),
+            MATCH_ARM_LIST | MATCH_ARM | RECORD_FIELD_LIST | VARIANT_LIST | RECORD_EXPR_FIELD_LIST
+        )
+    })
+}
+
+/// Punctuation that is never preceded by a space.
+fn is_tight_punct(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        T![;] | T![,] | T![.] | T![')'] | T![']'] | T![?] | T![:] | T![::] | T![<] | T![>]
+    )
+}
+
+fn is_binary_operator(token: &SyntaxToken) -> bool {
+    // The operator is the only token that's a direct child of a binary expression.
+    token.parent().map_or(false, |parent| parent.kind() == BIN_EXPR)
+}
+
+fn is_closure_param_list_end(token: &SyntaxToken) -> bool {
+    token.parent().map_or(false, |parent| parent.kind() == PARAM_LIST)
+        && token.next_sibling_or_token().is_none()
+}
+
+fn trim_trailing_indent(res: &mut String) {
+    res.truncate(res.trim_end_matches(|c| c == ' ' || c == '\t').len());
+    if res.ends_with('\n') {
+        res.pop();
+    }
+}
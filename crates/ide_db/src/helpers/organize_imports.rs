@@ -0,0 +1,167 @@
+//! Merges, sorts and groups the `use` items of a scope.
+use std::cmp::Ordering;
+
+use syntax::{
+    ast::{self, edit::IndentLevel, make, AstNode, AttrsOwner, ModuleItemOwner, VisibilityOwner},
+    ted::{self, Position},
+    Direction, SyntaxElement, SyntaxKind,
+};
+
+use crate::helpers::{
+    insert_use::{ImportGranularity, ImportGroup, ImportScope, InsertUseConfig},
+    merge_imports::{try_merge_imports, use_tree_path_cmp, MergeBehavior},
+};
+
+/// Splits and merges the `use` items of `scope` according to `cfg.granularity`, sorts them and,
+/// if `cfg.group` is set, separates the std, external crate and local groups with blank lines. The
+/// organized imports replace the first `use` item of the scope.
+///
+/// `use` items with attributes are left alone. `scope` must be mutable.
+pub fn organize_imports(scope: &ImportScope, cfg: &InsertUseConfig) {
+    let items = match scope {
+        ImportScope::File(it) => it.items(),
+        ImportScope::Module(it) => it.items(),
+    };
+    let use_items: Vec<ast::Use> = items
+        .filter_map(|item| match item {
+            ast::Item::Use(it) if it.attrs().next().is_none() => Some(it),
+            _ => None,
+        })
+        .collect();
+    let anchor = match use_items.first() {
+        Some(it) => it.clone(),
+        None => return,
+    };
+
+    let merge = match cfg.granularity {
+        ImportGranularity::Crate => Some(MergeBehavior::Crate),
+        ImportGranularity::Module => Some(MergeBehavior::Module),
+        ImportGranularity::Item | ImportGranularity::Preserve => None,
+    };
+    let flattened: Vec<ast::Use> = use_items
+        .iter()
+        .flat_map(|use_item| match cfg.granularity {
+            ImportGranularity::Preserve => vec![use_item.clone()],
+            _ => flatten_use_item(use_item),
+        })
+        .collect();
+    let mut organized: Vec<ast::Use> = Vec::new();
+    for use_item in flattened {
+        let use_item = use_item.clone_subtree().clone_for_update();
+        if let Some(merge) = merge {
+            let merged = organized.iter().enumerate().find_map(|(idx, it)| {
+                try_merge_imports(it, &use_item, merge).map(|merged| (idx, merged))
+            });
+            if let Some((idx, merged)) = merged {
+                organized[idx] = merged;
+                continue;
+            }
+        }
+        organized.push(use_item);
+    }
+    organized.sort_by(|lhs, rhs| {
+        let group_ord =
+            if cfg.group { import_group(lhs).cmp(&import_group(rhs)) } else { Ordering::Equal };
+        group_ord.then_with(|| use_cmp(lhs, rhs))
+    });
+
+    let indent = IndentLevel::from_node(anchor.syntax());
+    let mut elements: Vec<SyntaxElement> = Vec::new();
+    let mut prev_group = None;
+    for use_item in organized {
+        let group = import_group(&use_item);
+        if let Some(prev_group) = prev_group {
+            let blank_line = if cfg.group && prev_group != group { "\n" } else { "" };
+            elements.push(make::tokens::whitespace(&format!("{}\n{}", blank_line, indent)).into());
+        }
+        prev_group = Some(group);
+        elements.push(use_item.syntax().clone().into());
+    }
+    // Separates the organized imports from the old `use` items, it is removed along with them.
+    elements.push(make::tokens::whitespace(&format!("\n{}", indent)).into());
+    ted::insert_all(Position::before(anchor.syntax()), elements);
+
+    for use_item in use_items {
+        remove_with_whitespace(&use_item);
+    }
+}
+
+/// Splits `use_item` into one `use` item per imported name, `use a::{b, c::{self, d}};` becomes
+/// `use a::b;`, `use a::c;` and `use a::c::d;`.
+fn flatten_use_item(use_item: &ast::Use) -> Vec<ast::Use> {
+    let tree = match use_item.use_tree() {
+        // `use ::{a, b};` can't be split without changing what the paths refer to.
+        Some(it)
+            if it.use_tree_list().is_some()
+                && (it.path().is_some() || it.coloncolon_token().is_none()) =>
+        {
+            it
+        }
+        _ => return vec![use_item.clone()],
+    };
+    let mut trees = Vec::new();
+    flatten_tree(None, &tree, &mut trees);
+    trees.into_iter().map(|tree| make::use_(use_item.visibility(), tree)).collect()
+}
+
+fn flatten_tree(prefix: Option<ast::Path>, tree: &ast::UseTree, acc: &mut Vec<ast::UseTree>) {
+    let is_self = tree.path().map_or(false, |path| {
+        path.qualifier().is_none() && path.segment().map_or(false, |it| it.self_token().is_some())
+    });
+    let path = match (prefix, tree.path()) {
+        (Some(prefix), Some(_)) if is_self => Some(prefix),
+        (Some(prefix), Some(path)) => Some(make::path_concat(prefix, path)),
+        (prefix, path) => prefix.or(path),
+    };
+    match (tree.use_tree_list(), path) {
+        (Some(list), path) => {
+            for tree in list.use_trees() {
+                flatten_tree(path.clone(), &tree, acc);
+            }
+        }
+        (None, Some(path)) => {
+            acc.push(make::use_tree(path, None, tree.rename(), tree.star_token().is_some()))
+        }
+        (None, None) => acc.push(tree.clone()),
+    }
+}
+
+/// Removes `use_item` along with the whitespace preceding it, or following it if there is none.
+fn remove_with_whitespace(use_item: &ast::Use) {
+    let whitespace = [Direction::Prev, Direction::Next].iter().find_map(|&dir| {
+        use_item
+            .syntax()
+            .siblings_with_tokens(dir)
+            .nth(1)
+            .filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+    });
+    if let Some(whitespace) = whitespace {
+        ted::remove(whitespace);
+    }
+    ted::remove(use_item.syntax());
+}
+
+fn import_group(use_item: &ast::Use) -> ImportGroup {
+    match use_item.use_tree().and_then(|it| it.path()) {
+        Some(path) => ImportGroup::new(&path),
+        None => ImportGroup::ExternCrate,
+    }
+}
+
+fn use_cmp(lhs: &ast::Use, rhs: &ast::Use) -> Ordering {
+    let (lhs, rhs) = match (lhs.use_tree(), rhs.use_tree()) {
+        (Some(lhs), Some(rhs)) => (lhs, rhs),
+        _ => return Ordering::Equal,
+    };
+    match (lhs.path(), rhs.path()) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(lhs_path), Some(rhs_path)) => use_tree_path_cmp(
+            &lhs_path,
+            lhs.use_tree_list().is_some(),
+            &rhs_path,
+            rhs.use_tree_list().is_some(),
+        ),
+    }
+}
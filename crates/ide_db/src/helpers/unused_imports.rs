@@ -0,0 +1,294 @@
+//! Finds `use` trees whose imported names are never used in their scope.
+use hir::{AsAssocItem, AssocItemContainer, PathResolution, Semantics};
+use rustc_hash::FxHashMap;
+use syntax::{
+    ast::{self, make, AstNode, AttrsOwner, NameOwner, VisibilityOwner},
+    match_ast, ted, Direction, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, TextRange,
+};
+
+use crate::{base_db::FileId, RootDatabase};
+
+/// Returns the leaf use trees of `file_id` that import something never used in their scope.
+///
+/// Glob imports, re-exports and `use` items with attributes are never reported. Names that
+/// can't be resolved, or appear inside macro inputs that can't be mapped into the expansion,
+/// are considered used.
+pub fn find_unused_imports(sema: &Semantics<RootDatabase>, file_id: FileId) -> Vec<ast::UseTree> {
+    let _p = profile::span("find_unused_imports");
+    let source_file = sema.parse(file_id);
+    let mut usages_by_scope: FxHashMap<SyntaxNode, ScopeUsages> = FxHashMap::default();
+    let mut res = Vec::new();
+
+    for use_item in source_file.syntax().descendants().filter_map(ast::Use::cast) {
+        if use_item.visibility().is_some()
+            || use_item.attrs().next().is_some()
+            || use_item.syntax().ancestors().any(|it| allows_unused_imports(&it))
+        {
+            continue;
+        }
+        let scope = match use_item.syntax().parent() {
+            Some(it) => it,
+            None => continue,
+        };
+        let leaves = use_item
+            .syntax()
+            .descendants()
+            .filter_map(ast::UseTree::cast)
+            .filter(|it| it.use_tree_list().is_none() && it.star_token().is_none());
+        for tree in leaves {
+            let usages = usages_by_scope
+                .entry(scope.clone())
+                .or_insert_with(|| ScopeUsages::collect(sema, &scope));
+            if is_unused(sema, &tree, usages) {
+                res.push(tree);
+            }
+        }
+    }
+    res
+}
+
+/// Removes `tree` from its `use` item, removing the whole item if nothing else is left in it.
+///
+/// `tree` must be mutable.
+pub fn remove_use_tree(tree: &ast::UseTree) {
+    let mut tree = tree.clone();
+    loop {
+        let parent = match tree.syntax().parent() {
+            Some(it) => it,
+            None => return,
+        };
+        match_ast! {
+            match parent {
+                ast::UseTreeList(list) => {
+                    if list.use_trees().count() > 1 {
+                        tree.remove();
+                        unwrap_single_tree(&list);
+                        return;
+                    }
+                    match list.syntax().parent().and_then(ast::UseTree::cast) {
+                        Some(parent_tree) => tree = parent_tree,
+                        None => return,
+                    }
+                },
+                ast::Use(use_item) => {
+                    remove_use_item(&use_item);
+                    return;
+                },
+                _ => return,
+            }
+        }
+    }
+}
+
+/// Turns `a::{b::c}` into `a::b::c` if `list` is left with a single tree.
+fn unwrap_single_tree(list: &ast::UseTreeList) {
+    let mut trees = list.use_trees();
+    let (single, parent) = match (trees.next(), trees.next(), list.parent_use_tree().path()) {
+        (Some(single), None, Some(parent)) => (single, parent),
+        _ => return,
+    };
+    let path = match single.path() {
+        Some(it) if it.segment().map_or(true, |it| it.self_token().is_none()) => it,
+        _ => return,
+    };
+    let tree = make::use_tree(
+        make::path_concat(parent, path),
+        single.use_tree_list(),
+        single.rename(),
+        single.star_token().is_some(),
+    )
+    .clone_for_update();
+    ted::replace(list.parent_use_tree().syntax(), tree.syntax());
+}
+
+/// Removes `use_item` together with the adjacent whitespace that spans fewer lines, so that blank
+/// lines separating it from its neighbours are kept.
+fn remove_use_item(use_item: &ast::Use) {
+    let whitespace = |dir| {
+        use_item
+            .syntax()
+            .siblings_with_tokens(dir)
+            .nth(1)
+            .filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+    };
+    let newlines = |it: &Option<SyntaxElement>| {
+        it.as_ref().map_or(0, |it| it.to_string().matches('\n').count())
+    };
+    let (prev, next) = (whitespace(Direction::Prev), whitespace(Direction::Next));
+    let to_remove = if prev.is_some() && newlines(&prev) <= newlines(&next) { prev } else { next };
+    if let Some(whitespace) = to_remove {
+        ted::remove(whitespace);
+    }
+    ted::remove(use_item.syntax());
+}
+
+/// The names referenced in a scope, together with the traits whose items are used there.
+struct ScopeUsages {
+    /// Name references that could refer to an import, by text. `None` if the reference can't be
+    /// resolved.
+    names: FxHashMap<String, Vec<(TextRange, Option<PathResolution>)>>,
+    /// Traits whose methods or associated items are used.
+    traits: Vec<hir::Trait>,
+    /// Names of method calls that failed to resolve.
+    unresolved_methods: Vec<String>,
+}
+
+impl ScopeUsages {
+    fn collect(sema: &Semantics<RootDatabase>, scope: &SyntaxNode) -> ScopeUsages {
+        let mut res = ScopeUsages {
+            names: FxHashMap::default(),
+            traits: Vec::new(),
+            unresolved_methods: Vec::new(),
+        };
+        for token in scope_tokens(scope) {
+            if token.kind() != SyntaxKind::IDENT {
+                continue;
+            }
+            let in_token_tree = token.ancestors().any(|it| ast::TokenTree::can_cast(it.kind()));
+            let token = if in_token_tree {
+                let descended = sema.descend_into_macros(token.clone());
+                if descended == token {
+                    // Either not a macro input, or the token doesn't make it into the expansion.
+                    res.names
+                        .entry(token.text().to_string())
+                        .or_default()
+                        .push((token.text_range(), None));
+                    continue;
+                }
+                descended
+            } else {
+                token
+            };
+            res.add_token(sema, &token);
+        }
+        res
+    }
+
+    fn add_token(&mut self, sema: &Semantics<RootDatabase>, token: &SyntaxToken) {
+        let name_ref = match token.parent().and_then(ast::NameRef::cast) {
+            Some(it) => it,
+            None => return,
+        };
+        if let Some(call) = name_ref.syntax().parent().and_then(ast::MethodCallExpr::cast) {
+            match sema.resolve_method_call(&call).and_then(|it| it.as_assoc_item(sema.db)) {
+                Some(item) => self.traits.extend(assoc_item_trait(sema.db, item)),
+                None => self.unresolved_methods.push(name_ref.text().to_string()),
+            }
+            return;
+        }
+        let path = match name_ref
+            .syntax()
+            .parent()
+            .and_then(ast::PathSegment::cast)
+            .map(|it| it.parent_path())
+        {
+            Some(it) => it,
+            None => return,
+        };
+        let in_nested_use_tree =
+            path.syntax().ancestors().find_map(ast::UseTree::cast).map_or(false, |it| {
+                it.syntax().parent().map_or(false, |it| ast::UseTreeList::can_cast(it.kind()))
+            });
+        if in_nested_use_tree {
+            // Only the first segment of a `use` can refer to an import of its scope.
+            return;
+        }
+        let resolution = sema.resolve_path(&path);
+        if path.qualifier().is_some() {
+            let item = match &resolution {
+                Some(PathResolution::AssocItem(it)) => Some(*it),
+                Some(PathResolution::Def(def)) => def.as_assoc_item(sema.db),
+                _ => None,
+            };
+            self.traits.extend(item.and_then(|it| assoc_item_trait(sema.db, it)));
+            return;
+        }
+        let range = sema.original_range(name_ref.syntax()).range;
+        self.names.entry(name_ref.text().to_string()).or_default().push((range, resolution));
+    }
+}
+
+fn is_unused(sema: &Semantics<RootDatabase>, tree: &ast::UseTree, usages: &ScopeUsages) -> bool {
+    let path = match tree.path() {
+        Some(it) => it,
+        None => return false,
+    };
+    let segment = match path.segment() {
+        Some(it) => it,
+        None => return false,
+    };
+    if segment.self_token().is_some()
+        || segment.super_token().is_some()
+        || segment.crate_token().is_some()
+    {
+        return false;
+    }
+    let resolution = match sema.resolve_path(&path) {
+        Some(it) => it,
+        None => return false,
+    };
+
+    if let PathResolution::Def(hir::ModuleDef::Trait(trait_)) = resolution {
+        if usages.traits.contains(&trait_) {
+            return false;
+        }
+        let has_unresolved_method = trait_.items(sema.db).into_iter().any(|item| {
+            item.name(sema.db).map_or(false, |name| {
+                usages.unresolved_methods.iter().any(|it| *it == name.to_string())
+            })
+        });
+        if has_unresolved_method {
+            return false;
+        }
+    }
+
+    let name = match tree.rename() {
+        Some(rename) => match rename.name() {
+            Some(name) => name.text().to_string(),
+            // `use Trait as _;`, only brings the trait's items into scope.
+            None => return matches!(resolution, PathResolution::Def(hir::ModuleDef::Trait(_))),
+        },
+        None => match segment.name_ref() {
+            Some(it) => it.text().to_string(),
+            None => return false,
+        },
+    };
+    let references = match usages.names.get(&name) {
+        Some(it) => it,
+        None => return true,
+    };
+    let tree_range = tree.syntax().text_range();
+    !references.iter().filter(|(range, _)| !tree_range.contains_range(*range)).any(|(_, it)| {
+        match it {
+            None => true,
+            Some(it) => *it == resolution,
+        }
+    })
+}
+
+/// All tokens of `scope`, skipping nested modules which don't see the scope's imports.
+fn scope_tokens(scope: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> + '_ {
+    scope.descendants_with_tokens().filter_map(|it| it.into_token()).filter(move |token| {
+        !token.ancestors().take_while(|it| it != scope).any(|it| ast::Module::can_cast(it.kind()))
+    })
+}
+
+fn assoc_item_trait(db: &RootDatabase, item: hir::AssocItem) -> Option<hir::Trait> {
+    match item.container(db) {
+        AssocItemContainer::Trait(it) => Some(it),
+        // Method resolution yields the trait's items, so items of impls are inherent.
+        AssocItemContainer::Impl(_) => None,
+    }
+}
+
+fn allows_unused_imports(node: &SyntaxNode) -> bool {
+    node.children().filter_map(ast::Attr::cast).any(|attr| match attr.as_simple_call() {
+        Some((name, args)) if name == "allow" => args
+            .syntax()
+            .text()
+            .to_string()
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .any(|lint| lint == "unused_imports" || lint == "unused"),
+        _ => false,
+    })
+}
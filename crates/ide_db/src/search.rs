@@ -103,7 +103,7 @@ impl SearchScope {
         SearchScope { entries }
     }
 
-    fn krate(db: &RootDatabase, of: hir::Crate) -> SearchScope {
+    pub fn krate(db: &RootDatabase, of: hir::Crate) -> SearchScope {
         let root_file = of.root_file(db);
         let source_root_id = db.file_source_root(root_file);
         let source_root = db.source_root(source_root_id);
@@ -10,7 +10,7 @@ use syntax::ast::{self, make};
 use crate::RootDatabase;
 
 /// Enum types that implement `std::ops::Try` trait.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TryEnum {
     Result,
     Option,
@@ -37,9 +37,17 @@
 // | Constraint    | Restricts placeholder
 //
 // | kind(literal) | Is a literal (e.g. `42` or `"forty two"`)
+// | type(T)       | Has type `T`, possibly after auto-dereferencing (e.g. `type(Vec<_>)` or `type([_])`)
+// | impls(Trait)  | Has a type that implements `Trait` (e.g. `impls(Clone)`)
 // | not(a)        | Negates the constraint `a`
 // |===
 //
+// In `type(T)`, `_` matches any type and type arguments that are omitted also match any type.
+// Paths in constraints are resolved in the same scope as paths in the search pattern. Type
+// constraints apply to expressions, patterns and types, so they can also be used on placeholders in
+// pattern or type positions. For example `${v:type([_])}.iter().count() ==>> $v.len()` only rewrites
+// calls where `$v` is a slice, or something that dereferences to a slice such as a `Vec`.
+//
 // Available via the command `rust-analyzer.ssr`.
 //
 // ```rust
@@ -343,6 +351,11 @@ impl Match {
     pub fn matched_text(&self) -> String {
         self.matched_node.text().to_string()
     }
+
+    /// The range of the matched code in the original file.
+    pub fn range(&self) -> FileRange {
+        self.range
+    }
 }
 
 impl std::error::Error for SsrError {}
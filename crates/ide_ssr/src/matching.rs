@@ -6,7 +6,7 @@ use crate::{
     resolving::{ResolvedPattern, ResolvedRule, UfcsCallInfo},
     SsrMatches,
 };
-use hir::Semantics;
+use hir::{HirDisplay, Semantics};
 use ide_db::base_db::FileRange;
 use rustc_hash::FxHashMap;
 use std::{cell::Cell, iter::Peekable};
@@ -177,6 +177,11 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
         // Handle placeholders.
         if let Some(placeholder) = self.get_placeholder_for_node(pattern) {
             for constraint in &placeholder.constraints {
+                // Constraints that need type information are expensive, so we defer them to the
+                // second phase.
+                if matches!(phase, Phase::First) && constraint.needs_types() {
+                    continue;
+                }
                 self.check_constraint(constraint, code)?;
             }
             if let Phase::Second(matches_out) = phase {
@@ -329,10 +334,128 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
                     fail_match!("Constraint {:?} failed for '{}'", constraint, code.text());
                 }
             }
+            Constraint::Type(pattern_type) => {
+                let code_type = self.type_of_node(code)?;
+                if !code_type.autoderef(self.sema.db).any(|ty| self.type_matches(pattern_type, &ty))
+                {
+                    fail_match!(
+                        "Code '{}' has type `{}`, not `{}`",
+                        code.text(),
+                        code_type.display(self.sema.db),
+                        pattern_type
+                    );
+                }
+            }
+            Constraint::Impls(path) => {
+                let code_type = self.type_of_node(code)?;
+                let trait_ = match self.rule.pattern.constraint_paths.get(path.syntax()) {
+                    Some(hir::PathResolution::Def(hir::ModuleDef::Trait(trait_))) => *trait_,
+                    _ => fail_match!("Trait `{}` wasn't resolved", path),
+                };
+                if !code_type.impls_trait_uniquely(self.sema.db, trait_, &[]) {
+                    fail_match!(
+                        "Code '{}' has type `{}`, which doesn't implement `{}`",
+                        code.text(),
+                        code_type.display(self.sema.db),
+                        path
+                    );
+                }
+            }
         }
         Ok(())
     }
 
+    /// Returns the type of an expression or pattern, or the type that a type reference refers to.
+    fn type_of_node(&self, code: &SyntaxNode) -> Result<hir::Type, MatchFailed> {
+        let ty = if let Some(expr) = ast::Expr::cast(code.clone()) {
+            self.sema.type_of_expr(&expr)
+        } else if let Some(pat) = ast::Pat::cast(code.clone()) {
+            self.sema.type_of_pat(&pat)
+        } else if let Some(ty) = ast::Type::cast(code.clone()) {
+            self.sema.resolve_type(&ty)
+        } else if let Some(ty) = ast::TypeArg::cast(code.clone()).and_then(|arg| arg.ty()) {
+            self.sema.resolve_type(&ty)
+        } else {
+            None
+        };
+        ty.filter(|ty| !ty.is_unknown())
+            .ok_or_else(|| match_error!("Failed to get type of '{}'", code.text()))
+    }
+
+    /// Checks whether `ty` has the shape of the type in a `type(..)` constraint. Paths in the
+    /// constraint were resolved in the scope of the rule, so we compare what they resolve to.
+    fn type_matches(&self, pattern: &ast::Type, ty: &hir::Type) -> bool {
+        let db = self.sema.db;
+        match pattern {
+            ast::Type::InferType(_) => true,
+            ast::Type::ParenType(paren) => {
+                paren.ty().map_or(false, |inner| self.type_matches(&inner, ty))
+            }
+            ast::Type::RefType(reference) => {
+                if reference.mut_token().is_some() && !ty.is_mutable_reference() {
+                    return false;
+                }
+                match (reference.ty(), ty.remove_ref()) {
+                    (Some(inner), Some(ty)) => self.type_matches(&inner, &ty),
+                    _ => false,
+                }
+            }
+            ast::Type::SliceType(slice) => match (slice.ty(), ty.as_slice()) {
+                (Some(inner), Some(ty)) => self.type_matches(&inner, &ty),
+                _ => false,
+            },
+            ast::Type::ArrayType(array) => match (array.ty(), ty.as_array()) {
+                (Some(inner), Some(ty)) => self.type_matches(&inner, &ty),
+                _ => false,
+            },
+            ast::Type::TupleType(tuple) => {
+                let fields = ty.tuple_fields(db);
+                let pattern_fields: Vec<_> = tuple.fields().collect();
+                (!fields.is_empty() || ty.is_unit())
+                    && fields.len() == pattern_fields.len()
+                    && pattern_fields.iter().zip(&fields).all(|(p, ty)| self.type_matches(p, ty))
+            }
+            ast::Type::PathType(path_type) => {
+                let path = match path_type.path() {
+                    Some(path) => path,
+                    None => return false,
+                };
+                let resolution = match self.rule.pattern.constraint_paths.get(path.syntax()) {
+                    Some(resolution) => resolution,
+                    None => return false,
+                };
+                match resolution {
+                    hir::PathResolution::Def(hir::ModuleDef::Adt(adt)) => {
+                        if ty.as_adt() != Some(*adt) {
+                            return false;
+                        }
+                        let pattern_args = path
+                            .segment()
+                            .and_then(|segment| segment.generic_arg_list())
+                            .into_iter()
+                            .flat_map(|args| args.generic_args())
+                            .filter_map(|arg| match arg {
+                                ast::GenericArg::TypeArg(arg) => arg.ty(),
+                                _ => None,
+                            });
+                        // Type arguments that aren't given in the pattern match anything.
+                        pattern_args
+                            .zip(ty.type_arguments())
+                            .all(|(p, ty)| self.type_matches(&p, &ty))
+                    }
+                    hir::PathResolution::Def(hir::ModuleDef::BuiltinType(builtin)) => {
+                        ty.as_builtin() == Some(*builtin)
+                    }
+                    hir::PathResolution::Def(hir::ModuleDef::TypeAlias(alias)) => {
+                        alias.ty(db).equals_normalized(db, ty)
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
     /// Paths are matched based on whether they refer to the same thing, even if they're written
     /// differently.
     fn attempt_match_path(
@@ -608,7 +731,6 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
         pattern_type: &hir::Type,
         expr: &ast::Expr,
     ) -> Result<usize, MatchFailed> {
-        use hir::HirDisplay;
         let code_type = self.sema.type_of_expr(&expr).ok_or_else(|| {
             match_error!("Failed to get receiver type for `{}`", expr.syntax().text())
         })?;
@@ -726,6 +848,17 @@ impl PlaceholderMatch {
     }
 }
 
+impl Constraint {
+    /// Returns whether checking this constraint requires type inference.
+    fn needs_types(&self) -> bool {
+        match self {
+            Constraint::Kind(_) => false,
+            Constraint::Not(sub) => sub.needs_types(),
+            Constraint::Type(_) | Constraint::Impls(_) => true,
+        }
+    }
+}
+
 impl NodeKind {
     fn matches(&self, node: &SyntaxNode) -> Result<(), MatchFailed> {
         let ok = match self {
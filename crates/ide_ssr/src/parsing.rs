@@ -47,6 +47,11 @@ pub(crate) struct Var(pub(crate) String);
 pub(crate) enum Constraint {
     Kind(NodeKind),
     Not(Box<Constraint>),
+    /// The type of the matched code, possibly after auto-dereferencing, has the given shape. `_`
+    /// matches any type.
+    Type(ast::Type),
+    /// The type of the matched code implements the given trait.
+    Impls(ast::Path),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -333,10 +338,46 @@ fn parse_constraint(tokens: &mut std::vec::IntoIter<Token>) -> Result<Constraint
             expect_token(tokens, ")")?;
             Ok(Constraint::Not(Box::new(sub)))
         }
+        "type" => {
+            let text = parse_constraint_argument(tokens)?;
+            let ty = ast::Type::parse(&text)
+                .map_err(|()| SsrError(format!("Invalid type in type constraint: `{}`", text)))?;
+            Ok(Constraint::Type(ty))
+        }
+        "impls" => {
+            let text = parse_constraint_argument(tokens)?;
+            let path = ast::Path::parse(&text)
+                .map_err(|()| SsrError(format!("Invalid trait in impls constraint: `{}`", text)))?;
+            Ok(Constraint::Impls(path))
+        }
         x => bail!("Unsupported constraint type '{}'", x),
     }
 }
 
+/// Returns the source text between the parenthesis of a constraint like `type(Vec<_>)`, which may
+/// itself contain balanced parenthesis.
+fn parse_constraint_argument(tokens: &mut std::vec::IntoIter<Token>) -> Result<String, SsrError> {
+    expect_token(tokens, "(")?;
+    let mut text = String::new();
+    let mut depth = 0;
+    loop {
+        let t = tokens
+            .next()
+            .ok_or_else(|| SsrError::new("Unexpected end of constraint while looking for ')'"))?;
+        match t.kind {
+            T!['('] => depth += 1,
+            T![')'] if depth == 0 => break,
+            T![')'] => depth -= 1,
+            _ => {}
+        }
+        text.push_str(&t.text);
+    }
+    if text.trim().is_empty() {
+        bail!("Constraint is missing an argument");
+    }
+    Ok(text)
+}
+
 fn expect_token(tokens: &mut std::vec::IntoIter<Token>, expected: &str) -> Result<(), SsrError> {
     if let Some(t) = tokens.next() {
         if t.text == expected {
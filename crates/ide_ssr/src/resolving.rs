@@ -1,6 +1,6 @@
 //! This module is responsible for resolving paths within rules.
 
-use crate::errors::error;
+use crate::errors::{bail, error};
 use crate::{parsing, SsrError};
 use ide_db::base_db::FilePosition;
 use parsing::Placeholder;
@@ -25,6 +25,10 @@ pub(crate) struct ResolvedPattern {
     pub(crate) resolved_paths: FxHashMap<SyntaxNode, ResolvedPath>,
     pub(crate) ufcs_function_calls: FxHashMap<SyntaxNode, UfcsCallInfo>,
     pub(crate) contains_self: bool,
+    // Paths within placeholder constraints, e.g. the `Vec` in `${a:type(Vec<_>)}`, resolved in the
+    // scope of the rule. These are kept separate from `resolved_paths`, since we don't want to search
+    // for usages of them.
+    pub(crate) constraint_paths: FxHashMap<SyntaxNode, hir::PathResolution>,
 }
 
 pub(crate) struct ResolvedPath {
@@ -97,6 +101,12 @@ impl Resolver<'_, '_> {
                 None
             })
             .collect();
+        let mut constraint_paths = FxHashMap::default();
+        for placeholder in self.placeholders_by_stand_in.values() {
+            for constraint in &placeholder.constraints {
+                self.resolve_constraint(constraint, &mut constraint_paths)?;
+            }
+        }
         let contains_self =
             pattern.descendants_with_tokens().any(|node_or_token| match node_or_token {
                 SyntaxElement::Token(t) => t.kind() == T![self],
@@ -108,9 +118,49 @@ impl Resolver<'_, '_> {
             placeholders_by_stand_in: self.placeholders_by_stand_in.clone(),
             ufcs_function_calls,
             contains_self,
+            constraint_paths,
         })
     }
 
+    fn resolve_constraint(
+        &self,
+        constraint: &parsing::Constraint,
+        constraint_paths: &mut FxHashMap<SyntaxNode, hir::PathResolution>,
+    ) -> Result<(), SsrError> {
+        use syntax::ast::AstNode;
+        match constraint {
+            parsing::Constraint::Kind(_) => {}
+            parsing::Constraint::Not(sub) => self.resolve_constraint(sub, constraint_paths)?,
+            parsing::Constraint::Type(ty) => {
+                for path in ty.syntax().descendants().filter_map(ast::PathType::cast) {
+                    let path = match path.path() {
+                        Some(path) => path,
+                        None => continue,
+                    };
+                    let resolution = self
+                        .resolution_scope
+                        .resolve_path(&path)
+                        .ok_or_else(|| error!("Failed to resolve type `{}`", path))?;
+                    constraint_paths.insert(path.syntax().clone(), resolution);
+                }
+            }
+            parsing::Constraint::Impls(path) => {
+                let db = self.resolution_scope.scope.db;
+                let trait_ = match self.resolution_scope.resolve_path(path) {
+                    Some(hir::PathResolution::Def(hir::ModuleDef::Trait(trait_))) => trait_,
+                    _ => bail!("Failed to resolve trait `{}`", path),
+                };
+                // The `Self` parameter is the only one we can fill in.
+                if hir::GenericDef::from(trait_).type_params(db).len() > 1 {
+                    bail!("Trait `{}` has type parameters, which aren't supported", path);
+                }
+                constraint_paths
+                    .insert(path.syntax().clone(), hir::PathResolution::Def(trait_.into()));
+            }
+        }
+        Ok(())
+    }
+
     fn resolve(
         &self,
         node: SyntaxNode,
@@ -524,6 +524,131 @@ fn literal_constraint() {
     assert_matches("Some(${a:not(kind(literal))})", code, &["Some(x1)", "Some(40 + 2)"]);
 }
 
+#[test]
+fn type_constraint() {
+    let code = r#"
+        #[lang = "deref"]
+        trait Deref { type Target; fn deref(&self) -> &Self::Target; }
+        struct Vec<T>(T);
+        impl<T> Deref for Vec<T> { type Target = [T]; fn deref(&self) -> &[T] { loop {} } }
+        struct Wrapper(i32);
+        fn f1(v: Vec<i32>, w: Vec<u8>, r: &Vec<i32>, s: &[i32], x: Wrapper) {
+            foo(v);
+            foo(w);
+            foo(r);
+            foo(s);
+            foo(x);
+        }
+        fn foo<T>(t: T) {}
+        "#;
+    assert_matches("foo(${a:type(Vec<_>)})", code, &["foo(v)", "foo(w)", "foo(r)"]);
+    assert_matches("foo(${a:type(Vec<i32>)})", code, &["foo(v)", "foo(r)"]);
+    assert_matches("foo(${a:type(&Vec<_>)})", code, &["foo(r)"]);
+    assert_matches("foo(${a:type([i32])})", code, &["foo(v)", "foo(r)", "foo(s)"]);
+    assert_matches("foo(${a:not(type(Vec<_>))})", code, &["foo(s)", "foo(x)"]);
+}
+
+#[test]
+fn type_alias_constraint() {
+    let code = r#"
+        trait Tr { type Out; }
+        struct S;
+        impl Tr for S { type Out = u32; }
+        type Id = u32;
+        type Proj = <S as Tr>::Out;
+        fn f1(a: u32, b: i64, c: Proj) {
+            foo(a);
+            foo(b);
+            foo(c);
+        }
+        fn foo<T>(t: T) {}
+        "#;
+    assert_matches("foo(${a:type(Id)})", code, &["foo(a)", "foo(c)"]);
+    assert_matches("foo(${a:type(Proj)})", code, &["foo(a)", "foo(c)"]);
+}
+
+#[test]
+fn type_constraint_replacement() {
+    assert_ssr_transform(
+        "${v:type(Vec<_>)}.iter().count() ==>> $v.len()",
+        r#"
+        struct Vec<T>(T);
+        impl<T> Vec<T> { fn iter(&self) -> Iter { Iter } fn len(&self) -> usize { 0 } }
+        struct Iter;
+        impl Iter { fn count(self) -> usize { 0 } }
+        struct Other;
+        impl Other { fn iter(&self) -> Iter { Iter } }
+        fn f1(v: Vec<i32>, o: Other) {
+            let a = v.iter().count();
+            let b = o.iter().count();
+        }
+        "#,
+        expect![[r#"
+            struct Vec<T>(T);
+            impl<T> Vec<T> { fn iter(&self) -> Iter { Iter } fn len(&self) -> usize { 0 } }
+            struct Iter;
+            impl Iter { fn count(self) -> usize { 0 } }
+            struct Other;
+            impl Other { fn iter(&self) -> Iter { Iter } }
+            fn f1(v: Vec<i32>, o: Other) {
+                let a = v.len();
+                let b = o.iter().count();
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn impls_constraint() {
+    let code = r#"
+        trait Clone { fn clone(&self) -> Self; }
+        struct A;
+        struct B;
+        impl Clone for A { fn clone(&self) -> A { A } }
+        fn f1(a: A, b: B) {
+            foo(a);
+            foo(b);
+            foo(unknown());
+        }
+        fn foo<T>(t: T) {}
+        "#;
+    assert_matches("foo(${a:impls(Clone)})", code, &["foo(a)"]);
+    assert_matches("foo(${a:not(impls(Clone))})", code, &["foo(b)", "foo(unknown())"]);
+}
+
+#[test]
+fn constraints_in_type_and_pattern_positions() {
+    let code = r#"
+        trait Clone { fn clone(&self) -> Self; }
+        struct A;
+        struct B;
+        impl Clone for A { fn clone(&self) -> A { A } }
+        struct Wrap<T>(T);
+        fn f1(a: Wrap<A>, b: Wrap<B>) {
+            let Wrap(x) = a;
+            let Wrap(y) = b;
+        }
+        "#;
+    assert_matches("Wrap<${t:impls(Clone)}>", code, &["Wrap<A>"]);
+    assert_matches("Wrap(${p:type(B)})", code, &["Wrap(y)"]);
+}
+
+#[test]
+fn invalid_type_constraints() {
+    assert_eq!(
+        parse_error_text("foo(${a:type()}) ==>> bar($a)"),
+        "Parse error: Constraint is missing an argument"
+    );
+    assert_eq!(
+        parse_error_text("${a:type(Vec<_> ==>> $a"),
+        "Parse error: Unexpected end of constraint while looking for ')'"
+    );
+    let (db, position, selections) = single_file("fn foo() {}");
+    let mut match_finder = MatchFinder::in_context(&db, position, selections);
+    let err = match_finder.add_rule("foo(${a:impls(Missing)}) ==>> $a".parse().unwrap());
+    assert_eq!(err.unwrap_err().to_string(), "Parse error: Failed to resolve trait `Missing`");
+}
+
 #[test]
 fn match_reordered_struct_instantiation() {
     assert_matches(
@@ -14,8 +14,31 @@ pub(crate) fn expand_rules(
     rules: &[crate::Rule],
     input: &tt::Subtree,
 ) -> ExpandResult<tt::Subtree> {
-    let mut match_: Option<(matcher::Match, &crate::Rule)> = None;
-    for rule in rules {
+    expand_rules_with(rules, input, &mut |_, _| ()).1
+}
+
+/// Like `expand_rules`, but records how `input` matched against each of the rules that were
+/// tried.
+pub(crate) fn trace_rules(rules: &[crate::Rule], input: &tt::Subtree) -> ExpansionTrace {
+    let mut traces = Vec::new();
+    let (used_rule, expansion) = expand_rules_with(rules, input, &mut |match_, err| {
+        traces.push(RuleTrace { bindings: match_.bindings.to_meta_vars(), err: err.cloned() })
+    });
+    ExpansionTrace { rules: traces, used_rule, expansion }
+}
+
+/// Expands `input` with the first rule that matches it, calling `on_rule` with the match and the
+/// error (if any) of every rule that is tried, in order. Returns the index of the rule used
+/// for the expansion along with the expansion.
+fn expand_rules_with(
+    rules: &[crate::Rule],
+    input: &tt::Subtree,
+    on_rule: &mut dyn FnMut(&matcher::Match, Option<&ExpandError>),
+) -> (Option<usize>, ExpandResult<tt::Subtree>) {
+    let mut match_: Option<(matcher::Match, usize)> = None;
+    // The error of the rule which matched the most tokens before failing.
+    let mut furthest_err: Option<(usize, ExpandError)> = None;
+    for (idx, rule) in rules.iter().enumerate() {
         let new_match = matcher::match_(&rule.lhs, input);
 
         if new_match.err.is_none() {
@@ -24,8 +47,26 @@ pub(crate) fn expand_rules(
             // `test_repeat_bad_var` test fail.
             let ExpandResult { value, err: transcribe_err } =
                 transcriber::transcribe(&rule.rhs, &new_match.bindings);
+            on_rule(&new_match, transcribe_err.as_ref());
             if transcribe_err.is_none() {
-                return ExpandResult::ok(value);
+                return (Some(idx), ExpandResult::ok(value));
+            }
+        } else {
+            on_rule(&new_match, new_match.err.as_ref());
+            match (&mut furthest_err, &new_match.err) {
+                // Rules failing at the same token: list what any of them expected.
+                (
+                    Some((progress, ExpandError::NoRuleExpectedToken(prev))),
+                    Some(ExpandError::NoRuleExpectedToken(new)),
+                ) if *progress == new_match.progress => {
+                    for it in &new.expected {
+                        if !prev.expected.contains(it) {
+                            prev.expected.push(it.clone());
+                        }
+                    }
+                }
+                (Some((progress, _)), _) if *progress >= new_match.progress => (),
+                (_, err) => furthest_err = err.clone().map(|err| (new_match.progress, err)),
             }
         }
         // Use the rule if we matched more tokens, or bound variables count
@@ -33,19 +74,77 @@ pub(crate) fn expand_rules(
             if (new_match.unmatched_tts, -(new_match.bound_count as i32))
                 < (prev_match.unmatched_tts, -(prev_match.bound_count as i32))
             {
-                match_ = Some((new_match, rule));
+                match_ = Some((new_match, idx));
             }
         } else {
-            match_ = Some((new_match, rule));
+            match_ = Some((new_match, idx));
         }
     }
-    if let Some((match_, rule)) = match_ {
+    if let Some((match_, idx)) = match_ {
         // if we got here, there was no match without errors
         let ExpandResult { value, err: transcribe_err } =
-            transcriber::transcribe(&rule.rhs, &match_.bindings);
-        ExpandResult { value, err: match_.err.or(transcribe_err) }
+            transcriber::transcribe(&rules[idx].rhs, &match_.bindings);
+        let err = match match_.err {
+            Some(err) => Some(furthest_err.map_or(err, |(_, err)| err)),
+            None => transcribe_err,
+        };
+        (Some(idx), ExpandResult { value, err })
     } else {
-        ExpandResult::only_err(ExpandError::NoMatchingRule)
+        (None, ExpandResult::only_err(ExpandError::NoMatchingRule))
+    }
+}
+
+/// How a macro invocation was matched against the rules of a macro, for debugging macros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpansionTrace {
+    /// The rules that were tried, in order. Rules after the first one that matches aren't tried.
+    pub rules: Vec<RuleTrace>,
+    /// The index of the rule used for the expansion. If no rule matches, this is the rule which
+    /// came closest to matching, and `expansion` contains an error.
+    pub used_rule: Option<usize>,
+    pub expansion: ExpandResult<tt::Subtree>,
+}
+
+/// The result of matching a macro invocation against one rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTrace {
+    /// The meta variables bound by the rule, sorted by name. These can be incomplete if the rule
+    /// doesn't match.
+    pub bindings: Vec<(SmolStr, MetaVarBinding)>,
+    /// Why the rule didn't match or couldn't be transcribed, `None` if it matched.
+    pub err: Option<ExpandError>,
+}
+
+/// The tokens a meta variable is bound to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaVarBinding {
+    Fragment(tt::TokenTree),
+    /// A variable inside a repetition, with one binding per repetition.
+    Nested(Vec<MetaVarBinding>),
+    /// A variable inside a repetition which matched zero times.
+    Empty,
+}
+
+impl Bindings {
+    fn to_meta_vars(&self) -> Vec<(SmolStr, MetaVarBinding)> {
+        let mut res: Vec<_> =
+            self.inner.iter().map(|(name, it)| (name.clone(), it.to_meta_var())).collect();
+        res.sort_by(|(a, _), (b, _)| a.cmp(b));
+        res
+    }
+}
+
+impl Binding {
+    fn to_meta_var(&self) -> MetaVarBinding {
+        match self {
+            Binding::Fragment(Fragment::Tokens(tt)) | Binding::Fragment(Fragment::Ast(tt)) => {
+                MetaVarBinding::Fragment(tt.clone())
+            }
+            Binding::Nested(it) => {
+                MetaVarBinding::Nested(it.iter().map(Self::to_meta_var).collect())
+            }
+            Binding::Empty => MetaVarBinding::Empty,
+        }
     }
 }
 
@@ -136,6 +235,43 @@ mod tests {
         // Add an err test case for ($($i:ident)) => ($())
     }
 
+    #[test]
+    fn test_trace_rules() {
+        let rules = create_rules(&format_macro(
+            "
+            ($i:ident) => (fn $i() {});
+            ($($e:expr),*) => ($($e;)*);
+            ($e:expr) => ($e);
+            ",
+        ));
+        let source_file = ast::SourceFile::parse("foo!(1, 2 + 2);").ok().unwrap();
+        let macro_invocation =
+            source_file.syntax().descendants().find_map(ast::MacroCall::cast).unwrap();
+        let (invocation_tt, _) = ast_to_token_tree(&macro_invocation.token_tree().unwrap());
+        let trace = trace_rules(&rules.rules, &invocation_tt);
+
+        assert_eq!(trace.used_rule, Some(1));
+        assert_eq!(trace.expansion.err, None);
+        assert_eq!(trace.expansion.value.to_string(), "1 ;2 + 2 ;");
+        assert_eq!(trace.rules.len(), 2);
+        assert!(trace.rules[0].err.is_some());
+        assert_eq!(trace.rules[1].err, None);
+        match &trace.rules[1].bindings[..] {
+            [(name, MetaVarBinding::Nested(values))] => {
+                assert_eq!(name, "e");
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|it| match it {
+                        MetaVarBinding::Fragment(tt) => tt.to_string(),
+                        _ => panic!("unexpected binding {:?}", it),
+                    })
+                    .collect();
+                assert_eq!(values, vec!["1", "2 + 2"]);
+            }
+            bindings => panic!("unexpected bindings {:?}", bindings),
+        }
+    }
+
     fn assert_err(macro_body: &str, invocation: &str, err: ExpandError) {
         assert_eq!(
             expand_first(&create_rules(&format_macro(macro_body)), invocation).err,
@@ -65,7 +65,7 @@ use crate::{
     expander::{Binding, Bindings, Fragment},
     parser::{Op, OpDelimited, OpDelimitedIter, RepeatKind, Separator},
     tt_iter::TtIter,
-    ExpandError, MetaTemplate,
+    ExpandError, MatchFailure, MetaTemplate,
 };
 
 use super::ExpandResult;
@@ -109,6 +109,8 @@ pub(super) struct Match {
     pub(super) unmatched_tts: usize,
     /// The number of bound variables
     pub(super) bound_count: usize,
+    /// How many input tokens were matched before matching failed.
+    pub(super) progress: usize,
 }
 
 impl Match {
@@ -363,6 +365,7 @@ struct MatchState<'t> {
 /// - `eof_items`: the set of items that would be valid if this was the EOF.
 /// - `bb_items`: the set of items that are waiting for the black-box parser.
 /// - `error_items`: the set of items in errors, used for error-resilient parsing
+/// - `expected`: descriptions of the tokens the items expect next, used for error messages
 fn match_loop_inner<'t>(
     src: TtIter<'t>,
     stack: &[TtIter<'t>],
@@ -373,6 +376,7 @@ fn match_loop_inner<'t>(
     next_items: &mut Vec<MatchState<'t>>,
     eof_items: &mut SmallVec<[MatchState<'t>; 1]>,
     error_items: &mut SmallVec<[MatchState<'t>; 1]>,
+    expected: &mut Vec<String>,
 ) {
     macro_rules! try_push {
         ($items: expr, $it:expr) => {
@@ -417,6 +421,7 @@ fn match_loop_inner<'t>(
                     let sep_len = item.sep.as_ref().map_or(0, Separator::tt_count);
                     if item.sep.is_some() && sep_idx != sep_len {
                         let sep = item.sep.as_ref().unwrap();
+                        expected.extend(separator_text(sep, sep_idx).map(|it| format!("`{}`", it)));
                         if src.clone().expect_separator(&sep, sep_idx) {
                             item.dot.next();
                             item.sep_parsed = Some(sep_idx + 1);
@@ -468,6 +473,9 @@ fn match_loop_inner<'t>(
                 })
             }
             OpDelimited::Op(Op::Subtree { tokens, delimiter }) => {
+                if let Some(delimiter) = delimiter {
+                    expected.push(format!("`{}`", delimiter_text(Some(delimiter.kind)).0));
+                }
                 if let Ok(subtree) = src.clone().expect_subtree() {
                     if subtree.delimiter_kind() == delimiter.map(|it| it.kind) {
                         item.stack.push(item.dot);
@@ -478,6 +486,7 @@ fn match_loop_inner<'t>(
             }
             OpDelimited::Op(Op::Var { kind, name, .. }) => {
                 if let Some(kind) = kind {
+                    expected.push(kind.to_string());
                     let mut fork = src.clone();
                     let match_res = match_meta_var(kind.as_str(), &mut fork);
                     match match_res.err {
@@ -504,6 +513,7 @@ fn match_loop_inner<'t>(
                 }
             }
             OpDelimited::Op(Op::Leaf(leaf)) => {
+                expected.push(format!("`{}`", leaf));
                 if let Err(err) = match_leaf(&leaf, &mut src.clone()) {
                     res.add_err(err);
                     item.is_error = true;
@@ -519,6 +529,9 @@ fn match_loop_inner<'t>(
                 }
             }
             OpDelimited::Close => {
+                if let Some(delimiter) = item.dot.delimiter() {
+                    expected.push(format!("`{}`", delimiter_text(Some(delimiter.kind)).1));
+                }
                 let is_delim_closed = src.peek_n(0).is_none() && !stack.is_empty();
                 if is_delim_closed {
                     item.dot.next();
@@ -529,9 +542,11 @@ fn match_loop_inner<'t>(
     }
 }
 
-fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
-    let mut src = TtIter::new(src);
+fn match_loop(pattern: &MetaTemplate, input: &tt::Subtree) -> Match {
+    let mut src = TtIter::new(input);
     let mut stack: SmallVec<[TtIter; 1]> = SmallVec::new();
+    // The delimiters of `src` and of the token trees in `stack`, outermost first.
+    let mut delimiters: SmallVec<[Option<tt::Delimiter>; 1]> = smallvec![input.delimiter];
     let mut res = Match::default();
     let mut error_recover_item = None;
 
@@ -555,6 +570,7 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
         let mut bb_items = SmallVec::new();
         let mut eof_items = SmallVec::new();
         let mut error_items = SmallVec::new();
+        let mut expected = Vec::new();
 
         stdx::always!(next_items.is_empty());
 
@@ -568,6 +584,7 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
             &mut next_items,
             &mut eof_items,
             &mut error_items,
+            &mut expected,
         );
         stdx::always!(cur_items.is_empty());
 
@@ -590,7 +607,11 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
                 if let Some(item) = error_recover_item {
                     res.bindings = bindings_builder.build(&item);
                 }
-                res.add_err(ExpandError::UnexpectedToken);
+                if eof_items.is_empty() {
+                    res.fail(&src, &delimiters, expected);
+                } else {
+                    res.add_err(ExpandError::UnexpectedToken);
+                }
             }
             return res;
         }
@@ -604,12 +625,16 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
             || (!bb_items.is_empty() && !next_items.is_empty())
             || bb_items.len() > 1
         {
+            if bb_items.is_empty() && next_items.is_empty() {
+                res.fail(&src, &delimiters, expected);
+            } else {
+                res.add_err(err!("leftover tokens"));
+            }
             res.unmatched_tts += src.len();
             while let Some(it) = stack.pop() {
                 src = it;
                 res.unmatched_tts += src.len();
             }
-            res.add_err(err!("leftover tokens"));
 
             if let Some(error_reover_item) = error_recover_item {
                 res.bindings = bindings_builder.build(&error_reover_item);
@@ -624,10 +649,17 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
             match src.next() {
                 Some(tt::TokenTree::Subtree(subtree)) => {
                     stack.push(src.clone());
+                    delimiters.push(subtree.delimiter);
                     src = TtIter::new(subtree);
+                    res.progress += 1;
+                }
+                Some(tt::TokenTree::Leaf(_)) => res.progress += 1,
+                None if !stack.is_empty() => {
+                    src = stack.pop().unwrap();
+                    delimiters.pop();
+                    res.progress += 1;
                 }
-                None if !stack.is_empty() => src = stack.pop().unwrap(),
-                _ => (),
+                None => (),
             }
         }
         // Finally, we have the case where we need to call the black-box parser to get some
@@ -650,6 +682,8 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
                 if let Some(err) = match_res.err {
                     res.add_err(err);
                 }
+                res.progress +=
+                    src.clone().take(src.len() - iter.len()).map(token_count).sum::<usize>();
                 src = iter.clone();
                 item.dot.next();
             } else {
@@ -661,6 +695,72 @@ fn match_loop(pattern: &MetaTemplate, src: &tt::Subtree) -> Match {
     }
 }
 
+impl Match {
+    /// Records that no item matches the next token of `src`.
+    fn fail(&mut self, src: &TtIter, delimiters: &[Option<tt::Delimiter>], expected: Vec<String>) {
+        let (token, text) = match src.peek_n(0) {
+            Some(tt::TokenTree::Leaf(leaf)) => {
+                let id = match leaf {
+                    tt::Leaf::Ident(it) => it.id,
+                    tt::Leaf::Literal(it) => it.id,
+                    tt::Leaf::Punct(it) => it.id,
+                };
+                (Some(id), SmolStr::new(leaf.to_string()))
+            }
+            Some(tt::TokenTree::Subtree(subtree)) => (
+                subtree.delimiter.map(|it| it.id),
+                SmolStr::new(delimiter_text(subtree.delimiter_kind()).0),
+            ),
+            None => {
+                let delimiter = delimiters.last().copied().flatten();
+                (
+                    delimiter.map(|it| it.id),
+                    SmolStr::new(delimiter_text(delimiter.map(|it| it.kind)).1),
+                )
+            }
+        };
+        let mut deduped = Vec::new();
+        for it in expected {
+            if !deduped.contains(&it) {
+                deduped.push(it);
+            }
+        }
+        let failure = MatchFailure {
+            token: token.filter(|&it| it != tt::TokenId::unspecified()),
+            text,
+            end_of_input: src.peek_n(0).is_none() && delimiters.len() == 1,
+            expected: deduped,
+        };
+        self.err = Some(ExpandError::NoRuleExpectedToken(Box::new(failure)));
+        self.err_count += 1;
+    }
+}
+
+fn token_count(tt: &tt::TokenTree) -> usize {
+    match tt {
+        tt::TokenTree::Leaf(_) => 1,
+        tt::TokenTree::Subtree(subtree) => subtree.count() + 2,
+    }
+}
+
+fn delimiter_text(kind: Option<tt::DelimiterKind>) -> (&'static str, &'static str) {
+    match kind {
+        Some(tt::DelimiterKind::Parenthesis) => ("(", ")"),
+        Some(tt::DelimiterKind::Brace) => ("{", "}"),
+        Some(tt::DelimiterKind::Bracket) => ("[", "]"),
+        None => ("", ""),
+    }
+}
+
+fn separator_text(separator: &Separator, idx: usize) -> Option<SmolStr> {
+    match separator {
+        Separator::Literal(it) if idx == 0 => Some(it.text.clone()),
+        Separator::Ident(it) if idx == 0 => Some(it.text.clone()),
+        Separator::Puncts(it) => it.get(idx).map(|it| SmolStr::new(it.char.to_string())),
+        _ => None,
+    }
+}
+
 fn match_leaf(lhs: &tt::Leaf, src: &mut TtIter) -> Result<(), ExpandError> {
     let rhs = match src.expect_leaf() {
         Ok(l) => l,
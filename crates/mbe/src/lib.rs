@@ -18,6 +18,8 @@ mod token_map;
 
 use std::fmt;
 
+use syntax::{SmolStr, SyntaxKind, TextRange};
+
 pub use tt::{Delimiter, DelimiterKind, Punct};
 
 use crate::{
@@ -36,6 +38,8 @@ pub enum ParseError {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ExpandError {
     NoMatchingRule,
+    /// None of the rules of a macro match its input.
+    NoRuleExpectedToken(Box<MatchFailure>),
     UnexpectedToken,
     BindingError(String),
     ConversionError,
@@ -44,6 +48,30 @@ pub enum ExpandError {
     Other(String),
 }
 
+/// The position in the input of a macro call at which the rule that matched the most tokens
+/// failed to match.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MatchFailure {
+    /// The token no rule expected. If the input, or a delimited group in it, ended too early,
+    /// this is the closing delimiter. `None` if the token has no id.
+    pub token: Option<tt::TokenId>,
+    /// The text of `token`.
+    pub text: SmolStr,
+    /// Whether the whole input ended too early.
+    pub end_of_input: bool,
+    /// What the rule expected instead, like `` `,` `` or `expr`.
+    pub expected: Vec<String>,
+}
+
+impl MatchFailure {
+    /// Returns the range of `token` in the source `token_map` was created from.
+    pub fn range(&self, token_map: &TokenMap) -> Option<TextRange> {
+        let kind =
+            self.text.chars().next().and_then(SyntaxKind::from_char).unwrap_or(SyntaxKind::IDENT);
+        token_map.range_by_token(self.token?, kind)
+    }
+}
+
 impl From<tt::ExpansionError> for ExpandError {
     fn from(it: tt::ExpansionError) -> Self {
         ExpandError::ProcMacroError(it)
@@ -54,6 +82,17 @@ impl fmt::Display for ExpandError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ExpandError::NoMatchingRule => f.write_str("no rule matches input tokens"),
+            ExpandError::NoRuleExpectedToken(it) => {
+                if it.end_of_input {
+                    f.write_str("unexpected end of macro invocation")?;
+                } else {
+                    write!(f, "no rules expected the token `{}`", it.text)?;
+                }
+                if !it.expected.is_empty() {
+                    write!(f, "; expected {}", it.expected.join(" or "))?;
+                }
+                Ok(())
+            }
             ExpandError::UnexpectedToken => f.write_str("unexpected token in input"),
             ExpandError::BindingError(e) => f.write_str(e),
             ExpandError::ConversionError => f.write_str("could not convert tokens"),
@@ -65,6 +104,7 @@ impl fmt::Display for ExpandError {
 }
 
 pub use crate::{
+    expander::{ExpansionTrace, MetaVarBinding, RuleTrace},
     syntax_bridge::{
         ast_to_token_tree, parse_exprs_with_sep, parse_to_token_tree, syntax_node_to_token_tree,
         token_tree_to_syntax_node,
@@ -162,6 +202,16 @@ impl Shift {
     fn unshift(self, id: tt::TokenId) -> Option<tt::TokenId> {
         id.0.checked_sub(self.0).map(tt::TokenId)
     }
+
+    /// Maps the input token `err` points at back to its id in the macro call.
+    fn unshift_err(self, err: &mut Option<ExpandError>) {
+        if let Some(ExpandError::NoRuleExpectedToken(failure)) = err {
+            // `shift_all` doesn't shift the delimiters of the input itself.
+            if !failure.end_of_input {
+                failure.token = failure.token.and_then(|id| self.unshift(id));
+            }
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -199,7 +249,21 @@ impl MacroRules {
         // apply shift
         let mut tt = tt.clone();
         self.shift.shift_all(&mut tt);
-        expander::expand_rules(&self.rules, &tt)
+        let mut res = expander::expand_rules(&self.rules, &tt);
+        self.shift.unshift_err(&mut res.err);
+        res
+    }
+
+    /// Expands `tt` like `expand`, recording how it matched against each rule.
+    pub fn trace_expand(&self, tt: &tt::Subtree) -> ExpansionTrace {
+        let mut tt = tt.clone();
+        self.shift.shift_all(&mut tt);
+        let mut trace = expander::trace_rules(&self.rules, &tt);
+        self.shift.unshift_err(&mut trace.expansion.err);
+        for rule in trace.rules.iter_mut() {
+            self.shift.unshift_err(&mut rule.err);
+        }
+        trace
     }
 
     pub fn map_id_down(&self, id: tt::TokenId) -> tt::TokenId {
@@ -252,7 +316,21 @@ impl MacroDef {
         // apply shift
         let mut tt = tt.clone();
         self.shift.shift_all(&mut tt);
-        expander::expand_rules(&self.rules, &tt)
+        let mut res = expander::expand_rules(&self.rules, &tt);
+        self.shift.unshift_err(&mut res.err);
+        res
+    }
+
+    /// Expands `tt` like `expand`, recording how it matched against each rule.
+    pub fn trace_expand(&self, tt: &tt::Subtree) -> ExpansionTrace {
+        let mut tt = tt.clone();
+        self.shift.shift_all(&mut tt);
+        let mut trace = expander::trace_rules(&self.rules, &tt);
+        self.shift.unshift_err(&mut trace.expansion.err);
+        for rule in trace.rules.iter_mut() {
+            self.shift.unshift_err(&mut rule.err);
+        }
+        trace
     }
 
     pub fn map_id_down(&self, id: tt::TokenId) -> tt::TokenId {
@@ -44,6 +44,10 @@ impl<'a> OpDelimitedIter<'a> {
     pub(crate) fn reset(&self) -> Self {
         Self { inner: &self.inner, idx: 0, delimited: self.delimited }
     }
+
+    pub(crate) fn delimiter(&self) -> Option<&'a Delimiter> {
+        self.delimited
+    }
 }
 
 impl<'a> Iterator for OpDelimitedIter<'a> {
@@ -39,6 +39,12 @@ macro_rules! impl_fixture {
                 assert_eq!(self.try_expand_tt(invocation).as_ref(), Err(err));
             }
 
+            #[allow(unused)]
+            fn assert_expand_err_message(&self, invocation: &str, message: &str) {
+                let err = self.try_expand_tt(invocation).unwrap_err();
+                assert_eq!(err.to_string(), message);
+            }
+
             #[allow(unused)]
             fn expand_items(&self, invocation: &str) -> SyntaxNode {
                 let expanded = self.expand_tt(invocation);
@@ -1912,7 +1912,10 @@ fn test_expand_bad_literal() {
         macro_rules! foo { ($i:literal) => {}; }
     "#,
     )
-    .assert_expand_err(r#"foo!(&k");"#, &ExpandError::BindingError("".into()));
+    .assert_expand_err_message(
+        r#"foo!(&k");"#,
+        "no rules expected the token `(`; expected literal",
+    );
 }
 
 #[test]
@@ -1922,8 +1925,35 @@ fn test_empty_comments() {
         macro_rules! one_arg_macro { ($fmt:expr) => (); }
     "#,
     )
-    .assert_expand_err(
+    .assert_expand_err_message(
         r#"one_arg_macro!(/**/)"#,
-        &ExpandError::BindingError("expected Expr".into()),
+        "unexpected end of macro invocation; expected expr",
+    );
+}
+
+#[test]
+fn test_no_rules_expected_token() {
+    let rules = parse_macro(
+        r#"
+        macro_rules! foo {
+            (a $x:ident) => {};
+            (b $x:ident, $e:expr) => {};
+            ([$x:ident $y:ident]) => {};
+        }
+    "#,
+    );
+    rules.assert_expand_err_message(
+        "foo!(b x; 1);",
+        "no rules expected the token `;`; expected `,`",
+    );
+    rules.assert_expand_err_message(
+        "foo!(b x,);",
+        "unexpected end of macro invocation; expected expr",
+    );
+    rules
+        .assert_expand_err_message("foo!([x]);", "no rules expected the token `]`; expected ident");
+    rules.assert_expand_err_message(
+        "foo!(c);",
+        "no rules expected the token `c`; expected `a` or `b` or `[`",
     );
 }
@@ -234,6 +234,7 @@ pub enum SyntaxKind {
     NAME,
     NAME_REF,
     LET_STMT,
+    LET_ELSE,
     EXPR_STMT,
     GENERIC_PARAM_LIST,
     GENERIC_PARAM,
@@ -85,10 +85,45 @@ xflags::xflags! {
             optional --with-proc-macro
         }
 
+        /// Dump an LSIF index of the workspace to stdout.
+        cmd lsif
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+        {
+            /// Load OUT_DIR values by running `cargo check` before analysis.
+            optional --load-output-dirs
+            /// Use proc-macro-srv for proc-macro expanding.
+            optional --with-proc-macro
+        }
+
         cmd ssr
             /// A structured search replace rule (`$a.foo($b) ==> bar($a, $b)`)
             repeated rule: SsrRule
-        {}
+        {
+            /// Read additional rules from a file, one rule per line.
+            repeated --rules-file path: PathBuf
+            /// Print a unified diff of the changes instead of writing them to disk.
+            optional --dry-run
+            /// Print matches and exit with a non-zero status code if any rule matched.
+            optional --check
+            /// Print the locations of matches as JSON instead of changing any files.
+            optional --json
+        }
+
+        /// Rewrite the imports of all workspace files to a consistent granularity.
+        cmd normalize-imports
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+        {
+            /// How imports are merged: `preserve`, `crate` (default), `module` or `item`.
+            optional --granularity granularity: String
+            /// Don't separate std, external crate and local imports with blank lines.
+            optional --no-group
+            /// Print a unified diff of the changes instead of writing them to disk.
+            optional --dry-run
+            /// Exit with a non-zero status code if any file would change, without changing it.
+            optional --check
+        }
 
         cmd search
             /// A structured search replace pattern (`$a.foo($b)`)
@@ -96,6 +131,8 @@ xflags::xflags! {
         {
             /// Prints debug information for any nodes with source exactly equal to snippet.
             optional --debug snippet: String
+            /// Print the locations of matches as JSON.
+            optional --json
         }
 
         cmd proc-macro {}
@@ -123,7 +160,9 @@ pub enum RustAnalyzerCmd {
     Highlight(Highlight),
     AnalysisStats(AnalysisStats),
     Diagnostics(Diagnostics),
+    Lsif(Lsif),
     Ssr(Ssr),
+    NormalizeImports(NormalizeImports),
     Search(Search),
     ProcMacro(ProcMacro),
 }
@@ -171,9 +210,32 @@ pub struct Diagnostics {
     pub with_proc_macro: bool,
 }
 
+#[derive(Debug)]
+pub struct Lsif {
+    pub path: PathBuf,
+
+    pub load_output_dirs: bool,
+    pub with_proc_macro: bool,
+}
+
 #[derive(Debug)]
 pub struct Ssr {
     pub rule: Vec<SsrRule>,
+
+    pub rules_file: Vec<PathBuf>,
+    pub dry_run: bool,
+    pub check: bool,
+    pub json: bool,
+}
+
+#[derive(Debug)]
+pub struct NormalizeImports {
+    pub path: PathBuf,
+
+    pub granularity: Option<String>,
+    pub no_group: bool,
+    pub dry_run: bool,
+    pub check: bool,
 }
 
 #[derive(Debug)]
@@ -181,6 +243,7 @@ pub struct Search {
     pub pattern: Vec<SsrPattern>,
 
     pub debug: Option<String>,
+    pub json: bool,
 }
 
 #[derive(Debug)]
@@ -10,10 +10,10 @@ use std::{convert::TryFrom, env, fs, path::Path, process};
 use lsp_server::Connection;
 use project_model::ProjectManifest;
 use rust_analyzer::{
-    cli::{self, AnalysisStatsCmd},
+    cli::{self, AnalysisStatsCmd, NormalizeImportsCmd, SsrCmd},
     config::Config,
     from_json,
-    lsp_ext::supports_utf8,
+    lsp_ext::{self, supports_utf8},
     Result,
 };
 use vfs::AbsPathBuf;
@@ -100,8 +100,28 @@ fn try_main() -> Result<()> {
         flags::RustAnalyzerCmd::Diagnostics(cmd) => {
             cli::diagnostics(&cmd.path, cmd.load_output_dirs, cmd.with_proc_macro)?
         }
-        flags::RustAnalyzerCmd::Ssr(cmd) => cli::apply_ssr_rules(cmd.rule)?,
-        flags::RustAnalyzerCmd::Search(cmd) => cli::search_for_patterns(cmd.pattern, cmd.debug)?,
+        flags::RustAnalyzerCmd::Lsif(cmd) => {
+            cli::lsif(&cmd.path, cmd.load_output_dirs, cmd.with_proc_macro)?
+        }
+        flags::RustAnalyzerCmd::Ssr(cmd) => SsrCmd {
+            rules: cmd.rule,
+            rules_files: cmd.rules_file,
+            dry_run: cmd.dry_run,
+            check: cmd.check,
+            json: cmd.json,
+        }
+        .run()?,
+        flags::RustAnalyzerCmd::NormalizeImports(cmd) => NormalizeImportsCmd {
+            path: cmd.path,
+            granularity: cmd.granularity,
+            no_group: cmd.no_group,
+            dry_run: cmd.dry_run,
+            check: cmd.check,
+        }
+        .run()?,
+        flags::RustAnalyzerCmd::Search(cmd) => {
+            cli::search_for_patterns(cmd.pattern, cmd.debug, cmd.json)?
+        }
     }
     Ok(())
 }
@@ -155,6 +175,8 @@ fn run_server() -> Result<()> {
 
     let (initialize_id, initialize_params) = connection.initialize_start()?;
     log::info!("InitializeParams: {}", initialize_params);
+    let pull_diagnostics_caps =
+        rust_analyzer::pull_diagnostics_capabilities(&initialize_params["capabilities"]);
     let initialize_params =
         from_json::<lsp_types::InitializeParams>("InitializeParams", initialize_params)?;
 
@@ -171,13 +193,14 @@ fn run_server() -> Result<()> {
     };
 
     let mut config = Config::new(root_path, initialize_params.capabilities);
+    config.pull_diagnostics_caps = pull_diagnostics_caps;
     if let Some(json) = initialize_params.initialization_options {
         config.update(json);
     }
 
     let server_capabilities = rust_analyzer::server_capabilities(&config);
 
-    let initialize_result = lsp_types::InitializeResult {
+    let initialize_result = lsp_ext::InitializeResult {
         capabilities: server_capabilities,
         server_info: Some(lsp_types::ServerInfo {
             name: String::from("rust-analyzer"),
@@ -13,10 +13,19 @@ use lsp_types::{
 };
 use serde_json::json;
 
-use crate::config::{Config, RustfmtConfig};
+use crate::config::{Config, PullDiagnosticsCapabilities, RustfmtConfig};
+use crate::lsp_ext;
 use crate::semantic_tokens;
 
-pub fn server_capabilities(config: &Config) -> ServerCapabilities {
+pub fn server_capabilities(config: &Config) -> lsp_ext::ServerCapabilities {
+    lsp_ext::ServerCapabilities {
+        base: base_server_capabilities(config),
+        type_hierarchy_provider: Some(true),
+        diagnostic_provider: diagnostic_provider(config),
+    }
+}
+
+fn base_server_capabilities(config: &Config) -> ServerCapabilities {
     ServerCapabilities {
         text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
             open_close: Some(true),
@@ -125,6 +134,31 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
     }
 }
 
+/// Parses the LSP 3.17 pull diagnostics client capabilities out of the raw
+/// `ClientCapabilities` JSON, `lsp-types` drops these fields while parsing.
+///
+/// Clients without them get diagnostics pushed via `publishDiagnostics`.
+pub fn pull_diagnostics_capabilities(
+    client_caps: &serde_json::Value,
+) -> Option<PullDiagnosticsCapabilities> {
+    if !client_caps["textDocument"]["diagnostic"].is_object() {
+        return None;
+    }
+    let refresh_support =
+        client_caps["workspace"]["diagnostics"]["refreshSupport"].as_bool().unwrap_or(false);
+    Some(PullDiagnosticsCapabilities { refresh_support })
+}
+
+/// The `diagnosticProvider` server capability, advertised only to clients
+/// that negotiated pull diagnostics.
+fn diagnostic_provider(config: &Config) -> Option<lsp_ext::DiagnosticOptions> {
+    config.pull_diagnostics().then(|| lsp_ext::DiagnosticOptions {
+        identifier: Some("rust-analyzer".to_string()),
+        inter_file_dependencies: true,
+        workspace_diagnostics: true,
+    })
+}
+
 fn completions_resolve_provider(client_caps: &ClientCapabilities) -> Option<bool> {
     if completion_item_edit_resolve(client_caps) {
         Some(true)
@@ -171,6 +205,8 @@ fn code_action_capabilities(client_caps: &ClientCapabilities) -> CodeActionProvi
                     CodeActionKind::REFACTOR_EXTRACT,
                     CodeActionKind::REFACTOR_INLINE,
                     CodeActionKind::REFACTOR_REWRITE,
+                    CodeActionKind::SOURCE,
+                    CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
                 ]),
                 resolve_provider: Some(true),
                 work_done_progress_options: Default::default(),
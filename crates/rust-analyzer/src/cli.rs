@@ -3,6 +3,8 @@
 pub(crate) mod load_cargo;
 mod analysis_stats;
 mod diagnostics;
+mod lsif;
+mod normalize_imports;
 mod progress_report;
 mod ssr;
 
@@ -17,7 +19,9 @@ pub use self::{
     analysis_stats::AnalysisStatsCmd,
     diagnostics::diagnostics,
     load_cargo::{load_workspace, load_workspace_at, LoadCargoConfig},
-    ssr::{apply_ssr_rules, search_for_patterns},
+    lsif::lsif,
+    normalize_imports::NormalizeImportsCmd,
+    ssr::{search_for_patterns, SsrCmd},
 };
 
 #[derive(Clone, Copy)]
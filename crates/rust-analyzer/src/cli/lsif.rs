@@ -0,0 +1,485 @@
+//! Dumps an [LSIF](https://microsoft.github.io/language-server-protocol/specifications/lsif/0.5.0/specification/)
+//! index of a Cargo workspace to stdout, so that code navigation can be served
+//! without a running language server.
+
+mod lsif_types;
+
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
+
+use ide::{
+    Analysis, AnalysisHost, FileId, FilePosition, FileRange, HoverConfig, NavigationTarget,
+    RootDatabase, SearchScope, SymbolKind,
+};
+use ide_db::base_db::{SourceDatabase, SourceDatabaseExt};
+use rustc_hash::FxHashMap;
+use syntax::{AstNode, SyntaxKind::*, T};
+use vfs::{AbsPath, AbsPathBuf, Vfs};
+
+use crate::{
+    cli::{
+        load_cargo::{load_workspace_at, LoadCargoConfig},
+        Result,
+    },
+    line_index::{LineEndings, LineIndex, OffsetEncoding},
+    to_proto,
+};
+
+use self::lsif_types::{
+    Edge, EdgeData, EdgeDataMultiIn, Element, Encoding, Entry, Id, Item, ItemKind, MetaData,
+    Moniker, MonikerKind, ToolInfo, UniquenessLevel, Vertex,
+};
+
+/// A definition is identified by the location of its name, which is what
+/// `goto_definition` gives us for every reference to it.
+type DefKey = FileRange;
+
+struct DefData {
+    result_set: Id,
+    nav: NavigationTarget,
+    /// Position of the first token resolving to this definition, used to
+    /// query hover and references.
+    position: FilePosition,
+}
+
+struct LsifManager<'a> {
+    count: Id,
+    out: &'a mut dyn Write,
+    analysis: &'a Analysis,
+    db: &'a RootDatabase,
+    vfs: &'a Vfs,
+    /// Only references inside the indexed files are reported, searching the
+    /// sysroot for usages of `Option` would be prohibitively slow.
+    local_files: Vec<FileId>,
+    line_indices: FxHashMap<FileId, Arc<LineIndex>>,
+    documents: FxHashMap<FileId, Id>,
+    ranges: FxHashMap<FileRange, Id>,
+    defs: FxHashMap<DefKey, DefData>,
+}
+
+impl LsifManager<'_> {
+    fn add(&mut self, data: Element) -> Result<Id> {
+        self.count += 1;
+        let id = self.count;
+        let entry = Entry { id, data };
+        serde_json::to_writer(&mut self.out, &entry)?;
+        writeln!(self.out)?;
+        Ok(id)
+    }
+
+    fn add_vertex(&mut self, vertex: Vertex) -> Result<Id> {
+        self.add(Element::Vertex(vertex))
+    }
+
+    fn add_edge(&mut self, edge: Edge) -> Result<Id> {
+        self.add(Element::Edge(edge))
+    }
+
+    fn line_index(&mut self, file_id: FileId) -> Result<Arc<LineIndex>> {
+        if let Some(it) = self.line_indices.get(&file_id) {
+            return Ok(it.clone());
+        }
+        let index = self.analysis.file_line_index(file_id)?;
+        // LSIF only supports utf-16 offsets, see `Encoding`.
+        let line_index = Arc::new(LineIndex {
+            index,
+            endings: LineEndings::Unix,
+            encoding: OffsetEncoding::Utf16,
+        });
+        self.line_indices.insert(file_id, line_index.clone());
+        Ok(line_index)
+    }
+
+    fn add_range(&mut self, frange: FileRange) -> Result<Id> {
+        if let Some(&id) = self.ranges.get(&frange) {
+            return Ok(id);
+        }
+        let line_index = self.line_index(frange.file_id)?;
+        let id = self.add_vertex(Vertex::Range(to_proto::range(&line_index, frange.range)))?;
+        self.ranges.insert(frange, id);
+        Ok(id)
+    }
+
+    fn add_file(&mut self, file_id: FileId) -> Result<()> {
+        let path = self.vfs.file_path(file_id);
+        let path = match path.as_path() {
+            Some(it) => it,
+            None => return Ok(()),
+        };
+        let uri = to_proto::url_from_abs_path(path.as_ref());
+        let doc_id = self.add_vertex(Vertex::Document(lsif_types::Document {
+            uri,
+            language_id: "rust".to_string(),
+        }))?;
+        self.documents.insert(file_id, doc_id);
+
+        let source_file = self.analysis.parse(file_id)?;
+        let mut range_ids = Vec::new();
+        for token in source_file.syntax().descendants_with_tokens().filter_map(|it| it.into_token())
+        {
+            if !matches!(token.kind(), IDENT | LIFETIME_IDENT | T![self] | T![super] | T![crate]) {
+                continue;
+            }
+            let position = FilePosition { file_id, offset: token.text_range().start() };
+            let nav = match self.analysis.goto_definition(position)? {
+                Some(it) => match it.info.into_iter().next() {
+                    Some(nav) => nav,
+                    None => continue,
+                },
+                None => continue,
+            };
+            let result_set = self.add_def(nav, position)?;
+            let range_id = self.add_range(FileRange { file_id, range: token.text_range() })?;
+            self.add_edge(Edge::Next(EdgeData { in_v: result_set, out_v: range_id }))?;
+            range_ids.push(range_id);
+        }
+        if !range_ids.is_empty() {
+            self.add_edge(Edge::Contains(EdgeDataMultiIn { in_vs: range_ids, out_v: doc_id }))?;
+        }
+        Ok(())
+    }
+
+    fn add_def(&mut self, nav: NavigationTarget, position: FilePosition) -> Result<Id> {
+        let key = FileRange { file_id: nav.file_id, range: nav.focus_or_full_range() };
+        if let Some(def) = self.defs.get(&key) {
+            return Ok(def.result_set);
+        }
+        let result_set = self.add_vertex(Vertex::ResultSet)?;
+        self.defs.insert(key, DefData { result_set, nav, position });
+        Ok(result_set)
+    }
+
+    /// Attaches definition, reference, hover and moniker results to every
+    /// result set. Runs once all documents and their ranges are emitted, as
+    /// references may point into any of them.
+    fn add_results(&mut self) -> Result<()> {
+        let mut defs: Vec<_> = self.defs.drain().collect();
+        defs.sort_by_key(|(_, def)| def.result_set);
+        for (key, def) in defs {
+            self.add_definition_result(key, def.result_set)?;
+            self.add_reference_result(&def)?;
+            self.add_hover_result(&def)?;
+            self.add_moniker(&def)?;
+        }
+        Ok(())
+    }
+
+    fn add_definition_result(&mut self, key: DefKey, result_set: Id) -> Result<()> {
+        let (doc_id, range_id) = match (self.documents.get(&key.file_id), self.ranges.get(&key)) {
+            (Some(&doc_id), Some(&range_id)) => (doc_id, range_id),
+            _ => return Ok(()),
+        };
+        let result_id = self.add_vertex(Vertex::DefinitionResult)?;
+        self.add_edge(Edge::Definition(EdgeData { in_v: result_id, out_v: result_set }))?;
+        self.add_edge(Edge::Item(Item {
+            document: doc_id,
+            property: None,
+            edge_data: EdgeDataMultiIn { in_vs: vec![range_id], out_v: result_id },
+        }))?;
+        Ok(())
+    }
+
+    fn add_reference_result(&mut self, def: &DefData) -> Result<()> {
+        let refs = match self
+            .analysis
+            .find_all_refs(def.position, Some(SearchScope::files(&self.local_files)))?
+        {
+            Some(it) => it,
+            None => return Ok(()),
+        };
+        let mut definitions = Vec::new();
+        if let Some(decl) = &refs.declaration {
+            let frange =
+                FileRange { file_id: decl.nav.file_id, range: decl.nav.focus_or_full_range() };
+            definitions.extend(self.lookup_range(frange));
+        }
+        let mut references = Vec::new();
+        for (file_id, ranges) in refs.references {
+            for (range, _) in ranges {
+                references.extend(self.lookup_range(FileRange { file_id, range }));
+            }
+        }
+        if definitions.is_empty() && references.is_empty() {
+            return Ok(());
+        }
+
+        let result_id = self.add_vertex(Vertex::ReferenceResult)?;
+        self.add_edge(Edge::References(EdgeData { in_v: result_id, out_v: def.result_set }))?;
+        for (items, kind) in
+            [(definitions, ItemKind::Definitions), (references, ItemKind::References)]
+        {
+            for (doc_id, in_vs) in group_by_document(items) {
+                self.add_edge(Edge::Item(Item {
+                    document: doc_id,
+                    property: Some(kind),
+                    edge_data: EdgeDataMultiIn { in_vs, out_v: result_id },
+                }))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_hover_result(&mut self, def: &DefData) -> Result<()> {
+        let hover = match self.analysis.hover(
+            def.position,
+            &HoverConfig { links_in_hover: false, ..HoverConfig::NO_ACTIONS },
+        )? {
+            Some(it) => it.info,
+            None => return Ok(()),
+        };
+        let result = lsp_types::Hover {
+            contents: lsp_types::HoverContents::Markup(to_proto::markup_content(hover.markup)),
+            range: None,
+        };
+        let result_id = self.add_vertex(Vertex::HoverResult { result })?;
+        self.add_edge(Edge::Hover(EdgeData { in_v: result_id, out_v: def.result_set }))?;
+        Ok(())
+    }
+
+    /// Items that can be named from another crate get a moniker, so that dumps
+    /// of different crates can be linked together.
+    fn add_moniker(&mut self, def: &DefData) -> Result<()> {
+        let nav = &def.nav;
+        match nav.kind {
+            Some(
+                SymbolKind::Local
+                | SymbolKind::ValueParam
+                | SymbolKind::SelfParam
+                | SymbolKind::Label
+                | SymbolKind::LifetimeParam
+                | SymbolKind::TypeParam
+                | SymbolKind::ConstParam
+                | SymbolKind::Impl,
+            )
+            | None => return Ok(()),
+            _ => (),
+        }
+        let krate = match self.analysis.crate_for(nav.file_id)?.first() {
+            Some(&it) => it,
+            None => return Ok(()),
+        };
+        let crate_name = match &self.db.crate_graph()[krate].display_name {
+            Some(it) => it.to_string(),
+            None => return Ok(()),
+        };
+        let identifier = std::iter::once(crate_name.as_str())
+            .chain(nav.container_name.as_deref())
+            .chain(std::iter::once(nav.name.as_str()))
+            .collect::<Vec<_>>()
+            .join("::");
+        let kind = if self.documents.contains_key(&nav.file_id) {
+            MonikerKind::Export
+        } else {
+            MonikerKind::Import
+        };
+        let moniker_id = self.add_vertex(Vertex::Moniker(Moniker {
+            scheme: "rust-analyzer".to_string(),
+            identifier,
+            unique: UniquenessLevel::Scheme,
+            kind,
+        }))?;
+        self.add_edge(Edge::Moniker(EdgeData { in_v: moniker_id, out_v: def.result_set }))?;
+        Ok(())
+    }
+
+    fn lookup_range(&self, frange: FileRange) -> Option<(Id, Id)> {
+        let doc_id = *self.documents.get(&frange.file_id)?;
+        let range_id = *self.ranges.get(&frange)?;
+        Some((doc_id, range_id))
+    }
+}
+
+fn group_by_document(items: Vec<(Id, Id)>) -> Vec<(Id, Vec<Id>)> {
+    let mut res: Vec<(Id, Vec<Id>)> = Vec::new();
+    for (doc_id, range_id) in items {
+        match res.iter_mut().find(|(it, _)| *it == doc_id) {
+            Some((_, ranges)) => ranges.push(range_id),
+            None => res.push((doc_id, vec![range_id])),
+        }
+    }
+    res
+}
+
+pub fn lsif(path: &Path, load_out_dirs_from_check: bool, with_proc_macro: bool) -> Result<()> {
+    let now = Instant::now();
+    let cargo_config = Default::default();
+    let load_cargo_config =
+        LoadCargoConfig { load_out_dirs_from_check, with_proc_macro, wrap_rustc: false };
+    let path = AbsPathBuf::assert(std::env::current_dir()?.join(path));
+    let (host, vfs, _proc_macro) =
+        load_workspace_at(path.as_ref(), &cargo_config, &load_cargo_config, &|_| {})?;
+    eprintln!("Loaded workspace in {:?}", now.elapsed());
+
+    let stdout = io::stdout();
+    let count = dump(&host, &vfs, &path, &mut stdout.lock())?;
+
+    eprintln!("Generated LSIF with {} entries in {:?}", count, now.elapsed());
+    Ok(())
+}
+
+/// Writes the index of the local files of `host` to `out`, returning the number of entries.
+fn dump(host: &AnalysisHost, vfs: &Vfs, project_root: &AbsPath, out: &mut dyn Write) -> Result<Id> {
+    let db = host.raw_database();
+    let analysis = host.analysis();
+
+    let mut files: Vec<FileId> = vfs
+        .iter()
+        .map(|(file_id, _)| file_id)
+        .filter(|&file_id| {
+            let source_root = db.file_source_root(file_id);
+            !db.source_root(source_root).is_library
+        })
+        .collect();
+    files.sort_by_key(|it| it.0);
+
+    let mut lsif = LsifManager {
+        count: 0,
+        out,
+        analysis: &analysis,
+        db,
+        vfs: &vfs,
+        local_files: files.clone(),
+        line_indices: FxHashMap::default(),
+        documents: FxHashMap::default(),
+        ranges: FxHashMap::default(),
+        defs: FxHashMap::default(),
+    };
+    lsif.add_vertex(Vertex::MetaData(MetaData {
+        version: "0.5.0".to_string(),
+        project_root: to_proto::url_from_abs_path(project_root),
+        position_encoding: Encoding::Utf16,
+        tool_info: Some(ToolInfo {
+            name: "rust-analyzer".to_string(),
+            version: Some(env!("REV").to_string()),
+        }),
+    }))?;
+    for file_id in files {
+        lsif.add_file(file_id)?;
+    }
+    lsif.add_results()?;
+    lsif.out.flush()?;
+    Ok(lsif.count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use expect_test::expect;
+    use ide::Change;
+    use ide_db::base_db::{CrateGraph, CrateName, Edition, Env, FileSet, SourceRoot};
+    use serde_json::Value;
+    use vfs::VfsPath;
+
+    use super::*;
+
+    /// Renders each entry of the dump on a line, leaving out hover and metadata contents.
+    fn render(dump: &str) -> String {
+        let mut res = String::new();
+        for line in dump.lines() {
+            let entry: Value = serde_json::from_str(line).unwrap();
+            let label = entry["label"].as_str().unwrap();
+            let mut line = format!("{} {}", entry["id"], label);
+            match label {
+                "document" => {
+                    let uri = entry["uri"].as_str().unwrap();
+                    line.push_str(&format!(" {}", uri.rsplit('/').next().unwrap()));
+                }
+                "range" => {
+                    let pos = |it: &Value| format!("{}:{}", it["line"], it["character"]);
+                    line.push_str(&format!(" {}-{}", pos(&entry["start"]), pos(&entry["end"])));
+                }
+                "moniker" if entry["type"] == "vertex" => {
+                    line.push_str(&format!(" {}", entry["identifier"]))
+                }
+                _ => {}
+            }
+            if entry["type"] == "edge" {
+                let in_vs = match &entry["inVs"] {
+                    Value::Array(it) => it.iter().map(|it| it.to_string()).collect::<Vec<_>>(),
+                    _ => vec![entry["inV"].to_string()],
+                };
+                line.push_str(&format!(" {} -> {}", entry["outV"], in_vs.join(", ")));
+                if let Some(doc) = entry.get("document") {
+                    line.push_str(&format!(" in {}", doc));
+                }
+                if let Some(Value::String(property)) = entry.get("property") {
+                    line.push_str(&format!(" ({})", property));
+                }
+            }
+            res.push_str(&line);
+            res.push('\n');
+        }
+        res
+    }
+
+    #[test]
+    fn dumps_definitions_and_references() {
+        let text = "fn foo() {}\nfn main() { foo(); }\n";
+        let root = AbsPathBuf::assert(std::env::temp_dir().join("lsif"));
+        let path = VfsPath::from(root.join("main.rs"));
+
+        let mut vfs = Vfs::default();
+        vfs.set_file_contents(path.clone(), Some(text.as_bytes().to_vec()));
+        let file_id = vfs.file_id(&path).unwrap();
+        let mut file_set = FileSet::default();
+        file_set.insert(file_id, path);
+        let mut crate_graph = CrateGraph::default();
+        crate_graph.add_crate_root(
+            file_id,
+            Edition::Edition2018,
+            Some(CrateName::new("main").unwrap().into()),
+            Default::default(),
+            Env::default(),
+            Vec::new(),
+        );
+        let mut change = Change::new();
+        change.change_file(file_id, Some(Arc::new(text.to_string())));
+        change.set_roots(vec![SourceRoot::new_local(file_set)]);
+        change.set_crate_graph(crate_graph);
+        let mut host = AnalysisHost::default();
+        host.apply_change(change);
+
+        let mut out = Vec::new();
+        dump(&host, &vfs, &root, &mut out).unwrap();
+        expect![[r#"
+            1 metaData
+            2 document main.rs
+            3 resultSet
+            4 range 0:3-0:6
+            5 next 4 -> 3
+            6 resultSet
+            7 range 1:3-1:7
+            8 next 7 -> 6
+            9 range 1:12-1:15
+            10 next 9 -> 3
+            11 contains 2 -> 4, 7, 9
+            12 definitionResult
+            13 textDocument/definition 3 -> 12
+            14 item 12 -> 4 in 2
+            15 referenceResult
+            16 textDocument/references 3 -> 15
+            17 item 15 -> 4 in 2 (definitions)
+            18 item 15 -> 9 in 2 (references)
+            19 hoverResult
+            20 textDocument/hover 3 -> 19
+            21 moniker "main::foo"
+            22 moniker 3 -> 21
+            23 definitionResult
+            24 textDocument/definition 6 -> 23
+            25 item 23 -> 7 in 2
+            26 referenceResult
+            27 textDocument/references 6 -> 26
+            28 item 26 -> 7 in 2 (definitions)
+            29 hoverResult
+            30 textDocument/hover 6 -> 29
+            31 moniker "main::main"
+            32 moniker 6 -> 31
+        "#]]
+        .assert_eq(&render(&String::from_utf8(out).unwrap()));
+    }
+}
@@ -0,0 +1,146 @@
+//! Serialization types for the
+//! [LSIF 0.5](https://microsoft.github.io/language-server-protocol/specifications/lsif/0.5.0/specification/)
+//! dump format.
+//!
+//! Every [`Entry`] is written as a single line of JSON.
+
+use serde::Serialize;
+
+pub(crate) type Id = u64;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Entry {
+    pub(crate) id: Id,
+    #[serde(flatten)]
+    pub(crate) data: Element,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum Element {
+    Vertex(Vertex),
+    Edge(Edge),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "label", rename_all = "camelCase")]
+pub(crate) enum Vertex {
+    MetaData(MetaData),
+    Document(Document),
+    Range(lsp_types::Range),
+    ResultSet,
+    Moniker(Moniker),
+    DefinitionResult,
+    ReferenceResult,
+    HoverResult { result: lsp_types::Hover },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "label")]
+pub(crate) enum Edge {
+    #[serde(rename = "contains")]
+    Contains(EdgeDataMultiIn),
+    #[serde(rename = "next")]
+    Next(EdgeData),
+    #[serde(rename = "moniker")]
+    Moniker(EdgeData),
+    #[serde(rename = "item")]
+    Item(Item),
+    #[serde(rename = "textDocument/definition")]
+    Definition(EdgeData),
+    #[serde(rename = "textDocument/references")]
+    References(EdgeData),
+    #[serde(rename = "textDocument/hover")]
+    Hover(EdgeData),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EdgeData {
+    pub(crate) in_v: Id,
+    pub(crate) out_v: Id,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EdgeDataMultiIn {
+    pub(crate) in_vs: Vec<Id>,
+    pub(crate) out_v: Id,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Item {
+    pub(crate) document: Id,
+    #[serde(flatten)]
+    pub(crate) edge_data: EdgeDataMultiIn,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) property: Option<ItemKind>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ItemKind {
+    Definitions,
+    References,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MetaData {
+    /// The version of the LSIF format used.
+    pub(crate) version: String,
+    /// The project root (in form of an URI) used to compute this dump.
+    pub(crate) project_root: lsp_types::Url,
+    /// The string encoding used to compute line and character values in
+    /// positions and ranges.
+    pub(crate) position_encoding: Encoding,
+    pub(crate) tool_info: Option<ToolInfo>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Encoding {
+    /// Currently only 'utf-16' is supported due to the limitations in LSP.
+    #[serde(rename = "utf-16")]
+    Utf16,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ToolInfo {
+    pub(crate) name: String,
+    pub(crate) version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Document {
+    pub(crate) uri: lsp_types::Url,
+    pub(crate) language_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Moniker {
+    pub(crate) scheme: String,
+    pub(crate) identifier: String,
+    pub(crate) unique: UniquenessLevel,
+    pub(crate) kind: MonikerKind,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum UniquenessLevel {
+    /// The moniker is unique inside the moniker scheme.
+    Scheme,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum MonikerKind {
+    /// The moniker represent a symbol that is imported into a project.
+    Import,
+    /// The moniker represents a symbol that is exported from a project.
+    Export,
+}
@@ -0,0 +1,91 @@
+//! Rewrites the imports of all workspace files to a consistent style.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, format_err};
+use ide_db::{
+    base_db::SourceDatabaseExt,
+    helpers::insert_use::{ImportGranularity, InsertUseConfig},
+};
+
+use crate::cli::{
+    load_cargo::{load_workspace_at, LoadCargoConfig},
+    ssr::{display_path, unified_diff},
+    Result,
+};
+
+pub struct NormalizeImportsCmd {
+    pub path: PathBuf,
+    pub granularity: Option<String>,
+    pub no_group: bool,
+    pub dry_run: bool,
+    pub check: bool,
+}
+
+impl NormalizeImportsCmd {
+    pub fn run(self) -> Result<()> {
+        let granularity = match self.granularity.as_deref() {
+            None | Some("crate") => ImportGranularity::Crate,
+            Some("preserve") => ImportGranularity::Preserve,
+            Some("module") => ImportGranularity::Module,
+            Some("item") => ImportGranularity::Item,
+            Some(other) => bail!(
+                "unknown granularity `{}`, expected `preserve`, `crate`, `module` or `item`",
+                other
+            ),
+        };
+        let config = InsertUseConfig {
+            granularity,
+            enforce_granularity: true,
+            prefix_kind: hir::PrefixKind::Plain,
+            group: !self.no_group,
+        };
+
+        let cwd = std::env::current_dir()?;
+        let cargo_config = Default::default();
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: false,
+            wrap_rustc: false,
+            with_proc_macro: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
+        let db = host.raw_database();
+        let source_change = host
+            .analysis()
+            .normalize_imports(&config)
+            .map_err(|_| format_err!("normalizing imports was cancelled"))?;
+
+        let mut edits: Vec<_> = source_change
+            .source_file_edits
+            .into_iter()
+            .filter_map(|(file_id, edit)| {
+                let path = vfs.file_path(file_id).as_path()?.to_path_buf();
+                Some((path, file_id, edit))
+            })
+            .collect();
+        edits.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+        if self.dry_run {
+            for (_, file_id, edit) in &edits {
+                let before = db.file_text(*file_id);
+                print!("{}", unified_diff(&display_path(&vfs, &cwd, *file_id), &before, edit));
+            }
+        } else if self.check {
+            for (_, file_id, _) in &edits {
+                println!("{}", display_path(&vfs, &cwd, *file_id));
+            }
+        } else {
+            for (path, file_id, edit) in &edits {
+                let mut contents = db.file_text(*file_id).to_string();
+                edit.apply(&mut contents);
+                std::fs::write(path, contents)?;
+            }
+        }
+
+        if self.check && !edits.is_empty() {
+            bail!("imports of {} file(s) are not normalized", edits.len());
+        }
+        Ok(())
+    }
+}
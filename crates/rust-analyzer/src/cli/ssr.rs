@@ -1,48 +1,111 @@
 //! Applies structured search replace rules from the command line.
 
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, format_err};
+use ide_db::{
+    base_db::{FileId, SourceDatabaseExt},
+    LineIndexDatabase,
+};
+use ide_ssr::{Match, MatchFinder, SsrPattern, SsrRule};
+use text_edit::TextEdit;
+use vfs::Vfs;
+
 use crate::cli::{
     load_cargo::{load_workspace_at, LoadCargoConfig},
     Result,
 };
-use ide_ssr::{MatchFinder, SsrPattern, SsrRule};
 
-pub fn apply_ssr_rules(rules: Vec<SsrRule>) -> Result<()> {
-    use ide_db::base_db::SourceDatabaseExt;
-    let cargo_config = Default::default();
-    let load_cargo_config = LoadCargoConfig {
-        load_out_dirs_from_check: true,
-        wrap_rustc: false,
-        with_proc_macro: true,
-    };
-    let (host, vfs, _proc_macro) =
-        load_workspace_at(&std::env::current_dir()?, &cargo_config, &load_cargo_config, &|_| {})?;
-    let db = host.raw_database();
-    let mut match_finder = MatchFinder::at_first_file(db)?;
-    for rule in rules {
-        match_finder.add_rule(rule)?;
-    }
-    let edits = match_finder.edits();
-    for (file_id, edit) in edits {
-        if let Some(path) = vfs.file_path(file_id).as_path() {
-            let mut contents = db.file_text(file_id).to_string();
-            edit.apply(&mut contents);
-            std::fs::write(path, contents)?;
+pub struct SsrCmd {
+    pub rules: Vec<SsrRule>,
+    pub rules_files: Vec<PathBuf>,
+    pub dry_run: bool,
+    pub check: bool,
+    pub json: bool,
+}
+
+impl SsrCmd {
+    pub fn run(self) -> Result<()> {
+        let mut rules = self.rules;
+        for path in &self.rules_files {
+            rules.extend(read_rules_file(path)?);
+        }
+        if rules.is_empty() {
+            bail!("no rules given");
+        }
+
+        let cwd = std::env::current_dir()?;
+        let cargo_config = Default::default();
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: true,
+            wrap_rustc: false,
+            with_proc_macro: true,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(&cwd, &cargo_config, &load_cargo_config, &|_| {})?;
+        let db = host.raw_database();
+        let mut match_finder = MatchFinder::at_first_file(db)?;
+        for rule in rules {
+            match_finder.add_rule(rule)?;
+        }
+
+        let match_count = if self.json || self.check {
+            let matches = match_finder.matches().flattened().matches;
+            if self.json {
+                print_matches_as_json(db, &vfs, &cwd, &matches)?;
+            } else if !self.dry_run {
+                print_matches(db, &vfs, &cwd, &matches);
+            }
+            matches.len()
+        } else {
+            0
+        };
+
+        let mut edits: Vec<_> = match_finder
+            .edits()
+            .into_iter()
+            .filter_map(|(file_id, edit)| {
+                let path = vfs.file_path(file_id).as_path()?.to_path_buf();
+                Some((path, file_id, edit))
+            })
+            .collect();
+        edits.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+        if self.dry_run {
+            for (_, file_id, edit) in &edits {
+                let before = db.file_text(*file_id);
+                print!("{}", unified_diff(&display_path(&vfs, &cwd, *file_id), &before, edit));
+            }
+        } else if !self.check && !self.json {
+            for (path, file_id, edit) in &edits {
+                let mut contents = db.file_text(*file_id).to_string();
+                edit.apply(&mut contents);
+                std::fs::write(path, contents)?;
+            }
+        }
+
+        if self.check && (match_count > 0 || !edits.is_empty()) {
+            bail!("{} match(es) found", match_count);
         }
+        Ok(())
     }
-    Ok(())
 }
 
 /// Searches for `patterns`, printing debug information for any nodes whose text exactly matches
 /// `debug_snippet`. This is intended for debugging and probably isn't in it's current form useful
 /// for much else.
-pub fn search_for_patterns(patterns: Vec<SsrPattern>, debug_snippet: Option<String>) -> Result<()> {
-    use ide_db::base_db::SourceDatabaseExt;
+pub fn search_for_patterns(
+    patterns: Vec<SsrPattern>,
+    debug_snippet: Option<String>,
+    json: bool,
+) -> Result<()> {
     use ide_db::symbol_index::SymbolsDatabase;
+    let cwd = std::env::current_dir()?;
     let cargo_config = Default::default();
     let load_cargo_config =
         LoadCargoConfig { load_out_dirs_from_check: true, wrap_rustc: true, with_proc_macro: true };
-    let (host, _vfs, _proc_macro) =
-        load_workspace_at(&std::env::current_dir()?, &cargo_config, &load_cargo_config, &|_| {})?;
+    let (host, vfs, _proc_macro) =
+        load_workspace_at(&cwd, &cargo_config, &load_cargo_config, &|_| {})?;
     let db = host.raw_database();
     let mut match_finder = MatchFinder::at_first_file(db)?;
     for pattern in patterns {
@@ -57,6 +120,8 @@ pub fn search_for_patterns(patterns: Vec<SsrPattern>, debug_snippet: Option<Stri
                 }
             }
         }
+    } else if json {
+        print_matches_as_json(db, &vfs, &cwd, &match_finder.matches().flattened().matches)?;
     } else {
         for m in match_finder.matches().flattened().matches {
             // We could possibly at some point do something more useful than just printing
@@ -66,3 +131,271 @@ pub fn search_for_patterns(patterns: Vec<SsrPattern>, debug_snippet: Option<Stri
     }
     Ok(())
 }
+
+/// Reads rules from `path`, one per line. Empty lines and lines starting with `//` are skipped.
+fn read_rules_file(path: &Path) -> Result<Vec<SsrRule>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format_err!("failed to read {}: {}", path.display(), err))?;
+    let mut rules = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let rule = line
+            .parse::<SsrRule>()
+            .map_err(|err| format_err!("{}:{}: {}", path.display(), idx + 1, err))?;
+        rules.push(rule);
+    }
+    Ok(rules)
+}
+
+/// Returns the path of `file_id` relative to `cwd` if possible.
+pub(super) fn display_path(vfs: &Vfs, cwd: &Path, file_id: FileId) -> String {
+    let path = vfs.file_path(file_id);
+    match path.as_path() {
+        Some(abs_path) => {
+            let abs_path: &Path = abs_path.as_ref();
+            abs_path.strip_prefix(cwd).unwrap_or(abs_path).display().to_string()
+        }
+        None => path.to_string(),
+    }
+}
+
+fn sorted_locations(
+    db: &ide_db::RootDatabase,
+    vfs: &Vfs,
+    cwd: &Path,
+    matches: &[Match],
+) -> Vec<(String, ide::LineCol, ide::LineCol, String)> {
+    let mut res: Vec<_> = matches
+        .iter()
+        .map(|m| {
+            let range = m.range();
+            let line_index = db.line_index(range.file_id);
+            (
+                display_path(vfs, cwd, range.file_id),
+                line_index.line_col(range.range.start()),
+                line_index.line_col(range.range.end()),
+                m.matched_text(),
+            )
+        })
+        .collect();
+    res.sort_by(|(a_path, a_start, ..), (b_path, b_start, ..)| {
+        (a_path, a_start.line, a_start.col).cmp(&(b_path, b_start.line, b_start.col))
+    });
+    res
+}
+
+fn print_matches(db: &ide_db::RootDatabase, vfs: &Vfs, cwd: &Path, matches: &[Match]) {
+    for (path, start, _end, text) in sorted_locations(db, vfs, cwd, matches) {
+        println!("{}:{}:{}: {}", path, start.line + 1, start.col + 1, text);
+    }
+}
+
+/// Prints `matches` as a JSON array. Lines and columns are 1-based, as in compiler output.
+fn print_matches_as_json(
+    db: &ide_db::RootDatabase,
+    vfs: &Vfs,
+    cwd: &Path,
+    matches: &[Match],
+) -> Result<()> {
+    let json: Vec<_> = sorted_locations(db, vfs, cwd, matches)
+        .into_iter()
+        .map(|(path, start, end, text)| {
+            serde_json::json!({
+                "file": path,
+                "start": { "line": start.line + 1, "column": start.col + 1 },
+                "end": { "line": end.line + 1, "column": end.col + 1 },
+                "text": text,
+            })
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
+
+/// Number of unchanged lines shown around each change in a diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// A run of whole lines in the original text, together with what they're replaced by.
+struct ChangedLines {
+    /// Index of the first changed line.
+    start: usize,
+    /// Index one past the last changed line.
+    end: usize,
+    after: String,
+}
+
+/// Renders `edit` applied to `before` as a unified diff.
+pub(super) fn unified_diff(path: &str, before: &str, edit: &TextEdit) -> String {
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        before.match_indices('\n').map(|(idx, _)| idx + 1).filter(|&idx| idx < before.len()),
+    );
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset) - 1;
+    let line_end = |line: usize| line_starts.get(line + 1).copied().unwrap_or(before.len());
+
+    // Group the edits into runs of whole lines, merging edits that touch the same line.
+    let mut changes: Vec<(usize, usize, Vec<&text_edit::Indel>)> = Vec::new();
+    for indel in edit.iter() {
+        let start = line_of(indel.delete.start().into());
+        let end = line_of(indel.delete.end().into()) + 1;
+        match changes.last_mut() {
+            Some((_, prev_end, indels)) if start <= *prev_end => {
+                *prev_end = end.max(*prev_end);
+                indels.push(indel);
+            }
+            _ => changes.push((start, end, vec![indel])),
+        }
+    }
+    let changes: Vec<ChangedLines> = changes
+        .into_iter()
+        .map(|(start, end, indels)| {
+            let offset = line_starts[start];
+            let mut after = String::new();
+            let mut pos = offset;
+            for indel in indels {
+                after.push_str(&before[pos..indel.delete.start().into()]);
+                after.push_str(&indel.insert);
+                pos = indel.delete.end().into();
+            }
+            after.push_str(&before[pos..line_end(end - 1)]);
+            ChangedLines { start, end, after }
+        })
+        .collect();
+
+    let lines: Vec<&str> = before.split_inclusive('\n').collect();
+    let mut res = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut line_delta = 0isize;
+    let mut idx = 0;
+    while idx < changes.len() {
+        // Changes whose context would overlap are shown in the same hunk.
+        let mut last = idx;
+        while last + 1 < changes.len()
+            && changes[last + 1].start <= changes[last].end + 2 * DIFF_CONTEXT
+        {
+            last += 1;
+        }
+        let hunk_start = changes[idx].start.saturating_sub(DIFF_CONTEXT);
+        let hunk_end = (changes[last].end + DIFF_CONTEXT).min(lines.len());
+
+        let mut body = String::new();
+        let mut new_len = 0;
+        let mut line = hunk_start;
+        for change in &changes[idx..=last] {
+            for context in &lines[line..change.start] {
+                push_diff_line(&mut body, ' ', context);
+            }
+            new_len += change.start - line;
+            for old in &lines[change.start..change.end] {
+                push_diff_line(&mut body, '-', old);
+            }
+            for new in change.after.split_inclusive('\n') {
+                push_diff_line(&mut body, '+', new);
+                new_len += 1;
+            }
+            line = change.end;
+        }
+        for context in &lines[line..hunk_end] {
+            push_diff_line(&mut body, ' ', context);
+        }
+        new_len += hunk_end - line;
+
+        let old_len = hunk_end - hunk_start;
+        let new_start = (hunk_start as isize + line_delta) as usize;
+        res.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(hunk_start, old_len),
+            hunk_range(new_start, new_len)
+        ));
+        res.push_str(&body);
+        line_delta += new_len as isize - old_len as isize;
+        idx = last + 1;
+    }
+    res
+}
+
+fn hunk_range(start: usize, len: usize) -> String {
+    // Empty ranges are reported as starting at the line before them.
+    let start = if len == 0 { start } else { start + 1 };
+    if len == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, len)
+    }
+}
+
+fn push_diff_line(buf: &mut String, prefix: char, line: &str) {
+    buf.push(prefix);
+    buf.push_str(line);
+    if !line.ends_with('\n') {
+        buf.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::{TextRange, TextSize};
+    use text_edit::TextEdit;
+
+    use super::unified_diff;
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(TextSize::from(start), TextSize::from(end))
+    }
+
+    #[test]
+    fn diff_of_separate_changes() {
+        let before = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let mut builder = TextEdit::builder();
+        builder.replace(range(2, 3), "B".to_string());
+        builder.insert(TextSize::from(22), "x\n".to_string());
+        let diff = unified_diff("src/lib.rs", before, &builder.finish());
+        assert_eq!(
+            diff,
+            "--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -9,4 +9,5 @@
+ i
+ j
+ k
+-l
++x
++l
+"
+        );
+    }
+
+    #[test]
+    fn diff_merges_nearby_changes() {
+        let before = "fn f() {\n    foo(1);\n    foo(2);\n}";
+        let mut builder = TextEdit::builder();
+        builder.replace(range(13, 16), "bar".to_string());
+        builder.replace(range(25, 28), "bar".to_string());
+        builder.insert(TextSize::from(34), "\n".to_string());
+        let diff = unified_diff("lib.rs", before, &builder.finish());
+        assert_eq!(
+            diff,
+            "--- a/lib.rs
++++ b/lib.rs
+@@ -1,4 +1,4 @@
+ fn f() {
+-    foo(1);
+-    foo(2);
+-}
+\\ No newline at end of file
++    bar(1);
++    bar(2);
++}
+"
+        );
+    }
+}
@@ -10,7 +10,10 @@
 use std::{ffi::OsString, iter, path::PathBuf};
 
 use flycheck::FlycheckConfig;
-use ide::{AssistConfig, CompletionConfig, DiagnosticsConfig, HoverConfig, InlayHintsConfig};
+use ide::{
+    AssistConfig, CompletionConfig, DiagnosticsConfig, HighlightConfig, HoverConfig,
+    InjectedLanguage, InlayHintsConfig, Snippet, SnippetScope,
+};
 use ide_db::helpers::{
     insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
     SnippetCap,
@@ -18,7 +21,10 @@ use ide_db::helpers::{
 use lsp_types::{ClientCapabilities, MarkupKind};
 use project_model::{CargoConfig, ProjectJson, ProjectJsonData, ProjectManifest, RustcSource};
 use rustc_hash::{FxHashMap, FxHashSet};
-use serde::{de::DeserializeOwned, Deserialize};
+use serde::{
+    de::{DeserializeOwned, Error},
+    Deserialize,
+};
 use vfs::AbsPathBuf;
 
 use crate::{
@@ -104,6 +110,12 @@ config_data! {
         /// Toggles the additional completions that automatically show method calls and field accesses
         /// with `self` prefixed to them when inside a method.
         completion_autoself_enable: bool       = "true",
+        /// Custom completion snippets, keyed by name. Each snippet has
+        /// `prefix` or `postfix` triggers, a `body`, an optional `description`,
+        /// the item paths it `requires` (imported on completion), whether those
+        /// paths must resolve for the snippet to show (`requireResolved`) and
+        /// its `scope` (`expr` or `item`).
+        completion_snippets: FxHashMap<String, SnippetDef> = "{}",
 
         /// Whether to show native rust-analyzer diagnostics.
         diagnostics_enable: bool                = "true",
@@ -112,6 +124,11 @@ config_data! {
         diagnostics_enableExperimental: bool    = "true",
         /// List of rust-analyzer diagnostics to disable.
         diagnostics_disabled: FxHashSet<String> = "[]",
+        /// Whether to show diagnostics for imports that are never used.
+        diagnostics_unusedImports_enable: bool  = "false",
+        /// Whether to show diagnostics for private and `pub(crate)` items
+        /// that are never used in their crate.
+        diagnostics_unusedItems_enable: bool    = "false",
         /// Map of prefixes to be substituted when parsing diagnostic file paths.
         /// This should be the reverse mapping of what is passed to `rustc` as `--remap-path-prefix`.
         diagnostics_remapPrefix: FxHashMap<String, String> = "{}",
@@ -140,6 +157,13 @@ config_data! {
         /// By disabling semantic tokens for strings, other grammars can be used to highlight
         /// their contents.
         highlighting_strings: bool = "true",
+        /// Map of paths of functions and macros, like `regex::Regex::new`, to the language
+        /// (`"regex"` or `"sql"`) their string literal argument is highlighted as.
+        highlighting_injections: FxHashMap<String, String> = "{}",
+
+        /// Whether to show the size and alignment of structs, unions and enums, and the offsets
+        /// of fields, in hover.
+        hover_memoryLayout: bool           = "true",
 
         /// Whether to show `Debug` action. Only applies when
         /// `#rust-analyzer.hoverActions.enable#` is set.
@@ -163,11 +187,21 @@ config_data! {
 
         /// Whether to show inlay type hints for method chains.
         inlayHints_chainingHints: bool      = "true",
+        /// Whether to show inlay hints for what closures capture and how.
+        inlayHints_closureCaptureHints: bool = "false",
+        /// Whether to show inlay type hints for return types of closures with
+        /// block bodies.
+        inlayHints_closureReturnTypeHints: bool = "false",
+        /// Whether to show inlay hints for elided lifetimes in function signatures.
+        inlayHints_lifetimeElisionHints: bool = "false",
         /// Maximum length for inlay hints. Set to null to have an unlimited length.
         inlayHints_maxLength: Option<usize> = "25",
         /// Whether to show function parameter name inlay hints at the call
         /// site.
         inlayHints_parameterHints: bool     = "true",
+        /// Whether to name elided lifetimes after the parameters they come
+        /// from instead of numbering them.
+        inlayHints_paramNamesForLifetimeElisionHints: bool = "false",
         /// Whether to show inlay type hints for variables.
         inlayHints_typeHints: bool          = "true",
 
@@ -188,6 +222,10 @@ config_data! {
         /// Whether to show `References` lens. Only applies when
         /// `#rust-analyzer.lens.enable#` is set.
         lens_references: bool = "false",
+        /// Whether to show `Implements` lens on methods of trait impls and
+        /// `Overrides` lens on trait methods with a default body. Only applies
+        /// when `#rust-analyzer.lens.enable#` is set.
+        lens_traitMethods: bool = "false",
 
         /// Disable project auto-discovery in favor of explicitly specified set
         /// of projects.
@@ -252,7 +290,11 @@ pub struct Config {
     data: ConfigData,
     detached_files: Vec<AbsPathBuf>,
     pub discovered_projects: Option<Vec<ProjectManifest>>,
+    /// LSP 3.17 pull diagnostics capabilities of the client, which
+    /// `lsp_types::ClientCapabilities` can't represent yet.
+    pub pull_diagnostics_caps: Option<PullDiagnosticsCapabilities>,
     pub root_path: AbsPathBuf,
+    snippets: Vec<Snippet>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -280,11 +322,12 @@ pub struct LensConfig {
     pub implementations: bool,
     pub method_refs: bool,
     pub refs: bool, // for Struct, Enum, Union and Trait
+    pub trait_methods: bool,
 }
 
 impl LensConfig {
     pub fn any(&self) -> bool {
-        self.implementations || self.runnable() || self.references()
+        self.implementations || self.runnable() || self.references() || self.trait_methods
     }
 
     pub fn none(&self) -> bool {
@@ -312,6 +355,12 @@ pub enum FilesWatcher {
     Notify,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct PullDiagnosticsCapabilities {
+    /// Whether the client handles `workspace/diagnostic/refresh` requests.
+    pub refresh_support: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct NotificationsConfig {
     pub cargo_toml_not_found: bool,
@@ -348,7 +397,9 @@ impl Config {
             data: ConfigData::default(),
             detached_files: Vec::new(),
             discovered_projects: None,
+            pull_diagnostics_caps: None,
             root_path,
+            snippets: Vec::new(),
         }
     }
     pub fn update(&mut self, mut json: serde_json::Value) {
@@ -361,6 +412,30 @@ impl Config {
             .map(AbsPathBuf::assert)
             .collect();
         self.data = ConfigData::from_json(json);
+        self.snippets = self
+            .data
+            .completion_snippets
+            .iter()
+            .filter_map(|(name, def)| {
+                let scope = match def.scope {
+                    SnippetScopeDef::Expr => SnippetScope::Expr,
+                    SnippetScopeDef::Item => SnippetScope::Item,
+                };
+                let snippet = Snippet::new(
+                    &def.prefix,
+                    &def.postfix,
+                    &def.body,
+                    def.description.as_ref().unwrap_or(name),
+                    &def.requires,
+                    scope,
+                    def.require_resolved,
+                );
+                if snippet.is_none() {
+                    log::error!("failed to load snippet `{}`", name);
+                }
+                snippet
+            })
+            .collect();
     }
 
     pub fn json_schema() -> serde_json::Value {
@@ -526,12 +601,23 @@ impl Config {
     }
 
     pub fn publish_diagnostics(&self) -> bool {
+        self.data.diagnostics_enable && self.pull_diagnostics_caps.is_none()
+    }
+    pub fn pull_diagnostics(&self) -> bool {
+        self.pull_diagnostics_caps.is_some()
+    }
+    pub fn diagnostics_refresh(&self) -> bool {
+        self.pull_diagnostics_caps.as_ref().map_or(false, |it| it.refresh_support)
+    }
+    pub fn diagnostics_enable(&self) -> bool {
         self.data.diagnostics_enable
     }
     pub fn diagnostics(&self) -> DiagnosticsConfig {
         DiagnosticsConfig {
             disable_experimental: !self.data.diagnostics_enableExperimental,
             disabled: self.data.diagnostics_disabled.clone(),
+            unused_imports: self.data.diagnostics_unusedImports_enable,
+            unused_items: self.data.diagnostics_unusedItems_enable,
         }
     }
     pub fn diagnostics_map(&self) -> DiagnosticsMapConfig {
@@ -654,6 +740,12 @@ impl Config {
             type_hints: self.data.inlayHints_typeHints,
             parameter_hints: self.data.inlayHints_parameterHints,
             chaining_hints: self.data.inlayHints_chainingHints,
+            lifetime_elision_hints: self.data.inlayHints_lifetimeElisionHints,
+            param_names_for_lifetime_elision_hints: self
+                .data
+                .inlayHints_paramNamesForLifetimeElisionHints,
+            closure_return_type_hints: self.data.inlayHints_closureReturnTypeHints,
+            closure_capture_hints: self.data.inlayHints_closureCaptureHints,
             max_length: self.data.inlayHints_maxLength,
         }
     }
@@ -683,6 +775,7 @@ impl Config {
             add_call_parenthesis: self.data.completion_addCallParenthesis,
             add_call_argument_snippets: self.data.completion_addCallArgumentSnippets,
             insert_use: self.insert_use_config(),
+            snippets: self.snippets.clone(),
             snippet_cap: SnippetCap::new(try_or!(
                 self.caps
                     .text_document
@@ -713,11 +806,29 @@ impl Config {
             implementations: self.data.lens_enable && self.data.lens_implementations,
             method_refs: self.data.lens_enable && self.data.lens_methodReferences,
             refs: self.data.lens_enable && self.data.lens_references,
+            trait_methods: self.data.lens_enable && self.data.lens_traitMethods,
         }
     }
     pub fn highlighting_strings(&self) -> bool {
         self.data.highlighting_strings
     }
+    pub fn highlighting_config(&self) -> HighlightConfig {
+        HighlightConfig {
+            injections: self
+                .data
+                .highlighting_injections
+                .iter()
+                .filter_map(|(path, language)| {
+                    let language = match language.as_str() {
+                        "regex" => InjectedLanguage::Regex,
+                        "sql" => InjectedLanguage::Sql,
+                        _ => return None,
+                    };
+                    Some((path.clone(), language))
+                })
+                .collect(),
+        }
+    }
     pub fn hover(&self) -> HoverConfig {
         HoverConfig {
             implementations: self.data.hoverActions_enable
@@ -727,6 +838,7 @@ impl Config {
             debug: self.data.hoverActions_enable && self.data.hoverActions_debug,
             goto_type_def: self.data.hoverActions_enable && self.data.hoverActions_gotoTypeDef,
             links_in_hover: self.data.hoverActions_linksInHover,
+            memory_layout: self.data.hover_memoryLayout,
             markdown: try_or!(
                 self.caps
                     .text_document
@@ -778,6 +890,51 @@ impl Config {
     }
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SnippetDef {
+    #[serde(deserialize_with = "single_or_array", default)]
+    prefix: Vec<String>,
+    #[serde(deserialize_with = "single_or_array", default)]
+    postfix: Vec<String>,
+    description: Option<String>,
+    #[serde(deserialize_with = "single_or_array")]
+    body: Vec<String>,
+    #[serde(deserialize_with = "single_or_array", default)]
+    requires: Vec<String>,
+    #[serde(default = "default_true")]
+    require_resolved: bool,
+    #[serde(default)]
+    scope: SnippetScopeDef,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum SnippetScopeDef {
+    Expr,
+    Item,
+}
+
+impl Default for SnippetScopeDef {
+    fn default() -> Self {
+        SnippetScopeDef::Expr
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn single_or_array<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(it) => Ok(vec![it]),
+        it => serde_json::from_value(it).map_err(D::Error::custom),
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(untagged)]
 enum ManifestOrProjectJson {
@@ -946,6 +1103,22 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
         "FxHashMap<String, String>" => set! {
             "type": "object",
         },
+        "FxHashMap<String, SnippetDef>" => set! {
+            "type": "object",
+            "additionalProperties": {
+                "type": "object",
+                "properties": {
+                    "prefix": { "type": ["string", "array"], "items": { "type": "string" } },
+                    "postfix": { "type": ["string", "array"], "items": { "type": "string" } },
+                    "body": { "type": ["string", "array"], "items": { "type": "string" } },
+                    "description": { "type": "string" },
+                    "requires": { "type": ["string", "array"], "items": { "type": "string" } },
+                    "requireResolved": { "type": "boolean" },
+                    "scope": { "type": "string", "enum": ["expr", "item"] },
+                },
+                "required": ["body"],
+            },
+        },
         "Option<usize>" => set! {
             "type": ["null", "integer"],
             "minimum": 0,
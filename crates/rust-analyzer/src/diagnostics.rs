@@ -3,12 +3,13 @@ pub(crate) mod to_proto;
 
 use std::{mem, sync::Arc};
 
-use ide::FileId;
+use ide::{FileId, SourceRootId};
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::lsp_ext;
 
 pub(crate) type CheckFixes = Arc<FxHashMap<FileId, Vec<Fix>>>;
+pub(crate) type CheckDiagnostics = Arc<FxHashMap<FileId, Vec<lsp_types::Diagnostic>>>;
 
 #[derive(Debug, Default, Clone)]
 pub struct DiagnosticsMapConfig {
@@ -22,9 +23,52 @@ pub(crate) struct DiagnosticCollection {
     // FIXME: should be FxHashMap<FileId, Vec<ra_id::Diagnostic>>
     pub(crate) native: FxHashMap<FileId, Vec<lsp_types::Diagnostic>>,
     // FIXME: should be Vec<flycheck::Diagnostic>
-    pub(crate) check: FxHashMap<FileId, Vec<lsp_types::Diagnostic>>,
+    pub(crate) check: CheckDiagnostics,
     pub(crate) check_fixes: CheckFixes,
     changes: FxHashSet<FileId>,
+    pub(crate) revisions: Arc<DiagnosticsRevisions>,
+}
+
+/// Records when the inputs of the diagnostics of each file last changed, so
+/// pulled diagnostics are only recomputed for the files a change can affect.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct DiagnosticsRevisions {
+    /// Bumped on every change.
+    current: u64,
+    /// The last change that can affect any file, like a configuration or
+    /// project change.
+    all: u64,
+    /// The last edit each source root can observe, either to its own files or
+    /// to the files of the crates it depends on.
+    source_roots: FxHashMap<SourceRootId, u64>,
+    /// The last change of the check diagnostics of each file.
+    check: FxHashMap<FileId, u64>,
+}
+
+impl DiagnosticsRevisions {
+    pub(crate) fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// The revision at which the diagnostics of `file_id` last may have
+    /// changed. Pulled diagnostics computed at or after it are up to date.
+    pub(crate) fn changed_at(&self, file_id: FileId, source_root: SourceRootId) -> u64 {
+        let source_root = self.source_roots.get(&source_root).copied().unwrap_or_default();
+        let check = self.check.get(&file_id).copied().unwrap_or_default();
+        self.all.max(source_root).max(check)
+    }
+}
+
+/// The diagnostics last reported for a file in the pull model.
+#[derive(Debug, Clone)]
+pub(crate) struct PulledDiagnostics {
+    /// The [`DiagnosticsRevisions::current`] revision the diagnostics were
+    /// computed at.
+    pub(crate) computed_at: u64,
+    /// Bumped only when the diagnostics of the file change, used as the
+    /// result id.
+    pub(crate) result_id: u64,
+    pub(crate) hash: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -36,7 +80,14 @@ pub(crate) struct Fix {
 impl DiagnosticCollection {
     pub(crate) fn clear_check(&mut self) {
         Arc::make_mut(&mut self.check_fixes).clear();
-        self.changes.extend(self.check.drain().map(|(key, _value)| key))
+        let cleared: Vec<FileId> =
+            Arc::make_mut(&mut self.check).drain().map(|(key, _value)| key).collect();
+        let revisions = Arc::make_mut(&mut self.revisions);
+        revisions.current += 1;
+        for &file_id in &cleared {
+            revisions.check.insert(file_id, revisions.current);
+        }
+        self.changes.extend(cleared);
     }
 
     pub(crate) fn add_check_diagnostic(
@@ -45,11 +96,9 @@ impl DiagnosticCollection {
         diagnostic: lsp_types::Diagnostic,
         fixes: Vec<lsp_ext::CodeAction>,
     ) {
-        let diagnostics = self.check.entry(file_id).or_default();
-        for existing_diagnostic in diagnostics.iter() {
-            if are_diagnostics_equal(&existing_diagnostic, &diagnostic) {
-                return;
-            }
+        let mut existing = self.check.get(&file_id).into_iter().flatten();
+        if existing.any(|it| are_diagnostics_equal(it, &diagnostic)) {
+            return;
         }
 
         let check_fixes = Arc::make_mut(&mut self.check_fixes);
@@ -57,8 +106,11 @@ impl DiagnosticCollection {
             .entry(file_id)
             .or_default()
             .extend(fixes.into_iter().map(|action| Fix { range: diagnostic.range, action }));
-        diagnostics.push(diagnostic);
+        Arc::make_mut(&mut self.check).entry(file_id).or_default().push(diagnostic);
         self.changes.insert(file_id);
+        let revisions = Arc::make_mut(&mut self.revisions);
+        revisions.current += 1;
+        revisions.check.insert(file_id, revisions.current);
     }
 
     pub(crate) fn set_native_diagnostics(
@@ -90,6 +142,25 @@ impl DiagnosticCollection {
         native.chain(check)
     }
 
+    /// Marks the pulled diagnostics of all files as stale.
+    pub(crate) fn invalidate_all(&mut self) {
+        let revisions = Arc::make_mut(&mut self.revisions);
+        revisions.current += 1;
+        revisions.all = revisions.current;
+    }
+
+    /// Marks the pulled diagnostics of the files in `source_roots` as stale.
+    pub(crate) fn invalidate_source_roots(
+        &mut self,
+        source_roots: impl IntoIterator<Item = SourceRootId>,
+    ) {
+        let revisions = Arc::make_mut(&mut self.revisions);
+        revisions.current += 1;
+        for source_root in source_roots {
+            revisions.source_roots.insert(source_root, revisions.current);
+        }
+    }
+
     pub(crate) fn take_changes(&mut self) -> Option<FxHashSet<FileId>> {
         if self.changes.is_empty() {
             return None;
@@ -2,7 +2,10 @@
 use std::convert::TryFrom;
 
 use ide::{Annotation, AnnotationKind, AssistKind, LineCol, LineColUtf16};
-use ide_db::base_db::{FileId, FilePosition, FileRange};
+use ide_db::{
+    base_db::{FileId, FilePosition, FileRange},
+    helpers::insert_use::ImportGranularity,
+};
 use syntax::{TextRange, TextSize};
 use vfs::AbsPathBuf;
 
@@ -75,12 +78,25 @@ pub(crate) fn assist_kind(kind: lsp_types::CodeActionKind) -> Option<AssistKind>
         k if k == &lsp_types::CodeActionKind::REFACTOR_EXTRACT => AssistKind::RefactorExtract,
         k if k == &lsp_types::CodeActionKind::REFACTOR_INLINE => AssistKind::RefactorInline,
         k if k == &lsp_types::CodeActionKind::REFACTOR_REWRITE => AssistKind::RefactorRewrite,
+        k if k == &lsp_types::CodeActionKind::SOURCE => AssistKind::Source,
+        k if k == &lsp_types::CodeActionKind::SOURCE_ORGANIZE_IMPORTS => {
+            AssistKind::SourceOrganizeImports
+        }
         _ => return None,
     };
 
     Some(assist_kind)
 }
 
+pub(crate) fn import_granularity(granularity: lsp_ext::ImportGranularity) -> ImportGranularity {
+    match granularity {
+        lsp_ext::ImportGranularity::Preserve => ImportGranularity::Preserve,
+        lsp_ext::ImportGranularity::Crate => ImportGranularity::Crate,
+        lsp_ext::ImportGranularity::Module => ImportGranularity::Module,
+        lsp_ext::ImportGranularity::Item => ImportGranularity::Item,
+    }
+}
+
 pub(crate) fn annotation(
     snap: &GlobalStateSnapshot,
     code_lens: lsp_types::CodeLens,
@@ -114,5 +130,29 @@ pub(crate) fn annotation(
                 },
             })
         }
+        lsp_ext::CodeLensResolveData::ImplementsTraitItem(params) => {
+            let file_id = snap.url_to_file_id(&params.text_document.uri)?;
+            let line_index = snap.file_line_index(file_id)?;
+
+            Ok(Annotation {
+                range: text_range(&line_index, code_lens.range),
+                kind: AnnotationKind::ImplementsTraitItem {
+                    position: file_position(snap, params)?,
+                    data: None,
+                },
+            })
+        }
+        lsp_ext::CodeLensResolveData::Overrides(params) => {
+            let file_id = snap.url_to_file_id(&params.text_document.uri)?;
+            let line_index = snap.file_line_index(file_id)?;
+
+            Ok(Annotation {
+                range: text_range(&line_index, code_lens.range),
+                kind: AnnotationKind::HasOverrides {
+                    position: file_position(snap, params)?,
+                    data: None,
+                },
+            })
+        }
     }
 }
@@ -8,18 +8,20 @@ use std::{sync::Arc, time::Instant};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use flycheck::FlycheckHandle;
 use ide::{Analysis, AnalysisHost, Cancellable, Change, FileId};
-use ide_db::base_db::{CrateId, VfsPath};
+use ide_db::base_db::{CrateId, SourceDatabase, SourceDatabaseExt, SourceRootId, VfsPath};
 use lsp_types::{SemanticTokens, Url};
 use parking_lot::{Mutex, RwLock};
 use project_model::{
     BuildDataCollector, BuildDataResult, CargoWorkspace, ProcMacroClient, ProjectWorkspace, Target,
 };
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use vfs::AnchoredPathBuf;
 
 use crate::{
     config::Config,
-    diagnostics::{CheckFixes, DiagnosticCollection},
+    diagnostics::{
+        CheckDiagnostics, CheckFixes, DiagnosticCollection, DiagnosticsRevisions, PulledDiagnostics,
+    },
     document::DocumentData,
     from_proto,
     line_index::{LineEndings, LineIndex},
@@ -59,6 +61,7 @@ pub(crate) struct GlobalState {
     pub(crate) diagnostics: DiagnosticCollection,
     pub(crate) mem_docs: FxHashMap<VfsPath, DocumentData>,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    pub(crate) pulled_diagnostics: Arc<Mutex<FxHashMap<FileId, PulledDiagnostics>>>,
     pub(crate) shutdown_requested: bool,
     pub(crate) last_reported_status: Option<lsp_ext::ServerStatusParams>,
     pub(crate) source_root_config: SourceRootConfig,
@@ -94,9 +97,12 @@ pub(crate) struct GlobalStateSnapshot {
     pub(crate) config: Arc<Config>,
     pub(crate) analysis: Analysis,
     pub(crate) check_fixes: CheckFixes,
+    pub(crate) check_diagnostics: CheckDiagnostics,
+    pub(crate) diagnostics_revisions: Arc<DiagnosticsRevisions>,
     pub(crate) latest_requests: Arc<RwLock<LatestRequests>>,
     mem_docs: FxHashMap<VfsPath, DocumentData>,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    pub(crate) pulled_diagnostics: Arc<Mutex<FxHashMap<FileId, PulledDiagnostics>>>,
     vfs: Arc<RwLock<(vfs::Vfs, FxHashMap<FileId, LineEndings>)>>,
     pub(crate) workspaces: Arc<Vec<ProjectWorkspace>>,
 }
@@ -129,6 +135,7 @@ impl GlobalState {
             diagnostics: Default::default(),
             mem_docs: FxHashMap::default(),
             semantic_tokens_cache: Arc::new(Default::default()),
+            pulled_diagnostics: Arc::new(Default::default()),
             shutdown_requested: false,
             last_reported_status: None,
             source_root_config: SourceRootConfig::default(),
@@ -161,6 +168,7 @@ impl GlobalState {
         let _p = profile::span("GlobalState::process_changes");
         let mut fs_changes = Vec::new();
         let mut has_fs_changes = false;
+        let mut changed_file_ids = Vec::new();
 
         let change = {
             let mut change = Change::new();
@@ -191,6 +199,7 @@ impl GlobalState {
                 } else {
                     None
                 };
+                changed_file_ids.push(file.file_id);
                 change.change_file(file.file_id, text);
             }
             if has_fs_changes {
@@ -201,10 +210,39 @@ impl GlobalState {
         };
 
         self.analysis_host.apply_change(change);
+        if has_fs_changes {
+            self.diagnostics.invalidate_all();
+        } else {
+            let source_roots = self.affected_source_roots(&changed_file_ids);
+            self.diagnostics.invalidate_source_roots(source_roots);
+        }
         self.maybe_refresh(&fs_changes);
         true
     }
 
+    /// The source roots whose diagnostics can change when the given files
+    /// change: their own source roots and those of all crates depending on
+    /// the crates in them.
+    fn affected_source_roots(&self, file_ids: &[FileId]) -> FxHashSet<SourceRootId> {
+        let db = self.analysis_host.raw_database();
+        let crate_graph = db.crate_graph();
+        let mut res = FxHashSet::default();
+        for &file_id in file_ids {
+            let source_root = db.file_source_root(file_id);
+            if !res.insert(source_root) {
+                continue;
+            }
+            for &krate in db.source_root_crates(source_root).iter() {
+                res.extend(
+                    crate_graph
+                        .transitive_rev_deps(krate)
+                        .map(|it| db.file_source_root(crate_graph[it].root_file_id)),
+                );
+            }
+        }
+        res
+    }
+
     pub(crate) fn snapshot(&self) -> GlobalStateSnapshot {
         GlobalStateSnapshot {
             config: Arc::clone(&self.config),
@@ -213,8 +251,11 @@ impl GlobalState {
             vfs: Arc::clone(&self.vfs),
             latest_requests: Arc::clone(&self.latest_requests),
             check_fixes: Arc::clone(&self.diagnostics.check_fixes),
+            check_diagnostics: Arc::clone(&self.diagnostics.check),
+            diagnostics_revisions: Arc::clone(&self.diagnostics.revisions),
             mem_docs: self.mem_docs.clone(),
             semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
+            pulled_diagnostics: Arc::clone(&self.pulled_diagnostics),
         }
     }
 
@@ -290,6 +331,25 @@ impl GlobalStateSnapshot {
         Ok(res)
     }
 
+    /// Returns the Rust files of the workspace, skipping library files.
+    pub(crate) fn workspace_files(&self) -> Cancellable<Vec<FileId>> {
+        let files: Vec<FileId> = self
+            .vfs
+            .read()
+            .0
+            .iter()
+            .filter(|(_, path)| matches!(path.name_and_extension(), Some((_, Some("rs")))))
+            .map(|(file_id, _)| file_id)
+            .collect();
+        let mut res = Vec::new();
+        for file_id in files {
+            if !self.analysis.is_library_file(file_id)? {
+                res.push(file_id);
+            }
+        }
+        Ok(res)
+    }
+
     pub(crate) fn url_file_version(&self, url: &Url) -> Option<i32> {
         let path = from_proto::vfs_path(&url).ok()?;
         Some(self.mem_docs.get(&path)?.version)
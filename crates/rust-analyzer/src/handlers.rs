@@ -3,6 +3,8 @@
 //! `ide` crate.
 
 use std::{
+    collections::hash_map::Entry,
+    hash::{Hash, Hasher},
     io::{Read, Write as _},
     process::{self, Command, Stdio},
 };
@@ -26,6 +28,7 @@ use lsp_types::{
     TextDocumentIdentifier, TextDocumentPositionParams, Url, WorkspaceEdit,
 };
 use project_model::TargetKind;
+use rustc_hash::{FxHashMap, FxHasher};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, to_value};
 use stdx::format_to;
@@ -34,6 +37,7 @@ use syntax::{algo, ast, AstNode, TextRange, TextSize};
 use crate::{
     cargo_target_spec::CargoTargetSpec,
     config::RustfmtConfig,
+    diagnostics::PulledDiagnostics,
     diff::diff,
     from_proto,
     global_state::{GlobalState, GlobalStateSnapshot},
@@ -158,6 +162,19 @@ pub(crate) fn handle_expand_macro(
     Ok(res.map(|it| lsp_ext::ExpandedMacro { name: it.name, expansion: it.expansion }))
 }
 
+pub(crate) fn handle_expand_macro_steps(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::ExpandMacroParams,
+) -> Result<Option<Vec<lsp_ext::MacroExpansionStep>>> {
+    let _p = profile::span("handle_expand_macro_steps");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let offset = from_proto::offset(&line_index, params.position);
+
+    let res = snap.analysis.expand_macro_steps(FilePosition { file_id, offset })?;
+    Ok(res.map(|steps| steps.into_iter().map(to_proto::macro_expansion_step).collect()))
+}
+
 pub(crate) fn handle_selection_range(
     snap: GlobalStateSnapshot,
     params: lsp_types::SelectionRangeParams,
@@ -750,10 +767,8 @@ pub(crate) fn handle_completion(
             let mut new_completion_items =
                 to_proto::completion_item(insert_replace_support, &line_index, item.clone());
 
-            if completion_config.enable_imports_on_the_fly {
-                for new_item in &mut new_completion_items {
-                    fill_resolve_data(&mut new_item.data, &item, &text_document_position);
-                }
+            for new_item in &mut new_completion_items {
+                fill_resolve_data(&mut new_item.data, &item, &text_document_position);
             }
 
             new_completion_items
@@ -797,8 +812,10 @@ pub(crate) fn handle_completion_resolve(
         .resolve_completion_edits(
             &snap.config.completion(),
             FilePosition { file_id, offset },
-            &resolve_data.full_import_path,
-            resolve_data.imported_name,
+            resolve_data
+                .imports
+                .into_iter()
+                .map(|import| (import.full_import_path, import.imported_name)),
         )?
         .into_iter()
         .flat_map(|edit| edit.into_iter().map(|indel| to_proto::text_edit(&line_index, indel)))
@@ -862,11 +879,10 @@ pub(crate) fn handle_hover(
     let _p = profile::span("handle_hover");
     let position = from_proto::file_position(&snap, params.text_document_position_params)?;
     let hover_config = snap.config.hover();
-    let info =
-        match snap.analysis.hover(position, hover_config.links_in_hover, hover_config.markdown)? {
-            None => return Ok(None),
-            Some(info) => info,
-        };
+    let info = match snap.analysis.hover(position, &hover_config)? {
+        None => return Ok(None),
+        Some(info) => info,
+    };
     let line_index = snap.file_line_index(position.file_id)?;
     let range = to_proto::range(&line_index, info.range);
     let hover = lsp_ext::Hover {
@@ -1142,6 +1158,7 @@ pub(crate) fn handle_code_lens(
                 annotate_impls: lens_config.implementations,
                 annotate_references: lens_config.refs,
                 annotate_method_references: lens_config.method_refs,
+                annotate_trait_methods: lens_config.trait_methods,
                 run: lens_config.run,
                 debug: lens_config.debug,
             },
@@ -1215,6 +1232,22 @@ pub(crate) fn handle_ssr(
     to_proto::workspace_edit(&snap, source_change)
 }
 
+pub(crate) fn handle_normalize_imports(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::NormalizeImportsParams,
+) -> Result<lsp_types::WorkspaceEdit> {
+    let _p = profile::span("handle_normalize_imports");
+    let mut config = snap.config.assist().insert_use;
+    if let Some(granularity) = params.granularity {
+        config.granularity = from_proto::import_granularity(granularity);
+    }
+    if let Some(group) = params.group {
+        config.group = group;
+    }
+    let source_change = snap.analysis.normalize_imports(&config)?;
+    to_proto::workspace_edit(&snap, source_change)
+}
+
 pub(crate) fn publish_diagnostics(
     snap: &GlobalStateSnapshot,
     file_id: FileId,
@@ -1248,6 +1281,102 @@ pub(crate) fn publish_diagnostics(
     Ok(diagnostics)
 }
 
+pub(crate) fn handle_document_diagnostic(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::DocumentDiagnosticParams,
+) -> Result<lsp_ext::DocumentDiagnosticReport> {
+    let _p = profile::span("handle_document_diagnostic");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    pull_diagnostics(&snap, file_id, params.previous_result_id.as_deref())
+}
+
+pub(crate) fn handle_workspace_diagnostic(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::WorkspaceDiagnosticParams,
+) -> Result<lsp_ext::WorkspaceDiagnosticReport> {
+    let _p = profile::span("handle_workspace_diagnostic");
+    let previous_result_ids: FxHashMap<Url, String> =
+        params.previous_result_ids.into_iter().map(|it| (it.uri, it.value)).collect();
+
+    let mut items = Vec::new();
+    for file_id in snap.workspace_files()? {
+        let uri = snap.file_id_to_url(file_id);
+        let previous_result_id = previous_result_ids.get(&uri).map(String::as_str);
+        let report = pull_diagnostics(&snap, file_id, previous_result_id)?;
+        let version = snap.url_file_version(&uri);
+        items.push(lsp_ext::WorkspaceDocumentDiagnosticReport { uri, version, report });
+    }
+    Ok(lsp_ext::WorkspaceDiagnosticReport { items })
+}
+
+/// Computes the diagnostics of a file for the pull model.
+///
+/// Every file has its own result id, which only changes when the diagnostics
+/// of the file do. If nothing the diagnostics of the file depend on changed
+/// since they were last computed, the previous report is reused without
+/// computing anything.
+fn pull_diagnostics(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+    previous_result_id: Option<&str>,
+) -> Result<lsp_ext::DocumentDiagnosticReport> {
+    let unchanged = |result_id: String| {
+        lsp_ext::DocumentDiagnosticReport::Unchanged(lsp_ext::UnchangedDocumentDiagnosticReport {
+            result_id,
+        })
+    };
+
+    let revisions = &snap.diagnostics_revisions;
+    let changed_at = revisions.changed_at(file_id, snap.analysis.file_source_root(file_id)?);
+    if let Some(pulled) = snap.pulled_diagnostics.lock().get(&file_id) {
+        let result_id = pulled.result_id.to_string();
+        if pulled.computed_at >= changed_at && previous_result_id == Some(result_id.as_str()) {
+            return Ok(unchanged(result_id));
+        }
+    }
+
+    let mut items = if snap.config.diagnostics_enable() {
+        publish_diagnostics(snap, file_id)?
+    } else {
+        Vec::new()
+    };
+    items.extend(snap.check_diagnostics.get(&file_id).into_iter().flatten().cloned());
+
+    let hash = {
+        let mut hasher = FxHasher::default();
+        serde_json::to_string(&items)?.hash(&mut hasher);
+        hasher.finish()
+    };
+    let result_id = match snap.pulled_diagnostics.lock().entry(file_id) {
+        // Computed by a newer snapshot already, these diagnostics are stale.
+        Entry::Occupied(it) if it.get().computed_at > revisions.current() => None,
+        Entry::Occupied(mut it) => {
+            let pulled = it.get_mut();
+            if pulled.hash != hash {
+                pulled.result_id += 1;
+                pulled.hash = hash;
+            }
+            pulled.computed_at = revisions.current();
+            Some(pulled.result_id.to_string())
+        }
+        Entry::Vacant(it) => {
+            it.insert(PulledDiagnostics { computed_at: revisions.current(), result_id: 0, hash });
+            Some("0".to_string())
+        }
+    };
+
+    let report = match result_id {
+        Some(result_id) if previous_result_id == Some(result_id.as_str()) => unchanged(result_id),
+        result_id => {
+            lsp_ext::DocumentDiagnosticReport::Full(lsp_ext::FullDocumentDiagnosticReport {
+                result_id,
+                items,
+            })
+        }
+    };
+    Ok(report)
+}
+
 pub(crate) fn handle_inlay_hints(
     snap: GlobalStateSnapshot,
     params: InlayHintsParams,
@@ -1355,6 +1484,74 @@ pub(crate) fn handle_call_hierarchy_outgoing(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_type_hierarchy_prepare(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::TypeHierarchyPrepareParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_prepare");
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+
+    let nav_info = match snap.analysis.prepare_type_hierarchy(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+
+    let RangeInfo { range: _, info: navs } = nav_info;
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::type_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_type_hierarchy_supertypes(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::TypeHierarchySupertypesParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_supertypes");
+    let fpos = type_hierarchy_item_position(&snap, params.item)?;
+
+    let navs = match snap.analysis.supertypes(fpos)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::type_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_type_hierarchy_subtypes(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::TypeHierarchySubtypesParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_subtypes");
+    let fpos = type_hierarchy_item_position(&snap, params.item)?;
+
+    let navs = match snap.analysis.subtypes(fpos)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::type_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Some(res))
+}
+
+fn type_hierarchy_item_position(
+    snap: &GlobalStateSnapshot,
+    item: lsp_ext::TypeHierarchyItem,
+) -> Result<FilePosition> {
+    let doc = TextDocumentIdentifier::new(item.uri);
+    let frange = from_proto::file_range(snap, doc, item.selection_range)?;
+    Ok(FilePosition { file_id: frange.file_id, offset: frange.range.start() })
+}
+
 pub(crate) fn handle_semantic_tokens_full(
     snap: GlobalStateSnapshot,
     params: SemanticTokensParams,
@@ -1365,7 +1562,7 @@ pub(crate) fn handle_semantic_tokens_full(
     let text = snap.analysis.file_text(file_id)?;
     let line_index = snap.file_line_index(file_id)?;
 
-    let highlights = snap.analysis.highlight(file_id)?;
+    let highlights = snap.analysis.highlight(&snap.config.highlighting_config(), file_id)?;
     let highlight_strings = snap.config.highlighting_strings();
     let semantic_tokens =
         to_proto::semantic_tokens(&text, &line_index, highlights, highlight_strings);
@@ -1386,7 +1583,7 @@ pub(crate) fn handle_semantic_tokens_full_delta(
     let text = snap.analysis.file_text(file_id)?;
     let line_index = snap.file_line_index(file_id)?;
 
-    let highlights = snap.analysis.highlight(file_id)?;
+    let highlights = snap.analysis.highlight(&snap.config.highlighting_config(), file_id)?;
     let highlight_strings = snap.config.highlighting_strings();
     let semantic_tokens =
         to_proto::semantic_tokens(&text, &line_index, highlights, highlight_strings);
@@ -1417,7 +1614,7 @@ pub(crate) fn handle_semantic_tokens_range(
     let text = snap.analysis.file_text(frange.file_id)?;
     let line_index = snap.file_line_index(frange.file_id)?;
 
-    let highlights = snap.analysis.highlight_range(frange)?;
+    let highlights = snap.analysis.highlight_range(&snap.config.highlighting_config(), frange)?;
     let highlight_strings = snap.config.highlighting_strings();
     let semantic_tokens =
         to_proto::semantic_tokens(&text, &line_index, highlights, highlight_strings);
@@ -1756,6 +1953,11 @@ fn run_rustfmt(
 #[derive(Debug, Serialize, Deserialize)]
 struct CompletionResolveData {
     position: lsp_types::TextDocumentPositionParams,
+    imports: Vec<CompletionImport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CompletionImport {
     full_import_path: String,
     imported_name: String,
 }
@@ -1765,16 +1967,22 @@ fn fill_resolve_data(
     item: &ide::CompletionItem,
     position: &TextDocumentPositionParams,
 ) -> Option<()> {
-    let import_edit = item.import_to_add()?;
-    let import_path = &import_edit.import.import_path;
-
-    *resolve_data = Some(
-        to_value(CompletionResolveData {
-            position: position.to_owned(),
-            full_import_path: import_path.to_string(),
-            imported_name: import_path.segments().last()?.to_string(),
+    if item.imports_to_add().is_empty() {
+        return None;
+    }
+    let imports = item
+        .imports_to_add()
+        .iter()
+        .map(|import_edit| {
+            let import_path = &import_edit.import.import_path;
+            Some(CompletionImport {
+                full_import_path: import_path.to_string(),
+                imported_name: import_path.segments().last()?.to_string(),
+            })
         })
-        .unwrap(),
-    );
+        .collect::<Option<Vec<_>>>()?;
+
+    *resolve_data =
+        Some(to_value(CompletionResolveData { position: position.to_owned(), imports }).unwrap());
     Some(())
 }
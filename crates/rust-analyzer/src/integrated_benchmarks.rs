@@ -142,6 +142,7 @@ fn integrated_completion_benchmark() {
                 enforce_granularity: true,
                 group: true,
             },
+            snippets: Vec::new(),
         };
         let position =
             FilePosition { file_id, offset: TextSize::try_from(completion_offset).unwrap() };
@@ -177,6 +178,7 @@ fn integrated_completion_benchmark() {
                 enforce_granularity: true,
                 group: true,
             },
+            snippets: Vec::new(),
         };
         let position =
             FilePosition { file_id, offset: TextSize::try_from(completion_offset).unwrap() };
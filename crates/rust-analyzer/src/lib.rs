@@ -45,7 +45,10 @@ mod integrated_benchmarks;
 use serde::de::DeserializeOwned;
 use std::fmt;
 
-pub use crate::{caps::server_capabilities, main_loop::main_loop};
+pub use crate::{
+    caps::{pull_diagnostics_capabilities, server_capabilities},
+    main_loop::main_loop,
+};
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Result<T, E = Error> = std::result::Result<T, E>;
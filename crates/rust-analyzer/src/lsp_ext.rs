@@ -106,6 +106,40 @@ pub struct ExpandedMacro {
     pub expansion: String,
 }
 
+pub enum ExpandMacroSteps {}
+
+impl Request for ExpandMacroSteps {
+    type Params = ExpandMacroParams;
+    type Result = Option<Vec<MacroExpansionStep>>;
+    const METHOD: &'static str = "rust-analyzer/expandMacroSteps";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroExpansionStep {
+    pub depth: u32,
+    pub name: String,
+    pub input: String,
+    pub rules: Vec<MacroRuleMatch>,
+    pub used_rule: Option<u32>,
+    pub expansion: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroRuleMatch {
+    pub pattern: String,
+    pub bindings: Vec<MacroBinding>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroBinding {
+    pub name: String,
+    pub value: String,
+}
+
 pub enum MatchingBrace {}
 
 impl Request for MatchingBrace {
@@ -232,6 +266,9 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    LifetimeHint,
+    ClosureReturnTypeHint,
+    ClosureCaptureHint,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -264,6 +301,32 @@ pub struct SsrParams {
     pub selections: Vec<lsp_types::Range>,
 }
 
+pub enum NormalizeImports {}
+
+impl Request for NormalizeImports {
+    type Params = NormalizeImportsParams;
+    type Result = lsp_types::WorkspaceEdit;
+    const METHOD: &'static str = "rust-analyzer/normalizeImports";
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeImportsParams {
+    /// Defaults to the `assist.importGranularity` setting.
+    pub granularity: Option<ImportGranularity>,
+    /// Defaults to the `assist.importGroup` setting.
+    pub group: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportGranularity {
+    Preserve,
+    Crate,
+    Module,
+    Item,
+}
+
 pub enum ServerStatusNotification {}
 
 impl Notification for ServerStatusNotification {
@@ -426,6 +489,8 @@ pub struct OpenCargoTomlParams {
 pub(crate) enum CodeLensResolveData {
     Impls(lsp_types::request::GotoImplementationParams),
     References(lsp_types::TextDocumentPositionParams),
+    ImplementsTraitItem(lsp_types::TextDocumentPositionParams),
+    Overrides(lsp_types::TextDocumentPositionParams),
 }
 
 pub fn supports_utf8(caps: &lsp_types::ClientCapabilities) -> bool {
@@ -492,3 +557,198 @@ pub enum WorkspaceSymbolSearchKind {
     OnlyTypes,
     AllSymbols,
 }
+
+#[derive(Debug)]
+pub enum TypeHierarchyPrepare {}
+
+impl Request for TypeHierarchyPrepare {
+    type Params = TypeHierarchyPrepareParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "textDocument/prepareTypeHierarchy";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyPrepareParams {
+    #[serde(flatten)]
+    pub text_document_position_params: lsp_types::TextDocumentPositionParams,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchySupertypes {}
+
+impl Request for TypeHierarchySupertypes {
+    type Params = TypeHierarchySupertypesParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/supertypes";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchySupertypesParams {
+    pub item: TypeHierarchyItem,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchySubtypes {}
+
+impl Request for TypeHierarchySubtypes {
+    type Params = TypeHierarchySubtypesParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/subtypes";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchySubtypesParams {
+    pub item: TypeHierarchyItem,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyItem {
+    pub name: String,
+    pub kind: lsp_types::SymbolKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<lsp_types::SymbolTag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub uri: lsp_types::Url,
+    pub range: Range,
+    pub selection_range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+pub enum DocumentDiagnosticRequest {}
+
+impl Request for DocumentDiagnosticRequest {
+    type Params = DocumentDiagnosticParams;
+    type Result = DocumentDiagnosticReport;
+    const METHOD: &'static str = "textDocument/diagnostic";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+    pub text_document: TextDocumentIdentifier,
+    pub identifier: Option<String>,
+    pub previous_result_id: Option<String>,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DocumentDiagnosticReport {
+    Full(FullDocumentDiagnosticReport),
+    Unchanged(UnchangedDocumentDiagnosticReport),
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FullDocumentDiagnosticReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub items: Vec<lsp_types::Diagnostic>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnchangedDocumentDiagnosticReport {
+    pub result_id: String,
+}
+
+pub enum WorkspaceDiagnosticRequest {}
+
+impl Request for WorkspaceDiagnosticRequest {
+    type Params = WorkspaceDiagnosticParams;
+    type Result = WorkspaceDiagnosticReport;
+    const METHOD: &'static str = "workspace/diagnostic";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticParams {
+    pub identifier: Option<String>,
+    pub previous_result_ids: Vec<PreviousResultId>,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousResultId {
+    pub uri: lsp_types::Url,
+    pub value: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticReport {
+    pub items: Vec<WorkspaceDocumentDiagnosticReport>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDocumentDiagnosticReport {
+    pub uri: lsp_types::Url,
+    pub version: Option<i32>,
+    #[serde(flatten)]
+    pub report: DocumentDiagnosticReport,
+}
+
+pub enum WorkspaceDiagnosticRefresh {}
+
+impl Request for WorkspaceDiagnosticRefresh {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "workspace/diagnostic/refresh";
+}
+
+/// `lsp_types::InitializeResult` with the extended [`ServerCapabilities`].
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResult {
+    pub capabilities: ServerCapabilities,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_info: Option<lsp_types::ServerInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset_encoding: Option<String>,
+}
+
+/// `lsp_types::ServerCapabilities` plus the LSP 3.17 capabilities `lsp-types`
+/// doesn't support yet.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCapabilities {
+    #[serde(flatten)]
+    pub base: lsp_types::ServerCapabilities,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_hierarchy_provider: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostic_provider: Option<DiagnosticOptions>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    pub inter_file_dependencies: bool,
+    pub workspace_diagnostics: bool,
+}
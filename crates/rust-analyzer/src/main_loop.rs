@@ -13,6 +13,7 @@ use ide_db::base_db::VfsPath;
 use lsp_server::{Connection, Notification, Request, Response};
 use lsp_types::notification::Notification as _;
 use project_model::BuildDataCollector;
+use rustc_hash::FxHashSet;
 use vfs::ChangeKind;
 
 use crate::{
@@ -432,19 +433,22 @@ impl GlobalState {
             if self.config.code_lens_refresh() {
                 self.send_request::<lsp_types::request::CodeLensRefresh>((), |_, _| ());
             }
+
+            // Diagnostics pulled while the workspace was loading are stale.
+            if !was_quiescent && self.config.diagnostics_refresh() {
+                self.send_request::<lsp_ext::WorkspaceDiagnosticRefresh>((), |_, _| ());
+            }
         }
 
         if let Some(diagnostic_changes) = self.diagnostics.take_changes() {
-            for file_id in diagnostic_changes {
-                let url = file_id_to_url(&self.vfs.read().0, file_id);
-                let diagnostics = self.diagnostics.diagnostics_for(file_id).cloned().collect();
-                let version = from_proto::vfs_path(&url)
-                    .map(|path| self.mem_docs.get(&path).map(|it| it.version))
-                    .unwrap_or_default();
-
-                self.send_notification::<lsp_types::notification::PublishDiagnostics>(
-                    lsp_types::PublishDiagnosticsParams { uri: url, diagnostics, version },
-                );
+            if self.config.pull_diagnostics() {
+                // Only `cargo check` diagnostics end up here in pull mode, ask
+                // the client to pull them if it can.
+                if self.config.diagnostics_refresh() {
+                    self.send_request::<lsp_ext::WorkspaceDiagnosticRefresh>((), |_, _| ());
+                }
+            } else {
+                self.publish_diagnostics(diagnostic_changes);
             }
         }
 
@@ -517,6 +521,7 @@ impl GlobalState {
             .on::<lsp_ext::ViewCrateGraph>(handlers::handle_view_crate_graph)
             .on::<lsp_ext::ViewItemTree>(handlers::handle_view_item_tree)
             .on::<lsp_ext::ExpandMacro>(handlers::handle_expand_macro)
+            .on::<lsp_ext::ExpandMacroSteps>(handlers::handle_expand_macro_steps)
             .on::<lsp_ext::ParentModule>(handlers::handle_parent_module)
             .on::<lsp_ext::Runnables>(handlers::handle_runnables)
             .on::<lsp_ext::RelatedTests>(handlers::handle_related_tests)
@@ -552,6 +557,11 @@ impl GlobalState {
             .on::<lsp_types::request::CallHierarchyOutgoingCalls>(
                 handlers::handle_call_hierarchy_outgoing,
             )
+            .on::<lsp_ext::TypeHierarchyPrepare>(handlers::handle_type_hierarchy_prepare)
+            .on::<lsp_ext::TypeHierarchySupertypes>(handlers::handle_type_hierarchy_supertypes)
+            .on::<lsp_ext::TypeHierarchySubtypes>(handlers::handle_type_hierarchy_subtypes)
+            .on::<lsp_ext::DocumentDiagnosticRequest>(handlers::handle_document_diagnostic)
+            .on::<lsp_ext::WorkspaceDiagnosticRequest>(handlers::handle_workspace_diagnostic)
             .on::<lsp_types::request::SemanticTokensFullRequest>(
                 handlers::handle_semantic_tokens_full,
             )
@@ -563,6 +573,7 @@ impl GlobalState {
             )
             .on::<lsp_types::request::WillRenameFiles>(handlers::handle_will_rename_files)
             .on::<lsp_ext::Ssr>(handlers::handle_ssr)
+            .on::<lsp_ext::NormalizeImports>(handlers::handle_normalize_imports)
             .finish();
         Ok(())
     }
@@ -649,13 +660,15 @@ impl GlobalState {
                 // Clear the diagnostics for the previously known version of the file.
                 // This prevents stale "cargo check" diagnostics if the file is
                 // closed, "cargo check" is run and then the file is reopened.
-                this.send_notification::<lsp_types::notification::PublishDiagnostics>(
-                    lsp_types::PublishDiagnosticsParams {
-                        uri: params.text_document.uri,
-                        diagnostics: Vec::new(),
-                        version,
-                    },
-                );
+                if !this.config.pull_diagnostics() {
+                    this.send_notification::<lsp_types::notification::PublishDiagnostics>(
+                        lsp_types::PublishDiagnosticsParams {
+                            uri: params.text_document.uri,
+                            diagnostics: Vec::new(),
+                            version,
+                        },
+                    );
+                }
                 Ok(())
             })?
             .on::<lsp_types::notification::DidSaveTextDocument>(|this, params| {
@@ -765,4 +778,18 @@ impl GlobalState {
             })
         }
     }
+
+    fn publish_diagnostics(&mut self, files: FxHashSet<FileId>) {
+        for file_id in files {
+            let url = file_id_to_url(&self.vfs.read().0, file_id);
+            let diagnostics = self.diagnostics.diagnostics_for(file_id).cloned().collect();
+            let version = from_proto::vfs_path(&url)
+                .map(|path| self.mem_docs.get(&path).map(|it| it.version))
+                .unwrap_or_default();
+
+            self.send_notification::<lsp_types::notification::PublishDiagnostics>(
+                lsp_types::PublishDiagnosticsParams { uri: url, diagnostics, version },
+            );
+        }
+    }
 }
@@ -53,6 +53,7 @@ impl GlobalState {
         self.analysis_host
             .raw_database_mut()
             .set_enable_proc_attr_macros(self.config.expand_proc_attr_macros());
+        self.diagnostics.invalidate_all();
     }
     pub(crate) fn maybe_refresh(&mut self, changes: &[(AbsPathBuf, ChangeKind)]) {
         if !changes.iter().any(|(path, kind)| is_interesting(path, *kind)) {
@@ -363,6 +364,7 @@ impl GlobalState {
         self.workspace_build_data = workspace_build_data;
 
         self.analysis_host.apply_change(change);
+        self.diagnostics.invalidate_all();
         self.process_changes();
         self.reload_flycheck();
         log::info!("did switch workspaces");
@@ -9,8 +9,9 @@ use ide::{
     Annotation, AnnotationKind, Assist, AssistKind, CallInfo, Cancellable, CompletionItem,
     CompletionItemKind, CompletionRelevance, Documentation, FileId, FileRange, FileSystemEdit,
     Fold, FoldKind, Highlight, HlMod, HlOperator, HlPunct, HlRange, HlTag, Indel, InlayHint,
-    InlayKind, InsertTextFormat, Markup, NavigationTarget, ReferenceAccess, RenameError, Runnable,
-    Severity, SourceChange, StructureNodeKind, SymbolKind, TextEdit, TextRange, TextSize,
+    InlayKind, InsertTextFormat, MacroExpansionStep, Markup, NavigationTarget, ReferenceAccess,
+    RenameError, Runnable, Severity, SourceChange, StructureNodeKind, SymbolKind, TextEdit,
+    TextRange, TextSize,
 };
 use itertools::Itertools;
 use serde_json::to_value;
@@ -374,6 +375,9 @@ pub(crate) fn inlay_hint(line_index: &LineIndex, inlay_hint: InlayHint) -> lsp_e
             InlayKind::ParameterHint => lsp_ext::InlayKind::ParameterHint,
             InlayKind::TypeHint => lsp_ext::InlayKind::TypeHint,
             InlayKind::ChainingHint => lsp_ext::InlayKind::ChainingHint,
+            InlayKind::LifetimeHint => lsp_ext::InlayKind::LifetimeHint,
+            InlayKind::ClosureReturnTypeHint => lsp_ext::InlayKind::ClosureReturnTypeHint,
+            InlayKind::ClosureCaptureHint => lsp_ext::InlayKind::ClosureCaptureHint,
         },
     }
 }
@@ -880,6 +884,26 @@ pub(crate) fn call_hierarchy_item(
     })
 }
 
+pub(crate) fn type_hierarchy_item(
+    snap: &GlobalStateSnapshot,
+    target: NavigationTarget,
+) -> Result<lsp_ext::TypeHierarchyItem> {
+    let name = target.name.to_string();
+    let detail = target.description.clone();
+    let kind = target.kind.map(symbol_kind).unwrap_or(lsp_types::SymbolKind::Struct);
+    let (uri, range, selection_range) = location_info(snap, target)?;
+    Ok(lsp_ext::TypeHierarchyItem {
+        name,
+        kind,
+        tags: None,
+        detail,
+        uri,
+        range,
+        selection_range,
+        data: None,
+    })
+}
+
 pub(crate) fn code_action_kind(kind: AssistKind) -> lsp_types::CodeActionKind {
     match kind {
         AssistKind::None | AssistKind::Generate => lsp_types::CodeActionKind::EMPTY,
@@ -888,6 +912,8 @@ pub(crate) fn code_action_kind(kind: AssistKind) -> lsp_types::CodeActionKind {
         AssistKind::RefactorExtract => lsp_types::CodeActionKind::REFACTOR_EXTRACT,
         AssistKind::RefactorInline => lsp_types::CodeActionKind::REFACTOR_INLINE,
         AssistKind::RefactorRewrite => lsp_types::CodeActionKind::REFACTOR_REWRITE,
+        AssistKind::Source => lsp_types::CodeActionKind::SOURCE,
+        AssistKind::SourceOrganizeImports => lsp_types::CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
     }
 }
 
@@ -947,6 +973,29 @@ pub(crate) fn runnable(
     })
 }
 
+pub(crate) fn macro_expansion_step(step: MacroExpansionStep) -> lsp_ext::MacroExpansionStep {
+    lsp_ext::MacroExpansionStep {
+        depth: step.depth as u32,
+        name: step.name,
+        input: step.input,
+        rules: step
+            .rules
+            .into_iter()
+            .map(|rule| lsp_ext::MacroRuleMatch {
+                pattern: rule.pattern,
+                bindings: rule
+                    .bindings
+                    .into_iter()
+                    .map(|(name, value)| lsp_ext::MacroBinding { name, value })
+                    .collect(),
+                error: rule.error,
+            })
+            .collect(),
+        used_rule: step.used_rule.map(|it| it as u32),
+        expansion: step.expansion,
+    }
+}
+
 pub(crate) fn code_lens(
     snap: &GlobalStateSnapshot,
     annotation: Annotation,
@@ -1040,6 +1089,76 @@ pub(crate) fn code_lens(
                 data: Some(to_value(lsp_ext::CodeLensResolveData::References(doc_pos)).unwrap()),
             })
         }
+        AnnotationKind::ImplementsTraitItem { position: file_position, data } => {
+            let line_index = snap.file_line_index(file_position.file_id)?;
+            let annotation_range = range(&line_index, annotation.range);
+            let url = url(snap, file_position.file_id);
+
+            let position = position(&line_index, file_position.offset);
+
+            let id = lsp_types::TextDocumentIdentifier { uri: url.clone() };
+
+            let doc_pos = lsp_types::TextDocumentPositionParams::new(id, position);
+
+            let command = data.map(|targets| {
+                let title = match targets.first().and_then(|it| it.container_name.as_ref()) {
+                    Some(trait_name) => format!("implements {}", trait_name),
+                    None => "implements trait".to_string(),
+                };
+                let locations: Vec<lsp_types::Location> = targets
+                    .into_iter()
+                    .filter_map(|target| {
+                        location(
+                            snap,
+                            FileRange { file_id: target.file_id, range: target.full_range },
+                        )
+                        .ok()
+                    })
+                    .collect();
+
+                command::show_references(title, &url, position, locations)
+            });
+
+            Ok(lsp_types::CodeLens {
+                range: annotation_range,
+                command,
+                data: Some(
+                    to_value(lsp_ext::CodeLensResolveData::ImplementsTraitItem(doc_pos)).unwrap(),
+                ),
+            })
+        }
+        AnnotationKind::HasOverrides { position: file_position, data } => {
+            let line_index = snap.file_line_index(file_position.file_id)?;
+            let annotation_range = range(&line_index, annotation.range);
+            let url = url(snap, file_position.file_id);
+
+            let position = position(&line_index, file_position.offset);
+
+            let id = lsp_types::TextDocumentIdentifier { uri: url.clone() };
+
+            let doc_pos = lsp_types::TextDocumentPositionParams::new(id, position);
+
+            let command = data.map(|targets| {
+                let locations: Vec<lsp_types::Location> = targets
+                    .into_iter()
+                    .filter_map(|target| {
+                        location(
+                            snap,
+                            FileRange { file_id: target.file_id, range: target.full_range },
+                        )
+                        .ok()
+                    })
+                    .collect();
+
+                command::show_references(override_title(locations.len()), &url, position, locations)
+            });
+
+            Ok(lsp_types::CodeLens {
+                range: annotation_range,
+                command,
+                data: Some(to_value(lsp_ext::CodeLensResolveData::Overrides(doc_pos)).unwrap()),
+            })
+        }
     }
 }
 
@@ -1135,6 +1254,14 @@ pub(crate) fn reference_title(count: usize) -> String {
     }
 }
 
+pub(crate) fn override_title(count: usize) -> String {
+    if count == 1 {
+        "1 override".into()
+    } else {
+        format!("{} overrides", count)
+    }
+}
+
 pub(crate) fn markup_content(markup: Markup) -> lsp_types::MarkupContent {
     let value = crate::markdown::format_docs(markup.as_str());
     lsp_types::MarkupContent { kind: lsp_types::MarkupKind::Markdown, value }
@@ -1188,6 +1315,7 @@ mod tests {
                         enforce_granularity: true,
                         group: true,
                     },
+                    snippets: Vec::new(),
                 },
                 ide_db::base_db::FilePosition { file_id, offset },
             )
@@ -25,8 +25,11 @@ use lsp_types::{
     PartialResultParams, Position, Range, RenameFilesParams, TextDocumentItem,
     TextDocumentPositionParams, WorkDoneProgressParams,
 };
-use rust_analyzer::lsp_ext::{OnEnter, Runnables, RunnablesParams};
-use serde_json::json;
+use rust_analyzer::lsp_ext::{
+    DocumentDiagnosticParams, DocumentDiagnosticRequest, OnEnter, PreviousResultId, Runnables,
+    RunnablesParams, WorkspaceDiagnosticParams, WorkspaceDiagnosticRequest,
+};
+use serde_json::{json, Value};
 use test_utils::skip_slow_tests;
 
 use crate::{
@@ -302,6 +305,157 @@ fn main() {}
     );
 }
 
+#[test]
+fn test_pull_diagnostics_unchanged() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = project(
+        r#"
+//- /Cargo.toml
+[package]
+name = "foo"
+version = "0.0.0"
+
+//- /src/lib.rs
+mod bar;
+mod baz;
+
+//- /src/baz.rs
+fn baz() {}
+"#,
+    )
+    .wait_until_workspace_is_loaded();
+
+    let params = || DocumentDiagnosticParams {
+        text_document: server.doc_id("src/lib.rs"),
+        identifier: None,
+        previous_result_id: None,
+        work_done_progress_params: WorkDoneProgressParams::default(),
+        partial_result_params: PartialResultParams::default(),
+    };
+    let report = server.send_request::<DocumentDiagnosticRequest>(params());
+    assert_eq!(report["kind"], "full");
+    assert_eq!(report["items"][0]["code"], "unresolved-module");
+    let result_id = report["resultId"].as_str().unwrap().to_string();
+
+    server.request::<DocumentDiagnosticRequest>(
+        DocumentDiagnosticParams { previous_result_id: Some(result_id.clone()), ..params() },
+        json!({ "kind": "unchanged", "resultId": result_id }),
+    );
+
+    // Editing another file doesn't change the diagnostics of `lib.rs`.
+    server.notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: server.doc_id("src/baz.rs").uri,
+            language_id: "rust".to_string(),
+            version: 0,
+            text: "fn baz() { 92; }".to_string(),
+        },
+    });
+    server.request::<DocumentDiagnosticRequest>(
+        DocumentDiagnosticParams { previous_result_id: Some(result_id.clone()), ..params() },
+        json!({ "kind": "unchanged", "resultId": result_id }),
+    );
+}
+
+#[test]
+fn test_pull_workspace_diagnostics_of_dependents() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let tmp_dir = TestDir::new();
+    let path = tmp_dir.path();
+    let project = json!({
+        "roots": [path],
+        "crates": [
+            {
+                "root_module": path.join("bar/lib.rs"),
+                "deps": [],
+                "edition": "2018",
+            },
+            {
+                "root_module": path.join("foo/lib.rs"),
+                "deps": [{ "crate": 0, "name": "bar" }],
+                "edition": "2018",
+            },
+        ]
+    });
+    let code = format!(
+        r#"
+//- /rust-project.json
+{PROJECT}
+
+//- /foo/lib.rs
+use bar::Bar;
+
+//- /bar/lib.rs
+mod baz;
+"#,
+        PROJECT = project.to_string(),
+    );
+    let server =
+        Project::with_fixture(&code).tmp_dir(tmp_dir).server().wait_until_workspace_is_loaded();
+
+    let pull = |previous: &Value| {
+        let previous_result_ids = previous["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|it| PreviousResultId {
+                uri: serde_json::from_value(it["uri"].clone()).unwrap(),
+                value: it["resultId"].as_str().unwrap().to_string(),
+            })
+            .collect();
+        server.send_request::<WorkspaceDiagnosticRequest>(WorkspaceDiagnosticParams {
+            identifier: None,
+            previous_result_ids,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+    };
+    let kind_of = |report: &Value, path: &str| {
+        let item = report["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|it| it["uri"].as_str().unwrap().ends_with(path))
+            .unwrap();
+        item["kind"].as_str().unwrap().to_string()
+    };
+
+    let report = pull(&json!({ "items": [] }));
+    assert_eq!(kind_of(&report, "foo/lib.rs"), "full");
+    assert_eq!(kind_of(&report, "bar/lib.rs"), "full");
+
+    // Editing the dependent leaves the dependency alone.
+    server.notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: server.doc_id("foo/lib.rs").uri,
+            language_id: "rust".to_string(),
+            version: 0,
+            text: "use bar::Bar;\nfn foo() {}".to_string(),
+        },
+    });
+    let report = pull(&report);
+    assert_eq!(kind_of(&report, "bar/lib.rs"), "unchanged");
+
+    // Editing the dependency can change the diagnostics of the dependent.
+    server.notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: server.doc_id("bar/lib.rs").uri,
+            language_id: "rust".to_string(),
+            version: 0,
+            text: "mod baz;\npub struct Bar;".to_string(),
+        },
+    });
+    let report = pull(&report);
+    assert_eq!(kind_of(&report, "foo/lib.rs"), "full");
+    assert_eq!(kind_of(&report, "bar/lib.rs"), "unchanged");
+}
+
 #[test]
 fn test_missing_module_code_action() {
     if skip_slow_tests() {
@@ -284,6 +284,36 @@ export function ssr(ctx: Ctx): Cmd {
     };
 }
 
+export function normalizeImports(ctx: Ctx): Cmd {
+    return async () => {
+        const client = ctx.client;
+        if (!client) return;
+
+        const items: Array<vscode.QuickPickItem & { granularity: ra.ImportGranularity }> = [
+            { label: "Crate", description: "Merge imports from the same crate", granularity: "crate" },
+            { label: "Module", description: "Merge imports from the same module", granularity: "module" },
+            { label: "Item", description: "Import every item separately", granularity: "item" },
+            { label: "Preserve", description: "Only sort and group imports", granularity: "preserve" },
+        ];
+        const picked = await vscode.window.showQuickPick(items, {
+            placeHolder: "Choose the granularity to normalize all imports of the workspace to",
+        });
+        if (!picked) return;
+
+        await vscode.window.withProgress({
+            location: vscode.ProgressLocation.Notification,
+            title: "Normalizing imports...",
+            cancellable: false,
+        }, async (_progress, _token) => {
+            const edit = await client.sendRequest(ra.normalizeImports, {
+                granularity: picked.granularity,
+            });
+
+            await vscode.workspace.applyEdit(client.protocol2CodeConverter.asWorkspaceEdit(edit));
+        });
+    };
+}
+
 export function serverVersion(ctx: Ctx): Cmd {
     return async () => {
         const { stdout } = spawnSync(ctx.serverPath, ["--version"], { encoding: "utf8" });
@@ -115,6 +115,9 @@ export class Config {
             typeHints: this.get<boolean>("inlayHints.typeHints"),
             parameterHints: this.get<boolean>("inlayHints.parameterHints"),
             chainingHints: this.get<boolean>("inlayHints.chainingHints"),
+            lifetimeElisionHints: this.get<boolean>("inlayHints.lifetimeElisionHints"),
+            closureReturnTypeHints: this.get<boolean>("inlayHints.closureReturnTypeHints"),
+            closureCaptureHints: this.get<boolean>("inlayHints.closureCaptureHints"),
             smallerHints: this.get<boolean>("inlayHints.smallerHints"),
             maxLength: this.get<null | number>("inlayHints.maxLength"),
         };
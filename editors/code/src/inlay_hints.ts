@@ -14,6 +14,9 @@ interface InlayHintsStyles {
     typeHints: InlayHintStyle;
     paramHints: InlayHintStyle;
     chainingHints: InlayHintStyle;
+    lifetimeHints: InlayHintStyle;
+    closureReturnTypeHints: InlayHintStyle;
+    closureCaptureHints: InlayHintStyle;
 }
 
 
@@ -23,7 +26,10 @@ export function activateInlayHints(ctx: Ctx) {
         async onConfigChange() {
             const anyEnabled = ctx.config.inlayHints.typeHints
                 || ctx.config.inlayHints.parameterHints
-                || ctx.config.inlayHints.chainingHints;
+                || ctx.config.inlayHints.chainingHints
+                || ctx.config.inlayHints.lifetimeElisionHints
+                || ctx.config.inlayHints.closureReturnTypeHints
+                || ctx.config.inlayHints.closureCaptureHints;
             const enabled = ctx.config.inlayHints.enable && anyEnabled;
 
             if (!enabled) return this.dispose();
@@ -51,13 +57,16 @@ export function activateInlayHints(ctx: Ctx) {
     maybeUpdater.onConfigChange().catch(console.error);
 }
 
-function createHintStyle(hintKind: "type" | "parameter" | "chaining", smallerHints: boolean): InlayHintStyle {
+function createHintStyle(hintKind: "type" | "parameter" | "chaining" | "lifetime" | "closureReturnType" | "closureCapture", smallerHints: boolean): InlayHintStyle {
     // U+200C is a zero-width non-joiner to prevent the editor from forming a ligature
     // between code and type hints
     const [pos, render] = ({
         type: ["after", (label: string) => `\u{200c}: ${label}`],
         parameter: ["before", (label: string) => `${label}: `],
         chaining: ["after", (label: string) => `\u{200c}: ${label}`],
+        lifetime: ["after", (label: string) => label],
+        closureReturnType: ["after", (label: string) => ` -> ${label}`],
+        closureCapture: ["before", (label: string) => `${label} `],
     } as const)[hintKind];
 
     const fg = new vscode.ThemeColor(`rust_analyzer.inlayHints.foreground.${hintKind}Hints`);
@@ -85,12 +94,18 @@ const smallHintsStyles = {
     typeHints: createHintStyle("type", true),
     paramHints: createHintStyle("parameter", true),
     chainingHints: createHintStyle("chaining", true),
+    lifetimeHints: createHintStyle("lifetime", true),
+    closureReturnTypeHints: createHintStyle("closureReturnType", true),
+    closureCaptureHints: createHintStyle("closureCapture", true),
 };
 
 const biggerHintsStyles = {
     typeHints: createHintStyle("type", false),
     paramHints: createHintStyle("parameter", false),
     chainingHints: createHintStyle("chaining", false),
+    lifetimeHints: createHintStyle("lifetime", false),
+    closureReturnTypeHints: createHintStyle("closureReturnType", false),
+    closureCaptureHints: createHintStyle("closureCapture", false),
 };
 
 class HintsUpdater implements Disposable {
@@ -193,21 +208,27 @@ class HintsUpdater implements Disposable {
     }
 
     private renderDecorations(editor: RustEditor, decorations: InlaysDecorations) {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
+        const { typeHints, paramHints, chainingHints, lifetimeHints, closureReturnTypeHints, closureCaptureHints } = this.inlayHintsStyles;
         if (this.pendingDisposeDecorations !== undefined) {
-            const { typeHints, paramHints, chainingHints } = this.pendingDisposeDecorations;
+            const { typeHints, paramHints, chainingHints, lifetimeHints, closureReturnTypeHints, closureCaptureHints } = this.pendingDisposeDecorations;
             editor.setDecorations(typeHints.decorationType, []);
             editor.setDecorations(paramHints.decorationType, []);
             editor.setDecorations(chainingHints.decorationType, []);
+            editor.setDecorations(lifetimeHints.decorationType, []);
+            editor.setDecorations(closureReturnTypeHints.decorationType, []);
+            editor.setDecorations(closureCaptureHints.decorationType, []);
         }
         editor.setDecorations(typeHints.decorationType, decorations.type);
         editor.setDecorations(paramHints.decorationType, decorations.param);
         editor.setDecorations(chainingHints.decorationType, decorations.chaining);
+        editor.setDecorations(lifetimeHints.decorationType, decorations.lifetime);
+        editor.setDecorations(closureReturnTypeHints.decorationType, decorations.closureReturnType);
+        editor.setDecorations(closureCaptureHints.decorationType, decorations.closureCapture);
     }
 
     private hintsToDecorations(hints: ra.InlayHint[]): InlaysDecorations {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
-        const decorations: InlaysDecorations = { type: [], param: [], chaining: [] };
+        const { typeHints, paramHints, chainingHints, lifetimeHints, closureReturnTypeHints, closureCaptureHints } = this.inlayHintsStyles;
+        const decorations: InlaysDecorations = { type: [], param: [], chaining: [], lifetime: [], closureReturnType: [], closureCapture: [] };
         const conv = this.ctx.client.protocol2CodeConverter;
 
         for (const hint of hints) {
@@ -224,6 +245,18 @@ class HintsUpdater implements Disposable {
                     decorations.chaining.push(chainingHints.toDecoration(hint, conv));
                     continue;
                 }
+                case ra.InlayHint.Kind.LifetimeHint: {
+                    decorations.lifetime.push(lifetimeHints.toDecoration(hint, conv));
+                    continue;
+                }
+                case ra.InlayHint.Kind.ClosureReturnTypeHint: {
+                    decorations.closureReturnType.push(closureReturnTypeHints.toDecoration(hint, conv));
+                    continue;
+                }
+                case ra.InlayHint.Kind.ClosureCaptureHint: {
+                    decorations.closureCapture.push(closureCaptureHints.toDecoration(hint, conv));
+                    continue;
+                }
             }
         }
         return decorations;
@@ -251,6 +284,9 @@ interface InlaysDecorations {
     type: vscode.DecorationOptions[];
     param: vscode.DecorationOptions[];
     chaining: vscode.DecorationOptions[];
+    lifetime: vscode.DecorationOptions[];
+    closureReturnType: vscode.DecorationOptions[];
+    closureCapture: vscode.DecorationOptions[];
 }
 
 interface RustSourceFile {
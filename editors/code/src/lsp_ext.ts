@@ -87,13 +87,17 @@ export interface TestInfo {
 
 export const relatedTests = new lc.RequestType<lc.TextDocumentPositionParams, TestInfo[], void>("rust-analyzer/relatedTests");
 
-export type InlayHint = InlayHint.TypeHint | InlayHint.ParamHint | InlayHint.ChainingHint;
+export type InlayHint = InlayHint.TypeHint | InlayHint.ParamHint | InlayHint.ChainingHint | InlayHint.LifetimeHint
+    | InlayHint.ClosureReturnTypeHint | InlayHint.ClosureCaptureHint;
 
 export namespace InlayHint {
     export const enum Kind {
         TypeHint = "TypeHint",
         ParamHint = "ParameterHint",
         ChainingHint = "ChainingHint",
+        LifetimeHint = "LifetimeHint",
+        ClosureReturnTypeHint = "ClosureReturnTypeHint",
+        ClosureCaptureHint = "ClosureCaptureHint",
     }
     interface Common {
         range: lc.Range;
@@ -102,6 +106,9 @@ export namespace InlayHint {
     export type TypeHint = Common & { kind: Kind.TypeHint };
     export type ParamHint = Common & { kind: Kind.ParamHint };
     export type ChainingHint = Common & { kind: Kind.ChainingHint };
+    export type LifetimeHint = Common & { kind: Kind.LifetimeHint };
+    export type ClosureReturnTypeHint = Common & { kind: Kind.ClosureReturnTypeHint };
+    export type ClosureCaptureHint = Common & { kind: Kind.ClosureCaptureHint };
 }
 export interface InlayHintsParams {
     textDocument: lc.TextDocumentIdentifier;
@@ -117,6 +124,13 @@ export interface SsrParams {
 }
 export const ssr = new lc.RequestType<SsrParams, lc.WorkspaceEdit, void>('experimental/ssr');
 
+export type ImportGranularity = "preserve" | "crate" | "module" | "item";
+export interface NormalizeImportsParams {
+    granularity?: ImportGranularity;
+    group?: boolean;
+}
+export const normalizeImports = new lc.RequestType<NormalizeImportsParams, lc.WorkspaceEdit, void>('rust-analyzer/normalizeImports');
+
 export interface CommandLink extends lc.Command {
     /**
      * A tooltip for the command, when represented in the UI.
@@ -140,6 +140,7 @@ async function initCommonContext(context: vscode.ExtensionContext, ctx: Ctx) {
     ctx.registerCommand('onEnter', commands.onEnter);
 
     ctx.registerCommand('ssr', commands.ssr);
+    ctx.registerCommand('normalizeImports', commands.normalizeImports);
     ctx.registerCommand('serverVersion', commands.serverVersion);
     ctx.registerCommand('toggleInlayHints', commands.toggleInlayHints);
 